@@ -1,14 +1,15 @@
 use std::path::PathBuf;
 
 use clap::{Parser, Subcommand};
+use nomi_core::instance::InstanceProfileId;
 
 #[derive(Parser)]
 #[command(verbatim_doc_comment)]
 ///     _   __                _
 ///    / | / /___  ____ ___  (_)
 ///   /  |/ / __ \/ __ `__ \/ /
-///  / /|  / /_/ / / / / / / /  
-/// /_/ |_/\____/_/ /_/ /_/_/   
+///  / /|  / /_/ / / / / / / /
+/// /_/ |_/\____/_/ /_/ /_/_/
 /// CLI client
 pub struct Cli {
     #[arg(long, short = 'g')]
@@ -19,7 +20,7 @@ pub struct Cli {
 
 #[derive(Subcommand)]
 pub enum Command {
-    /// Download version
+    /// Download version into a new instance
     Download {
         /// Profile name
         name: String,
@@ -30,17 +31,38 @@ pub enum Command {
         loader: Option<Loader>,
     },
     /// Launch game from profile
-    Launch { profile_id: i32 },
-    /// Create config with username
-    Register {
-        username: String,
-        #[arg(long, short)]
-        access_token: Option<String>,
-        #[arg(long, short)]
-        java_bin: Option<PathBuf>,
-        #[arg(long, short)]
-        uuid: Option<String>,
+    Launch {
+        /// Profile id in the `INSTANCE/PROFILE` form, see `list`
+        #[arg(value_parser = parse_profile_id)]
+        profile: InstanceProfileId,
     },
+    /// Verify files of the profile and download the broken ones again
+    Repair {
+        /// Profile id in the `INSTANCE/PROFILE` form, see `list`
+        #[arg(value_parser = parse_profile_id)]
+        profile: InstanceProfileId,
+    },
+    /// Export profile as a `.mrpack` modpack
+    Export {
+        /// Profile id in the `INSTANCE/PROFILE` form, see `list`
+        #[arg(value_parser = parse_profile_id)]
+        profile: InstanceProfileId,
+        /// Path to the `.mrpack` file
+        #[arg(value_parser = parse_absolute_path)]
+        output: PathBuf,
+        /// Modpack version
+        #[arg(long, short = 'v', default_value = "1.0.0")]
+        version: String,
+        /// Include `options.txt` and the `config` directory
+        #[arg(long)]
+        include_configs: bool,
+    },
+    /// Manage accounts
+    #[command(subcommand)]
+    Account(AccountCommand),
+    /// Manage Java installations
+    #[command(subcommand)]
+    Java(JavaCommand),
     /// Show list of existing profiles
     List,
 }
@@ -52,3 +74,50 @@ pub enum Loader {
         version: Option<String>,
     },
 }
+
+#[derive(Subcommand)]
+pub enum AccountCommand {
+    /// Show list of added accounts
+    List,
+    /// Add offline account
+    Add { username: String },
+    /// Sign in with Microsoft account
+    Login,
+    /// Remove account
+    Remove {
+        /// Account id or name
+        account: String,
+    },
+    /// Use account when a profile does not override it
+    Default {
+        /// Account id or name
+        account: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum JavaCommand {
+    /// Show list of Java installations found on the system
+    List,
+    /// Set Java used to launch the profile
+    Select {
+        /// Profile id in the `INSTANCE/PROFILE` form, see `list`
+        #[arg(value_parser = parse_profile_id)]
+        profile: InstanceProfileId,
+        /// Path to the `java` executable. Java is picked automatically if omitted
+        #[arg(value_parser = parse_absolute_path)]
+        java: Option<PathBuf>,
+    },
+}
+
+fn parse_profile_id(s: &str) -> Result<InstanceProfileId, String> {
+    let (instance, profile) = s.split_once('/').ok_or("expected `INSTANCE/PROFILE`")?;
+    let parse = |id: &str| id.parse::<usize>().map_err(|e| format!("`{id}` is not a valid id: {e}"));
+
+    Ok(InstanceProfileId::new(parse(instance)?, parse(profile)?))
+}
+
+/// Paths are resolved before switching to the game directory.
+fn parse_absolute_path(s: &str) -> Result<PathBuf, String> {
+    std::path::absolute(s).map_err(|e| format!("`{s}` is not a valid path: {e}"))
+}
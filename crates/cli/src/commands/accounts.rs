@@ -0,0 +1,98 @@
+use nomi_core::{
+    auth::Authenticator,
+    configs::{
+        account::{Account, AccountStore},
+        profile::VersionProfile,
+    },
+    instance::launch::arguments::UserData,
+    repository::username::Username,
+    DOT_NOMI_ACCOUNTS_CONFIG, DOT_NOMI_AUTH_CACHE,
+};
+
+use crate::{args::AccountCommand, error::Error};
+
+/// Azure application id used for the Microsoft authentication.
+const MICROSOFT_CLIENT_ID: Option<&str> = option_env!("NOMI_MICROSOFT_CLIENT_ID");
+
+fn authenticator() -> anyhow::Result<Authenticator> {
+    MICROSOFT_CLIENT_ID
+        .map(Authenticator::new)
+        .ok_or_else(|| Error::General("Nomi was built without `NOMI_MICROSOFT_CLIENT_ID`".into()).into())
+}
+
+async fn read_store() -> anyhow::Result<AccountStore> {
+    let mut store = AccountStore::read_or_default(DOT_NOMI_ACCOUNTS_CONFIG).await?;
+    store.migrate_auth_cache(DOT_NOMI_AUTH_CACHE, DOT_NOMI_ACCOUNTS_CONFIG)?;
+
+    Ok(store)
+}
+
+/// Returns the id of the account with the provided id or name.
+fn find_account(store: &AccountStore, query: &str) -> anyhow::Result<String> {
+    store
+        .iter()
+        .find(|account| account.id() == query || account.name() == query)
+        .map(|account| account.id().to_owned())
+        .ok_or_else(|| Error::General(format!("There is no `{query}` account\nRun `account list` to see added accounts")).into())
+}
+
+pub async fn account(command: &AccountCommand) -> anyhow::Result<()> {
+    let mut store = read_store().await?;
+
+    match command {
+        AccountCommand::List => {
+            for account in store.iter() {
+                let marker = if store.default.as_deref() == Some(account.id()) { '*' } else { ' ' };
+                let kind = if account.is_online() { "Microsoft" } else { "Offline" };
+                println!("{marker} {}: {} ({kind})", account.name(), account.id());
+            }
+
+            return Ok(());
+        }
+        AccountCommand::Add { username } => {
+            let account = Account::offline(Username::new(username.as_str())?);
+            println!("Added `{}`", account.name());
+            store.add(account);
+        }
+        AccountCommand::Login => {
+            let authenticator = authenticator()?;
+            let device_code = authenticator.request_device_code().await?;
+            println!("{}", device_code.message);
+
+            let account = authenticator.wait_for_authorization(&device_code).await?;
+            println!("Signed in as `{}`", account.profile.name);
+            store.add(Account::Microsoft(account));
+        }
+        AccountCommand::Remove { account } => {
+            let id = find_account(&store, account)?;
+            store.remove(&id);
+        }
+        AccountCommand::Default { account } => {
+            let id = find_account(&store, account)?;
+            store.set_default(&id)?;
+        }
+    }
+
+    store.write(DOT_NOMI_ACCOUNTS_CONFIG).await
+}
+
+/// Returns [`UserData`] of the account used to launch the profile.
+///
+/// The store is written back if the account's token was refreshed.
+pub async fn user_data_for(profile: &VersionProfile) -> anyhow::Result<UserData> {
+    let mut store = read_store().await?;
+
+    let Some(id) = store.account_for(profile).map(|account| account.id().to_owned()) else {
+        return Err(Error::General("There is no account to launch the game with\nRun `account add` or `account login` before".into()).into());
+    };
+
+    let authenticator = authenticator().ok();
+    let before = store.clone();
+    let user_data = store.user_data(&id, authenticator.as_ref()).await?;
+
+    if store != before {
+        store.write(DOT_NOMI_ACCOUNTS_CONFIG).await?;
+    }
+
+    Ok(user_data)
+}
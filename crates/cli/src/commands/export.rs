@@ -0,0 +1,70 @@
+use std::path::Path;
+
+use nomi_core::{
+    configs::profile::ProfileState,
+    instance::{Instance, InstanceProfileId},
+    modpacks::mrpack::{override_candidates, MrpackExporter},
+};
+use nomi_modding::source::{ModFile, SourceKind};
+use serde::Deserialize;
+
+use crate::error::Error;
+
+use super::ProfileConfig;
+
+/// Directory of the instance where the client keeps the profile's mods.
+const MODS_STASH_DIR: &str = ".nomi/mods_stash";
+
+/// Fields of a mod from the client's profile config that are needed to export it.
+#[derive(Deserialize)]
+struct ProfileMod {
+    #[serde(default)]
+    source: SourceKind,
+    is_downloaded: bool,
+    files: Vec<ModFile>,
+}
+
+pub async fn export(id: InstanceProfileId, output: &Path, version: &str, include_configs: bool) -> anyhow::Result<()> {
+    let config = ProfileConfig::read(id).await?;
+
+    let ProfileState::Downloaded(instance) = &config.profile.state else {
+        return Err(Error::General("Download the profile to export it".into()).into());
+    };
+
+    let mut exporter = MrpackExporter::new(&config.profile.name, version, config.profile.version(), instance.loader_profile())?;
+
+    let instance_path = Instance::path_from_id(id.instance());
+    let stash = instance_path.join(MODS_STASH_DIR).join(id.profile().to_string());
+
+    for profile_mod in &config.mods {
+        let profile_mod = profile_mod.clone().try_into::<ProfileMod>()?;
+
+        if !profile_mod.is_downloaded {
+            continue;
+        }
+
+        for file in profile_mod.files {
+            let path = format!("mods/{}", file.filename);
+            let local = stash.join(&file.filename);
+
+            // Modpacks can only link to Modrinth, the other files are put into the overrides.
+            match (profile_mod.source, file.url) {
+                (SourceKind::Modrinth, Some(url)) => exporter.add_file(path, local, vec![url]).await?,
+                _ => exporter.add_override(path, local)?,
+            }
+        }
+    }
+
+    if include_configs {
+        for path in override_candidates(&instance_path)? {
+            let local = instance_path.join(&path);
+            exporter.add_override(path, local)?;
+        }
+    }
+
+    exporter.export(output).await?;
+
+    println!("Exported `{}` to {}", config.profile.name, output.display());
+
+    Ok(())
+}
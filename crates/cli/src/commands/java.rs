@@ -0,0 +1,47 @@
+use nomi_core::{
+    configs::profile::ProfileState,
+    repository::java_installation::{discover_java_installations, JavaInstallation},
+};
+
+use crate::{args::JavaCommand, error::Error};
+
+use super::ProfileConfig;
+
+pub async fn java(command: &JavaCommand) -> anyhow::Result<()> {
+    match command {
+        JavaCommand::List => {
+            for installation in discover_java_installations().await {
+                let vendor = installation.vendor.as_deref().unwrap_or("Unknown vendor");
+                println!(
+                    "Java {} ({vendor}, {}): {}",
+                    installation.version,
+                    installation.arch,
+                    installation.path.display()
+                );
+            }
+
+            Ok(())
+        }
+        JavaCommand::Select { profile, java } => {
+            let mut config = ProfileConfig::read(*profile).await?;
+
+            let ProfileState::Downloaded(instance) = &mut config.profile.state else {
+                return Err(Error::General("This profile is not downloaded\nDownload it before selecting Java".into()).into());
+            };
+
+            instance.settings.java_runner = match java {
+                Some(path) => {
+                    let installation = JavaInstallation::probe(path).await?;
+                    println!("Using Java {} for `{}`", installation.version, config.profile.name);
+                    Some(installation.runner())
+                }
+                None => {
+                    println!("Java for `{}` is picked automatically", config.profile.name);
+                    None
+                }
+            };
+
+            config.write().await
+        }
+    }
+}
@@ -1,237 +1,261 @@
-use std::path::{Path, PathBuf};
-
 use nomi_core::{
-    configs::{
-        profile::{VersionProfileBuilder, VersionProfilesConfig},
-        read_toml_config,
-        user::Settings,
-        write_toml_config,
+    configs::profile::{ProfileState, VersionProfile},
+    downloads::traits::{DownloadResult, Downloader},
+    fs::{read_toml_config, write_toml_config},
+    game_paths::GamePaths,
+    instance::{
+        launch::{features::LaunchFeatures, LaunchSettings},
+        load_instances,
+        logs::PrintLogs,
+        verify::{verify, RepairReport},
+        Instance, InstanceProfileId, Profile, ProfilePayload,
     },
-    instance::{launch::LaunchSettings, Inner, InstanceBuilder},
-    repository::{java_runner::JavaRunner, username::Username},
+    loaders::{combined::VanillaCombinedDownloader, fabric::Fabric},
+    repository::manifest::VersionType,
+    state::get_launcher_manifest,
+    INSTANCES_DIR,
 };
+use serde::{Deserialize, Serialize};
 use tracing::warn;
 
 use crate::{
-    args::{Cli, Loader},
+    args::{Cli, Command, Loader},
     error::Error,
 };
 
-pub async fn process_args(args: &Cli) -> anyhow::Result<()> {
-    use crate::args::Command::*;
-    match args {
-        Cli {
-            game_dir,
-            command:
-                Download {
-                    name,
-                    version,
-                    loader,
-                },
-        } => download(name, game_dir, version, loader.as_ref()).await,
-        Cli {
-            game_dir,
-            command: Launch { profile_id },
-        } => {
-            let _ = launch(game_dir, profile_id).await?;
-            Ok(())
-        }
-        Cli {
-            game_dir,
-            command:
-                Register {
-                    username,
-                    access_token,
-                    java_bin,
-                    uuid,
-                },
-        } => {
-            register(
-                game_dir,
-                username,
-                access_token.as_ref(),
-                java_bin.as_ref(),
-                uuid.as_ref(),
-            )
-            .await
+mod accounts;
+mod export;
+mod java;
+
+/// Profile config shared with the client.
+///
+/// Mods are managed by the client, the CLI only keeps them intact.
+#[derive(Serialize, Deserialize)]
+pub struct ProfileConfig {
+    pub profile: VersionProfile,
+    #[serde(default)]
+    pub mods: Vec<toml::Value>,
+}
+
+impl ProfileConfig {
+    pub async fn read(id: InstanceProfileId) -> anyhow::Result<Self> {
+        let path = GamePaths::from_id(id).profile_config();
+
+        if !path.exists() {
+            return Err(Error::General("No such profile\nRun `list` to see installed profiles".into()).into());
         }
-        Cli {
-            game_dir,
-            command: List,
-        } => list(game_dir).await,
+
+        Ok(read_toml_config(path).await?)
+    }
+
+    pub async fn write(&self) -> anyhow::Result<()> {
+        Ok(write_toml_config(self, GamePaths::from_id(self.profile.id).profile_config()).await?)
     }
 }
 
-pub async fn download(
-    name: &String,
-    dir: &Path,
-    game_version: &String,
-    loader: Option<&Loader>,
-) -> anyhow::Result<()> {
-    let dir = match dir.is_absolute() {
-        true => dir.to_path_buf(),
-        false => {
-            warn!("`GAME_DIR` is not absolute. Adding to the current dir");
-            std::env::current_dir()?.join(dir)
-        }
-    };
-    let mc_dir = &dir.join("minecraft");
-    let builder = InstanceBuilder::new()
-        .name(name.to_string())
-        .version(game_version.to_string())
-        .assets(mc_dir.join("assets"))
-        .game(mc_dir.clone())
-        .libraries(mc_dir.join("libraries"))
-        .version_path(mc_dir.join("versions").join(game_version));
-
-    let instance = if let Some(loader) = loader {
-        match loader {
-            Loader::Fabric { version } => builder
-                .instance(Inner::fabric(game_version, version.as_ref()).await?)
-                .build(),
+pub async fn process_args(args: &Cli) -> anyhow::Result<()> {
+    match &args.command {
+        Command::Download { name, version, loader } => download(name, version, loader.as_ref()).await,
+        Command::Launch { profile } => launch(*profile).await,
+        Command::Repair { profile } => repair(*profile).await,
+        Command::Export {
+            profile,
+            output,
+            version,
+            include_configs,
+        } => export::export(*profile, output, version, *include_configs).await,
+        Command::Account(command) => accounts::account(command).await,
+        Command::Java(command) => java::java(command).await,
+        Command::List => list(),
+    }
+}
+
+/// Instances of the game directory. It is fine to have none yet.
+fn instances() -> anyhow::Result<Vec<Instance>> {
+    if !std::path::Path::new(INSTANCES_DIR).exists() {
+        return Ok(Vec::new());
+    }
+
+    Ok(load_instances()?)
+}
+
+/// Runs the downloader and returns the number of files that were not downloaded.
+async fn run_downloader(downloader: Box<dyn Downloader<Data = DownloadResult>>) -> anyhow::Result<usize> {
+    let io = downloader.io();
+    let (sender, mut receiver) = tokio::sync::mpsc::channel(100);
+
+    let failed = tokio::spawn(async move {
+        let mut failed = 0;
+        while let Some(DownloadResult(result)) = receiver.recv().await {
+            if let Err(error) = result {
+                warn!(%error, "Download failed");
+                failed += 1;
+            }
         }
-    } else {
-        builder
-            .instance(Inner::vanilla(game_version).await?)
-            .build()
-    };
+        failed
+    });
 
-    instance.download().await?;
+    downloader.download(&sender).await;
+    drop(sender);
 
-    // if !instance.assets.exists() {
-    instance.assets().await?.download().await?;
-    // }
+    io.await?;
 
-    let configs = dir.join(".nomi/configs");
+    Ok(failed.await?)
+}
 
-    let mut profiles: VersionProfilesConfig = if configs.join("Profiles.toml").exists() {
-        read_toml_config(configs.join("Profiles.toml")).await?
-    } else {
-        VersionProfilesConfig { profiles: vec![] }
+/// Runs the downloader and fails if any of the files was not downloaded.
+async fn download_all(downloader: Box<dyn Downloader<Data = DownloadResult>>) -> anyhow::Result<()> {
+    match run_downloader(downloader).await? {
+        0 => Ok(()),
+        failed => Err(Error::General(format!("{failed} files failed to download")).into()),
+    }
+}
+
+pub async fn download(name: &str, version: &str, loader: Option<&Loader>) -> anyhow::Result<()> {
+    let Some(manifest_version) = get_launcher_manifest().await?.find_version(version) else {
+        return Err(Error::General(format!("There is no `{version}` version")).into());
     };
 
-    let settings = LaunchSettings {
-        access_token: None,
-        username: Username::default(),
-        uuid: None,
-        assets: instance.assets.clone(),
-        game_dir: instance.game.clone(),
-        java_bin: JavaRunner::default(),
-        libraries_dir: instance.libraries.clone(),
-        manifest_file: instance.version_path.join(format!("{}.json", game_version)),
-        natives_dir: instance.version_path.join("natives"),
-        version_jar_file: instance.version_path.join(format!("{}.jar", game_version)),
-        version: game_version.to_string(),
-        version_type: "release".into(),
+    let version_type = match manifest_version.version_type.as_str() {
+        "snapshot" => VersionType::Snapshot,
+        _ => VersionType::Release,
     };
 
-    let launch_instance = instance.launch_instance(
-        settings,
-        Some(vec!["-Xms2G".to_string(), "-Xmx4G".to_string()]),
-    );
-    let profile = VersionProfileBuilder::new()
-        .id(profiles.create_id())
-        .instance(launch_instance)
-        .is_downloaded(true)
-        .name(name.to_string())
-        .build();
-    profiles.add_profile(profile);
+    let instance_id = instances()?.iter().map(|instance| instance.id() + 1).max().unwrap_or_default();
+    let mut instance = Instance::new(name, instance_id);
+    let id = instance.next_id();
+    let game_paths = GamePaths::from_id(id);
 
-    write_toml_config(&profiles, confgis.join("Profiles.toml")).await?;
+    let builder = Profile::builder()
+        .name(name.to_owned())
+        .version(version.to_owned())
+        .game_paths(game_paths.clone());
 
-    Ok(())
-}
+    let combined_downloader = VanillaCombinedDownloader::new(version, game_paths.clone()).await?;
 
-pub async fn launch(dir: &Path, profile_id: &i32) -> anyhow::Result<i32> {
-    if !dir.join(".nomi/configs/User.toml").exists()
-        || !dir.join(".nomi/configs/Profiles.toml").exists()
-    {
-        return Err(Error::General(
-            "`User.toml` and `Profiles.toml` not found\nRun `register` and `download` before"
-                .into(),
-        )
-        .into());
+    let profile = match loader {
+        None => builder.downloader(Box::new(combined_downloader)),
+        Some(Loader::Fabric { version }) => {
+            let combined = combined_downloader
+                .with_loader(|game_version, game_paths| Fabric::new(game_version, version.as_ref(), game_paths))
+                .await?;
+            builder.downloader(Box::new(combined))
+        }
     }
+    .build();
 
-    let profiles_cfg: VersionProfilesConfig =
-        read_toml_config(dir.join(".nomi/configs/Profiles.toml")).await?;
+    let settings = LaunchSettings {
+        java_runner: None,
+        version: version.to_owned(),
+        version_type,
+        features: LaunchFeatures::default(),
+    };
 
-    let user_cfg: Settings = read_toml_config(dir.join(".nomi/configs/User.toml")).await?;
+    let launch_instance = profile.launch_instance(settings, Some(vec!["-Xms2G".to_string(), "-Xmx4G".to_string()]));
 
-    let p = profiles_cfg
-        .profiles
-        .into_iter()
-        .find(|p| &p.id == profile_id);
+    download_all(Box::new(profile.assets().await?)).await?;
+    download_all(profile.downloader().into_downloader()).await?;
 
-    let Some(mut profile) = p else {
-        return Err(
-            Error::General("No such profile\nRun `list` to see installed versions".into()).into(),
-        );
+    let config = ProfileConfig {
+        profile: VersionProfile::builder()
+            .id(id)
+            .name(name.to_owned())
+            .state(ProfileState::downloaded(launch_instance))
+            .build(),
+        mods: Vec::new(),
     };
+    config.write().await?;
 
-    /*
-       TODO: `jvm_args` don't works
-    */
+    instance.add_profile(ProfilePayload::from_version_profile(&config.profile, &game_paths.profile_config()));
+    instance.set_main_profile(id);
+    instance.write().await?;
 
-    profile.instance.set_username(user_cfg.username);
-    profile.instance.set_access_token(user_cfg.access_token);
-    profile.instance.set_uuid(user_cfg.uuid);
+    println!("Downloaded `{name}` as {}/{}", id.instance(), id.profile());
 
-    profile.launch().await
+    Ok(())
 }
 
-pub async fn register(
-    dir: &Path,
-    username: &str,
-    access_token: Option<&String>,
-    java_bin: Option<&PathBuf>,
-    uuid: Option<&String>,
-) -> anyhow::Result<()> {
-    let dir = match dir.is_absolute() {
-        true => dir.to_path_buf(),
-        false => {
-            warn!("`GAME_DIR` is not absolute. Adding to the current dir");
-            std::env::current_dir()?.join(dir)
-        }
-    };
+/// Launches the profile and exits with the game's exit code.
+///
+/// The crash report is printed if the game crashed.
+pub async fn launch(id: InstanceProfileId) -> anyhow::Result<()> {
+    let config = ProfileConfig::read(id).await?;
+    let user_data = accounts::user_data_for(&config.profile).await?;
 
-    let settings = Settings {
-        username: Username::new(username)?,
-        access_token: access_token.map(String::from),
-        java_bin: java_bin.map(|p| JavaRunner::path(p.clone())),
-        uuid: uuid.map(String::from),
-    };
+    let mut process = config.profile.launch(GamePaths::from_id(id), user_data, None).await?;
+    process.write_logs(&PrintLogs).await;
+    let status = process.wait().await?;
 
-    write_toml_config(&settings, dir.join(".nomi/configs/User.toml")).await?;
+    if let Some(report) = process.crash_report().await? {
+        eprintln!("{report}");
+    }
 
-    Ok(())
+    // There is no code if the game was killed by a signal.
+    std::process::exit(status.code().unwrap_or(1))
 }
 
-pub async fn list(dir: &Path) -> anyhow::Result<()> {
-    let dir = match dir.is_absolute() {
-        true => dir.to_path_buf(),
-        false => {
-            warn!("`GAME_DIR` is not absolute. Adding to the current dir");
-            std::env::current_dir()?.join(dir)
-        }
+pub async fn repair(id: InstanceProfileId) -> anyhow::Result<()> {
+    let config = ProfileConfig::read(id).await?;
+
+    let ProfileState::Downloaded(instance) = &config.profile.state else {
+        return Err(Error::General("This profile is not downloaded".into()).into());
     };
 
-    if !dir.join(".nomi/configs/User.toml").exists()
-        || !dir.join(".nomi/configs/Profiles.toml").exists()
-    {
-        return Err(Error::General(
-            "`User.toml` and `Profiles.toml` not found\nRun `register` and `download` before"
-                .into(),
-        )
-        .into());
+    let report = verify(&GamePaths::from_id(id), instance).await?;
+
+    if report.is_ok() {
+        println!("All {} files are valid", report.checked);
+        return Ok(());
     }
 
-    let prof: VersionProfilesConfig =
-        read_toml_config(dir.join(".nomi/configs/Profiles.toml")).await?;
+    println!(
+        "Found {} missing and {} corrupted files",
+        report.missing().count(),
+        report.mismatched().count()
+    );
 
-    prof.profiles.iter().for_each(|p| {
-        println!("{}: {}", p.name, p.id);
-    });
+    run_downloader(Box::new(report.repair())).await?;
+
+    // Failed downloads are only counted, so check what is still broken.
+    let report = RepairReport {
+        remaining: report.recheck().await,
+        found: report,
+    };
+
+    println!("{} files were downloaded again", report.repaired());
+
+    if report.remaining.is_ok() {
+        return Ok(());
+    }
+
+    for broken in &report.remaining.broken {
+        let reason = if broken.file.url.is_some() {
+            "download failed"
+        } else {
+            "cannot be downloaded, download the profile again"
+        };
+        eprintln!("  {}: {reason}", broken.file.path.display());
+    }
+
+    Err(Error::General(format!("{} files are still broken", report.remaining.broken.len())).into())
+}
+
+pub fn list() -> anyhow::Result<()> {
+    for instance in instances()? {
+        println!("{}", instance.name());
+
+        for profile in instance.profiles() {
+            let state = if profile.is_downloaded { "" } else { " (not downloaded)" };
+            println!(
+                "  {}/{}: {} {} {}{state}",
+                profile.id.instance(),
+                profile.id.profile(),
+                profile.name,
+                profile.version,
+                profile.loader
+            );
+        }
+    }
 
     Ok(())
 }
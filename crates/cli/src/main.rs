@@ -7,10 +7,15 @@ pub mod commands;
 pub mod error;
 
 #[tokio::main]
-async fn main() {
+async fn main() -> anyhow::Result<()> {
     let sub = tracing_subscriber::fmt().finish();
     tracing::subscriber::set_global_default(sub).unwrap();
 
     let args = Cli::parse();
-    process_args(&args).await.unwrap();
+
+    // Launcher's paths are relative to the game directory.
+    std::fs::create_dir_all(&args.game_dir)?;
+    std::env::set_current_dir(&args.game_dir)?;
+
+    process_args(&args).await
 }
@@ -1,16 +1,24 @@
-use std::{collections::HashSet, sync::Arc};
+use std::{collections::HashSet, path::PathBuf, process::ExitStatus, sync::Arc};
 
 use egui_task_manager::*;
-use nomi_core::{instance::InstanceProfileId, repository::fabric_meta::FabricVersions};
-use nomi_modding::modrinth::{
-    project::{Project, ProjectId},
-    version::Version,
+use itertools::Itertools;
+use nomi_core::{
+    auth::{microsoft::DeviceCode, MicrosoftAccount},
+    configs::account::{Account, AccountStore},
+    fs::write_toml_config_sync,
+    instance::{crash::CrashReport, log_sessions::LogSession, verify::RepairReport, Instance, InstanceProfileId},
+    repository::fabric_meta::FabricVersions,
+    repository::java_installation::JavaInstallation,
+    DOT_NOMI_ACCOUNTS_CONFIG,
 };
+use nomi_modding::source::{ModProject, ModVersion};
+use parking_lot::RwLock;
+use tracing::warn;
 
 use crate::{
     errors_pool::ErrorPoolExt,
     toasts,
-    views::{InstancesConfig, SimpleDependency},
+    views::{InstancesConfig, LoaderVersion, OpenedLogSession, SimpleDependency},
 };
 
 pub struct FabricDataCollection;
@@ -35,6 +43,28 @@ impl<'c> TasksCollection<'c> for FabricDataCollection {
     }
 }
 
+pub struct LoaderVersionsCollection;
+
+impl<'c> TasksCollection<'c> for LoaderVersionsCollection {
+    type Context = &'c mut Vec<LoaderVersion>;
+
+    type Target = Option<Vec<LoaderVersion>>;
+
+    type Executor = executors::Linear;
+
+    fn name() -> &'static str {
+        "Loader versions collection"
+    }
+
+    fn handle(context: Self::Context) -> Handler<'c, Self::Target> {
+        Handler::new(|value| {
+            if let Some(value) = value {
+                *context = value
+            }
+        })
+    }
+}
+
 pub struct AssetsCollection;
 
 impl<'c> TasksCollection<'c> for AssetsCollection {
@@ -73,6 +103,124 @@ impl<'c> TasksCollection<'c> for JavaDownloadingCollection {
     }
 }
 
+pub struct FilesVerificationCollection;
+
+impl<'c> TasksCollection<'c> for FilesVerificationCollection {
+    type Context = ();
+
+    type Target = Option<RepairReport>;
+
+    type Executor = executors::Parallel;
+
+    fn name() -> &'static str {
+        "Files verification collection"
+    }
+
+    fn handle(_context: Self::Context) -> Handler<'c, Self::Target> {
+        Handler::new(|report| {
+            let Some(RepairReport { found, remaining }) = report else {
+                return;
+            };
+
+            if found.is_ok() {
+                toasts::add(|toasts| toasts.success(format!("All {} files are valid", found.checked)));
+                return;
+            }
+
+            let message = format!(
+                "Found {} missing and {} corrupted files, {} of them were downloaded again",
+                found.missing().count(),
+                found.mismatched().count(),
+                found.broken.len() - remaining.broken.len()
+            );
+            toasts::add(|toasts| toasts.success(message));
+
+            if remaining.unrepairable().next().is_some() {
+                toasts::add(|toasts| toasts.warning("Some loader libraries cannot be restored, download the profile again"));
+            }
+
+            let failed = remaining.broken.iter().filter(|broken| broken.file.url.is_some()).collect_vec();
+            for broken in &failed {
+                warn!(path = %broken.file.path.display(), "The file is still broken after the repair");
+            }
+
+            if let Some(first) = failed.first() {
+                let name = first.file.path.file_name().unwrap_or_default().to_string_lossy();
+                let message = match failed.len() {
+                    1 => format!("Could not download `{name}` again"),
+                    count => format!("Could not download `{name}` and {} other files again", count - 1),
+                };
+                toasts::add(|toasts| toasts.error(message));
+            }
+        })
+    }
+}
+
+pub struct ModpackExportCollection;
+
+impl<'c> TasksCollection<'c> for ModpackExportCollection {
+    type Context = ();
+
+    type Target = Option<PathBuf>;
+
+    type Executor = executors::Linear;
+
+    fn name() -> &'static str {
+        "Modpack export collection"
+    }
+
+    fn handle(_context: Self::Context) -> Handler<'c, Self::Target> {
+        Handler::new(|path| {
+            if let Some(path) = path {
+                toasts::add(|toasts| toasts.success(format!("Exported the modpack into {}", path.display())));
+            }
+        })
+    }
+}
+
+pub struct ModpackImportCollection;
+
+impl<'c> TasksCollection<'c> for ModpackImportCollection {
+    type Context = &'c mut InstancesConfig;
+
+    type Target = Option<Instance>;
+
+    type Executor = executors::Linear;
+
+    fn name() -> &'static str {
+        "Modpack import collection"
+    }
+
+    fn handle(context: Self::Context) -> Handler<'c, Self::Target> {
+        Handler::new(|instance: Option<Instance>| {
+            let Some(instance) = instance else {
+                return;
+            };
+
+            toasts::add(|toasts| toasts.success(format!("Imported {}", instance.name())));
+            context.add_instance(instance);
+        })
+    }
+}
+
+pub struct JavaInstallationsCollection;
+
+impl<'c> TasksCollection<'c> for JavaInstallationsCollection {
+    type Context = &'c mut Vec<JavaInstallation>;
+
+    type Target = Vec<JavaInstallation>;
+
+    type Executor = executors::Linear;
+
+    fn name() -> &'static str {
+        "Java installations collection"
+    }
+
+    fn handle(context: Self::Context) -> Handler<'c, Self::Target> {
+        Handler::new(|installations| *context = installations)
+    }
+}
+
 pub struct GameDownloadingCollection;
 
 impl<'c> TasksCollection<'c> for GameDownloadingCollection {
@@ -158,9 +306,9 @@ impl<'c> TasksCollection<'c> for InstanceDeletionCollection {
 pub struct ProjectCollection;
 
 impl<'c> TasksCollection<'c> for ProjectCollection {
-    type Context = &'c mut Option<Project>;
+    type Context = &'c mut Option<ModProject>;
 
-    type Target = Option<Project>;
+    type Target = Option<ModProject>;
 
     type Executor = executors::Linear;
 
@@ -180,9 +328,9 @@ impl<'c> TasksCollection<'c> for ProjectCollection {
 pub struct ProjectVersionsCollection;
 
 impl<'c> TasksCollection<'c> for ProjectVersionsCollection {
-    type Context = &'c mut Vec<Arc<Version>>;
+    type Context = &'c mut Vec<Arc<ModVersion>>;
 
-    type Target = Option<Vec<Version>>;
+    type Target = Option<Vec<ModVersion>>;
 
     type Executor = executors::Linear;
 
@@ -191,7 +339,7 @@ impl<'c> TasksCollection<'c> for ProjectVersionsCollection {
     }
 
     fn handle(context: Self::Context) -> Handler<'c, Self::Target> {
-        Handler::new(|value: Option<Vec<Version>>| {
+        Handler::new(|value: Option<Vec<ModVersion>>| {
             if let Some(value) = value {
                 context.extend(value.into_iter().map(Arc::new));
             }
@@ -202,7 +350,7 @@ impl<'c> TasksCollection<'c> for ProjectVersionsCollection {
 pub struct DependenciesCollection;
 
 impl<'c> TasksCollection<'c> for DependenciesCollection {
-    type Context = (&'c mut Vec<SimpleDependency>, Option<&'c ProjectId>);
+    type Context = (&'c mut Vec<SimpleDependency>, Option<&'c String>);
 
     type Target = Option<Vec<SimpleDependency>>;
 
@@ -248,30 +396,101 @@ impl<'c> TasksCollection<'c> for ModsDownloadingCollection {
     }
 }
 
+pub struct GameExit {
+    pub profile_id: InstanceProfileId,
+    pub status: ExitStatus,
+    pub was_killed: bool,
+    pub crash_report: Option<CrashReport>,
+}
+
 pub struct GameRunnerCollection;
 
 impl<'c> TasksCollection<'c> for GameRunnerCollection {
-    type Context = ();
+    type Context = &'c mut HashSet<InstanceProfileId>;
 
-    type Target = Option<()>;
+    type Target = Option<GameExit>;
 
-    type Executor = executors::Linear;
+    type Executor = executors::Parallel;
 
     fn name() -> &'static str {
         "Game runner collection"
     }
 
-    fn handle(_context: Self::Context) -> Handler<'c, Self::Target> {
-        Handler::new(|_| ())
+    fn handle(context: Self::Context) -> Handler<'c, Self::Target> {
+        Handler::new(|exit| {
+            let Some(exit) = exit else {
+                return;
+            };
+
+            if exit.status.success() || exit.was_killed {
+                return;
+            }
+
+            context.insert(exit.profile_id);
+
+            let message = match exit.status.code() {
+                Some(code) => format!("The game crashed with exit code {code}"),
+                None => String::from("The game crashed"),
+            };
+            toasts::add(|toasts| toasts.error(message));
+
+            if exit.crash_report.is_some() {
+                toasts::add(|toasts| toasts.info("See the crash report in the Logs tab"));
+            }
+        })
+    }
+}
+
+pub struct LogSessionsCollection;
+
+impl<'c> TasksCollection<'c> for LogSessionsCollection {
+    type Context = &'c mut Vec<LogSession>;
+
+    type Target = Option<Vec<LogSession>>;
+
+    type Executor = executors::Linear;
+
+    fn name() -> &'static str {
+        "Log sessions collection"
+    }
+
+    fn handle(context: Self::Context) -> Handler<'c, Self::Target> {
+        Handler::new(|sessions| {
+            if let Some(sessions) = sessions {
+                *context = sessions
+            }
+        })
+    }
+}
+
+pub struct OpenLogSessionCollection;
+
+impl<'c> TasksCollection<'c> for OpenLogSessionCollection {
+    type Context = &'c mut Option<OpenedLogSession>;
+
+    type Target = Option<OpenedLogSession>;
+
+    type Executor = executors::Linear;
+
+    fn name() -> &'static str {
+        "Open log session collection"
+    }
+
+    fn handle(context: Self::Context) -> Handler<'c, Self::Target> {
+        Handler::new(|opened| {
+            if let Some(opened) = opened {
+                *context = Some(opened)
+            }
+        })
     }
 }
 
 pub struct DownloadAddedModsCollection;
 
 impl<'c> TasksCollection<'c> for DownloadAddedModsCollection {
-    type Context = (&'c mut HashSet<ProjectId>, &'c InstancesConfig);
+    type Context = (&'c mut HashSet<String>, &'c InstancesConfig);
 
-    type Target = (InstanceProfileId, ProjectId);
+    type Target = (InstanceProfileId, String);
 
     type Executor = executors::Parallel;
 
@@ -286,3 +505,51 @@ impl<'c> TasksCollection<'c> for DownloadAddedModsCollection {
         })
     }
 }
+
+pub struct DeviceCodeCollection;
+
+impl<'c> TasksCollection<'c> for DeviceCodeCollection {
+    type Context = &'c mut Option<DeviceCode>;
+
+    type Target = Option<DeviceCode>;
+
+    type Executor = executors::Linear;
+
+    fn name() -> &'static str {
+        "Device code collection"
+    }
+
+    fn handle(context: Self::Context) -> Handler<'c, Self::Target> {
+        Handler::new(|value| {
+            if let Some(value) = value {
+                *context = Some(value)
+            }
+        })
+    }
+}
+
+pub struct MicrosoftAuthCollection;
+
+impl<'c> TasksCollection<'c> for MicrosoftAuthCollection {
+    type Context = &'c RwLock<AccountStore>;
+
+    type Target = Option<MicrosoftAccount>;
+
+    type Executor = executors::Linear;
+
+    fn name() -> &'static str {
+        "Microsoft authentication collection"
+    }
+
+    fn handle(context: Self::Context) -> Handler<'c, Self::Target> {
+        Handler::new(|value: Option<MicrosoftAccount>| {
+            if let Some(account) = value {
+                toasts::add(|toasts| toasts.success(format!("Signed in as {}", account.profile.name)));
+
+                let mut store = context.write();
+                store.add(Account::Microsoft(account));
+                write_toml_config_sync(&*store, DOT_NOMI_ACCOUNTS_CONFIG).report_error();
+            }
+        })
+    }
+}
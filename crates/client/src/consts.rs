@@ -1,3 +1,6 @@
 pub const DOT_NOMI_MODS_STASH_DIR: &str = ".nomi/mods_stash";
 pub const NOMI_LOADED_LOCK_FILE: &str = "Loaded.lock";
 pub const NOMI_LOADED_LOCK_FILE_NAME: &str = "Loaded";
+
+/// Azure application id used for the Microsoft authentication.
+pub const MICROSOFT_CLIENT_ID: Option<&str> = option_env!("NOMI_MICROSOFT_CLIENT_ID");
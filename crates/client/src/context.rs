@@ -40,13 +40,16 @@ impl MyContext {
             versions: Vec::new(),
         };
 
+        // Applies the offline mode from the settings before anything is fetched.
+        let states = States::new();
+
         let launcher_manifest_ref = pollster::block_on(get_launcher_manifest()).report_error().unwrap_or(EMPTY_MANIFEST);
 
         Self {
             egui_layer,
             launcher_manifest: launcher_manifest_ref,
             file_dialog: FileDialog::new(),
-            states: States::new(),
+            states,
             manager: TaskManager::new(),
 
             is_allowed_to_take_action: true,
@@ -91,6 +94,7 @@ impl TabViewer for MyContext {
                 profile_info_state: &mut self.states.profile_info,
                 manager: &mut self.manager,
                 settings_state: &self.states.settings,
+                accounts_state: &self.states.accounts,
                 profiles_state: &mut self.states.instances,
                 menu_state: &mut self.states.add_profile_menu,
                 tabs_state: &mut self.states.tabs,
@@ -101,6 +105,7 @@ impl TabViewer for MyContext {
             .ui(ui),
             TabKind::Settings => SettingsPage {
                 java_state: &mut self.states.java,
+                accounts_state: &mut self.states.accounts,
                 manager: &mut self.manager,
                 settings_state: &mut self.states.settings,
                 client_settings_state: &mut self.states.client_settings,
@@ -110,6 +115,8 @@ impl TabViewer for MyContext {
             TabKind::Logs => Logs {
                 egui_layer: &self.egui_layer,
                 logs_state: &mut self.states.logs_state,
+                manager: &mut self.manager,
+                instances: &self.states.instances.instances,
             }
             .ui(ui),
             TabKind::DownloadProgress => {
@@ -132,6 +139,9 @@ impl TabViewer for MyContext {
                 profile: profile.clone(),
                 tabs_state: &mut self.states.tabs,
                 profile_info_state: &mut self.states.profile_info,
+                accounts_state: &self.states.accounts,
+                java_state: &self.states.java,
+                transfers: &self.states.instances.transfers,
             }
             .ui(ui),
         };
@@ -5,35 +5,50 @@ use eframe::egui::Context;
 use egui_task_manager::TaskProgressShared;
 use nomi_core::{
     configs::profile::{Loader, ProfileState},
-    downloads::{progress::MappedSender, traits::Downloader, AssetsDownloader},
-    game_paths::GamePaths,
-    instance::{launch::LaunchSettings, Profile},
+    downloads::{
+        java_runtime::{JavaRuntimeDownloader, JavaRuntimeManager},
+        traits::Downloader,
+        AssetsDownloader,
+    },
+    game_paths::{log_configs_dir, GamePaths},
+    instance::{
+        launch::{features::LaunchFeatures, LaunchInstance, LaunchSettings},
+        verify::{verify, RepairReport},
+        Profile,
+    },
     loaders::{
         combined::VanillaCombinedDownloader,
         fabric::Fabric,
         forge::{Forge, ForgeVersion},
+        neoforge::NeoForge,
+        quilt::Quilt,
     },
-    repository::java_runner::JavaRunner,
+    repository::{java_runner::JavaRunner, manifest::JavaVersion},
     state::get_launcher_manifest,
+    DOT_NOMI_JAVA_DIR,
 };
 use parking_lot::RwLock;
 
-use crate::{errors_pool::ErrorPoolExt, views::ModdedProfile};
+use crate::{errors_pool::ErrorPoolExt, progress::TransferHandle, views::ModdedProfile};
 
 pub async fn task_download_version(
     progress_shared: TaskProgressShared,
     ctx: Context,
+    transfer: TransferHandle,
     profile: Arc<RwLock<ModdedProfile>>,
-    java_runner: JavaRunner,
+    java_runner: Option<JavaRunner>,
 ) -> Option<()> {
-    try_download_version(progress_shared, ctx, profile, java_runner).await.report_error()
+    try_download_version(progress_shared, ctx, transfer, profile, java_runner)
+        .await
+        .report_error()
 }
 
 async fn try_download_version(
     progress_shared: TaskProgressShared,
     ctx: Context,
+    transfer: TransferHandle,
     profile: Arc<RwLock<ModdedProfile>>,
-    java_runner: JavaRunner,
+    java_runner: Option<JavaRunner>,
 ) -> anyhow::Result<()> {
     let launch_instance = {
         let version_profile = {
@@ -58,6 +73,12 @@ async fn try_download_version(
             .game_paths(game_paths.clone());
 
         let combined_downloader = VanillaCombinedDownloader::new(version_profile.version(), game_paths.clone()).await?;
+        let java_runtime = match java_runner {
+            Some(_) => None,
+            None => java_runtime_downloader(&combined_downloader.manifest().java_version).await,
+        };
+        let installer_runner = java_runner.unwrap_or_else(JavaRunner::from_environment);
+
         let instance = match loader {
             Loader::Vanilla => builder.downloader(Box::new(combined_downloader)),
             Loader::Fabric { version } => {
@@ -66,9 +87,22 @@ async fn try_download_version(
                     .await?;
                 builder.downloader(Box::new(combined))
             }
-            Loader::Forge => {
+            Loader::Forge { version } => {
+                let forge_version = version.clone().map_or(ForgeVersion::Recommended, ForgeVersion::Specific);
+                let combined = combined_downloader
+                    .with_loader(|game_version, game_paths| Forge::new(game_version, forge_version, game_paths, installer_runner))
+                    .await?;
+                builder.downloader(Box::new(combined))
+            }
+            Loader::Quilt { version } => {
+                let combined = combined_downloader
+                    .with_loader(|game_version, game_paths| Quilt::new(game_version, version.as_ref(), game_paths))
+                    .await?;
+                builder.downloader(Box::new(combined))
+            }
+            Loader::NeoForge { version } => {
                 let combined = combined_downloader
-                    .with_loader(|game_version, game_paths| Forge::new(game_version, ForgeVersion::Recommended, game_paths, java_runner))
+                    .with_loader(|game_version, game_paths| NeoForge::new(game_version, version.as_ref(), game_paths, installer_runner))
                     .await?;
                 builder.downloader(Box::new(combined))
             }
@@ -79,6 +113,7 @@ async fn try_download_version(
             java_runner: None,
             version: version.to_string(),
             version_type: version_type.clone(),
+            features: LaunchFeatures::default(),
         };
 
         let launch_instance = instance.launch_instance(settings, Some(vec!["-Xms2G".to_string(), "-Xmx4G".to_string()]));
@@ -87,13 +122,21 @@ async fn try_download_version(
         let io = instance.io();
         let downloader = instance.into_downloader();
 
-        let _ = progress_shared.set_total(downloader.total());
+        let _ = progress_shared.set_total(downloader.total() + java_runtime.as_ref().map_or(0, Downloader::total));
 
-        let mapped_sender = MappedSender::new_progress_mapper(Box::new(progress_shared.sender())).with_side_effect(move || ctx.request_repaint());
+        let mapped_sender = transfer
+            .progress_mapper(Box::new(progress_shared.sender()))
+            .with_side_effect(move || ctx.request_repaint());
         downloader.download(&mapped_sender).await;
 
         io.await?;
 
+        if let Some(java_runtime) = java_runtime {
+            let io = java_runtime.io();
+            Box::new(java_runtime).download(&mapped_sender).await;
+            io.await.report_error();
+        }
+
         launch_instance
     };
 
@@ -102,27 +145,93 @@ async fn try_download_version(
     Ok(())
 }
 
-pub async fn task_assets(progress_shared: TaskProgressShared, ctx: Context, version: String, assets_dir: PathBuf) -> Option<()> {
-    try_assets(progress_shared, ctx, version, assets_dir).await.report_error()
+/// Returns a downloader for the Java runtime required by the version if it is not installed yet.
+///
+/// It is used only when no Java is configured. The runtime is not essential for the download
+/// since the launch falls back to the Java found in the environment.
+async fn java_runtime_downloader(java_version: &JavaVersion) -> Option<JavaRuntimeDownloader> {
+    let manager = JavaRuntimeManager::new(DOT_NOMI_JAVA_DIR);
+
+    match manager.installed_runner(java_version).await.report_error()? {
+        Some(_) => None,
+        None => manager.downloader(java_version).await.report_error(),
+    }
+}
+
+/// Verifies the files of the profile and downloads the broken ones again.
+pub async fn task_verify_files(
+    progress_shared: TaskProgressShared,
+    ctx: Context,
+    transfer: TransferHandle,
+    paths: GamePaths,
+    instance: LaunchInstance,
+) -> Option<RepairReport> {
+    try_verify_files(progress_shared, ctx, transfer, paths, instance).await.report_error()
 }
 
-async fn try_assets(progress_shared: TaskProgressShared, ctx: Context, version: String, assets_dir: PathBuf) -> anyhow::Result<()> {
+async fn try_verify_files(
+    progress_shared: TaskProgressShared,
+    ctx: Context,
+    transfer: TransferHandle,
+    paths: GamePaths,
+    instance: LaunchInstance,
+) -> anyhow::Result<RepairReport> {
+    let report = verify(&paths, &instance).await?;
+
+    let downloader = report.repair();
+
+    let _ = progress_shared.set_total(downloader.total());
+
+    let mapped_sender = transfer
+        .progress_mapper(Box::new(progress_shared.sender()))
+        .with_side_effect(move || ctx.request_repaint());
+
+    Box::new(downloader).download(&mapped_sender).await;
+
+    // Failed downloads are only logged, so check what is still broken.
+    let remaining = report.recheck().await;
+
+    Ok(RepairReport { found: report, remaining })
+}
+
+pub async fn task_assets(
+    progress_shared: TaskProgressShared,
+    ctx: Context,
+    transfer: TransferHandle,
+    version: String,
+    assets_dir: PathBuf,
+) -> Option<()> {
+    try_assets(progress_shared, ctx, transfer, version, assets_dir).await.report_error()
+}
+
+async fn try_assets(
+    progress_shared: TaskProgressShared,
+    ctx: Context,
+    transfer: TransferHandle,
+    version: String,
+    assets_dir: PathBuf,
+) -> anyhow::Result<()> {
     let manifest = get_launcher_manifest().await?;
     let version_manifest = manifest.get_version_manifest(version).await?;
 
+    let logging = version_manifest.logging.and_then(|logging| logging.client);
+
     let downloader = AssetsDownloader::new(
         version_manifest.asset_index.url,
         version_manifest.asset_index.id,
         assets_dir.join("objects"),
         assets_dir.join("indexes"),
     )
-    .await?;
+    .await?
+    .with_logging_config(logging.as_ref(), &log_configs_dir(&assets_dir));
 
     let io = downloader.io();
 
     let _ = progress_shared.set_total(downloader.total());
 
-    let mapped_sender = MappedSender::new_progress_mapper(Box::new(progress_shared.sender())).with_side_effect(move || ctx.request_repaint());
+    let mapped_sender = transfer
+        .progress_mapper(Box::new(progress_shared.sender()))
+        .with_side_effect(move || ctx.request_repaint());
 
     Box::new(downloader).download(&mapped_sender).await;
 
@@ -1,7 +1,7 @@
 // Remove console window in release builds
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use collections::{AssetsCollection, GameDownloadingCollection, GameRunnerCollection, JavaDownloadingCollection};
+use collections::{AssetsCollection, GameDownloadingCollection, JavaDownloadingCollection};
 use context::MyContext;
 use eframe::{
     egui::{self, Align, Button, Frame, Id, Layout, RichText, ScrollArea, ViewportBuilder},
@@ -133,10 +133,15 @@ impl eframe::App for MyTabs {
             .manager
             .add_collection::<collections::AssetsCollection>(())
             .add_collection::<collections::FabricDataCollection>(&mut self.context.states.add_profile_menu.fabric_versions)
+            .add_collection::<collections::LoaderVersionsCollection>(&mut self.context.states.add_profile_menu.loader_versions)
             .add_collection::<collections::GameDeletionCollection>(&self.context.states.instances.instances)
             .add_collection::<collections::InstanceDeletionCollection>(&mut self.context.states.instances.instances)
             .add_collection::<collections::GameDownloadingCollection>(&self.context.states.instances.instances)
             .add_collection::<collections::JavaDownloadingCollection>(())
+            .add_collection::<collections::FilesVerificationCollection>(())
+            .add_collection::<collections::ModpackExportCollection>(())
+            .add_collection::<collections::ModpackImportCollection>(&mut self.context.states.instances.instances)
+            .add_collection::<collections::JavaInstallationsCollection>(&mut self.context.states.java.installations)
             .add_collection::<collections::ProjectCollection>(&mut self.context.states.mod_manager.current_project)
             .add_collection::<collections::ProjectVersionsCollection>(&mut self.context.states.mod_manager.current_versions)
             .add_collection::<collections::DependenciesCollection>((
@@ -144,11 +149,15 @@ impl eframe::App for MyTabs {
                 self.context.states.mod_manager.current_project.as_ref().map(|p| &p.id),
             ))
             .add_collection::<collections::ModsDownloadingCollection>(&self.context.states.instances.instances)
-            .add_collection::<collections::GameRunnerCollection>(())
+            .add_collection::<collections::GameRunnerCollection>(&mut self.context.states.instances.crashed_profiles)
+            .add_collection::<collections::LogSessionsCollection>(&mut self.context.states.logs_state.history.sessions)
+            .add_collection::<collections::OpenLogSessionCollection>(&mut self.context.states.logs_state.history.opened)
             .add_collection::<collections::DownloadAddedModsCollection>((
                 &mut self.context.states.profile_info.currently_downloading_mods,
                 &self.context.states.instances.instances,
-            ));
+            ))
+            .add_collection::<collections::DeviceCodeCollection>(&mut self.context.states.accounts.device_code)
+            .add_collection::<collections::MicrosoftAuthCollection>(&self.context.states.accounts.store);
 
         ctx.set_pixels_per_point(self.context.states.client_settings.pixels_per_point);
 
@@ -156,6 +165,10 @@ impl eframe::App for MyTabs {
             self.context.states.java.download_java(&mut self.context.manager, ctx.clone());
         }
 
+        if !self.context.states.java.is_discovery_requested {
+            self.context.states.java.discover_installations(&mut self.context.manager);
+        }
+
         // egui::Window::new("Loaded profiles").show(ctx, |ui| ui_for_loaded_profiles(ui));
 
         egui::TopBottomPanel::top("top_panel_id").show(ctx, |ui| {
@@ -265,6 +278,7 @@ impl eframe::App for MyTabs {
                     CreateInstanceMenu {
                         instances_state: &mut self.context.states.instances,
                         create_instance_menu_state: &mut self.context.states.create_instance_menu,
+                        manager: &mut self.context.manager,
                     }
                     .ui(ui);
                 });
@@ -340,7 +354,6 @@ impl eframe::App for MyTabs {
             manager.get_collection::<AssetsCollection>(),
             manager.get_collection::<JavaDownloadingCollection>(),
             manager.get_collection::<GameDownloadingCollection>(),
-            manager.get_collection::<GameRunnerCollection>(),
         ]
         .iter()
         .all(|c| c.tasks().is_empty());
@@ -1,29 +1,38 @@
 use std::{
+    collections::{HashMap, HashSet},
     ffi::OsStr,
     path::{Path, PathBuf},
     sync::{mpsc::Sender, Arc},
 };
 
+use anyhow::anyhow;
 use eframe::egui::Context;
 use egui_task_manager::{Progress, TaskProgressShared};
 use itertools::Itertools;
 use nomi_core::{
-    calculate_sha1,
-    downloads::{progress::MappedSender, traits::Downloader, DownloadSet, FileDownloader},
-    fs::read_toml_config,
-    instance::{Instance, InstanceProfileId},
+    configs::profile::{Loader, ProfileState, VersionProfile},
+    downloads::{
+        progress::{MappedSender, ProgressSender},
+        traits::{DownloadResult, Downloader},
+        DownloadSet, FileDownloader,
+    },
+    fs::{read_toml_config, write_toml_config},
+    game_paths::GamePaths,
+    instance::{loader::LoaderProfile, Instance, InstanceProfileId, ProfilePayload},
+    modpacks::mrpack::{MrpackArchive, MrpackExporter, MrpackFile},
+    repository::manifest::VersionType,
 };
 use nomi_modding::{
-    modrinth::{
-        project::{ProjectData, ProjectId},
-        version::{ProjectVersionsData, Version, VersionId},
-    },
+    curseforge::{classes, modpack::CurseForgePack, mods::MultipleModsData, ModLoaderType},
+    modrinth::{project::MultipleProjectsData, version::VersionFilesData},
+    source::{ModFile, ModVersion, SourceKind, VersionFilter},
     Query,
 };
 use serde::{Deserialize, Serialize};
 use tokio::{fs::File, io::AsyncWriteExt};
+use tracing::{error, warn};
 
-use crate::{errors_pool::ErrorPoolExt, progress::UnitProgress, DOT_NOMI_MODS_STASH_DIR, NOMI_LOADED_LOCK_FILE, NOMI_LOADED_LOCK_FILE_NAME};
+use crate::{errors_pool::ErrorPoolExt, toasts, views::ModdedProfile, DOT_NOMI_MODS_STASH_DIR, NOMI_LOADED_LOCK_FILE, NOMI_LOADED_LOCK_FILE_NAME};
 
 #[derive(Serialize, Deserialize, Default, PartialEq, Eq, Hash, Debug)]
 #[serde(transparent)]
@@ -33,87 +42,116 @@ pub struct ModsConfig {
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Debug, PartialOrd, Ord, Clone)]
 pub struct Mod {
-    pub project_id: ProjectId,
+    /// Mods that were added before the sources existed come from Modrinth.
+    #[serde(default)]
+    pub source: SourceKind,
+    pub project_id: String,
     pub name: String,
-    pub version_id: VersionId,
+    pub version_id: String,
     pub version_name: Option<String>,
     pub version_number: Option<String>,
     pub is_downloaded: bool,
     pub files: Vec<ModFile>,
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash, Debug, PartialOrd, Ord)]
-pub struct ModFile {
-    pub sha1: String,
-    pub url: String,
-    pub filename: String,
+impl Mod {
+    /// The mod is marked as downloaded.
+    pub fn from_version(name: String, version: &ModVersion) -> Self {
+        Self {
+            source: version.source,
+            project_id: version.project_id.clone(),
+            name,
+            version_id: version.id.clone(),
+            version_name: Some(version.name.clone()),
+            version_number: Some(version.version_number.clone()),
+            is_downloaded: true,
+            files: version.files.clone(),
+        }
+    }
+
+    /// Project ids are only unique within a source.
+    pub fn is_same_project(&self, other: &Mod) -> bool {
+        self.source == other.source && self.project_id == other.project_id
+    }
+
+    /// Resolves the URLs of the files with the mod's source.
+    async fn downloader(&self, dir: &Path) -> anyhow::Result<DownloadSet> {
+        let mut set = DownloadSet::new();
+
+        for file in &self.files {
+            let url = match self.source.source() {
+                Some(source) => source.download_url(&self.project_id, &self.version_id, file).await?,
+                None => file
+                    .url
+                    .clone()
+                    .ok_or_else(|| anyhow!("{} is a local file and cannot be downloaded", file.filename))?,
+            };
+
+            let mut downloader = FileDownloader::new(url, dir.join(&file.filename)).verify_first(true);
+            if let Some(sha1) = &file.sha1 {
+                downloader = downloader.with_sha1(sha1.clone());
+            }
+
+            set.add(Box::new(downloader.into_retry()));
+        }
+
+        Ok(set)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct SimpleDependency {
     pub name: String,
-    pub versions: Vec<Arc<Version>>,
-    pub project_id: ProjectId,
+    pub versions: Vec<Arc<ModVersion>>,
+    pub project_id: String,
     pub is_required: bool,
 }
 
-pub async fn download_added_mod(progress: TaskProgressShared, ctx: Context, target_path: PathBuf, files: Vec<ModFile>) {
-    let _ = progress.set_total(files.len() as u32);
+pub async fn download_added_mod(progress: TaskProgressShared, ctx: Context, target_path: PathBuf, modification: Mod) {
+    let _ = progress.set_total(modification.files.len() as u32);
 
-    let mut set = DownloadSet::new();
-
-    for file in files {
-        let downloader = FileDownloader::new(file.url, target_path.join(file.filename))
-            .with_sha1(file.sha1)
-            .into_retry();
-        set.add(Box::new(downloader));
-    }
+    let Some(set) = modification.downloader(&target_path).await.report_error() else {
+        return;
+    };
 
     let sender = MappedSender::new_progress_mapper(Box::new(progress.sender())).with_side_effect(move || ctx.request_repaint());
 
     Box::new(set).download(&sender).await;
 }
 
-pub async fn get_and_proceed_deps(version: Arc<Version>, game_version: String, loader: String) -> Option<Vec<SimpleDependency>> {
+pub async fn get_and_proceed_deps(version: Arc<ModVersion>, game_version: String, loader: String) -> Option<Vec<SimpleDependency>> {
     let mut deps = Vec::new();
     proceed_deps(&mut deps, version, game_version, loader).await.report_error().map(|_| deps)
 }
 
-pub async fn proceed_deps(dist: &mut Vec<SimpleDependency>, version: Arc<Version>, game_version: String, loader: String) -> anyhow::Result<()> {
-    for dep in &version.dependencies {
-        let query = Query::new(
-            ProjectVersionsData::builder()
-                .id_or_slug(dep.project_id.clone())
-                .game_versions(vec![game_version.clone()])
-                .loaders(vec![loader.clone()])
-                .build(),
-        );
-
-        let data = query.query().await?;
-
-        let versions = data.into_iter().map(Arc::new).collect_vec();
+pub async fn proceed_deps(dist: &mut Vec<SimpleDependency>, version: Arc<ModVersion>, game_version: String, loader: String) -> anyhow::Result<()> {
+    let Some(source) = version.source.source() else {
+        return Ok(());
+    };
 
-        let query = Query::new(ProjectData::new(dep.project_id.clone()));
-        let project = query.query().await?;
+    let filter = VersionFilter {
+        game_versions: vec![game_version],
+        loaders: vec![loader],
+    };
 
+    for dependency in source.dependencies(&version, &filter).await? {
         dist.push(SimpleDependency {
-            name: project.title.clone(),
-            versions: versions.clone(),
-            is_required: dep.dependency_type.as_ref().is_some_and(|d| d == "required") || dep.dependency_type.is_none(),
-            project_id: project.id,
+            name: dependency.project.title,
+            versions: dependency.versions.into_iter().map(Arc::new).collect_vec(),
+            project_id: dependency.project.id,
+            is_required: dependency.required,
         });
     }
 
     Ok(())
 }
 
-pub async fn download_mods(progress: TaskProgressShared, ctx: Context, versions: Vec<(Arc<Version>, PathBuf, String)>) -> anyhow::Result<Vec<Mod>> {
-    let _ = progress.set_total(
-        versions
-            .iter()
-            .map(|v| v.0.files.iter().filter(|f| f.primary).collect::<Vec<_>>().len() as u32)
-            .sum(),
-    );
+pub async fn download_mods(
+    progress: TaskProgressShared,
+    ctx: Context,
+    versions: Vec<(Arc<ModVersion>, PathBuf, String)>,
+) -> anyhow::Result<Vec<Mod>> {
+    let _ = progress.set_total(versions.iter().map(|v| v.0.files.len() as u32).sum());
 
     let mut mods = Vec::new();
     for (version, path, name) in versions {
@@ -124,46 +162,367 @@ pub async fn download_mods(progress: TaskProgressShared, ctx: Context, versions:
     Ok(mods)
 }
 
-pub async fn download_mod(sender: Sender<Box<dyn Progress>>, ctx: Context, dir: PathBuf, name: String, version: Arc<Version>) -> anyhow::Result<Mod> {
-    let mut set = DownloadSet::new();
+pub async fn download_mod(
+    sender: Sender<Box<dyn Progress>>,
+    ctx: Context,
+    dir: PathBuf,
+    name: String,
+    version: Arc<ModVersion>,
+) -> anyhow::Result<Mod> {
+    // We do not download any dependencies. Just the mod.
+    let mod_value = Mod::from_version(name, &version);
+    let set = mod_value.downloader(&dir).await?;
 
-    let mut downloaded_files = Vec::new();
+    let sender = MappedSender::new_progress_mapper(Box::new(sender)).with_side_effect(move || ctx.request_repaint());
 
-    // We do not download any dependencies. Just the mod.
-    for file in version.files.iter().filter(|f| f.primary) {
-        if tokio::fs::read_to_string(dir.join(&file.filename))
-            .await
-            .is_ok_and(|s| calculate_sha1(s) == file.hashes.sha1)
-        {
-            let _ = sender.send(Box::new(UnitProgress));
-            continue;
+    Box::new(set).download(&sender).await;
+
+    Ok(mod_value)
+}
+
+/// Everything that is needed to export a profile as a `.mrpack`.
+pub struct ModpackExport {
+    pub profile_id: InstanceProfileId,
+    pub name: String,
+    pub version_id: String,
+    pub game_version: String,
+    pub loader_profile: Option<LoaderProfile>,
+    pub mods: Vec<Mod>,
+    /// Paths relative to the instance directory.
+    pub overrides: Vec<String>,
+    pub output: PathBuf,
+}
+
+impl ModpackExport {
+    pub async fn export(self) -> anyhow::Result<PathBuf> {
+        let mut exporter = MrpackExporter::new(self.name, self.version_id, &self.game_version, self.loader_profile.as_ref())?;
+
+        let stash = mods_stash_path_for_profile(self.profile_id);
+        for modification in self.mods.iter().filter(|m| m.is_downloaded) {
+            for file in &modification.files {
+                let path = format!("mods/{}", file.filename);
+                let local = stash.join(&file.filename);
+
+                // Modpacks can only link to Modrinth, the other files are put into the overrides.
+                match (modification.source, &file.url) {
+                    (SourceKind::Modrinth, Some(url)) => exporter.add_file(path, local, vec![url.clone()]).await?,
+                    _ => exporter.add_override(path, local)?,
+                }
+            }
         }
 
-        downloaded_files.push(ModFile {
-            sha1: file.hashes.sha1.clone(),
-            url: file.url.clone(),
-            filename: file.filename.clone(),
-        });
+        let instance_path = Instance::path_from_id(self.profile_id.instance());
+        for path in self.overrides {
+            let local = instance_path.join(&path);
+            exporter.add_override(path, local)?;
+        }
+
+        exporter.export(&self.output).await?;
 
-        let downloader = FileDownloader::new(file.url.clone(), dir.join(&file.filename))
-            .with_sha1(file.hashes.sha1.clone())
-            .into_retry();
-        set.add(Box::new(downloader));
+        Ok(self.output)
     }
+}
 
-    let sender = MappedSender::new_progress_mapper(Box::new(sender)).with_side_effect(move || ctx.request_repaint());
+/// Creates a new instance from a `.mrpack` or a CurseForge modpack.
+pub struct ModpackImport {
+    pub instance_id: usize,
+    pub path: PathBuf,
+}
 
-    Box::new(set).download(&sender).await;
+impl ModpackImport {
+    pub async fn import(self, progress: TaskProgressShared, ctx: Context) -> anyhow::Result<Instance> {
+        let is_mrpack = self.path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("mrpack"));
+
+        if is_mrpack {
+            self.import_mrpack(progress, ctx).await
+        } else {
+            self.import_curseforge(progress, ctx).await
+        }
+    }
+
+    async fn import_mrpack(self, progress: TaskProgressShared, ctx: Context) -> anyhow::Result<Instance> {
+        let archive = MrpackArchive::open(&self.path).await?;
+
+        let instance = Instance::new(archive.name(), self.instance_id);
+        let profile = archive.version_profile(instance.next_id(), archive.name())?;
+
+        let instance_path = instance.path();
+        let stash = mods_stash_path_for_profile(profile.id);
+
+        let files = archive.client_files().collect_vec();
+        let _ = progress.set_total(files.len() as u32);
+
+        let mut set = DownloadSet::new();
+        let mut mod_files = Vec::new();
+
+        // Mods are kept in the stash, `load_mods` links them into the mods directory.
+        for file in files {
+            let path = Path::new(&file.path);
+            match (path.parent(), path.file_name()) {
+                (Some(parent), Some(filename)) if parent == Path::new("mods") => {
+                    let target = stash.join(filename);
+                    set.add(Box::new(file.downloader(target.clone())));
+                    mod_files.push((file, target));
+                }
+                _ => {
+                    set.add(Box::new(file.downloader(instance_path.join(path))));
+                }
+            }
+        }
+
+        let sender = MappedSender::new_progress_mapper(Box::new(progress.sender())).with_side_effect(move || ctx.request_repaint());
+        let failed = download_modpack_files(set, &sender).await;
+
+        archive.extract_overrides(&instance_path).await?;
+        stash_mods(&instance_path.join("mods"), &stash).await?;
+
+        let mut profile = ModdedProfile::new(profile);
+        profile.mods.mods = find_modrinth_mods(mod_files.iter().map(|(file, _)| *file)).await;
+        for (mod_value, (_, target)) in profile.mods.mods.iter_mut().zip(&mod_files) {
+            mod_value.is_downloaded = !failed.contains(target);
+        }
+        profile.mods.mods.sort();
+
+        report_failed_downloads(&failed);
+
+        write_imported_instance(instance, profile).await
+    }
+
+    async fn import_curseforge(self, progress: TaskProgressShared, ctx: Context) -> anyhow::Result<Instance> {
+        let pack = CurseForgePack::open(&self.path).await?;
+
+        let loader = match pack.loader() {
+            None => Loader::Vanilla,
+            Some((ModLoaderType::Forge, version)) => Loader::Forge {
+                version: Some(version.to_owned()),
+            },
+            Some((ModLoaderType::Fabric, version)) => Loader::Fabric {
+                version: Some(version.to_owned()),
+            },
+            Some((ModLoaderType::Quilt, version)) => Loader::Quilt {
+                version: Some(version.to_owned()),
+            },
+            Some((ModLoaderType::NeoForge, version)) => Loader::NeoForge {
+                version: Some(version.to_owned()),
+            },
+            Some((loader, _)) => return Err(anyhow!("{loader:?} is not supported")),
+        };
+
+        let instance = Instance::new(pack.name(), self.instance_id);
+        let profile = VersionProfile::builder()
+            .id(instance.next_id())
+            .name(pack.name().to_owned())
+            .state(ProfileState::not_downloaded(pack.game_version().to_owned(), VersionType::Release, loader))
+            .build();
+
+        let instance_path = instance.path();
+        let stash = mods_stash_path_for_profile(profile.id);
+
+        let files = pack.resolve_files().await?;
+
+        let projects = files.iter().map(|file| file.mod_id).unique().collect_vec();
+        let projects = Query::new(MultipleModsData::new(projects))
+            .query()
+            .await?
+            .data
+            .into_iter()
+            .map(|project| (project.id, project))
+            .collect::<HashMap<_, _>>();
+
+        let mut set = DownloadSet::new();
+        let mut restricted = Vec::new();
+        let mut mods = Vec::new();
+
+        for file in files {
+            let project = projects.get(&file.mod_id);
+            let class_id = project.and_then(|project| project.class_id);
+
+            let dir = match class_id {
+                Some(classes::RESOURCE_PACKS) => instance_path.join("resourcepacks"),
+                Some(classes::SHADERS) => instance_path.join("shaderpacks"),
+                _ => stash.clone(),
+            };
+
+            let version = ModVersion::from(file);
+
+            for file in &version.files {
+                let Some(url) = file.url.clone() else {
+                    restricted.push(file.filename.clone());
+                    continue;
+                };
+
+                let mut downloader = FileDownloader::new(url, dir.join(&file.filename)).verify_first(true);
+                if let Some(sha1) = &file.sha1 {
+                    downloader = downloader.with_sha1(sha1.clone());
+                }
+
+                set.add(Box::new(downloader.into_retry()));
+            }
+
+            if !matches!(class_id, Some(classes::RESOURCE_PACKS | classes::SHADERS)) {
+                let name = project.map_or_else(|| version.name.clone(), |project| project.name.clone());
+                mods.push(Mod::from_version(name, &version));
+            }
+        }
+
+        let _ = progress.set_total(set.total());
+
+        let sender = MappedSender::new_progress_mapper(Box::new(progress.sender())).with_side_effect(move || ctx.request_repaint());
+        let failed = download_modpack_files(set, &sender).await;
+
+        pack.extract_overrides(&instance_path).await?;
+        stash_mods(&instance_path.join("mods"), &stash).await?;
+
+        // Restricted files have no url and are never downloaded.
+        for mod_value in &mut mods {
+            mod_value.is_downloaded = mod_value
+                .files
+                .iter()
+                .all(|file| file.url.is_some() && !failed.contains(&stash.join(&file.filename)));
+        }
+
+        if !restricted.is_empty() {
+            warn!(files = ?restricted, "The authors do not allow downloading these files through launchers");
+            toasts::add(|toasts| toasts.warning(format!("Download these files from CurseForge manually: {}", restricted.join(", "))));
+        }
+
+        report_failed_downloads(&failed);
+
+        let mut profile = ModdedProfile::new(profile);
+        profile.mods.mods = mods;
+        profile.mods.mods.sort();
+
+        write_imported_instance(instance, profile).await
+    }
+}
+
+/// Writes the configs of the imported profile and makes it the main one.
+async fn write_imported_instance(mut instance: Instance, profile: ModdedProfile) -> anyhow::Result<Instance> {
+    let path = GamePaths::from_instance_path(instance.path(), profile.profile.id.profile()).profile_config();
+    instance.add_profile(ProfilePayload::from_version_profile(&profile.profile, &path));
+    instance.set_main_profile(profile.profile.id);
+
+    write_toml_config(&profile, path).await?;
+    instance.write().await?;
+
+    Ok(instance)
+}
 
-    Ok(Mod {
-        name,
-        version_id: version.id.clone(),
-        version_name: Some(version.name.clone()),
-        version_number: Some(version.version_number.clone()),
+/// Downloads the files of a modpack and returns the paths of the ones that were not downloaded.
+async fn download_modpack_files(set: DownloadSet, sender: &dyn ProgressSender<DownloadResult>) -> HashSet<PathBuf> {
+    let (helper, mut results) = tokio::sync::mpsc::channel(set.total().max(1) as usize);
+    Box::new(set.with_helper(helper)).download(sender).await;
+
+    let mut failed = HashSet::new();
+    while let Some(DownloadResult(result)) = results.recv().await {
+        if let Err(error) = result {
+            error!(%error, "Cannot download the modpack file");
+            failed.extend(error.path().map(Path::to_path_buf));
+        }
+    }
+
+    failed
+}
+
+fn report_failed_downloads(failed: &HashSet<PathBuf>) {
+    if failed.is_empty() {
+        return;
+    }
+
+    let names = failed
+        .iter()
+        .filter_map(|path| path.file_name())
+        .map(|name| name.to_string_lossy())
+        .sorted()
+        .join(", ");
+    let message = format!("Could not download these files, import the modpack again: {names}");
+    toasts::add(|toasts| toasts.error(message));
+}
+
+/// Moves the mods extracted from the overrides into the stash.
+async fn stash_mods(mods_dir: &Path, stash: &Path) -> anyhow::Result<()> {
+    let mut dir = match tokio::fs::read_dir(mods_dir).await {
+        Ok(dir) => dir,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(error.into()),
+    };
+
+    tokio::fs::create_dir_all(stash).await?;
+
+    while let Some(entry) = dir.next_entry().await? {
+        if entry.file_type().await?.is_file() {
+            tokio::fs::rename(entry.path(), stash.join(entry.file_name())).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Looks up the mods on Modrinth by their hashes, the returned mods are in the same order as `files`.
+///
+/// Mods that are not published on Modrinth are recorded with [`local_mod`].
+async fn find_modrinth_mods<'a>(files: impl Iterator<Item = &'a MrpackFile>) -> Vec<Mod> {
+    let files = files.collect_vec();
+    if files.is_empty() {
+        return Vec::new();
+    }
+
+    let hashes = files.iter().map(|file| file.hashes.sha1.clone()).collect_vec();
+    let mut versions = Query::new(VersionFilesData::new(hashes))
+        .query()
+        .await
+        .inspect_err(|error| warn!(%error, "Cannot find the mods on Modrinth"))
+        .unwrap_or_default();
+
+    let projects = versions.values().map(|version| version.project_id.clone()).unique().collect_vec();
+    let titles: HashMap<_, _> = if projects.is_empty() {
+        HashMap::new()
+    } else {
+        Query::new(MultipleProjectsData::new(projects))
+            .query()
+            .await
+            .inspect_err(|error| warn!(%error, "Cannot get the mods' projects from Modrinth"))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|project| (project.id, project.title))
+            .collect()
+    };
+
+    files
+        .into_iter()
+        .map(|file| {
+            let Some(version) = versions.remove(&file.hashes.sha1) else {
+                return local_mod(file);
+            };
+
+            let name = titles.get(&version.project_id).cloned().unwrap_or_else(|| version.name.clone());
+
+            let mut mod_value = Mod::from_version(name, &ModVersion::from(version));
+            mod_value.files.retain(|f| f.sha1.as_ref() == Some(&file.hashes.sha1));
+            mod_value
+        })
+        .collect()
+}
+
+fn local_mod(file: &MrpackFile) -> Mod {
+    let filename = Path::new(&file.path)
+        .file_name()
+        .map_or_else(|| file.path.clone(), |name| name.to_string_lossy().into_owned());
+
+    Mod {
+        source: SourceKind::Local,
+        project_id: filename.clone(),
+        name: filename.clone(),
+        version_id: file.hashes.sha1.clone(),
+        version_name: None,
+        version_number: None,
         is_downloaded: true,
-        files: downloaded_files,
-        project_id: version.project_id.clone(),
-    })
+        files: vec![ModFile {
+            filename,
+            url: file.downloads.first().cloned(),
+            sha1: Some(file.hashes.sha1.clone()),
+        }],
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -1,9 +1,91 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
 use egui_task_manager::Progress;
+use nomi_core::downloads::{
+    progress::{MappedSender, ProgressSender, TransferProgress, TransferRate},
+    traits::{DownloadResult, DownloadStatus},
+};
+use parking_lot::Mutex;
+
+type TransfersMap = Arc<Mutex<BTreeMap<usize, (String, TransferRate)>>>;
+
+/// Speed of the running downloads.
+#[derive(Default, Clone)]
+pub struct TransfersState {
+    transfers: TransfersMap,
+    next_id: Arc<AtomicUsize>,
+}
 
-pub struct UnitProgress;
+impl TransfersState {
+    /// Registers a transfer that is shown until the returned handle is dropped.
+    pub fn start(&self, name: impl Into<String>) -> TransferHandle {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.transfers.lock().insert(id, (name.into(), TransferRate::new()));
 
-impl Progress for UnitProgress {
-    fn apply(&self, current: &mut u32) {
-        *current += 1;
+        TransferHandle {
+            id,
+            transfers: self.transfers.clone(),
+        }
     }
+
+    /// Returns the name and the rate of every running transfer.
+    pub fn transfers(&self) -> Vec<(String, TransferRate)> {
+        self.transfers.lock().values().cloned().collect()
+    }
+}
+
+pub struct TransferHandle {
+    id: usize,
+    transfers: TransfersMap,
+}
+
+impl TransferHandle {
+    pub fn update(&self, progress: TransferProgress) {
+        if let Some((_, rate)) = self.transfers.lock().get_mut(&self.id) {
+            rate.update(progress, Instant::now());
+        }
+    }
+
+    /// Maps the download results into the task progress and records the transferred bytes.
+    pub fn progress_mapper(self, sender: Box<dyn ProgressSender<Box<dyn Progress>>>) -> MappedSender<DownloadResult, Box<dyn Progress>> {
+        MappedSender::new(sender, move |result: DownloadResult| {
+            if let Ok(DownloadStatus::Transfer(progress)) = &result.0 {
+                self.update(*progress);
+            }
+
+            Box::new(result) as Box<dyn Progress>
+        })
+    }
+}
+
+impl Drop for TransferHandle {
+    fn drop(&mut self) {
+        self.transfers.lock().remove(&self.id);
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn megabytes(bytes: u64) -> f64 {
+    bytes as f64 / 1024.0 / 1024.0
+}
+
+/// `12.3 / 45.6 MB, 7.8 MB/s, 0:04 left`
+pub fn format_transfer(rate: &TransferRate) -> String {
+    let TransferProgress { downloaded, total } = rate.progress();
+    let speed = rate.bytes_per_second() / 1024.0 / 1024.0;
+
+    let mut text = format!("{:.1} / {:.1} MB, {speed:.1} MB/s", megabytes(downloaded), megabytes(total));
+
+    if let Some(eta) = rate.eta().map(|eta| eta.as_secs()) {
+        text.push_str(&format!(", {}:{:02} left", eta / 60, eta % 60));
+    }
+
+    text
 }
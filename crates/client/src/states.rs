@@ -1,24 +1,36 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::Arc};
 
 use eframe::egui::{Context, Ui};
 use egui_task_manager::{Caller, Task, TaskManager};
 use nomi_core::{
-    downloads::{java::JavaDownloader, progress::MappedSender, traits::Downloader},
-    fs::read_toml_config_sync,
-    repository::java_runner::JavaRunner,
-    DOT_NOMI_JAVA_DIR, DOT_NOMI_JAVA_EXECUTABLE, DOT_NOMI_SETTINGS_CONFIG,
+    auth::{microsoft::DeviceCode, Authenticator, MicrosoftAccount},
+    cache::set_offline_mode,
+    configs::account::{Account, AccountStore},
+    downloads::{java::JavaDownloader, traits::Downloader},
+    fs::{read_toml_config_sync, write_toml_config_sync},
+    instance::launch::arguments::UserData,
+    repository::{
+        java_installation::{discover_java_installations, JavaInstallation},
+        java_runner::JavaRunner,
+        username::Username,
+    },
+    DOT_NOMI_ACCOUNTS_CONFIG, DOT_NOMI_AUTH_CACHE, DOT_NOMI_JAVA_DIR, DOT_NOMI_JAVA_EXECUTABLE, DOT_NOMI_SETTINGS_CONFIG,
 };
+use parking_lot::RwLock;
+use serde::Deserialize;
 use tracing::info;
 
 use crate::{
-    collections::JavaDownloadingCollection,
+    collections::{DeviceCodeCollection, JavaDownloadingCollection, JavaInstallationsCollection, MicrosoftAuthCollection},
     errors_pool::ErrorPoolExt,
+    progress::TransfersState,
     views::{
         add_tab_menu::TabsState,
         profiles::InstancesState,
         settings::{ClientSettingsState, SettingsState},
         AddProfileMenuState, CreateInstanceMenuState, LogsState, ModManagerState, ProfileInfoState,
     },
+    MICROSOFT_CLIENT_ID,
 };
 
 pub struct States {
@@ -26,6 +38,7 @@ pub struct States {
 
     pub logs_state: LogsState,
     pub java: JavaState,
+    pub accounts: AccountsState,
     pub instances: InstancesState,
     pub settings: SettingsState,
     pub client_settings: ClientSettingsState,
@@ -38,12 +51,25 @@ pub struct States {
 impl Default for States {
     fn default() -> Self {
         let settings = read_toml_config_sync::<SettingsState>(DOT_NOMI_SETTINGS_CONFIG).unwrap_or_default();
+        set_offline_mode(settings.client_settings.offline_mode);
+        settings.apply_mirrors();
+        settings.apply_network();
+        settings.apply_curseforge();
+
+        let transfers = TransfersState::default();
 
         Self {
             tabs: TabsState::new(),
             logs_state: LogsState::new(),
-            java: JavaState::new(),
-            instances: InstancesState::new(),
+            java: JavaState {
+                transfers: transfers.clone(),
+                ..JavaState::new()
+            },
+            accounts: AccountsState::new(),
+            instances: InstancesState {
+                transfers,
+                ..InstancesState::new()
+            },
             client_settings: settings.client_settings.clone(),
             settings,
             add_profile_menu: AddProfileMenuState::new(),
@@ -63,6 +89,12 @@ impl States {
 #[derive(Default)]
 pub struct JavaState {
     pub is_downloaded: bool,
+
+    /// Java runtimes found on the system.
+    pub installations: Vec<JavaInstallation>,
+    pub is_discovery_requested: bool,
+
+    pub transfers: TransfersState,
 }
 
 impl JavaState {
@@ -70,14 +102,30 @@ impl JavaState {
         let res = std::process::Command::new("java").arg("--version").spawn();
         Self {
             is_downloaded: res.is_ok() || PathBuf::from(DOT_NOMI_JAVA_EXECUTABLE).exists(),
+            installations: Vec::new(),
+            is_discovery_requested: false,
+            transfers: TransfersState::default(),
         }
     }
 
+    pub fn discover_installations(&mut self, manager: &mut TaskManager) {
+        self.is_discovery_requested = true;
+
+        let task = Task::new("Looking for Java installations", Caller::standard(discover_java_installations()));
+        manager.push_task::<JavaInstallationsCollection>(task);
+    }
+
+    pub fn find_installation(&self, runner: &JavaRunner) -> Option<&JavaInstallation> {
+        self.installations.iter().find(|installation| &installation.runner() == runner)
+    }
+
     pub fn download_java(&mut self, manager: &mut TaskManager, ctx: Context) {
         info!("Downloading Java");
 
         self.is_downloaded = true;
 
+        let transfer = self.transfers.start("Java");
+
         let caller = Caller::progressing(|progress| async move {
             let downloader = JavaDownloader::new(PathBuf::from(DOT_NOMI_JAVA_DIR));
 
@@ -85,7 +133,9 @@ impl JavaState {
 
             let io = downloader.io();
 
-            let mapped_sender = MappedSender::new_progress_mapper(Box::new(progress.sender())).with_side_effect(move || ctx.request_repaint());
+            let mapped_sender = transfer
+                .progress_mapper(Box::new(progress.sender()))
+                .with_side_effect(move || ctx.request_repaint());
 
             Box::new(downloader).download(&mapped_sender).await;
 
@@ -99,6 +149,139 @@ impl JavaState {
 
 pub fn download_java_and_update_config(ui: &mut Ui, manager: &mut TaskManager, java_state: &mut JavaState, settings_state: &mut SettingsState) {
     java_state.download_java(manager, ui.ctx().clone());
-    settings_state.java = JavaRunner::path(PathBuf::from(DOT_NOMI_JAVA_EXECUTABLE));
+    settings_state.java = Some(JavaRunner::path(PathBuf::from(DOT_NOMI_JAVA_EXECUTABLE)));
     settings_state.update_config();
 }
+
+/// Settings file used to hold a single offline account before the account store was introduced.
+#[derive(Deserialize)]
+struct LegacyUser {
+    username: String,
+    uuid: String,
+}
+
+pub struct AccountsState {
+    pub store: Arc<RwLock<AccountStore>>,
+
+    /// Device code received from Microsoft but not yet shown to the user.
+    pub device_code: Option<DeviceCode>,
+    /// Device code that the user should enter while the authentication is in progress.
+    pub shown_device_code: Option<DeviceCode>,
+
+    pub offline_username_buf: String,
+}
+
+impl Default for AccountsState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AccountsState {
+    pub fn new() -> Self {
+        let mut store = read_toml_config_sync::<AccountStore>(DOT_NOMI_ACCOUNTS_CONFIG).unwrap_or_default();
+
+        store.migrate_auth_cache(DOT_NOMI_AUTH_CACHE, DOT_NOMI_ACCOUNTS_CONFIG).report_error();
+
+        if store.is_empty() {
+            let legacy = read_toml_config_sync::<LegacyUser>(DOT_NOMI_SETTINGS_CONFIG).ok().and_then(|user| {
+                Username::new(user.username)
+                    .ok()
+                    .map(|username| Account::Offline { username, uuid: user.uuid })
+            });
+
+            store.add(legacy.unwrap_or_else(|| Account::offline(Username::default())));
+            write_toml_config_sync(&store, DOT_NOMI_ACCOUNTS_CONFIG).report_error();
+        }
+
+        Self {
+            store: Arc::new(RwLock::new(store)),
+            device_code: None,
+            shown_device_code: None,
+            offline_username_buf: String::new(),
+        }
+    }
+
+    pub fn authenticator() -> anyhow::Result<Authenticator> {
+        MICROSOFT_CLIENT_ID
+            .map(Authenticator::new)
+            .ok_or_else(|| anyhow::anyhow!("Nomi was built without `NOMI_MICROSOFT_CLIENT_ID`"))
+    }
+
+    pub fn update_config(&self) {
+        write_toml_config_sync(&*self.store.read(), DOT_NOMI_ACCOUNTS_CONFIG).report_error();
+    }
+
+    pub fn add_offline_account(&mut self) {
+        let Some(username) = Username::new(self.offline_username_buf.trim()).report_error() else {
+            return;
+        };
+
+        self.store.write().add(Account::offline(username));
+        self.offline_username_buf.clear();
+        self.update_config();
+    }
+
+    pub fn remove_account(&mut self, id: &str) {
+        self.store.write().remove(id);
+        self.update_config();
+    }
+
+    pub fn set_default(&mut self, id: &str) {
+        self.store.write().set_default(id).report_error();
+        self.update_config();
+    }
+
+    pub fn request_device_code(&self, manager: &mut TaskManager) {
+        let caller = Caller::standard(async { try_request_device_code().await.report_error() });
+
+        manager.push_task::<DeviceCodeCollection>(Task::new("Requesting Microsoft device code", caller));
+    }
+
+    /// Starts waiting for the user to enter the received device code.
+    pub fn wait_for_authorization(&mut self, manager: &mut TaskManager) {
+        let Some(device_code) = self.device_code.take() else {
+            return;
+        };
+
+        self.shown_device_code = Some(device_code.clone());
+
+        let caller = Caller::standard(async move { try_wait_for_authorization(device_code).await.report_error() });
+
+        manager.push_task::<MicrosoftAuthCollection>(Task::new("Signing in with Microsoft", caller));
+    }
+}
+
+async fn try_request_device_code() -> anyhow::Result<DeviceCode> {
+    AccountsState::authenticator()?.request_device_code().await
+}
+
+async fn try_wait_for_authorization(device_code: DeviceCode) -> anyhow::Result<MicrosoftAccount> {
+    AccountsState::authenticator()?.wait_for_authorization(&device_code).await
+}
+
+/// Returns [`UserData`] of the account, refreshing its tokens if needed.
+///
+/// The store is not locked during the refresh. Only the refreshed account is put back,
+/// so the changes made to the store in the meantime are kept.
+pub async fn user_data_for(store: Arc<RwLock<AccountStore>>, account_id: String) -> anyhow::Result<UserData> {
+    let Some(account) = store.read().get(&account_id).cloned() else {
+        anyhow::bail!("There is no account with id `{account_id}`");
+    };
+
+    let authenticator = AccountsState::authenticator().ok();
+    let (user_data, refreshed) = account.user_data(authenticator.as_ref()).await?;
+
+    if let Some(refreshed) = refreshed {
+        let updated = {
+            let mut store = store.write();
+            store.update(refreshed).then(|| store.clone())
+        };
+
+        if let Some(updated) = updated {
+            updated.write(DOT_NOMI_ACCOUNTS_CONFIG).await?;
+        }
+    }
+
+    Ok(user_data)
+}
@@ -7,15 +7,23 @@ use nomi_core::{
     fs::write_toml_config_sync,
     game_paths::GamePaths,
     instance::{Instance, ProfilePayload},
+    loaders::neoforge::NeoForge,
     repository::{
         fabric_meta::{get_fabric_versions, FabricVersions},
         launcher_manifest::{LauncherManifest, Version},
         manifest::VersionType,
+        neoforge_meta,
+        quilt_meta::get_quilt_versions,
     },
 };
 use parking_lot::RwLock;
 
-use crate::{collections::FabricDataCollection, errors_pool::ErrorPoolExt, ui_ext::UiExt, views::ModdedProfile};
+use crate::{
+    collections::{FabricDataCollection, LoaderVersionsCollection},
+    errors_pool::ErrorPoolExt,
+    ui_ext::UiExt,
+    views::ModdedProfile,
+};
 
 use super::{profiles::InstancesState, View};
 
@@ -36,6 +44,14 @@ pub struct AddProfileMenuState {
     selected_loader_buf: Loader,
 
     pub fabric_versions: FabricVersions,
+    /// Quilt or `NeoForge` versions, the newest first.
+    pub loader_versions: Vec<LoaderVersion>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LoaderVersion {
+    pub version: String,
+    pub stable: bool,
 }
 
 impl AddProfileMenuState {
@@ -50,6 +66,56 @@ impl AddProfileMenuState {
         );
         manager.push_task::<FabricDataCollection>(task);
     }
+
+    /// Requests versions of the selected Quilt or `NeoForge` loader.
+    pub fn request_loader_versions(&mut self, manager: &mut TaskManager) {
+        let Some(version) = self.selected_version_buf.as_ref().map(|v| v.id.clone()) else {
+            return;
+        };
+
+        self.loader_versions.clear();
+
+        let task = match self.selected_loader_buf {
+            Loader::Quilt { .. } => Task::new(
+                "Requesting available Quilt versions",
+                Caller::standard(async move {
+                    get_quilt_versions(version)
+                        .await
+                        .map(|versions| {
+                            versions
+                                .into_iter()
+                                .map(|v| LoaderVersion {
+                                    stable: v.loader.is_stable(),
+                                    version: v.loader.version,
+                                })
+                                .collect()
+                        })
+                        .report_error()
+                }),
+            ),
+            Loader::NeoForge { .. } => Task::new(
+                "Requesting available NeoForge versions",
+                Caller::standard(async move {
+                    NeoForge::get_versions(version)
+                        .await
+                        .map(|versions| {
+                            versions
+                                .into_iter()
+                                .rev()
+                                .map(|version| LoaderVersion {
+                                    stable: neoforge_meta::is_stable(&version),
+                                    version,
+                                })
+                                .collect()
+                        })
+                        .report_error()
+                }),
+            ),
+            Loader::Vanilla | Loader::Forge { .. } | Loader::Fabric { .. } => return,
+        };
+
+        manager.push_task::<LoaderVersionsCollection>(task);
+    }
 }
 
 impl Default for AddProfileMenuState {
@@ -69,6 +135,7 @@ impl AddProfileMenuState {
             selected_version_buf: None,
             selected_loader_buf: Loader::Vanilla,
             fabric_versions: Vec::new(),
+            loader_versions: Vec::new(),
         }
     }
 }
@@ -80,7 +147,9 @@ impl View for AddProfileMenu<'_> {
                 && match selected_loader {
                     Loader::Fabric { version } => func(version.as_ref()),
                     Loader::Vanilla => unreachable!(),
-                    Loader::Forge => unreachable!(),
+                    Loader::Forge { .. } => unreachable!(),
+                    Loader::Quilt { .. } => unreachable!(),
+                    Loader::NeoForge { .. } => unreachable!(),
                 }
         }
 
@@ -135,6 +204,10 @@ impl View for AddProfileMenu<'_> {
                             if matches!(self.menu_state.selected_loader_buf, Loader::Fabric { .. }) {
                                 self.menu_state.request_fabric_versions(self.manager)
                             }
+                            if let Loader::Quilt { version } | Loader::NeoForge { version } = &mut self.menu_state.selected_loader_buf {
+                                *version = None;
+                                self.menu_state.request_loader_versions(self.manager)
+                            }
                         }
                     }
                 });
@@ -144,13 +217,19 @@ impl View for AddProfileMenu<'_> {
                     .selected_text(format!("{}", self.menu_state.selected_loader_buf))
                     .show_ui(ui, |ui| {
                         ui.selectable_value(&mut self.menu_state.selected_loader_buf, Loader::Vanilla, "Vanilla");
-                        ui.selectable_value(&mut self.menu_state.selected_loader_buf, Loader::Forge, "Forge");
+                        ui.selectable_value(&mut self.menu_state.selected_loader_buf, Loader::Forge { version: None }, "Forge");
+                        let neoforge = ui.selectable_value(&mut self.menu_state.selected_loader_buf, Loader::NeoForge { version: None }, "NeoForge");
+                        let quilt = ui.selectable_value(&mut self.menu_state.selected_loader_buf, Loader::Quilt { version: None }, "Quilt");
                         let fabric = ui.selectable_value(&mut self.menu_state.selected_loader_buf, Loader::Fabric { version: None }, "Fabric");
 
                         if fabric.clicked() {
                             println!("Test!");
                             self.menu_state.request_fabric_versions(self.manager);
                         }
+
+                        if neoforge.clicked() || quilt.clicked() {
+                            self.menu_state.request_loader_versions(self.manager);
+                        }
                     });
             });
 
@@ -190,6 +269,42 @@ impl View for AddProfileMenu<'_> {
                     ui.label(RichText::new("Fabric is not available for this version").color(ui.visuals().error_fg_color));
                 }
             }
+
+            let loader_name = self.menu_state.selected_loader_buf.to_string();
+            if let Loader::Quilt { version } | Loader::NeoForge { version } = &mut self.menu_state.selected_loader_buf {
+                if !self.menu_state.loader_versions.is_empty() {
+                    egui::ComboBox::from_label(format!("Select {loader_name} version"))
+                        .selected_text(version.as_deref().unwrap_or("Latest"))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(version, None, "Latest")
+                                .on_hover_text("The newest stable version at the moment of downloading");
+
+                            for loader_version in &self.menu_state.loader_versions {
+                                let (stability_text, stability_color) = if loader_version.stable {
+                                    ("stable", Color32::GREEN)
+                                } else {
+                                    ("unstable", ui.visuals().warn_fg_color)
+                                };
+
+                                ui.horizontal(|ui| {
+                                    ui.selectable_value(
+                                        version,
+                                        Some(loader_version.version.clone()),
+                                        RichText::new(&loader_version.version).color(stability_color),
+                                    );
+                                    ui.label(RichText::new("❓").color(stability_color)).on_hover_text(stability_text);
+                                });
+                            }
+                        });
+                } else if !self.manager.get_collection::<LoaderVersionsCollection>().tasks().is_empty() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(format!("Requesting available {loader_name} versions"));
+                    });
+                } else {
+                    ui.label(RichText::new(format!("{loader_name} is not available for this version")).color(ui.visuals().error_fg_color));
+                }
+            }
         }
 
         let some_version_buf = || self.menu_state.selected_version_buf.is_some();
@@ -210,6 +325,7 @@ impl View for AddProfileMenu<'_> {
         };
 
         let fabric_versions_non_empty = || !self.menu_state.fabric_versions.is_empty();
+        let loader_versions_non_empty = || !self.menu_state.loader_versions.is_empty();
 
         if self.menu_state.profile_name_buf.trim().is_empty() {
             ui.error_label("You must enter the profile name");
@@ -232,7 +348,9 @@ impl View for AddProfileMenu<'_> {
                 self.menu_state.parent_instance.is_some()
                     && some_version_buf()
                     && (matches!(self.menu_state.selected_loader_buf, Loader::Vanilla)
-                        || matches!(self.menu_state.selected_loader_buf, Loader::Forge)
+                        || matches!(self.menu_state.selected_loader_buf, Loader::Forge { .. })
+                        || (matches!(self.menu_state.selected_loader_buf, Loader::Quilt { .. } | Loader::NeoForge { .. })
+                            && loader_versions_non_empty())
                         || (fabric_version_is_some() && fabric_versions_non_empty())),
                 egui::Button::new("Create"),
             )
@@ -252,6 +370,7 @@ impl View for AddProfileMenu<'_> {
                             loader: self.menu_state.selected_loader_buf.clone(),
                             version_type: self.menu_state.selected_version_type.clone(),
                         },
+                        account: None,
                     };
 
                     let path = GamePaths::from_instance_path(instance.path(), profile.id.profile()).profile_config();
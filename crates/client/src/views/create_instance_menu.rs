@@ -1,18 +1,21 @@
 use eframe::egui;
+use egui_task_manager::{Caller, Task, TaskManager};
 use nomi_core::instance::Instance;
 
-use crate::{errors_pool::ErrorPoolExt, toasts};
+use crate::{collections::ModpackImportCollection, errors_pool::ErrorPoolExt, toasts};
 
-use super::{InstancesState, View};
+use super::{InstancesState, ModpackImport, View};
 
 pub struct CreateInstanceMenu<'a> {
     pub instances_state: &'a mut InstancesState,
     pub create_instance_menu_state: &'a mut CreateInstanceMenuState,
+    pub manager: &'a mut TaskManager,
 }
 
 #[derive(Default)]
 pub struct CreateInstanceMenuState {
     pub name: String,
+    pub modpack_path: String,
 }
 
 impl CreateInstanceMenuState {
@@ -37,5 +40,30 @@ impl View for CreateInstanceMenu<'_> {
             self.instances_state.instances.update_instance_config(id).report_error();
             toasts::add(|toasts| toasts.success("New instance created"));
         }
+
+        ui.separator();
+
+        ui.label("Import a modpack");
+        egui::TextEdit::singleline(&mut self.create_instance_menu_state.modpack_path)
+            .hint_text("Path to the .mrpack or CurseForge .zip file")
+            .show(ui);
+
+        // The id is taken when the task is created, so only one import can run at a time.
+        let is_importing = !self.manager.get_collection::<ModpackImportCollection>().tasks().is_empty();
+        let path = self.create_instance_menu_state.modpack_path.trim();
+
+        if ui.add_enabled(!path.is_empty() && !is_importing, egui::Button::new("Import")).clicked() {
+            let import = ModpackImport {
+                instance_id: self.instances_state.instances.next_id(),
+                path: path.into(),
+            };
+
+            let ctx = ui.ctx().clone();
+            let task = Task::new(
+                "Importing a modpack",
+                Caller::progressing(move |progress| async move { import.import(progress, ctx).await.report_error() }),
+            );
+            self.manager.push_task::<ModpackImportCollection>(task);
+        }
     }
 }
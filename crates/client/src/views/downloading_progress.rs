@@ -1,5 +1,8 @@
+use eframe::egui::ProgressBar;
 use egui_task_manager::TaskManager;
 
+use crate::progress::format_transfer;
+
 use super::{profiles::InstancesState, View};
 
 pub struct DownloadingProgress<'a> {
@@ -18,6 +21,29 @@ impl View for DownloadingProgress<'_> {
                     });
                 }
             }
+
+            let transfers = self.profiles_state.transfers.transfers();
+
+            if !transfers.is_empty() {
+                ui.separator();
+                ui.heading("Transfers");
+            }
+
+            for (name, rate) in transfers {
+                ui.group(|ui| {
+                    ui.set_width(ui.available_width());
+                    ui.label(name);
+
+                    let progress = rate.progress();
+                    #[allow(clippy::cast_precision_loss)]
+                    let fraction = if progress.total == 0 {
+                        0.0
+                    } else {
+                        progress.downloaded as f32 / progress.total as f32
+                    };
+                    ui.add(ProgressBar::new(fraction).text(format_transfer(&rate)));
+                });
+            }
         });
     }
 }
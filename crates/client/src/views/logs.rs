@@ -1,31 +1,61 @@
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
-use eframe::egui;
-use nomi_core::instance::logs::GameLogsWriter;
+use eframe::egui::{self, Color32, RichText};
+use egui_task_manager::{Caller, Task, TaskManager};
+use nomi_core::instance::{
+    crash::CrashReport,
+    log_sessions::{LogSession, LogSessions},
+    logs::{GameLogsEvent, GameLogsWriter, LogLevel},
+};
 use parking_lot::Mutex;
+use time::{format_description, OffsetDateTime, UtcOffset};
 
-use crate::subscriber::EguiLayer;
+use crate::{
+    collections::{LogSessionsCollection, OpenLogSessionCollection},
+    errors_pool::ErrorPoolExt,
+    subscriber::EguiLayer,
+    ui_ext::UiExt,
+};
 
-use super::View;
+use super::{InstancesConfig, View};
 
 pub struct Logs<'a> {
     pub egui_layer: &'a EguiLayer,
     pub logs_state: &'a mut LogsState,
+    pub manager: &'a mut TaskManager,
+    pub instances: &'a InstancesConfig,
 }
 
 #[derive(Default)]
 pub struct LogsState {
     pub selected_tab: LogsPage,
     pub game_logs: Arc<GameLogs>,
+    pub hidden_levels: HashSet<LogLevel>,
+    pub search: String,
+    pub history: LogsHistory,
 }
 
 #[derive(Default, PartialEq)]
 pub enum LogsPage {
     #[default]
     Game,
+    History,
     Launcher,
 }
 
+/// Sessions of the previous launches saved by [`LogSessions`].
+#[derive(Default)]
+pub struct LogsHistory {
+    pub instance: Option<usize>,
+    pub sessions: Vec<LogSession>,
+    pub opened: Option<OpenedLogSession>,
+}
+
+pub struct OpenedLogSession {
+    pub session: LogSession,
+    pub events: Vec<GameLogsEvent>,
+}
+
 impl LogsState {
     pub fn new() -> Self {
         Self { ..Default::default() }
@@ -37,12 +67,14 @@ impl View for Logs<'_> {
         egui::TopBottomPanel::top("logs_page_panel").show_inside(ui, |ui| {
             ui.horizontal(|ui| {
                 ui.selectable_value(&mut self.logs_state.selected_tab, LogsPage::Game, "Game");
+                ui.selectable_value(&mut self.logs_state.selected_tab, LogsPage::History, "History");
                 ui.selectable_value(&mut self.logs_state.selected_tab, LogsPage::Launcher, "Launcher");
             });
         });
 
         match self.logs_state.selected_tab {
             LogsPage::Game => self.game_ui(ui),
+            LogsPage::History => self.history_ui(ui),
             LogsPage::Launcher => self.launcher_ui(ui),
         }
     }
@@ -50,11 +82,118 @@ impl View for Logs<'_> {
 
 impl Logs<'_> {
     pub fn game_ui(&mut self, ui: &mut egui::Ui) {
-        egui::ScrollArea::both().stick_to_bottom(true).show(ui, |ui| {
-            ui.vertical(|ui| {
-                let lock = self.logs_state.game_logs.logs.lock();
-                for message in lock.iter() {
-                    ui.label(message);
+        if let Some(report) = &*self.logs_state.game_logs.crash_report.lock() {
+            egui::TopBottomPanel::top("crash_report_panel").show_inside(ui, |ui| crash_report_ui(ui, report));
+        }
+
+        self.filter_panel_ui(ui, "game_logs_filter_panel");
+
+        let lock = self.logs_state.game_logs.logs.lock();
+        events_ui(ui, &lock, &self.logs_state.hidden_levels, &self.logs_state.search);
+    }
+
+    pub fn history_ui(&mut self, ui: &mut egui::Ui) {
+        egui::TopBottomPanel::top("logs_history_instance_panel").show_inside(ui, |ui| {
+            ui.horizontal(|ui| {
+                let selected_name = self
+                    .logs_state
+                    .history
+                    .instance
+                    .and_then(|id| self.instances.find_instance(id))
+                    .map_or_else(|| "Select an instance".to_owned(), |instance| instance.read().name().to_owned());
+
+                let previous = self.logs_state.history.instance;
+                egui::ComboBox::from_id_source("logs_history_instance")
+                    .selected_text(selected_name)
+                    .show_ui(ui, |ui| {
+                        for instance in &self.instances.instances {
+                            let instance = instance.read();
+                            ui.selectable_value(&mut self.logs_state.history.instance, Some(instance.id()), instance.name());
+                        }
+                    });
+
+                let is_changed = previous != self.logs_state.history.instance;
+                if is_changed {
+                    self.logs_state.history.sessions.clear();
+                    self.logs_state.history.opened = None;
+                }
+
+                let is_refresh_clicked = ui
+                    .add_enabled(self.logs_state.history.instance.is_some(), egui::Button::new("Refresh"))
+                    .clicked();
+
+                if let Some(id) = self.logs_state.history.instance.filter(|_| is_changed || is_refresh_clicked) {
+                    let task = Task::new(
+                        "Loading logs sessions",
+                        Caller::standard(async move { LogSessions::for_instance(id).list().await.report_error() }),
+                    );
+                    self.manager.push_task::<LogSessionsCollection>(task);
+                }
+            });
+        });
+
+        egui::SidePanel::left("logs_history_sessions_panel").show_inside(ui, |ui| {
+            if self.logs_state.history.instance.is_some() && self.logs_state.history.sessions.is_empty() {
+                ui.label("There are no saved sessions");
+            }
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for session in &self.logs_state.history.sessions {
+                    let is_opened = self.logs_state.history.opened.as_ref().is_some_and(|opened| &opened.session == session);
+
+                    let mut text = format_started_at(session);
+                    if !session.is_compressed() {
+                        text.push_str(" (unfinished)");
+                    }
+
+                    if ui.selectable_label(is_opened, text).clicked() {
+                        let session = session.clone();
+                        let task = Task::new(
+                            "Reading a logs session",
+                            Caller::standard(async move {
+                                let events = session.read().await.report_error()?;
+                                Some(OpenedLogSession { session, events })
+                            }),
+                        );
+                        self.manager.push_task::<OpenLogSessionCollection>(task);
+                    }
+                }
+            });
+        });
+
+        if self.logs_state.history.opened.is_none() {
+            return;
+        }
+
+        self.filter_panel_ui(ui, "logs_history_filter_panel");
+
+        if let Some(opened) = &self.logs_state.history.opened {
+            events_ui(ui, &opened.events, &self.logs_state.hidden_levels, &self.logs_state.search);
+        }
+    }
+
+    fn filter_panel_ui(&mut self, ui: &mut egui::Ui, id: &str) {
+        egui::TopBottomPanel::top(id.to_owned()).show_inside(ui, |ui| {
+            ui.horizontal(|ui| {
+                for level in LogLevel::ALL {
+                    let mut is_shown = !self.logs_state.hidden_levels.contains(&level);
+                    if ui
+                        .checkbox(&mut is_shown, RichText::new(level.as_str()).color(level_color(ui, level)))
+                        .changed()
+                    {
+                        if is_shown {
+                            self.logs_state.hidden_levels.remove(&level);
+                        } else {
+                            self.logs_state.hidden_levels.insert(level);
+                        }
+                    }
+                }
+
+                ui.separator();
+
+                ui.add(egui::TextEdit::singleline(&mut self.logs_state.search).hint_text("Search"));
+                if ui.button("Clear").clicked() {
+                    self.logs_state.search.clear();
                 }
             });
         });
@@ -65,9 +204,102 @@ impl Logs<'_> {
     }
 }
 
+fn level_color(ui: &egui::Ui, level: LogLevel) -> Color32 {
+    let visuals = ui.visuals();
+    match level {
+        LogLevel::Trace | LogLevel::Debug => visuals.weak_text_color(),
+        LogLevel::Info => visuals.text_color(),
+        LogLevel::Warn => visuals.warn_fg_color,
+        LogLevel::Error | LogLevel::Fatal => visuals.error_fg_color,
+    }
+}
+
+fn events_ui(ui: &mut egui::Ui, events: &[GameLogsEvent], hidden_levels: &HashSet<LogLevel>, search: &str) {
+    egui::ScrollArea::both().stick_to_bottom(true).show(ui, |ui| {
+        ui.vertical(|ui| {
+            let search = search.trim();
+
+            let events = events.iter().filter(|event| {
+                let is_level_shown = !event.level().is_some_and(|level| hidden_levels.contains(&level));
+                is_level_shown && (search.is_empty() || event.contains(search))
+            });
+
+            for event in events {
+                event_ui(ui, event);
+            }
+        });
+    });
+}
+
+/// Start time of the session in the local time zone.
+fn format_started_at(session: &LogSession) -> String {
+    let time = OffsetDateTime::from(session.started_at());
+    let time = UtcOffset::current_local_offset().map_or(time, |offset| time.to_offset(offset));
+
+    time.format(&format_description::parse("[year]-[month]-[day] [hour]:[minute]:[second]").unwrap())
+        .unwrap_or_else(|_| time.to_string())
+}
+
+fn event_ui(ui: &mut egui::Ui, event: &GameLogsEvent) {
+    ui.horizontal(|ui| {
+        if let Some(timestamp) = event.timestamp() {
+            ui.label(RichText::new(format!("[{timestamp}]")).weak());
+        }
+        if let Some(thread) = event.thread() {
+            ui.label(RichText::new(format!("[{thread}]")).weak());
+        }
+        if let Some(level) = event.level() {
+            ui.label(RichText::new(level.as_str()).color(level_color(ui, level)).strong());
+        }
+        if let Some(logger) = event.logger() {
+            ui.label(RichText::new(format!("({logger})")).weak());
+        }
+        ui.label(event.message());
+    });
+
+    if let Some(throwable) = event.throwable() {
+        ui.label(RichText::new(throwable).monospace().color(ui.visuals().error_fg_color));
+    }
+}
+
+fn crash_report_ui(ui: &mut egui::Ui, report: &CrashReport) {
+    match report.exit_code {
+        Some(code) => ui.error_label(format!("The game crashed with exit code {code}")),
+        None => ui.error_label("The game crashed"),
+    };
+
+    let mut field = |name: &str, value: &str| {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(name).strong());
+            ui.label(value);
+        });
+    };
+
+    if let Some(description) = &report.description {
+        field("Description:", description);
+    }
+    if let Some(exception) = &report.exception {
+        field("Exception:", exception);
+    }
+    if let Some(java_version) = &report.java_version {
+        field("Java version:", java_version);
+    }
+    if !report.suspected_mods.is_empty() {
+        field("Suspected mods:", &report.suspected_mods.join(", "));
+    }
+    if let Some(path) = report.source.path() {
+        field("Report:", &path.display().to_string());
+    }
+
+    for signature in &report.signatures {
+        ui.warn_label_with_icon_before(signature.to_string());
+    }
+}
+
 #[derive(Default)]
 pub struct GameLogs {
-    logs: Arc<Mutex<Vec<String>>>,
+    logs: Arc<Mutex<Vec<GameLogsEvent>>>,
+    crash_report: Arc<Mutex<Option<CrashReport>>>,
 }
 
 impl GameLogs {
@@ -77,11 +309,16 @@ impl GameLogs {
 
     pub fn clear(&self) {
         self.logs.lock().clear();
+        *self.crash_report.lock() = None;
+    }
+
+    pub fn set_crash_report(&self, report: CrashReport) {
+        *self.crash_report.lock() = Some(report);
     }
 }
 
 impl GameLogsWriter for GameLogs {
-    fn write(&self, data: nomi_core::instance::logs::GameLogsEvent) {
-        self.logs.lock().push(data.into_message());
+    fn write(&self, data: GameLogsEvent) {
+        self.logs.lock().push(data);
     }
 }
@@ -7,6 +7,7 @@ use std::{
 use eframe::egui::{self, Button, Color32, ComboBox, Id, Image, Key, Layout, RichText, ScrollArea, Vec2};
 use egui_infinite_scroll::{InfiniteScroll, LoadingState};
 use egui_task_manager::{Caller, Task, TaskManager};
+use itertools::Itertools;
 use nomi_core::{
     instance::{Instance, InstanceProfileId},
     DOT_NOMI_DATA_PACKS_DIR,
@@ -15,10 +16,9 @@ use nomi_modding::{
     capitalize_first_letters_whitespace_split,
     modrinth::{
         categories::{Categories, CategoriesData, Header},
-        project::{Project, ProjectData},
-        search::{Facets, Hit, InnerPart, Parts, ProjectType, SearchData},
-        version::{ProjectVersionsData, Version},
+        search::ProjectType,
     },
+    source::{ModProject, ModVersion, ProjectKind, SearchQuery, SourceKind, VersionFilter},
     Query,
 };
 use parking_lot::RwLock;
@@ -43,10 +43,11 @@ pub struct ModManager<'a> {
 
 #[derive(Default)]
 pub struct ModManagerState {
-    pub previous_facets: Facets,
-    pub previous_project_type: ProjectType,
+    pub source: SourceKind,
+    /// Search without the entered query, the scroll is reset when it changes.
+    pub previous_filters: Option<(SourceKind, SearchQuery)>,
     pub entered_search: String,
-    pub scroll: InfiniteScroll<Hit, u32>,
+    pub scroll: InfiniteScroll<ModProject, u32>,
     pub categories: Option<Categories>,
     pub current_project_type: ProjectType,
     pub headers: Vec<(Header, ProjectType)>,
@@ -56,9 +57,9 @@ pub struct ModManagerState {
     pub is_datapack: bool,
 
     pub data_pack_path: DataPackDownloadDirectory,
-    pub current_project: Option<Project>,
-    pub current_versions: Vec<Arc<Version>>,
-    pub selected_version: Option<Arc<Version>>,
+    pub current_project: Option<ModProject>,
+    pub current_versions: Vec<Arc<ModVersion>>,
+    pub selected_version: Option<Arc<ModVersion>>,
     pub current_dependencies: Vec<SimpleDependency>,
     pub selected_dependencies: HashMap<String, MaybeAddedDependency>,
 }
@@ -81,7 +82,7 @@ impl DataPackDownloadDirectory {
 }
 
 pub struct MaybeAddedDependency {
-    version: Option<Arc<Version>>,
+    version: Option<Arc<ModVersion>>,
     is_added: bool,
 }
 
@@ -94,13 +95,13 @@ fn fix_svg(text: &str, color: Color32) -> Option<String> {
     Some(format!("<svg xmlns=\"http://www.w3.org/2000/svg\" {s}"))
 }
 
-fn directory_from_project_type(project_type: ProjectType, profile_id: InstanceProfileId) -> PathBuf {
-    match project_type {
-        ProjectType::Mod | ProjectType::Modpack => mods_stash_path_for_profile(profile_id),
-        ProjectType::ResourcePack => Instance::path_from_id(profile_id.instance()).join("resourcepacks"),
-        ProjectType::Shader => Instance::path_from_id(profile_id.instance()).join("shaderpacks"),
-        ProjectType::DataPack => PathBuf::from(DOT_NOMI_DATA_PACKS_DIR),
-        _ => unreachable!("You cannot download plugins"),
+fn directory_from_project_kind(kind: ProjectKind, profile_id: InstanceProfileId) -> PathBuf {
+    match kind {
+        ProjectKind::Mod | ProjectKind::Modpack => mods_stash_path_for_profile(profile_id),
+        ProjectKind::ResourcePack => Instance::path_from_id(profile_id.instance()).join("resourcepacks"),
+        ProjectKind::Shader => Instance::path_from_id(profile_id.instance()).join("shaderpacks"),
+        ProjectKind::DataPack => PathBuf::from(DOT_NOMI_DATA_PACKS_DIR),
+        ProjectKind::Plugin => unreachable!("You cannot download plugins"),
     }
 }
 
@@ -116,12 +117,12 @@ impl ModManagerState {
         Self {
             categories,
             headers,
-            previous_facets: Facets::from_project_type(ProjectType::Mod),
+            source: SourceKind::Modrinth,
+            previous_filters: None,
             selected_categories: HashSet::new(),
             entered_search: String::new(),
             current_project_type: ProjectType::Mod,
-            previous_project_type: ProjectType::Mod,
-            scroll: Self::create_scroll(None, None),
+            scroll: Self::create_scroll(SourceKind::Modrinth, SearchQuery::builder().build()),
             is_download_window_open: false,
             current_project: None,
             current_versions: Vec::new(),
@@ -133,31 +134,24 @@ impl ModManagerState {
         }
     }
 
-    fn create_scroll(facets: Option<Facets>, query: Option<String>) -> InfiniteScroll<Hit, u32> {
+    fn create_scroll(source: SourceKind, search: SearchQuery) -> InfiniteScroll<ModProject, u32> {
         InfiniteScroll::new().end_loader_async(move |cursor| {
-            let facets = facets.clone();
-            let query = query.clone();
+            let mut search = search.clone();
             async move {
-                let offset = cursor.unwrap_or(0);
+                let mod_source = source
+                    .source()
+                    .ok_or_else(|| format!("{} projects cannot be searched", source.as_str()))?;
 
-                let data = SearchData::builder().offset(offset);
-                let mut data = match facets {
-                    Some(f) => data.facets(f).build(),
-                    None => data.build(),
-                };
+                search.offset = cursor.unwrap_or(0);
+                let projects = mod_source.search(&search).await.map_err(|e| format!("{:#?}", e))?;
 
-                data.set_query(query);
-
-                let query = Query::new(data);
-                let search = query.query().await.map_err(|e| format!("{:#?}", e))?;
-
-                Ok((search.hits, Some(offset + 10)))
+                Ok((projects, Some(search.offset + u32::from(search.limit))))
             }
         })
     }
 
-    pub fn update_scroll_with_facets(&mut self, facets: Option<Facets>, query: Option<String>) {
-        self.scroll = Self::create_scroll(facets, query);
+    pub fn update_scroll(&mut self, search: SearchQuery) {
+        self.scroll = Self::create_scroll(self.source, search);
     }
 
     pub fn clear_filter(&mut self) {
@@ -165,22 +159,14 @@ impl ModManagerState {
         self.entered_search = String::new();
     }
 
-    pub fn facets(&self, profile: &ModdedProfile) -> Facets {
-        let mut parts = if matches!(self.current_project_type, ProjectType::Mod) {
-            Parts::new()
-                .part(InnerPart::new().add_category(profile.profile.loader_name().to_lowercase()))
-                .add_project_type(ProjectType::Mod)
-        } else {
-            Parts::from_project_type(self.current_project_type)
-        };
-
-        if !self.selected_categories.is_empty() {
-            parts.add_part(InnerPart::from_vec(
-                self.selected_categories.iter().map(InnerPart::format_category).collect(),
-            ));
+    pub fn search(&self, profile: &ModdedProfile) -> SearchQuery {
+        SearchQuery {
+            query: self.query(),
+            kind: self.current_project_type.into(),
+            loader: Some(profile.profile.loader_name().to_lowercase()),
+            categories: self.selected_categories.iter().cloned().sorted().collect(),
+            ..SearchQuery::builder().build()
         }
-
-        Facets::new(parts)
     }
 
     pub fn query(&self) -> Option<String> {
@@ -192,6 +178,18 @@ impl View for ModManager<'_> {
     fn ui(self, ui: &mut eframe::egui::Ui) {
         egui::TopBottomPanel::top("mod_manager_top_panel").show_inside(ui, |ui| {
             ui.horizontal(|ui| {
+                ComboBox::from_id_source("mod_manager_source")
+                    .selected_text(self.mod_manager_state.source.as_str())
+                    .show_ui(ui, |ui| {
+                        for source in SourceKind::iter().filter(|source| source.source().is_some()) {
+                            if ui.selectable_value(&mut self.mod_manager_state.source, source, source.as_str()).clicked() {
+                                self.mod_manager_state.clear_filter()
+                            }
+                        }
+                    });
+
+                ui.separator();
+
                 for project_type in ProjectType::iter().filter(|t| !matches!(t, ProjectType::Plugin)) {
                     let enabled = {
                         (self.profile.read().profile.loader().support_mods() || matches!(project_type, ProjectType::DataPack))
@@ -239,7 +237,9 @@ impl View for ModManager<'_> {
                         self.mod_manager_state.clear_filter()
                     }
 
-                    if let Some(categories) = &self.mod_manager_state.categories {
+                    if self.mod_manager_state.source != SourceKind::Modrinth {
+                        ui.label("Categories are only available on Modrinth");
+                    } else if let Some(categories) = &self.mod_manager_state.categories {
                         let current = self.mod_manager_state.current_project_type;
                         let project_type = if current == ProjectType::Plugin || current == ProjectType::DataPack {
                             ProjectType::Mod
@@ -279,18 +279,6 @@ impl View for ModManager<'_> {
                                 }
                             }
                         }
-
-                        {
-                            let facets = self.mod_manager_state.facets(&self.profile.read());
-                            let query = self.mod_manager_state.query();
-                            if self.mod_manager_state.previous_facets != facets {
-                                self.mod_manager_state.previous_facets = facets.clone();
-                                self.mod_manager_state.scroll = ModManagerState::create_scroll(Some(facets), query);
-                            } else if self.mod_manager_state.previous_project_type != self.mod_manager_state.current_project_type {
-                                self.mod_manager_state.previous_project_type = self.mod_manager_state.current_project_type;
-                                self.mod_manager_state.scroll = ModManagerState::create_scroll(Some(facets), query);
-                            }
-                        }
                     } else {
                         ui.error_label("Unable to get categories");
                     }
@@ -298,6 +286,21 @@ impl View for ModManager<'_> {
             });
         });
 
+        {
+            let search = self.mod_manager_state.search(&self.profile.read());
+            let filters = (
+                self.mod_manager_state.source,
+                SearchQuery {
+                    query: None,
+                    ..search.clone()
+                },
+            );
+            if self.mod_manager_state.previous_filters.as_ref() != Some(&filters) {
+                self.mod_manager_state.previous_filters = Some(filters);
+                self.mod_manager_state.update_scroll(search);
+            }
+        }
+
         ScrollArea::vertical().show(ui, |ui| {
             ui.set_width(ui.available_width());
 
@@ -308,10 +311,8 @@ impl View for ModManager<'_> {
                     let resp = ui.text_edit_singleline(&mut self.mod_manager_state.entered_search);
 
                     let mut set_query = || {
-                        let facets = self.mod_manager_state.facets(&self.profile.read());
-                        let query = self.mod_manager_state.query();
-
-                        self.mod_manager_state.scroll = ModManagerState::create_scroll(Some(facets), query)
+                        let search = self.mod_manager_state.search(&self.profile.read());
+                        self.mod_manager_state.update_scroll(search)
                     };
 
                     if resp.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
@@ -327,14 +328,16 @@ impl View for ModManager<'_> {
                     ui.group(|ui| {
                         ui.set_width(ui.available_width());
                         ui.horizontal(|ui| {
-                            ui.add(egui::Image::new(&item.icon_url).fit_to_exact_size(Vec2::splat(50.0)));
+                            if let Some(icon) = &item.icon_url {
+                                ui.add(egui::Image::new(icon).fit_to_exact_size(Vec2::splat(50.0)));
+                            }
                             ui.vertical(|ui| {
                                 ui.label(&item.title);
                                 ui.label(&item.description);
 
                                 ui.horizontal(|ui| {
                                     let profile = self.profile.read();
-                                    if profile.mods.mods.iter().any(|m| m.project_id == item.project_id) {
+                                    if profile.mods.mods.iter().any(|m| m.source == item.source && m.project_id == item.id) {
                                         ui.colored_label(Color32::GREEN, "✅")
                                             .on_hover_text("This mod is already downloaded. Downloading it again will replace files.");
                                     }
@@ -352,39 +355,32 @@ impl View for ModManager<'_> {
 
                                         let loader = profile.profile.loader_name().to_lowercase();
 
-                                        let id = item.project_id.clone();
+                                        let source = item.source;
+                                        let id = item.id.clone();
                                         let get_project = Task::new(
                                             "Get project",
-                                            Caller::standard({
-                                                async move {
-                                                    let query = Query::new(ProjectData::new(id.clone()));
-                                                    query.query().await.report_error()
-                                                }
-                                            }),
+                                            Caller::standard(async move { source.source()?.project(&id).await.report_error() }),
                                         );
 
                                         self.task_manager.push_task::<ProjectCollection>(get_project);
 
                                         self.mod_manager_state.current_versions = Vec::new();
 
-                                        let id = item.project_id.clone();
-                                        let base_data = ProjectVersionsData::builder().id_or_slug(id).game_versions(vec![game_version]);
-
-                                        let versions_data = match self.mod_manager_state.current_project_type {
-                                            ProjectType::Mod => base_data.loaders(vec![loader]).build(),
-                                            ProjectType::Modpack => base_data.loaders(vec![loader]).build(),
-                                            ProjectType::ResourcePack => base_data.build(),
-                                            ProjectType::Shader => base_data.build(),
-                                            ProjectType::DataPack => base_data.build(),
+                                        let loaders = match self.mod_manager_state.current_project_type {
+                                            ProjectType::Mod | ProjectType::Modpack => vec![loader],
+                                            ProjectType::ResourcePack | ProjectType::Shader | ProjectType::DataPack => Vec::new(),
                                             ProjectType::Plugin => unreachable!(),
                                         };
 
+                                        let filter = VersionFilter {
+                                            game_versions: vec![game_version],
+                                            loaders,
+                                        };
+
+                                        let id = item.id.clone();
                                         let get_versions = Task::new(
                                             "Get project",
-                                            Caller::standard(async move {
-                                                let query = Query::new(versions_data);
-                                                query.query().await.report_error()
-                                            }),
+                                            Caller::standard(async move { source.source()?.versions(&id, &filter).await.report_error() }),
                                         );
 
                                         self.task_manager.push_task::<ProjectVersionsCollection>(get_versions);
@@ -424,7 +420,14 @@ impl View for ModManager<'_> {
                                     }
                                     ui.vertical(|ui| {
                                         ui.horizontal(|ui| {
-                                            if self.profile.read().mods.mods.iter().any(|m| m.project_id == project.id) {
+                                            if self
+                                                .profile
+                                                .read()
+                                                .mods
+                                                .mods
+                                                .iter()
+                                                .any(|m| m.source == project.source && m.project_id == project.id)
+                                            {
                                                 ui.label(RichText::new("✅").color(Color32::GREEN).heading())
                                                     .on_hover_text("This mod is already downloaded. Downloading it again will replace files.");
                                             }
@@ -487,7 +490,7 @@ impl View for ModManager<'_> {
                                     .mods
                                     .mods
                                     .iter()
-                                    .any(|m| dep.versions.first().is_some_and(|d| m.project_id == d.project_id));
+                                    .any(|m| dep.versions.first().is_some_and(|d| m.source == d.source && m.project_id == d.project_id));
 
                                 let is_added = if is_installed { false } else { dep.is_required };
 
@@ -587,7 +590,7 @@ impl View for ModManager<'_> {
 
                                 let profile = self.profile.clone();
 
-                                let project_type = project.project_type;
+                                let project_kind = project.kind;
 
                                 let _ = self.profiles_config.update_profile_config(self.profile.read().profile.id).report_error();
                                 let is_data_pack = self.mod_manager_state.is_datapack;
@@ -619,7 +622,7 @@ impl View for ModManager<'_> {
                                                     .unwrap_or(DataPackDownloadDirectory::DataPacks)
                                                     .as_path_buf(profile_id)
                                             } else {
-                                                directory_from_project_type(project_type, profile.read().profile.id)
+                                                directory_from_project_kind(project_kind, profile.read().profile.id)
                                             };
 
                                             let data = (version, path, name);
@@ -629,7 +632,7 @@ impl View for ModManager<'_> {
                                         let mods = download_mods(progress, ctx, versions_with_paths).await.report_error();
 
                                         if let Some((mut profile, mods)) = mods.map(|mods| (profile.write(), mods)) {
-                                            if matches!(project_type, ProjectType::Mod) {
+                                            if matches!(project_kind, ProjectKind::Mod) {
                                                 profile.mods.mods.extend(mods);
                                                 profile.mods.mods.sort();
                                                 profile.mods.mods.dedup();
@@ -650,7 +653,7 @@ impl View for ModManager<'_> {
     }
 }
 
-fn get_dependencies(task_manager: &mut TaskManager, profile: &Arc<RwLock<ModdedProfile>>, version: &Arc<Version>) {
+fn get_dependencies(task_manager: &mut TaskManager, profile: &Arc<RwLock<ModdedProfile>>, version: &Arc<ModVersion>) {
     let profile = profile.read();
     let game_version = profile.profile.version().to_owned();
     let loader = profile.profile.loader_name().to_lowercase();
@@ -2,16 +2,32 @@ use std::{collections::HashSet, sync::Arc};
 
 use eframe::egui::{self, Color32, Id, RichText, TextEdit};
 use egui_task_manager::{Caller, Task, TaskManager};
-use nomi_core::configs::profile::ProfileState;
-use nomi_modding::modrinth::project::ProjectId;
+use nomi_core::{
+    configs::profile::ProfileState,
+    game_paths::GamePaths,
+    instance::{
+        launch::features::{LaunchFeatures, QuickPlay, Resolution},
+        Instance,
+    },
+    modpacks::mrpack::override_candidates,
+    repository::{java_runner::JavaRunner, manifest::JavaVersion},
+};
 use parking_lot::RwLock;
 
 use crate::{
-    collections::DownloadAddedModsCollection, errors_pool::ErrorPoolExt, open_directory::open_directory_native, toasts, ui_ext::UiExt,
-    views::InstancesConfig, TabKind,
+    collections::{DownloadAddedModsCollection, FilesVerificationCollection, ModpackExportCollection},
+    download::task_verify_files,
+    errors_pool::ErrorPoolExt,
+    open_directory::open_directory_native,
+    progress::TransfersState,
+    states::{AccountsState, JavaState},
+    toasts,
+    ui_ext::UiExt,
+    views::{settings::java_installation_label, InstancesConfig},
+    TabKind,
 };
 
-use super::{download_added_mod, mods_stash_path_for_profile, Mod, ModdedProfile, TabsState, View};
+use super::{download_added_mod, mods_stash_path_for_profile, Mod, ModdedProfile, ModpackExport, TabsState, View};
 
 pub struct ProfileInfo<'a> {
     pub profiles: &'a InstancesConfig,
@@ -19,13 +35,23 @@ pub struct ProfileInfo<'a> {
     pub profile: Arc<RwLock<ModdedProfile>>,
     pub tabs_state: &'a mut TabsState,
     pub profile_info_state: &'a mut ProfileInfoState,
+    pub accounts_state: &'a AccountsState,
+    pub java_state: &'a JavaState,
+    pub transfers: &'a TransfersState,
 }
 
 #[derive(Default)]
 pub struct ProfileInfoState {
-    pub currently_downloading_mods: HashSet<ProjectId>,
+    /// Project ids of the mods.
+    pub currently_downloading_mods: HashSet<String>,
 
     pub profile_name: String,
+    pub profile_account: Option<String>,
+    /// `None` means that a runtime matching [`Self::profile_java_version`] is used.
+    pub profile_java: Option<JavaRunner>,
+    /// Java version required by the profile's game version.
+    pub profile_java_version: Option<JavaVersion>,
+    pub profile_features: LaunchFeatures,
     pub profile_jvm_args: Vec<String>,
     pub jvm_arg_to_add: String,
 
@@ -36,6 +62,12 @@ pub struct ProfileInfoState {
 
     pub is_export_window_open: bool,
     pub included_mods: Vec<bool>,
+
+    pub is_modpack_window_open: bool,
+    pub modpack_version: String,
+    pub modpack_output: String,
+    /// Files that can be included into the modpack's overrides and whether they are selected.
+    pub modpack_overrides: Vec<(String, bool)>,
 }
 
 impl ProfileInfoState {
@@ -45,7 +77,7 @@ impl ProfileInfoState {
 
     pub fn proceed_mods_import(&mut self, profile: &ModdedProfile) {
         for incoming in &self.mods_to_import {
-            let Some(existing) = profile.mods.mods.iter().find(|m| m.project_id == incoming.project_id) else {
+            let Some(existing) = profile.mods.mods.iter().find(|m| m.is_same_project(incoming)) else {
                 continue;
             };
 
@@ -60,13 +92,99 @@ impl ProfileInfoState {
 
     pub fn set_profile_to_edit(&mut self, profile: &ModdedProfile) {
         self.profile_name.clone_from(&profile.profile.name);
+        self.profile_account.clone_from(&profile.profile.account);
+
+        self.profile_java = None;
+        self.profile_java_version = None;
+        self.profile_features = LaunchFeatures::default();
 
         if let ProfileState::Downloaded(instance) = &profile.profile.state {
             self.profile_jvm_args = instance.jvm_arguments().into();
+            self.profile_java.clone_from(&instance.settings.java_runner);
+            self.profile_features.clone_from(&instance.settings.features);
+
+            let manifest_path = GamePaths::from_id(profile.profile.id).manifest_file(&instance.settings.version);
+            self.profile_java_version = std::fs::read_to_string(manifest_path)
+                .ok()
+                .and_then(|manifest| serde_json::from_str::<ManifestJavaVersion>(&manifest).ok())
+                .map(|manifest| manifest.java_version);
+        }
+    }
+}
+
+fn quick_play_name(quick_play: Option<&QuickPlay>) -> &'static str {
+    match quick_play {
+        None => "Main menu",
+        Some(QuickPlay::Singleplayer(_)) => "World",
+        Some(QuickPlay::Multiplayer(_)) => "Server",
+        Some(QuickPlay::Realms(_)) => "Realm",
+    }
+}
+
+fn launch_features_ui(ui: &mut egui::Ui, features: &mut LaunchFeatures) {
+    ui.horizontal(|ui| {
+        let mut has_custom_resolution = features.resolution.is_some();
+        ui.checkbox(&mut has_custom_resolution, "Custom resolution");
+
+        if !has_custom_resolution {
+            features.resolution = None;
+            return;
         }
+
+        let resolution = features.resolution.get_or_insert(Resolution { width: 854, height: 480 });
+        ui.add(egui::DragValue::new(&mut resolution.width).range(1..=7680));
+        ui.label("x");
+        ui.add(egui::DragValue::new(&mut resolution.height).range(1..=4320));
+    });
+
+    ui.checkbox(&mut features.fullscreen, "Fullscreen");
+    ui.checkbox(&mut features.demo, "Demo mode");
+
+    let selected = quick_play_name(features.quick_play.as_ref());
+
+    ui.horizontal(|ui| {
+        ui.label("Launch into");
+
+        egui::ComboBox::from_id_source("profile_quick_play")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                let variants = [
+                    None,
+                    Some(QuickPlay::Singleplayer(String::new())),
+                    Some(QuickPlay::Multiplayer(String::new())),
+                    Some(QuickPlay::Realms(String::new())),
+                ];
+
+                for variant in variants {
+                    let name = quick_play_name(variant.as_ref());
+                    if ui.selectable_label(selected == name, name).clicked() && selected != name {
+                        features.quick_play = variant;
+                    }
+                }
+            });
+
+        let (hint, target) = match &mut features.quick_play {
+            None => return,
+            Some(QuickPlay::Singleplayer(world)) => ("World directory name", world),
+            Some(QuickPlay::Multiplayer(server)) => ("host:port", server),
+            Some(QuickPlay::Realms(realm)) => ("Realm id", realm),
+        };
+
+        ui.add(TextEdit::singleline(target).hint_text(hint));
+    });
+
+    if features.quick_play.is_some() {
+        ui.small("Old versions can only join a server on start.");
     }
 }
 
+/// Only the part of the manifest that is needed to check the selected Java.
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ManifestJavaVersion {
+    java_version: JavaVersion,
+}
+
 pub struct ImportConflict {
     pub name: String,
     pub existing: Mod,
@@ -170,6 +288,7 @@ fn mod_info_ui(ui: &mut egui::Ui, modification: &Mod) {
     ui.label(&modification.name);
     ui.label(modification.version_name.as_deref().unwrap_or("None"));
     ui.label(modification.version_number.as_deref().unwrap_or("None"));
+    ui.label(modification.source.as_str());
 }
 
 impl View for ProfileInfo<'_> {
@@ -190,13 +309,13 @@ impl View for ProfileInfo<'_> {
                                     .mods
                                     .mods
                                     .iter()
-                                    .find(|m| m.project_id == incoming.project_id && m.version_id != incoming.version_id)
+                                    .find(|m| m.is_same_project(&incoming) && m.version_id != incoming.version_id)
                                 else {
                                     if let Some(modification) = profile
                                         .mods
                                         .mods
                                         .iter()
-                                        .find(|m| m.project_id == incoming.project_id && m.version_id == incoming.version_id)
+                                        .find(|m| m.is_same_project(&incoming) && m.version_id == incoming.version_id)
                                     {
                                         incoming.is_downloaded = modification.is_downloaded;
                                     };
@@ -269,7 +388,7 @@ impl View for ProfileInfo<'_> {
                             let mut lock = self.profile.write();
                             lock.mods.mods.extend(self.profile_info_state.mods_to_import.clone());
                             lock.mods.mods.sort();
-                            lock.mods.mods.dedup_by(|a, b| a.project_id == b.project_id);
+                            lock.mods.mods.dedup_by(|a, b| a.is_same_project(b));
                         }
 
                         {
@@ -338,6 +457,66 @@ impl View for ProfileInfo<'_> {
                 });
             });
 
+        egui::Window::new("Export modpack")
+            .open(&mut self.profile_info_state.is_modpack_window_open)
+            .show(ui.ctx(), |ui| {
+                egui::Grid::new("modpack_export_settings").show(ui, |ui| {
+                    ui.label("Version");
+                    ui.text_edit_singleline(&mut self.profile_info_state.modpack_version);
+                    ui.end_row();
+
+                    ui.label("Output file");
+                    ui.text_edit_singleline(&mut self.profile_info_state.modpack_output);
+                    ui.end_row();
+                });
+
+                ui.label("Include files");
+                egui::ScrollArea::both().max_height(300.0).show(ui, |ui| {
+                    if self.profile_info_state.modpack_overrides.is_empty() {
+                        ui.label("There are no config files");
+                    }
+
+                    for (path, is_included) in &mut self.profile_info_state.modpack_overrides {
+                        ui.checkbox(is_included, path.as_str());
+                    }
+                });
+
+                let profile = self.profile.read();
+                let ProfileState::Downloaded(instance) = &profile.profile.state else {
+                    ui.warn_label("Download the profile to export it");
+                    return;
+                };
+
+                let is_exporting = !self.task_manager.get_collection::<ModpackExportCollection>().tasks().is_empty();
+                let is_valid =
+                    !self.profile_info_state.modpack_version.trim().is_empty() && !self.profile_info_state.modpack_output.trim().is_empty();
+
+                if ui.add_enabled(is_valid && !is_exporting, egui::Button::new("Export")).clicked() {
+                    let export = ModpackExport {
+                        profile_id: profile.profile.id,
+                        name: profile.profile.name.clone(),
+                        version_id: self.profile_info_state.modpack_version.trim().to_owned(),
+                        game_version: profile.profile.version().to_owned(),
+                        loader_profile: instance.loader_profile().cloned(),
+                        mods: profile.mods.mods.clone(),
+                        overrides: self
+                            .profile_info_state
+                            .modpack_overrides
+                            .iter()
+                            .filter(|(_, is_included)| *is_included)
+                            .map(|(path, _)| path.clone())
+                            .collect(),
+                        output: self.profile_info_state.modpack_output.trim().into(),
+                    };
+
+                    let task = Task::new(
+                        format!("Exporting {}", profile.profile.name),
+                        Caller::standard(async move { export.export().await.report_error() }),
+                    );
+                    self.task_manager.push_task::<ModpackExportCollection>(task);
+                }
+            });
+
         egui::ScrollArea::both().auto_shrink([false, true]).show(ui, |ui| {
             ui.heading("Profile");
 
@@ -346,6 +525,72 @@ impl View for ProfileInfo<'_> {
             ui.label("Profile name");
             TextEdit::singleline(&mut self.profile_info_state.profile_name).show(ui);
 
+            ui.label("Account");
+
+            {
+                let store = self.accounts_state.store.read();
+                let selected_text = self
+                    .profile_info_state
+                    .profile_account
+                    .as_deref()
+                    .and_then(|id| store.get(id))
+                    .map_or("Default", |account| account.name());
+
+                egui::ComboBox::from_id_source("profile_account")
+                    .selected_text(selected_text)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.profile_info_state.profile_account, None, "Default");
+                        for account in store.iter() {
+                            ui.selectable_value(
+                                &mut self.profile_info_state.profile_account,
+                                Some(account.id().to_owned()),
+                                account.name(),
+                            );
+                        }
+                    });
+            }
+
+            ui.label("Java");
+
+            let is_downloaded = matches!(self.profile.read().profile.state, ProfileState::Downloaded(_));
+            ui.add_enabled_ui(is_downloaded, |ui| {
+                let selected_text = match &self.profile_info_state.profile_java {
+                    None => String::from("Automatic"),
+                    Some(runner) => self
+                        .java_state
+                        .find_installation(runner)
+                        .map_or_else(|| runner.get_string(), java_installation_label),
+                };
+
+                egui::ComboBox::from_id_source("profile_java")
+                    .selected_text(selected_text)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.profile_info_state.profile_java, None, "Automatic")
+                            .on_hover_text("Use a Java runtime that matches the game version. It will be downloaded if needed.");
+                        for installation in &self.java_state.installations {
+                            ui.selectable_value(
+                                &mut self.profile_info_state.profile_java,
+                                Some(installation.runner()),
+                                java_installation_label(installation),
+                            )
+                            .on_hover_text(installation.path.display().to_string());
+                        }
+                    });
+            });
+
+            if let (Some(runner), Some(java_version)) = (&self.profile_info_state.profile_java, &self.profile_info_state.profile_java_version) {
+                if let Some(installation) = self.java_state.find_installation(runner).filter(|i| !i.supports(java_version)) {
+                    ui.warn_label_with_icon_before(format!(
+                        "Java {} is too old for this profile. It requires Java {} or newer.",
+                        installation.version, java_version.major_version
+                    ));
+                }
+            }
+
+            ui.label("Launch options");
+
+            ui.add_enabled_ui(is_downloaded, |ui| launch_features_ui(ui, &mut self.profile_info_state.profile_features));
+
             ui.label("JVM arguments");
 
             ui.small("Each element should represent only one argument.");
@@ -378,8 +623,11 @@ impl View for ProfileInfo<'_> {
                     {
                         let mut profile = self.profile.write();
                         profile.profile.name.clone_from(&self.profile_info_state.profile_name);
+                        profile.profile.account.clone_from(&self.profile_info_state.profile_account);
                         if let ProfileState::Downloaded(instance) = &mut profile.profile.state {
                             instance.jvm_arguments_mut().clone_from(&self.profile_info_state.profile_jvm_args);
+                            instance.settings.java_runner.clone_from(&self.profile_info_state.profile_java);
+                            instance.settings.features.clone_from(&self.profile_info_state.profile_features);
                         }
 
                         if let Some(instance) = self.profiles.find_instance(profile.profile.id.instance()) {
@@ -401,6 +649,30 @@ impl View for ProfileInfo<'_> {
                 }
             });
 
+            ui.heading("Files");
+
+            let is_verifying = !self.task_manager.get_collection::<FilesVerificationCollection>().tasks().is_empty();
+            ui.add_enabled_ui(is_downloaded && !is_verifying, |ui| {
+                if ui
+                    .button("Verify files")
+                    .on_hover_text("Check the libraries, client jar and assets of the profile and download the missing or corrupted ones again.")
+                    .clicked()
+                {
+                    let profile = self.profile.read();
+                    if let ProfileState::Downloaded(instance) = &profile.profile.state {
+                        let instance = instance.clone();
+                        let paths = GamePaths::from_id(profile.profile.id);
+                        let ctx = ui.ctx().clone();
+                        let transfer = self.transfers.start(format!("Verifying {}", profile.profile.name));
+                        let task = Task::new(
+                            format!("Verifying files ({})", profile.profile.name),
+                            Caller::progressing(move |progress| task_verify_files(progress, ctx, transfer, paths, instance)),
+                        );
+                        self.task_manager.push_task::<FilesVerificationCollection>(task);
+                    }
+                }
+            });
+
             ui.heading("Mods");
 
             ui.add_enabled_ui(self.profile.read().profile.loader().support_mods(), |ui| {
@@ -412,6 +684,24 @@ impl View for ProfileInfo<'_> {
                     self.profile_info_state.included_mods = vec![true; self.profile.read().mods.mods.len()];
                 }
 
+                if ui
+                    .toggle_button(&mut self.profile_info_state.is_modpack_window_open, "Export modpack")
+                    .on_hover_text("Export the profile with its mods and configs as a Modrinth modpack (.mrpack)")
+                    .clicked()
+                {
+                    let profile = self.profile.read();
+                    let instance_path = Instance::path_from_id(profile.profile.id.instance());
+
+                    self.profile_info_state.modpack_version = String::from("1.0.0");
+                    self.profile_info_state.modpack_output = format!("./exports/{}.mrpack", profile.profile.name);
+                    self.profile_info_state.modpack_overrides = override_candidates(&instance_path)
+                        .report_error()
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|path| (path, false))
+                        .collect();
+                }
+
                 if ui
                     .button("Open mods folder")
                     .on_hover_text("Open a folder where mods for this profile are located.")
@@ -457,7 +747,7 @@ impl View for ProfileInfo<'_> {
                                 let no = ui.button("No");
 
                                 if yes.clicked() {
-                                    mods_to_remove.push(m.project_id.clone());
+                                    mods_to_remove.push((m.source, m.project_id.clone()));
                                     let path = mods_stash_path_for_profile(profile_id);
                                     for file in &m.files {
                                         std::fs::remove_file(path.join(&file.filename)).report_error();
@@ -471,13 +761,13 @@ impl View for ProfileInfo<'_> {
                         });
                     } else {
                         let profile_id = self.profile.read().profile.id;
-                        let files = m.files.clone();
+                        let modification = m.clone();
                         let project_id = m.project_id.clone();
                         let ctx = ui.ctx().clone();
                         let download_task = Task::new(
                             "Download mod",
                             Caller::progressing(move |progress| async move {
-                                download_added_mod(progress, ctx, mods_stash_path_for_profile(profile_id), files).await;
+                                download_added_mod(progress, ctx, mods_stash_path_for_profile(profile_id), modification).await;
                                 (profile_id, project_id)
                             }),
                         );
@@ -492,7 +782,7 @@ impl View for ProfileInfo<'_> {
                 }
             });
 
-            vec.retain(|m| !mods_to_remove.contains(&m.project_id));
+            vec.retain(|m| !mods_to_remove.iter().any(|(source, id)| m.source == *source && m.project_id == *id));
             if !mods_to_remove.is_empty() {
                 self.profiles.update_profile_config(self.profile.read().profile.id).report_error();
             }
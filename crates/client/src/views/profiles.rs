@@ -1,4 +1,9 @@
-use std::{collections::HashSet, mem, path::PathBuf, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+    path::PathBuf,
+    sync::Arc,
+};
 
 use anyhow::bail;
 use eframe::egui::{self, Id, RichText, TextWrapMode, Ui};
@@ -9,8 +14,11 @@ use nomi_core::{
     configs::profile::{ProfileState, VersionProfile},
     fs::write_toml_config_sync,
     game_paths::GamePaths,
-    instance::{delete_profile, launch::arguments::UserData, load_instances, Instance, InstanceProfileId, ProfilePayload},
-    repository::{launcher_manifest::LauncherManifest, username::Username},
+    instance::{
+        delete_profile, load_instances, log_sessions::LogSessions, logs::FanOutLogs, process::GameProcess, Instance, InstanceProfileId,
+        ProfilePayload,
+    },
+    repository::launcher_manifest::LauncherManifest,
 };
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
@@ -18,9 +26,11 @@ use tracing::error;
 
 use crate::{
     cache::GLOBAL_CACHE,
-    collections::{AssetsCollection, GameDeletionCollection, GameDownloadingCollection, GameRunnerCollection, InstanceDeletionCollection},
+    collections::{AssetsCollection, GameDeletionCollection, GameDownloadingCollection, GameExit, GameRunnerCollection, InstanceDeletionCollection},
     download::{task_assets, task_download_version},
     errors_pool::ErrorPoolExt,
+    progress::TransfersState,
+    states::{user_data_for, AccountsState},
     toasts,
     ui_ext::UiExt,
     TabKind,
@@ -32,6 +42,7 @@ pub struct Instances<'a> {
     pub is_allowed_to_take_action: bool,
     pub manager: &'a mut TaskManager,
     pub settings_state: &'a SettingsState,
+    pub accounts_state: &'a AccountsState,
     pub profile_info_state: &'a mut ProfileInfoState,
 
     pub logs_state: &'a LogsState,
@@ -44,7 +55,10 @@ pub struct Instances<'a> {
 
 pub struct InstancesState {
     pub currently_downloading_profiles: HashSet<InstanceProfileId>,
+    pub running_profiles: Arc<RwLock<HashMap<InstanceProfileId, GameProcess>>>,
+    pub crashed_profiles: HashSet<InstanceProfileId>,
     pub instances: InstancesConfig,
+    pub transfers: TransfersState,
 }
 
 impl Default for InstancesState {
@@ -57,9 +71,16 @@ impl InstancesState {
     pub fn new() -> Self {
         Self {
             currently_downloading_profiles: HashSet::new(),
+            running_profiles: Arc::new(RwLock::new(HashMap::new())),
+            crashed_profiles: HashSet::new(),
             instances: InstancesConfig::load(),
+            transfers: TransfersState::default(),
         }
     }
+
+    pub fn running_process(&self, id: InstanceProfileId) -> Option<GameProcess> {
+        self.running_profiles.read().get(&id).cloned()
+    }
 }
 
 #[derive(Serialize, Deserialize, Default)]
@@ -142,7 +163,7 @@ impl InstancesConfig {
         };
 
         let profile = profile.read();
-        write_toml_config_sync(&*profile, path)
+        write_toml_config_sync(&*profile, path).map_err(Into::into)
     }
 
     pub fn update_all_instance_configs(&self) -> anyhow::Result<()> {
@@ -161,12 +182,31 @@ impl InstancesConfig {
 
         let instance = instance.read();
 
-        instance.write_blocking()
+        instance.write_blocking().map_err(Into::into)
     }
 }
 
 impl Instances<'_> {
+    /// Shows the "Stop" button if the profile is running.
+    ///
+    /// Returns `true` if the button was shown.
+    fn stop_button_ui(&self, ui: &mut Ui, id: InstanceProfileId) -> bool {
+        let Some(process) = self.profiles_state.running_process(id) else {
+            return false;
+        };
+
+        if ui.button("Stop").on_hover_text("Kill the game process").clicked() {
+            process.start_kill();
+        }
+
+        true
+    }
+
     fn profile_action_ui(&mut self, ui: &mut Ui, profile_payload: &ProfilePayload) {
+        if self.stop_button_ui(ui, profile_payload.id) {
+            return;
+        }
+
         let button = if profile_payload.is_downloaded {
             ui.add_enabled(self.is_allowed_to_take_action, egui::Button::new("Launch"))
         } else {
@@ -190,11 +230,17 @@ impl Instances<'_> {
         let profile = profile_lock.read();
         match &profile.profile.state {
             ProfileState::Downloaded(instance) => {
-                let user_data = UserData {
-                    username: Username::new(self.settings_state.username.clone()).unwrap(),
-                    uuid: Some(self.settings_state.uuid.clone()),
-                    access_token: None,
+                let Some(account_id) = self
+                    .accounts_state
+                    .store
+                    .read()
+                    .account_for(&profile.profile)
+                    .map(|account| account.id().to_owned())
+                else {
+                    toasts::add(|toasts| toasts.error("Add an account in the settings to launch the game"));
+                    return;
                 };
+                let accounts = self.accounts_state.store.clone();
 
                 let instance = instance.clone();
                 let java_runner = self.settings_state.java.clone();
@@ -202,6 +248,9 @@ impl Instances<'_> {
                 let should_load_mods = profile.profile.loader().support_mods();
                 let profile_id = profile.profile.id;
 
+                let running_profiles = self.profiles_state.running_profiles.clone();
+                self.profiles_state.crashed_profiles.remove(&profile_id);
+
                 let game_logs = self.logs_state.game_logs.clone();
                 game_logs.clear();
                 let run_game = Task::new(
@@ -211,10 +260,42 @@ impl Instances<'_> {
                             load_mods(profile_id).await.report_error();
                         }
 
-                        instance
-                            .launch(GamePaths::from_id(profile_id), user_data, &java_runner, &*game_logs)
+                        let user_data = user_data_for(accounts, account_id).await.report_error()?;
+
+                        let mut process = instance
+                            .launch(GamePaths::from_id(profile_id), user_data, java_runner.as_ref())
                             .await
-                            .report_error()
+                            .report_error()?;
+
+                        running_profiles.write().insert(profile_id, process.clone());
+
+                        let file_logs = LogSessions::for_instance(profile_id.instance()).start().await.report_error();
+
+                        let mut writer = FanOutLogs::new().with(&*game_logs);
+                        if let Some(file_logs) = &file_logs {
+                            writer.push(file_logs);
+                        }
+                        process.write_logs(&writer).await;
+                        drop(writer);
+
+                        if let Some(file_logs) = file_logs {
+                            file_logs.finish().await.report_error();
+                        }
+                        let status = process.wait().await.report_error();
+
+                        running_profiles.write().remove(&profile_id);
+
+                        let crash_report = process.crash_report().await.report_error().flatten();
+                        if let Some(report) = &crash_report {
+                            game_logs.set_crash_report(report.clone());
+                        }
+
+                        status.map(|status| GameExit {
+                            profile_id,
+                            status,
+                            was_killed: process.was_killed(),
+                            crash_report,
+                        })
                     }),
                 );
 
@@ -227,9 +308,10 @@ impl Instances<'_> {
 
                 let game_paths = GamePaths::from_id(profile.profile.id);
                 let ctx = ui.ctx().clone();
+                let transfer = self.profiles_state.transfers.start(format!("Assets ({})", profile.profile.version()));
                 let assets_task = Task::new(
                     format!("Assets ({})", profile.profile.version()),
-                    Caller::progressing(|progress| task_assets(progress, ctx, game_version, game_paths.assets)),
+                    Caller::progressing(|progress| task_assets(progress, ctx, transfer, game_version, game_paths.assets)),
                 );
                 self.manager.push_task::<AssetsCollection>(assets_task);
 
@@ -238,10 +320,13 @@ impl Instances<'_> {
                 let id = profile.profile.id;
                 let ctx = ui.ctx().clone();
                 let java_runner = self.settings_state.java.clone();
+                let transfer = self.profiles_state.transfers.start(format!("Version {}", profile.profile.version()));
                 let game_task = Task::new(
                     format!("Downloading version {}", profile.profile.version()),
                     Caller::progressing(move |progress| async move {
-                        task_download_version(progress, ctx, profile_clone, java_runner).await.map(|()| id)
+                        task_download_version(progress, ctx, transfer, profile_clone, java_runner)
+                            .await
+                            .map(|()| id)
                     }),
                 );
                 self.manager.push_task::<GameDownloadingCollection>(game_task);
@@ -252,7 +337,7 @@ impl Instances<'_> {
     fn show_profiles_for_instance(&mut self, ui: &mut Ui, profiles: &[ProfilePayload]) {
         TableBuilder::new(ui)
             .column(Column::auto().at_least(120.0).at_most(240.0))
-            .columns(Column::auto(), 5)
+            .columns(Column::auto(), 6)
             .header(20.0, |mut header| {
                 header.col(|ui| {
                     ui.label("Name");
@@ -278,6 +363,13 @@ impl Instances<'_> {
                         row.col(|ui| {
                             ui.label(profile.loader.name());
                         });
+                        row.col(|ui| {
+                            if self.profiles_state.running_profiles.read().contains_key(&profile.id) {
+                                ui.label("Running");
+                            } else if self.profiles_state.crashed_profiles.contains(&profile.id) {
+                                ui.error_label("Crashed");
+                            }
+                        });
                         row.col(|ui| self.profile_action_ui(ui, profile));
 
                         row.col(|ui| {
@@ -348,6 +440,10 @@ impl View for Instances<'_> {
                         ui.label(RichText::new(instance.name()).strong());
 
                         if let Some(profile_lock) = instance.main_profile().and_then(|id| self.profiles_state.instances.find_profile(id)) {
+                            if self.stop_button_ui(ui, profile_lock.read().profile.id) {
+                                return;
+                            }
+
                             let response = if profile_lock.read().profile.is_downloaded() {
                                 ui.add_enabled(self.is_allowed_to_take_action, egui::Button::new("Launch"))
                             } else {
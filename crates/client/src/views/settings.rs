@@ -1,23 +1,33 @@
-use std::{path::PathBuf, sync::LazyLock};
+use std::path::PathBuf;
 
-use eframe::egui::{self};
+use eframe::egui::{self, Id};
 use egui_file_dialog::FileDialog;
 use egui_form::{garde::field_path, Form, FormField};
 use egui_task_manager::TaskManager;
-use garde::{Error, Validate};
-use nomi_core::{fs::write_toml_config_sync, regex::Regex, repository::java_runner::JavaRunner, Uuid, DOT_NOMI_SETTINGS_CONFIG};
+use garde::Validate;
+use nomi_core::{
+    cache::set_offline_mode,
+    fs::write_toml_config_sync,
+    mirrors::Mirrors,
+    network::{http_client, NetworkConfig},
+    repository::{java_installation::JavaInstallation, java_runner::JavaRunner},
+    DOT_NOMI_SETTINGS_CONFIG,
+};
+use nomi_modding::CurseForgeConfig;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    collections::JavaDownloadingCollection,
+    collections::{DeviceCodeCollection, JavaDownloadingCollection, JavaInstallationsCollection, MicrosoftAuthCollection},
     errors_pool::ErrorPoolExt,
-    states::{download_java_and_update_config, JavaState},
+    states::{download_java_and_update_config, AccountsState, JavaState},
+    ui_ext::UiExt,
 };
 
 use super::View;
 
 pub struct SettingsPage<'a> {
     pub java_state: &'a mut JavaState,
+    pub accounts_state: &'a mut AccountsState,
     pub manager: &'a mut TaskManager,
 
     pub settings_state: &'a mut SettingsState,
@@ -27,30 +37,59 @@ pub struct SettingsPage<'a> {
 
 #[derive(Debug, Validate, Serialize, Deserialize, Clone)]
 pub struct SettingsState {
-    #[garde(custom(check_username))]
-    pub username: String,
-    #[garde(custom(check_uuid))]
-    pub uuid: String,
+    /// Java used for every profile without its own runner.
+    /// If it is not set, a runtime matching the game version is used.
     #[garde(skip)]
-    pub java: JavaRunner,
+    #[serde(default)]
+    pub java: Option<JavaRunner>,
 
     #[garde(skip)]
     pub client_settings: ClientSettingsState,
+
+    #[garde(skip)]
+    #[serde(default)]
+    pub mirrors: Mirrors,
+
+    #[garde(skip)]
+    #[serde(default)]
+    pub network: NetworkConfig,
+
+    #[garde(skip)]
+    #[serde(default)]
+    pub curseforge: CurseForgeConfig,
 }
 
 impl SettingsState {
     pub fn update_config(&self) {
         write_toml_config_sync(&self, DOT_NOMI_SETTINGS_CONFIG).report_error();
     }
+
+    /// Makes every download use the configured mirrors.
+    pub fn apply_mirrors(&self) {
+        self.mirrors.clone().set_global();
+        nomi_modding::set_mirror(self.mirrors.modding.clone());
+    }
+
+    pub fn apply_curseforge(&self) {
+        nomi_modding::set_curseforge_config(self.curseforge.clone());
+    }
+
+    /// Rebuilds the shared HTTP client. Mod queries use the same client.
+    pub fn apply_network(&self) {
+        if self.network.apply().report_error().is_some() {
+            nomi_modding::set_client(http_client());
+        }
+    }
 }
 
 impl Default for SettingsState {
     fn default() -> Self {
         SettingsState {
-            username: "Nomi".to_owned(),
-            uuid: Uuid::new_v4().to_string(),
-            java: JavaRunner::command("java"),
+            java: None,
             client_settings: ClientSettingsState::default(),
+            mirrors: Mirrors::default(),
+            network: NetworkConfig::default(),
+            curseforge: CurseForgeConfig::default(),
         }
     }
 }
@@ -58,35 +97,217 @@ impl Default for SettingsState {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ClientSettingsState {
     pub pixels_per_point: f32,
+    #[serde(default)]
+    pub offline_mode: bool,
 }
 
 impl Default for ClientSettingsState {
     fn default() -> Self {
-        Self { pixels_per_point: 1.5 }
+        Self {
+            pixels_per_point: 1.5,
+            offline_mode: false,
+        }
     }
 }
 
-fn check_username(value: &str, _context: &()) -> garde::Result {
-    static REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[a-zA-Z0-9_]{3,16}$").unwrap());
-
-    REGEX.captures(value).map_or_else(
-        || {
-            Err(Error::new(
-                "
-Invalid username form
-The username cannot be more than 16 letters or less than 3
-You may use:
-A-Z characters, a-z characters, 0-9 numbers, `_` (underscore) symbol
-    ",
-            ))
-        },
-        |_| Ok(()),
-    )
-}
+impl SettingsPage<'_> {
+    fn mirrors_ui(&mut self, ui: &mut egui::Ui) {
+        fn mirror_ui(ui: &mut egui::Ui, label: &str, hint: &str, mirror: &mut Option<String>) {
+            ui.label(label);
+            let mut value = mirror.clone().unwrap_or_default();
+            if ui.add(egui::TextEdit::singleline(&mut value).hint_text(hint)).changed() {
+                *mirror = Some(value).filter(|value| !value.trim().is_empty());
+            }
+            ui.end_row();
+        }
+
+        let mirrors = &mut self.settings_state.mirrors;
+
+        egui::Grid::new("mirrors_grid").num_columns(2).show(ui, |ui| {
+            mirror_ui(ui, "Metadata", "https://piston-meta.mojang.com", &mut mirrors.meta);
+            mirror_ui(ui, "Libraries", "https://libraries.minecraft.net", &mut mirrors.libraries);
+            mirror_ui(ui, "Assets", "https://resources.download.minecraft.net", &mut mirrors.assets);
+            mirror_ui(ui, "Loaders", "https://meta.fabricmc.net", &mut mirrors.loaders);
+            mirror_ui(ui, "Mods", "https://api.modrinth.com", &mut mirrors.modding);
+        });
+
+        ui.label("Mirror's URL replaces the original host, the rest of the path is kept. Leave empty to use the original host");
+    }
+
+    fn network_ui(&mut self, ui: &mut egui::Ui) {
+        let network = &mut self.settings_state.network;
+
+        egui::Grid::new("network_grid").num_columns(2).show(ui, |ui| {
+            ui.label("User agent");
+            ui.text_edit_singleline(&mut network.user_agent);
+            ui.end_row();
+
+            ui.label("Proxy");
+            let mut proxy = network.proxy.clone().unwrap_or_default();
+            if ui
+                .add(egui::TextEdit::singleline(&mut proxy).hint_text("socks5://127.0.0.1:1080"))
+                .changed()
+            {
+                network.proxy = Some(proxy).filter(|proxy| !proxy.trim().is_empty());
+            }
+            ui.end_row();
+
+            ui.label("Connect timeout");
+            ui.add(egui::DragValue::new(&mut network.connect_timeout_secs).range(1..=300).suffix(" s"));
+            ui.end_row();
+
+            ui.label("Read timeout");
+            ui.add(egui::DragValue::new(&mut network.read_timeout_secs).range(1..=600).suffix(" s"));
+            ui.end_row();
+
+            ui.label("Simultaneous downloads");
+            ui.add(egui::DragValue::new(&mut network.max_concurrent_downloads).range(1..=256));
+            ui.end_row();
+
+            ui.label("Downloads per host");
+            ui.add(egui::DragValue::new(&mut network.max_connections_per_host).range(1..=64))
+                .on_hover_text("Limits the files downloaded from the same host at the same time");
+            ui.end_row();
+        });
+    }
+
+    fn curseforge_ui(&mut self, ui: &mut egui::Ui) {
+        let curseforge = &mut self.settings_state.curseforge;
+
+        egui::Grid::new("curseforge_grid").num_columns(2).show(ui, |ui| {
+            ui.label("API key");
+            let mut api_key = curseforge.api_key.clone().unwrap_or_default();
+            if ui.add(egui::TextEdit::singleline(&mut api_key).password(true)).changed() {
+                curseforge.api_key = Some(api_key).filter(|api_key| !api_key.trim().is_empty());
+            }
+            ui.end_row();
+
+            ui.label("Mirror");
+            let mut mirror = curseforge.mirror.clone().unwrap_or_default();
+            if ui
+                .add(egui::TextEdit::singleline(&mut mirror).hint_text("https://api.curseforge.com"))
+                .changed()
+            {
+                curseforge.mirror = Some(mirror).filter(|mirror| !mirror.trim().is_empty());
+            }
+            ui.end_row();
+        });
+
+        ui.label("The key is required to import CurseForge modpacks. It can be generated in the CurseForge for Studios console");
+    }
+
+    fn java_installations_ui(&mut self, ui: &mut egui::Ui) {
+        let is_discovering = !self.manager.get_collection::<JavaInstallationsCollection>().tasks().is_empty();
 
-fn check_uuid(value: &str, _context: &()) -> garde::Result {
-    static REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$").unwrap());
-    REGEX.captures(value).map_or_else(|| Err(Error::new("Invalid UUID")), |_| Ok(()))
+        ui.horizontal(|ui| {
+            let selected_text = self
+                .settings_state
+                .java
+                .as_ref()
+                .and_then(|runner| self.java_state.find_installation(runner))
+                .map_or_else(|| String::from("No installation selected"), java_installation_label);
+
+            egui::ComboBox::from_label("Detected installations")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    for installation in &self.java_state.installations {
+                        ui.selectable_value(
+                            &mut self.settings_state.java,
+                            Some(installation.runner()),
+                            java_installation_label(installation),
+                        )
+                        .on_hover_text(installation.path.display().to_string());
+                    }
+                });
+
+            if is_discovering {
+                ui.spinner();
+            } else if ui.button("Refresh").clicked() {
+                self.java_state.discover_installations(self.manager);
+            }
+        });
+    }
+
+    fn accounts_ui(&mut self, ui: &mut egui::Ui) {
+        let accounts = self.accounts_state.store.read().clone();
+
+        egui::Grid::new("accounts_grid").num_columns(4).show(ui, |ui| {
+            for account in accounts.iter() {
+                let is_default = accounts.default.as_deref() == Some(account.id());
+                if ui.radio(is_default, "").on_hover_text("Use by default").clicked() && !is_default {
+                    self.accounts_state.set_default(account.id());
+                }
+
+                ui.label(account.name());
+                ui.label(if account.is_online() { "Microsoft" } else { "Offline" });
+
+                ui.button_with_confirm_popup(Id::new("remove_account").with(account.id()), "Remove", |ui| {
+                    ui.label(format!("Are you sure you want to remove {}?", account.name()));
+                    ui.horizontal(|ui| {
+                        let yes_button = ui.button("Yes");
+                        let no_button = ui.button("No");
+
+                        if yes_button.clicked() {
+                            self.accounts_state.remove_account(account.id());
+                        }
+
+                        if yes_button.clicked() || no_button.clicked() {
+                            ui.memory_mut(|mem| mem.close_popup());
+                        }
+                    });
+                });
+
+                ui.end_row();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.accounts_state.offline_username_buf);
+            if ui
+                .add_enabled(
+                    !self.accounts_state.offline_username_buf.trim().is_empty(),
+                    egui::Button::new("Add offline account"),
+                )
+                .clicked()
+            {
+                self.accounts_state.add_offline_account();
+            }
+        });
+
+        if self.accounts_state.device_code.is_some() {
+            self.accounts_state.wait_for_authorization(self.manager);
+        }
+
+        let is_authenticating = !self.manager.get_collection::<MicrosoftAuthCollection>().tasks().is_empty();
+
+        match &self.accounts_state.shown_device_code {
+            Some(device_code) if is_authenticating => {
+                ui.label(&device_code.message);
+                ui.horizontal(|ui| {
+                    ui.hyperlink(&device_code.verification_uri);
+                    ui.label(egui::RichText::new(&device_code.user_code).monospace().strong());
+                    if ui.button("Copy code").clicked() {
+                        ui.ctx().copy_text(device_code.user_code.clone());
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Waiting for you to sign in");
+                });
+            }
+            _ => {
+                if ui
+                    .add_enabled(
+                        self.manager.get_collection::<DeviceCodeCollection>().tasks().is_empty(),
+                        egui::Button::new("Sign in with Microsoft"),
+                    )
+                    .clicked()
+                {
+                    self.accounts_state.request_device_code(self.manager);
+                }
+            }
+        }
+    }
 }
 
 impl View for SettingsPage<'_> {
@@ -97,22 +318,16 @@ impl View for SettingsPage<'_> {
 
         {
             if let Some(path) = self.file_dialog.update(ui.ctx()).selected() {
-                if let JavaRunner::Path(java_path) = &mut self.settings_state.java {
+                if let Some(JavaRunner::Path(java_path)) = &mut self.settings_state.java {
                     if java_path != path {
                         *java_path = dbg!(path).to_path_buf();
                     }
                 }
             }
 
-            ui.heading("User");
-
-            FormField::new(&mut form, field_path!("username"))
-                .label("Username")
-                .ui(ui, egui::TextEdit::singleline(&mut self.settings_state.username));
+            ui.heading("Accounts");
 
-            FormField::new(&mut form, field_path!("uuid"))
-                .label("UUID")
-                .ui(ui, egui::TextEdit::singleline(&mut self.settings_state.uuid));
+            self.accounts_ui(ui);
 
             ui.heading("Java");
 
@@ -128,31 +343,53 @@ impl View for SettingsPage<'_> {
             }
 
             FormField::new(&mut form, field_path!("java")).label("Java").ui(ui, |ui: &mut egui::Ui| {
-                ui.radio_value(&mut self.settings_state.java, JavaRunner::command("java"), "Command");
+                ui.radio_value(&mut self.settings_state.java, None, "Automatic");
 
-                ui.radio_value(&mut self.settings_state.java, JavaRunner::path(PathBuf::new()), "Custom path");
+                ui.radio_value(&mut self.settings_state.java, Some(JavaRunner::command("java")), "Command");
 
-                if matches!(settings_data.java, JavaRunner::Path(_)) && ui.button("Select custom java binary").clicked() {
+                ui.radio_value(&mut self.settings_state.java, Some(JavaRunner::path(PathBuf::new())), "Custom path");
+
+                if matches!(settings_data.java, Some(JavaRunner::Path(_))) && ui.button("Select custom java binary").clicked() {
                     self.file_dialog.select_file();
                 }
 
-                ui.label(format!(
-                    "Java will be run using {}",
-                    match &settings_data.java {
-                        JavaRunner::Command(command) => format!("{} command", command),
-                        JavaRunner::Path(path) => format!("{} executable", path.display()),
-                    }
-                ))
+                ui.label(match &settings_data.java {
+                    None => String::from("Java matching the game version will be downloaded and used"),
+                    Some(JavaRunner::Command(command)) => format!("Java will be run using {} command", command),
+                    Some(JavaRunner::Path(path)) => format!("Java will be run using {} executable", path.display()),
+                })
             });
 
+            self.java_installations_ui(ui);
+
             ui.heading("Launcher");
 
             ui.add(egui::Slider::new(&mut self.settings_state.client_settings.pixels_per_point, 0.5..=5.0).text("Pixels per point"));
+
+            ui.checkbox(&mut self.settings_state.client_settings.offline_mode, "Offline mode")
+                .on_hover_text("Use only the cached metadata. Downloaded profiles can still be launched");
+
+            ui.collapsing("Mirrors", |ui| self.mirrors_ui(ui));
+
+            ui.collapsing("Network", |ui| self.network_ui(ui));
+
+            ui.collapsing("CurseForge", |ui| self.curseforge_ui(ui));
         }
 
         if let Some(Ok(())) = form.handle_submit(&ui.button("Save"), ui) {
             *self.client_settings_state = settings_data.client_settings.clone();
+            set_offline_mode(settings_data.client_settings.offline_mode);
+            settings_data.apply_mirrors();
+            settings_data.apply_network();
+            settings_data.apply_curseforge();
             settings_data.update_config();
         }
     }
 }
+
+pub fn java_installation_label(installation: &JavaInstallation) -> String {
+    match &installation.vendor {
+        Some(vendor) => format!("Java {} ({vendor}, {})", installation.version, installation.arch),
+        None => format!("Java {} ({})", installation.version, installation.arch),
+    }
+}
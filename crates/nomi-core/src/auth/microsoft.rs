@@ -0,0 +1,124 @@
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use super::{AuthEndpoints, AuthError};
+
+const SCOPE: &str = "XboxLive.signin offline_access";
+
+/// First step of the authentication.
+///
+/// Obtains Microsoft OAuth tokens using the device code flow.
+#[async_trait::async_trait]
+pub trait MicrosoftAuth: Send + Sync {
+    async fn request_device_code(&self) -> anyhow::Result<DeviceCode>;
+
+    async fn poll_device_code(&self, device_code: &DeviceCode) -> anyhow::Result<DeviceCodeStatus>;
+
+    async fn refresh_token(&self, refresh_token: &str) -> anyhow::Result<MicrosoftToken>;
+}
+
+/// Code that user must enter at [`DeviceCode::verification_uri`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DeviceCode {
+    pub user_code: String,
+    pub device_code: String,
+    pub verification_uri: String,
+    pub expires_in: u64,
+    pub interval: u64,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceCodeStatus {
+    /// User has not finished signing in yet.
+    Pending,
+    /// Server asks to increase the polling interval.
+    SlowDown,
+    Authorized(MicrosoftToken),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MicrosoftToken {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_in: u64,
+}
+
+#[derive(Deserialize, Debug)]
+struct OAuthError {
+    error: String,
+}
+
+pub struct HttpMicrosoftAuth {
+    client: Client,
+    client_id: String,
+    url: String,
+}
+
+impl HttpMicrosoftAuth {
+    pub fn new(client: Client, client_id: impl Into<String>, endpoints: &AuthEndpoints) -> Self {
+        Self {
+            client,
+            client_id: client_id.into(),
+            url: endpoints.microsoft.clone(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl MicrosoftAuth for HttpMicrosoftAuth {
+    async fn request_device_code(&self) -> anyhow::Result<DeviceCode> {
+        self.client
+            .post(format!("{}/devicecode", self.url))
+            .form(&[("client_id", self.client_id.as_str()), ("scope", SCOPE)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn poll_device_code(&self, device_code: &DeviceCode) -> anyhow::Result<DeviceCodeStatus> {
+        let response = self
+            .client
+            .post(format!("{}/token", self.url))
+            .form(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+                ("client_id", self.client_id.as_str()),
+                ("device_code", device_code.device_code.as_str()),
+            ])
+            .send()
+            .await?;
+
+        if response.status() != StatusCode::BAD_REQUEST {
+            return Ok(DeviceCodeStatus::Authorized(response.error_for_status()?.json().await?));
+        }
+
+        let OAuthError { error } = response.json().await?;
+        match error.as_str() {
+            "authorization_pending" => Ok(DeviceCodeStatus::Pending),
+            "slow_down" => Ok(DeviceCodeStatus::SlowDown),
+            "authorization_declined" => Err(AuthError::AuthorizationDeclined.into()),
+            "expired_token" => Err(AuthError::DeviceCodeExpired.into()),
+            _ => Err(AuthError::Microsoft(error).into()),
+        }
+    }
+
+    async fn refresh_token(&self, refresh_token: &str) -> anyhow::Result<MicrosoftToken> {
+        self.client
+            .post(format!("{}/token", self.url))
+            .form(&[
+                ("grant_type", "refresh_token"),
+                ("client_id", self.client_id.as_str()),
+                ("refresh_token", refresh_token),
+                ("scope", SCOPE),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .map_err(Into::into)
+    }
+}
@@ -0,0 +1,76 @@
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::{AuthEndpoints, AuthError};
+
+/// The last steps of the authentication.
+///
+/// Logs into Minecraft services with the XSTS token and fetches the player's profile.
+#[async_trait::async_trait]
+pub trait MinecraftAuth: Send + Sync {
+    async fn login_with_xbox(&self, user_hash: &str, xsts_token: &str) -> anyhow::Result<MinecraftToken>;
+
+    async fn profile(&self, minecraft_access_token: &str) -> anyhow::Result<MinecraftProfile>;
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MinecraftToken {
+    pub access_token: String,
+    pub expires_in: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MinecraftProfile {
+    pub id: String,
+    pub name: String,
+}
+
+pub struct HttpMinecraftAuth {
+    client: Client,
+    url: String,
+}
+
+impl HttpMinecraftAuth {
+    pub fn new(client: Client, endpoints: &AuthEndpoints) -> Self {
+        Self {
+            client,
+            url: endpoints.minecraft_services.clone(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl MinecraftAuth for HttpMinecraftAuth {
+    async fn login_with_xbox(&self, user_hash: &str, xsts_token: &str) -> anyhow::Result<MinecraftToken> {
+        let body = json!({
+            "identityToken": format!("XBL3.0 x={user_hash};{xsts_token}")
+        });
+
+        self.client
+            .post(format!("{}/authentication/login_with_xbox", self.url))
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn profile(&self, minecraft_access_token: &str) -> anyhow::Result<MinecraftProfile> {
+        let response = self
+            .client
+            .get(format!("{}/minecraft/profile", self.url))
+            .bearer_auth(minecraft_access_token)
+            .send()
+            .await?;
+
+        // Account exists but does not own the game
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(AuthError::NoMinecraftProfile.into());
+        }
+
+        response.error_for_status()?.json().await.map_err(Into::into)
+    }
+}
@@ -0,0 +1,202 @@
+//! Microsoft account authentication.
+//!
+//! The flow consists of the following steps:
+//! 1. Microsoft OAuth device code flow ([`MicrosoftAuth`])
+//! 2. Xbox Live authentication ([`XboxAuth::authenticate`])
+//! 3. XSTS authorization ([`XboxAuth::authorize`])
+//! 4. Minecraft services login ([`MinecraftAuth::login_with_xbox`])
+//! 5. Minecraft profile lookup ([`MinecraftAuth::profile`])
+
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{instance::launch::arguments::UserData, network::http_client, repository::username::Username};
+
+pub mod microsoft;
+pub mod minecraft;
+pub mod xbox;
+
+use microsoft::{DeviceCode, DeviceCodeStatus, HttpMicrosoftAuth, MicrosoftAuth, MicrosoftToken};
+use minecraft::{HttpMinecraftAuth, MinecraftAuth, MinecraftProfile};
+use xbox::{HttpXboxAuth, XboxAuth};
+
+/// Token is considered expired a bit earlier
+/// so it does not expire while the game is starting.
+const EXPIRATION_LEEWAY: u64 = 60;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    #[error("User declined the authorization")]
+    AuthorizationDeclined,
+    #[error("Device code has expired")]
+    DeviceCodeExpired,
+    #[error("Microsoft returned an error: {0}")]
+    Microsoft(String),
+    #[error("The account does not have an Xbox account")]
+    NoXboxAccount,
+    #[error("Xbox Live is not available in the account's country")]
+    XboxUnavailable,
+    #[error("The account is a child account and must be added to a Family")]
+    ChildAccount,
+    #[error("XSTS authorization failed with code {0}")]
+    Xsts(u64),
+    #[error("Xbox Live did not return the user hash")]
+    NoUserHash,
+    #[error("The account does not own Minecraft")]
+    NoMinecraftProfile,
+}
+
+impl AuthError {
+    pub fn from_xsts_code(code: u64) -> Self {
+        match code {
+            2_148_916_233 => Self::NoXboxAccount,
+            2_148_916_235 => Self::XboxUnavailable,
+            2_148_916_236..=2_148_916_238 => Self::ChildAccount,
+            code => Self::Xsts(code),
+        }
+    }
+}
+
+/// Base urls of the services used during the authentication.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthEndpoints {
+    pub microsoft: String,
+    pub xbox_live: String,
+    pub xsts: String,
+    pub minecraft_services: String,
+}
+
+impl Default for AuthEndpoints {
+    fn default() -> Self {
+        Self {
+            microsoft: String::from("https://login.microsoftonline.com/consumers/oauth2/v2.0"),
+            xbox_live: String::from("https://user.auth.xboxlive.com"),
+            xsts: String::from("https://xsts.auth.xboxlive.com"),
+            minecraft_services: String::from("https://api.minecraftservices.com"),
+        }
+    }
+}
+
+/// Authenticated account that is cached on disk.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MicrosoftAccount {
+    pub profile: MinecraftProfile,
+    pub access_token: String,
+    pub refresh_token: String,
+    /// Unix timestamp (in seconds) when the `access_token` expires.
+    pub expires_at: u64,
+}
+
+impl MicrosoftAccount {
+    pub fn is_expired(&self) -> bool {
+        unix_timestamp() + EXPIRATION_LEEWAY >= self.expires_at
+    }
+
+    pub fn user_data(&self) -> anyhow::Result<UserData> {
+        Ok(UserData {
+            username: Username::new(&self.profile.name)?,
+            uuid: Some(self.profile.id.clone()),
+            access_token: Some(self.access_token.clone()),
+        })
+    }
+}
+
+pub struct Authenticator {
+    microsoft: Box<dyn MicrosoftAuth>,
+    xbox: Box<dyn XboxAuth>,
+    minecraft: Box<dyn MinecraftAuth>,
+}
+
+impl Authenticator {
+    pub fn new(client_id: impl Into<String>) -> Self {
+        Self::with_endpoints(client_id, &AuthEndpoints::default())
+    }
+
+    pub fn with_endpoints(client_id: impl Into<String>, endpoints: &AuthEndpoints) -> Self {
+        let client = http_client();
+
+        Self::from_parts(
+            HttpMicrosoftAuth::new(client.clone(), client_id, endpoints),
+            HttpXboxAuth::new(client.clone(), endpoints),
+            HttpMinecraftAuth::new(client, endpoints),
+        )
+    }
+
+    pub fn from_parts(microsoft: impl MicrosoftAuth + 'static, xbox: impl XboxAuth + 'static, minecraft: impl MinecraftAuth + 'static) -> Self {
+        Self {
+            microsoft: Box::new(microsoft),
+            xbox: Box::new(xbox),
+            minecraft: Box::new(minecraft),
+        }
+    }
+
+    /// Starts the device code flow.
+    ///
+    /// [`DeviceCode::message`] must be shown to the user.
+    pub async fn request_device_code(&self) -> anyhow::Result<DeviceCode> {
+        self.microsoft.request_device_code().await
+    }
+
+    /// Polls Microsoft until the user finishes signing in and then
+    /// runs the rest of the authentication flow.
+    #[tracing::instrument(skip_all, err)]
+    pub async fn wait_for_authorization(&self, device_code: &DeviceCode) -> anyhow::Result<MicrosoftAccount> {
+        let deadline = Instant::now() + Duration::from_secs(device_code.expires_in);
+        let mut interval = Duration::from_secs(device_code.interval);
+
+        loop {
+            if Instant::now() >= deadline {
+                return Err(AuthError::DeviceCodeExpired.into());
+            }
+
+            tokio::time::sleep(interval).await;
+
+            match self.microsoft.poll_device_code(device_code).await? {
+                DeviceCodeStatus::Pending => {}
+                DeviceCodeStatus::SlowDown => interval += Duration::from_secs(5),
+                DeviceCodeStatus::Authorized(token) => return self.login(token).await,
+            }
+        }
+    }
+
+    /// Obtains new tokens using the account's refresh token.
+    #[tracing::instrument(skip_all, err)]
+    pub async fn refresh(&self, account: &MicrosoftAccount) -> anyhow::Result<MicrosoftAccount> {
+        let token = self.microsoft.refresh_token(&account.refresh_token).await?;
+        self.login(token).await
+    }
+
+    pub async fn refresh_if_expired(&self, account: MicrosoftAccount) -> anyhow::Result<MicrosoftAccount> {
+        if account.is_expired() {
+            info!(name = account.profile.name, "Access token has expired. Refreshing");
+            return self.refresh(&account).await;
+        }
+
+        Ok(account)
+    }
+
+    async fn login(&self, token: MicrosoftToken) -> anyhow::Result<MicrosoftAccount> {
+        let xbox_live = self.xbox.authenticate(&token.access_token).await?;
+        let xsts = self.xbox.authorize(&xbox_live.token).await?;
+
+        let user_hash = xsts.user_hash().ok_or(AuthError::NoUserHash)?;
+
+        let minecraft_token = self.minecraft.login_with_xbox(user_hash, &xsts.token).await?;
+        let profile = self.minecraft.profile(&minecraft_token.access_token).await?;
+
+        info!(name = profile.name, "Successfully authenticated");
+
+        Ok(MicrosoftAccount {
+            profile,
+            access_token: minecraft_token.access_token,
+            refresh_token: token.refresh_token,
+            expires_at: unix_timestamp() + minecraft_token.expires_in,
+        })
+    }
+}
+
+fn unix_timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
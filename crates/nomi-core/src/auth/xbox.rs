@@ -0,0 +1,107 @@
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{AuthEndpoints, AuthError};
+
+/// Second and third steps of the authentication.
+///
+/// Exchanges Microsoft access token for the Xbox Live token
+/// and then authorizes it in the XSTS service.
+#[async_trait::async_trait]
+pub trait XboxAuth: Send + Sync {
+    async fn authenticate(&self, microsoft_access_token: &str) -> anyhow::Result<XboxToken>;
+
+    async fn authorize(&self, xbox_live_token: &str) -> anyhow::Result<XboxToken>;
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct XboxToken {
+    pub token: String,
+    pub display_claims: DisplayClaims,
+}
+
+impl XboxToken {
+    /// User hash that is required to log into Minecraft services.
+    pub fn user_hash(&self) -> Option<&str> {
+        self.display_claims.xui.first().map(|claim| claim.uhs.as_str())
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DisplayClaims {
+    pub xui: Vec<Xui>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Xui {
+    pub uhs: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct XstsError {
+    x_err: u64,
+}
+
+pub struct HttpXboxAuth {
+    client: Client,
+    xbox_live_url: String,
+    xsts_url: String,
+}
+
+impl HttpXboxAuth {
+    pub fn new(client: Client, endpoints: &AuthEndpoints) -> Self {
+        Self {
+            client,
+            xbox_live_url: endpoints.xbox_live.clone(),
+            xsts_url: endpoints.xsts.clone(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl XboxAuth for HttpXboxAuth {
+    async fn authenticate(&self, microsoft_access_token: &str) -> anyhow::Result<XboxToken> {
+        let body = json!({
+            "Properties": {
+                "AuthMethod": "RPS",
+                "SiteName": "user.auth.xboxlive.com",
+                "RpsTicket": format!("d={microsoft_access_token}")
+            },
+            "RelyingParty": "http://auth.xboxlive.com",
+            "TokenType": "JWT"
+        });
+
+        self.client
+            .post(format!("{}/user/authenticate", self.xbox_live_url))
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn authorize(&self, xbox_live_token: &str) -> anyhow::Result<XboxToken> {
+        let body = json!({
+            "Properties": {
+                "SandboxId": "RETAIL",
+                "UserTokens": [xbox_live_token]
+            },
+            "RelyingParty": "rp://api.minecraftservices.com/",
+            "TokenType": "JWT"
+        });
+
+        let response = self.client.post(format!("{}/xsts/authorize", self.xsts_url)).json(&body).send().await?;
+
+        if response.status() == StatusCode::UNAUTHORIZED {
+            let XstsError { x_err } = response.json().await?;
+            return Err(AuthError::from_xsts_code(x_err).into());
+        }
+
+        response.error_for_status()?.json().await.map_err(Into::into)
+    }
+}
@@ -0,0 +1,263 @@
+//! Local cache for the metadata fetched from the network (manifests, indexes, ...).
+//!
+//! Every entry stores the response body along with its `ETag` and `Last-Modified` headers.
+//! A fresh entry is returned without touching the network, a stale one gets revalidated.
+//! If the network is not available, or the [offline mode](set_offline_mode) is enabled,
+//! cached entries are returned regardless of their age.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        LazyLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::{
+    header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    StatusCode,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::{
+    calculate_sha1,
+    fs::{read_json_config, write_json_config, write_to_file},
+    mirrors::mirrored,
+    network::http_client,
+    DOT_NOMI_CACHE_DIR,
+};
+
+/// The list of versions changes with every release.
+pub const LAUNCHER_MANIFEST_TTL: Duration = Duration::from_mins(10);
+/// Version manifests, asset indexes and Java runtime manifests are not changed after they are published.
+pub const IMMUTABLE_TTL: Duration = Duration::from_hours(7 * 24);
+/// Index of the Java runtimes.
+pub const JAVA_RUNTIMES_TTL: Duration = Duration::from_hours(24);
+
+static OFFLINE_MODE: AtomicBool = AtomicBool::new(false);
+
+static METADATA_CACHE: LazyLock<MetadataCache> = LazyLock::new(|| MetadataCache::new(DOT_NOMI_CACHE_DIR));
+
+/// Forbids any network requests made through the [`MetadataCache`].
+pub fn set_offline_mode(offline: bool) {
+    OFFLINE_MODE.store(offline, Ordering::Relaxed);
+}
+
+pub fn is_offline_mode() -> bool {
+    OFFLINE_MODE.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CacheError {
+    #[error("`{url}` is not cached and the offline mode is enabled")]
+    Offline { url: String },
+
+    #[error("`{url}` responded with `304 Not Modified` to an unconditional request")]
+    UnexpectedNotModified { url: String },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct CacheEntry {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// Unix time in seconds when the entry was fetched or revalidated last time.
+    validated_at: u64,
+}
+
+impl CacheEntry {
+    fn is_fresh(&self, ttl: Duration) -> bool {
+        unix_now().saturating_sub(self.validated_at) < ttl.as_secs()
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs())
+}
+
+#[derive(Debug, Clone)]
+pub struct MetadataCache {
+    dir: PathBuf,
+}
+
+impl MetadataCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Cache stored in [`DOT_NOMI_CACHE_DIR`].
+    pub fn global() -> &'static MetadataCache {
+        &METADATA_CACHE
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn body_path(&self, url: &str) -> PathBuf {
+        self.dir.join(calculate_sha1(url))
+    }
+
+    fn entry_path(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", calculate_sha1(url)))
+    }
+
+    async fn read_cached(&self, url: &str) -> Option<(CacheEntry, Vec<u8>)> {
+        let entry = read_json_config::<CacheEntry>(self.entry_path(url)).await.ok()?;
+        let body = tokio::fs::read(self.body_path(url)).await.ok()?;
+
+        Some((entry, body))
+    }
+
+    async fn write_entry(&self, entry: &CacheEntry) -> crate::Result<()> {
+        write_json_config(entry, self.entry_path(&entry.url)).await
+    }
+
+    /// Returns the body of `url`, using the cached one if it is younger than `ttl`.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn get(&self, url: &str, ttl: Duration) -> crate::Result<Vec<u8>> {
+        let cached = self.read_cached(url).await;
+
+        if let Some((entry, body)) = &cached {
+            if is_offline_mode() || entry.is_fresh(ttl) {
+                debug!("Using the cached response");
+                return Ok(body.clone());
+            }
+        } else if is_offline_mode() {
+            return Err(CacheError::Offline { url: url.to_owned() }.into());
+        }
+
+        let fetched = self.fetch(url, cached.as_ref().map(|(entry, _)| entry)).await;
+
+        match (fetched, cached) {
+            (Ok(Some((entry, body))), _) => {
+                write_to_file(&body, self.body_path(url)).await?;
+                self.write_entry(&entry).await?;
+                Ok(body)
+            }
+            (Ok(None), Some((mut entry, body))) => {
+                debug!("The cached response is still valid");
+                entry.validated_at = unix_now();
+                self.write_entry(&entry).await?;
+                Ok(body)
+            }
+            (Ok(None), None) => Err(CacheError::UnexpectedNotModified { url: url.to_owned() }.into()),
+            (Err(error), Some((_, body))) => {
+                warn!(?error, "Unable to revalidate the cached response, using the stale one");
+                Ok(body)
+            }
+            (Err(error), None) => Err(error),
+        }
+    }
+
+    /// Same as [`MetadataCache::get`] but deserializes the body.
+    pub async fn get_json<T: DeserializeOwned>(&self, url: &str, ttl: Duration) -> crate::Result<T> {
+        let body = self.get(url, ttl).await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Makes a conditional request.
+    ///
+    /// Returns `None` if the server responded with `304 Not Modified`.
+    async fn fetch(&self, url: &str, cached: Option<&CacheEntry>) -> crate::Result<Option<(CacheEntry, Vec<u8>)>> {
+        let mut request = http_client().get(mirrored(url));
+
+        if let Some(etag) = cached.and_then(|entry| entry.etag.as_deref()) {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = cached.and_then(|entry| entry.last_modified.as_deref()) {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+
+        let response = request.send().await?;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+
+        let response = response.error_for_status()?;
+
+        let header = |name| response.headers().get(name).and_then(|value| value.to_str().ok()).map(ToOwned::to_owned);
+        let entry = CacheEntry {
+            url: url.to_owned(),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            validated_at: unix_now(),
+        };
+
+        let body = response.bytes().await?.to_vec();
+
+        Ok(Some((entry, body)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mock_server::{MockResponse, MockServer};
+
+    use super::*;
+
+    const BODY: &str = r#"{"id":"1.20.4"}"#;
+
+    /// Serves [`BODY`] with an `ETag` and answers `304` to the matching conditional requests.
+    async fn serve() -> MockServer {
+        MockServer::start(|request| {
+            if request.header("If-None-Match") == Some("\"v1\"") {
+                MockResponse::new(304, Vec::new())
+            } else {
+                MockResponse::json(200, BODY).with_header("ETag", "\"v1\"")
+            }
+        })
+        .await
+    }
+
+    fn cache(name: &str) -> MetadataCache {
+        let dir = std::env::temp_dir().join(format!("nomi-cache-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        MetadataCache::new(dir)
+    }
+
+    #[tokio::test]
+    async fn revalidation_test() {
+        let server = serve().await;
+        let url = format!("{}/manifest.json", server.url());
+        let cache = cache("revalidation");
+
+        let value: serde_json::Value = cache.get_json(&url, Duration::ZERO).await.unwrap();
+        assert_eq!(value["id"], "1.20.4");
+
+        // The entry is stale right away, so it is revalidated and the server answers `304`.
+        let body = cache.get(&url, Duration::ZERO).await.unwrap();
+        assert_eq!(body, BODY.as_bytes());
+
+        let requests = server.requests_to("/manifest.json");
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].header("If-None-Match"), Some("\"v1\""));
+
+        let _ = std::fs::remove_dir_all(cache.dir());
+    }
+
+    #[tokio::test]
+    async fn unreachable_test() {
+        let server = serve().await;
+        let url = format!("{}/manifest.json", server.url());
+        let cache = cache("unreachable");
+
+        cache.get(&url, LAUNCHER_MANIFEST_TTL).await.unwrap();
+
+        // Points the entry at the same cached body but makes every request fail.
+        let unreachable = "http://127.0.0.1:1/manifest.json";
+        std::fs::copy(cache.body_path(&url), cache.body_path(unreachable)).unwrap();
+        let mut entry = read_json_config::<CacheEntry>(cache.entry_path(&url)).await.unwrap();
+        entry.url = unreachable.to_owned();
+        entry.validated_at = 0;
+        cache.write_entry(&entry).await.unwrap();
+
+        assert_eq!(cache.get(unreachable, LAUNCHER_MANIFEST_TTL).await.unwrap(), BODY.as_bytes());
+        assert!(cache.get("http://127.0.0.1:1/other.json", LAUNCHER_MANIFEST_TTL).await.is_err());
+
+        let _ = std::fs::remove_dir_all(cache.dir());
+    }
+}
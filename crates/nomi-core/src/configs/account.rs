@@ -0,0 +1,333 @@
+use std::path::Path;
+
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{
+    auth::{Authenticator, MicrosoftAccount},
+    configs::profile::VersionProfile,
+    fs::{read_toml_config, read_toml_config_sync, write_toml_config, write_toml_config_sync},
+    instance::launch::arguments::UserData,
+    repository::username::Username,
+};
+
+/// An account that can be used to launch the game.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind")]
+pub enum Account {
+    Offline { username: Username, uuid: String },
+    Microsoft(MicrosoftAccount),
+}
+
+impl Account {
+    pub fn offline(username: Username) -> Self {
+        Self::Offline {
+            username,
+            uuid: uuid::Uuid::new_v4().to_string(),
+        }
+    }
+
+    /// Unique identifier of the account.
+    ///
+    /// It is the player's uuid for both offline and online accounts.
+    pub fn id(&self) -> &str {
+        match self {
+            Account::Offline { uuid, .. } => uuid,
+            Account::Microsoft(account) => &account.profile.id,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Account::Offline { username, .. } => username.get(),
+            Account::Microsoft(account) => &account.profile.name,
+        }
+    }
+
+    pub fn is_online(&self) -> bool {
+        matches!(self, Self::Microsoft(_))
+    }
+
+    /// Returns [`UserData`] of the account.
+    ///
+    /// Microsoft account's cached token is used while it is valid. Expired tokens
+    /// are refreshed with the `authenticator`, the refreshed account is returned
+    /// along with the data so the caller can store it.
+    pub async fn user_data(&self, authenticator: Option<&Authenticator>) -> anyhow::Result<(UserData, Option<Account>)> {
+        match self {
+            Account::Offline { username, uuid } => Ok((
+                UserData {
+                    username: username.clone(),
+                    uuid: Some(uuid.clone()),
+                    access_token: None,
+                },
+                None,
+            )),
+            Account::Microsoft(account) if !account.is_expired() => Ok((account.user_data()?, None)),
+            Account::Microsoft(account) => {
+                let Some(authenticator) = authenticator else {
+                    bail!(
+                        "The access token of `{}` has expired and cannot be refreshed without the authenticator",
+                        account.profile.name
+                    );
+                };
+
+                let refreshed = authenticator.refresh(account).await?;
+                let user_data = refreshed.user_data()?;
+
+                Ok((user_data, Some(Account::Microsoft(refreshed))))
+            }
+        }
+    }
+}
+
+/// Collection of all the accounts added to the launcher.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct AccountStore {
+    /// Id of the account that is used when a profile does not override it.
+    pub default: Option<String>,
+    #[serde(default)]
+    pub accounts: Vec<Account>,
+}
+
+impl AccountStore {
+    pub async fn read(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        Ok(read_toml_config(path).await?)
+    }
+
+    /// Same as [`AccountStore::read`] but returns an empty store if the file does not exist.
+    pub async fn read_or_default(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Self::read(path).await
+    }
+
+    pub async fn write(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        Ok(write_toml_config(self, path).await?)
+    }
+
+    /// Moves the account from the `auth_cache` written by older versions
+    /// (see [`DOT_NOMI_AUTH_CACHE`](crate::DOT_NOMI_AUTH_CACHE)) into the store.
+    ///
+    /// The store is written to `store_path` before the cache is removed.
+    /// Returns `true` if an account was migrated.
+    pub fn migrate_auth_cache(&mut self, auth_cache: impl AsRef<Path>, store_path: impl AsRef<Path>) -> anyhow::Result<bool> {
+        let auth_cache = auth_cache.as_ref();
+
+        if !auth_cache.exists() {
+            return Ok(false);
+        }
+
+        let account = read_toml_config_sync::<MicrosoftAccount>(auth_cache)?;
+        info!(name = account.profile.name, "Migrating the cached Microsoft account");
+
+        self.add(Account::Microsoft(account));
+        write_toml_config_sync(self, store_path)?;
+        std::fs::remove_file(auth_cache)?;
+
+        Ok(true)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Account> {
+        self.accounts.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Account> {
+        self.accounts.iter().find(|account| account.id() == id)
+    }
+
+    /// Adds the account or replaces the one with the same id.
+    ///
+    /// The first added account becomes the default one.
+    pub fn add(&mut self, account: Account) {
+        if self.default.is_none() {
+            self.default = Some(account.id().to_owned());
+        }
+
+        match self.accounts.iter_mut().find(|a| a.id() == account.id()) {
+            Some(existing) => *existing = account,
+            None => self.accounts.push(account),
+        }
+    }
+
+    /// Replaces the account with the same id.
+    ///
+    /// Returns `false` if there is no such account, e.g. it was removed in the meantime.
+    pub fn update(&mut self, account: Account) -> bool {
+        match self.accounts.iter_mut().find(|a| a.id() == account.id()) {
+            Some(existing) => {
+                *existing = account;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the account. If it was the default one
+    /// the first remaining account becomes the default.
+    pub fn remove(&mut self, id: &str) -> Option<Account> {
+        let index = self.accounts.iter().position(|account| account.id() == id)?;
+        let account = self.accounts.remove(index);
+
+        if self.default.as_deref() == Some(id) {
+            self.default = self.accounts.first().map(|account| account.id().to_owned());
+        }
+
+        Some(account)
+    }
+
+    pub fn set_default(&mut self, id: &str) -> anyhow::Result<()> {
+        if self.get(id).is_none() {
+            bail!("There is no account with id `{id}`");
+        }
+
+        self.default = Some(id.to_owned());
+
+        Ok(())
+    }
+
+    pub fn default_account(&self) -> Option<&Account> {
+        self.default.as_deref().and_then(|id| self.get(id))
+    }
+
+    /// Account that should be used to launch the profile.
+    ///
+    /// Profile's override is used if it still exists in the store, otherwise the default one.
+    pub fn account_for(&self, profile: &VersionProfile) -> Option<&Account> {
+        profile.account.as_deref().and_then(|id| self.get(id)).or_else(|| self.default_account())
+    }
+
+    /// Returns [`UserData`] for the account with the provided id, see [`Account::user_data`].
+    ///
+    /// The store is updated with the refreshed account.
+    /// It's up to the caller to write the store afterwards.
+    pub async fn user_data(&mut self, id: &str, authenticator: Option<&Authenticator>) -> anyhow::Result<UserData> {
+        let Some(account) = self.get(id) else {
+            bail!("There is no account with id `{id}`");
+        };
+
+        let (user_data, refreshed) = account.user_data(authenticator).await?;
+
+        if let Some(refreshed) = refreshed {
+            info!(name = refreshed.name(), "Updating refreshed account");
+            self.update(refreshed);
+        }
+
+        Ok(user_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::auth::minecraft::MinecraftProfile;
+
+    use super::*;
+
+    fn microsoft_account(id: &str, name: &str, expires_at: u64) -> MicrosoftAccount {
+        MicrosoftAccount {
+            profile: MinecraftProfile {
+                id: id.to_owned(),
+                name: name.to_owned(),
+            },
+            access_token: String::from("token"),
+            refresh_token: String::from("refresh"),
+            expires_at,
+        }
+    }
+
+    fn microsoft(id: &str, name: &str) -> Account {
+        Account::Microsoft(microsoft_account(id, name, 4_102_444_800))
+    }
+
+    #[test]
+    fn default_account_test() {
+        let mut store = AccountStore::default();
+
+        let offline = Account::offline(Username::new("Offline").unwrap());
+        let offline_id = offline.id().to_owned();
+
+        store.add(offline);
+        store.add(microsoft("069a79f444e94726a5befca90e38aaf5", "Notch"));
+
+        assert_eq!(store.default_account().map(Account::name), Some("Offline"));
+
+        store.set_default("069a79f444e94726a5befca90e38aaf5").unwrap();
+        assert_eq!(store.default_account().map(Account::name), Some("Notch"));
+        assert!(store.set_default("unknown").is_err());
+
+        store.remove("069a79f444e94726a5befca90e38aaf5");
+        assert_eq!(store.default.as_deref(), Some(offline_id.as_str()));
+    }
+
+    #[test]
+    fn replace_account_test() {
+        let mut store = AccountStore::default();
+
+        store.add(microsoft("069a79f444e94726a5befca90e38aaf5", "Notch"));
+        store.add(microsoft("069a79f444e94726a5befca90e38aaf5", "Renamed"));
+
+        assert_eq!(store.accounts.len(), 1);
+        assert_eq!(store.default_account().map(Account::name), Some("Renamed"));
+
+        assert!(store.update(microsoft("069a79f444e94726a5befca90e38aaf5", "Updated")));
+        assert!(!store.update(microsoft("853c80ef3c3749fdaa49938b674adae6", "jeb_")));
+        assert_eq!(store.accounts.len(), 1);
+        assert_eq!(store.default_account().map(Account::name), Some("Updated"));
+    }
+
+    #[tokio::test]
+    async fn cached_token_test() {
+        let mut store = AccountStore::default();
+        store.add(microsoft("069a79f444e94726a5befca90e38aaf5", "Notch"));
+        store.add(Account::Microsoft(microsoft_account("853c80ef3c3749fdaa49938b674adae6", "jeb_", 0)));
+
+        let user_data = store.user_data("069a79f444e94726a5befca90e38aaf5", None).await.unwrap();
+        assert_eq!(user_data.access_token.as_deref(), Some("token"));
+
+        assert!(store.user_data("853c80ef3c3749fdaa49938b674adae6", None).await.is_err());
+    }
+
+    #[test]
+    fn migrate_auth_cache_test() {
+        let dir = std::env::temp_dir().join(format!("nomi-accounts-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let auth_cache = dir.join("Auth.toml");
+        let store_path = dir.join("Accounts.toml");
+
+        let cached = microsoft_account("069a79f444e94726a5befca90e38aaf5", "Notch", 4_102_444_800);
+        write_toml_config_sync(&cached, &auth_cache).unwrap();
+
+        let mut store = AccountStore::default();
+        assert!(store.migrate_auth_cache(&auth_cache, &store_path).unwrap());
+
+        assert!(!auth_cache.exists());
+        assert_eq!(store.default_account(), Some(&Account::Microsoft(cached)));
+        assert_eq!(read_toml_config_sync::<AccountStore>(&store_path).unwrap(), store);
+
+        assert!(!store.migrate_auth_cache(&auth_cache, &store_path).unwrap());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn serialize_test() {
+        let mut store = AccountStore::default();
+        store.add(Account::offline(Username::new("Offline").unwrap()));
+        store.add(microsoft("069a79f444e94726a5befca90e38aaf5", "Notch"));
+
+        let toml = toml::to_string_pretty(&store).unwrap();
+        let deserialized = toml::from_str::<AccountStore>(&toml).unwrap();
+
+        assert_eq!(store, deserialized);
+    }
+}
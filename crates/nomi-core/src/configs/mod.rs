@@ -1,3 +1,4 @@
+pub mod account;
 pub mod profile;
 pub mod user;
 pub mod variables;
@@ -8,28 +8,81 @@ use crate::{
     game_paths::GamePaths,
     instance::{
         launch::{arguments::UserData, LaunchInstance},
-        logs::GameLogsWriter,
+        process::GameProcess,
         InstanceProfileId,
     },
     repository::{java_runner::JavaRunner, manifest::VersionType},
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[serde(from = "LoaderConfig")]
 pub enum Loader {
     #[default]
     Vanilla,
     Fabric {
         version: Option<String>,
     },
+    /// `None` means the recommended version.
+    Forge {
+        version: Option<String>,
+    },
+    Quilt {
+        version: Option<String>,
+    },
+    NeoForge {
+        version: Option<String>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(remote = "Loader")]
+enum LoaderDef {
+    Vanilla,
+    Fabric {
+        version: Option<String>,
+    },
+    Forge {
+        #[serde(default)]
+        version: Option<String>,
+    },
+    Quilt {
+        version: Option<String>,
+    },
+    NeoForge {
+        version: Option<String>,
+    },
+}
+
+/// Configs written before Forge had a version store it as a unit variant.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LoaderConfig {
+    Current(#[serde(with = "LoaderDef")] Loader),
+    Legacy(LegacyLoader),
+}
+
+#[derive(Deserialize)]
+enum LegacyLoader {
     Forge,
 }
 
+impl From<LoaderConfig> for Loader {
+    fn from(value: LoaderConfig) -> Self {
+        match value {
+            LoaderConfig::Current(loader) => loader,
+            LoaderConfig::Legacy(LegacyLoader::Forge) => Loader::Forge { version: None },
+        }
+    }
+}
+
 impl Display for Loader {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Loader::Vanilla => f.write_str("Vanilla"),
             Loader::Fabric { .. } => f.write_str("Fabric"),
-            Loader::Forge => f.write_str("Forge"),
+            Loader::Forge { .. } => f.write_str("Forge"),
+            Loader::Quilt { .. } => f.write_str("Quilt"),
+            Loader::NeoForge { .. } => f.write_str("NeoForge"),
         }
     }
 }
@@ -44,7 +97,15 @@ impl Loader {
     }
 
     pub fn is_forge(&self) -> bool {
-        matches!(*self, Self::Fabric { .. })
+        matches!(*self, Self::Forge { .. })
+    }
+
+    pub fn is_quilt(&self) -> bool {
+        matches!(*self, Self::Quilt { .. })
+    }
+
+    pub fn is_neoforge(&self) -> bool {
+        matches!(*self, Self::NeoForge { .. })
     }
 
     pub fn is_vanilla(&self) -> bool {
@@ -87,18 +148,17 @@ pub struct VersionProfile {
     pub name: String,
 
     pub state: ProfileState,
+
+    /// Id of the account that overrides the default one for this profile.
+    #[serde(default)]
+    #[builder(default)]
+    pub account: Option<String>,
 }
 
 impl VersionProfile {
-    pub async fn launch(
-        &self,
-        paths: GamePaths,
-        user_data: UserData,
-        java_runner: &JavaRunner,
-        logs_writer: &dyn GameLogsWriter,
-    ) -> anyhow::Result<()> {
+    pub async fn launch(&self, paths: GamePaths, user_data: UserData, java_runner: Option<&JavaRunner>) -> anyhow::Result<GameProcess> {
         match &self.state {
-            ProfileState::Downloaded(instance) => instance.launch(paths, user_data, java_runner, logs_writer).await,
+            ProfileState::Downloaded(instance) => Ok(instance.launch(paths, user_data, java_runner).await?),
             ProfileState::NotDownloaded { .. } => Err(anyhow!("This profile is not downloaded!")),
         }
     }
@@ -130,3 +190,35 @@ impl VersionProfile {
         matches!(self.state, ProfileState::Downloaded(_))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Config {
+        loader: Loader,
+    }
+
+    #[test]
+    fn loader_config_test() {
+        let legacy: Config = toml::from_str(r#"loader = "Forge""#).unwrap();
+        assert_eq!(legacy.loader, Loader::Forge { version: None });
+
+        let vanilla: Config = toml::from_str(r#"loader = "Vanilla""#).unwrap();
+        assert_eq!(vanilla.loader, Loader::Vanilla);
+
+        for loader in [
+            Loader::Forge {
+                version: Some("47.2.0".to_owned()),
+            },
+            Loader::Fabric { version: None },
+            Loader::NeoForge {
+                version: Some("20.4.237".to_owned()),
+            },
+        ] {
+            let config = Config { loader };
+            assert_eq!(toml::from_str::<Config>(&toml::to_string(&config).unwrap()).unwrap(), config);
+        }
+    }
+}
@@ -2,7 +2,11 @@ pub const DOT_NOMI_DIR: &str = "./.nomi";
 pub const DOT_NOMI_TEMP_DIR: &str = "./.nomi/temp";
 pub const DOT_NOMI_CONFIGS_DIR: &str = "./.nomi/configs";
 pub const DOT_NOMI_SETTINGS_CONFIG: &str = "./.nomi/configs/Settings.toml";
+/// Microsoft account cached by older versions. It is migrated into [`DOT_NOMI_ACCOUNTS_CONFIG`].
+pub const DOT_NOMI_AUTH_CACHE: &str = "./.nomi/configs/Auth.toml";
+pub const DOT_NOMI_ACCOUNTS_CONFIG: &str = "./.nomi/configs/Accounts.toml";
 pub const DOT_NOMI_LOGS_DIR: &str = "./.nomi/logs";
+pub const DOT_NOMI_CACHE_DIR: &str = "./.nomi/cache";
 pub const DOT_NOMI_JAVA_DIR: &str = "./.nomi/java";
 #[cfg(not(windows))]
 pub const DOT_NOMI_JAVA_EXECUTABLE: &str = "./.nomi/java/jdk-22.0.1/bin/java";
@@ -23,6 +27,8 @@ pub const INSTANCES_DIR: &str = "./instances";
 /// Path::new("./instances/example").join(INSTANCE_CONFIG)
 /// ```
 pub const INSTANCE_CONFIG: &str = ".nomi/Instance.toml";
+/// Directory with the game's logs with respect to instance's directory.
+pub const INSTANCE_LOGS_DIR: &str = ".nomi/logs";
 
 pub const NOMI_VERSION: &str = "0.2.0";
 pub const NOMI_NAME: &str = "Nomi";
@@ -1,24 +1,23 @@
-use anyhow::Result;
-use itertools::Itertools;
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, path::PathBuf};
-use tracing::info;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use crate::{
-    calculate_sha1,
+    cache::{MetadataCache, IMMUTABLE_TTL},
     downloads::{
         downloaders::file::FileDownloader,
         progress::ProgressSender,
+        scheduler::Priority,
         set::DownloadSet,
-        traits::{DownloadResult, Downloadable, Downloader},
+        traits::{DownloadResult, Downloader},
     },
     fs::write_json_config,
-    PinnedFutureWithBounds,
+    repository::manifest::LoggingConfig,
+    PinnedFutureWithBounds, Result,
 };
 
-use super::DownloadQueue;
-
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Assets {
     pub objects: HashMap<String, AssetInformation>,
@@ -30,91 +29,65 @@ pub struct AssetInformation {
     pub size: i64,
 }
 
+impl AssetInformation {
+    pub fn url(&self) -> String {
+        format!("https://resources.download.minecraft.net/{}/{}", &self.hash[0..2], self.hash)
+    }
+
+    /// Path of the asset inside the `objects` directory.
+    pub fn path(&self, objects: &Path) -> PathBuf {
+        objects.join(&self.hash[0..2]).join(&self.hash)
+    }
+}
+
+/// Downloads the assets with the [low](Priority::Low) priority,
+/// so the client jar and libraries of the installs running at the same time are downloaded first.
+///
+/// Assets that are already downloaded are [verified](FileDownloader::verify_first) and skipped by default.
 #[derive(Debug)]
 pub struct AssetsDownloader {
-    queue: DownloadQueue,
+    downloads: Vec<FileDownloader>,
+    verify_first: bool,
     assets: Assets,
     indexes: PathBuf,
     id: String,
 }
 
-#[derive(Debug)]
-pub struct Chunk {
-    ok: usize,
-    err: usize,
-    set: DownloadSet,
-}
-
-impl Chunk {
-    pub fn new(set: DownloadSet) -> Self {
-        Self { ok: 0, err: 0, set }
-    }
-}
-
-#[async_trait::async_trait]
-impl Downloader for Chunk {
-    type Data = DownloadResult;
+impl AssetsDownloader {
+    pub async fn new(url: String, id: String, objects: PathBuf, indexes: PathBuf) -> Result<Self> {
+        let assets: Assets = MetadataCache::global().get_json(&url, IMMUTABLE_TTL).await?;
 
-    fn total(&self) -> u32 {
-        self.set.total()
+        let downloads = assets
+            .objects
+            .values()
+            .map(|asset| FileDownloader::new(asset.url(), asset.path(&objects)).with_sha1(asset.hash.clone()))
+            .collect();
+
+        Ok(Self {
+            downloads,
+            verify_first: true,
+            assets,
+            indexes,
+            id,
+        })
     }
 
-    async fn download(mut self: Box<Self>, sender: &dyn ProgressSender<Self::Data>) {
-        let (helper_sender, mut helper_receiver) = tokio::sync::mpsc::channel(100);
-        let downloader = self.set.with_helper(helper_sender);
-        Box::new(downloader).download(sender).await;
-
-        while let Some(result) = helper_receiver.recv().await {
-            match result.0 {
-                Ok(_) => self.ok += 1,
-                Err(_) => self.err += 1,
-            }
+    /// Also downloads the log4j configuration into `log_configs`.
+    #[must_use]
+    pub fn with_logging_config(mut self, config: Option<&LoggingConfig>, log_configs: &Path) -> Self {
+        if let Some(file) = config.map(|config| &config.file) {
+            let downloader = FileDownloader::new(file.url.clone(), log_configs.join(&file.id)).with_sha1(file.sha1.clone());
+            self.downloads.push(downloader);
         }
 
-        if self.ok != 0 && self.err != 0 {
-            info!("Downloaded Chunk OK: {} ERR: {}", self.ok, self.err);
-        }
+        self
     }
-}
 
-impl AssetsDownloader {
-    pub async fn new(url: String, id: String, objects: PathBuf, indexes: PathBuf) -> Result<Self> {
-        let assets: Assets = Client::new().get(&url).send().await?.json().await?;
-
-        let mut queue = DownloadQueue::new();
-
-        assets
-            .objects
-            .iter()
-            .collect_vec()
-            .chunks(100)
-            .map(|c| c.iter().map(|(_, v)| v).copied())
-            .map(|chunk| {
-                chunk
-                    .filter_map(|asset| {
-                        let path = objects.join(&asset.hash[0..2]).join(&asset.hash);
-
-                        if path.exists() && std::fs::read(&path).ok().is_some_and(|buff| asset.hash == calculate_sha1(buff)) {
-                            None
-                        } else {
-                            let downloader = FileDownloader::new(
-                                format!("https://resources.download.minecraft.net/{}/{}", &asset.hash[0..2], asset.hash),
-                                path,
-                            )
-                            .with_sha1(asset.hash.clone())
-                            .into_retry();
-
-                            Some(downloader)
-                        }
-                    })
-                    .map::<Box<dyn Downloadable<Out = DownloadResult>>, _>(|downloader| Box::new(downloader))
-                    .collect::<Vec<_>>()
-            })
-            .map(DownloadSet::from_vec_dyn)
-            .map(Chunk::new)
-            .for_each(|downloader| queue.add_downloader(downloader));
-
-        Ok(Self { queue, assets, indexes, id })
+    /// Disabling it downloads every asset again.
+    #[must_use]
+    pub fn verify_first(mut self, verify_first: bool) -> Self {
+        self.verify_first = verify_first;
+        self
     }
 }
 
@@ -123,15 +96,21 @@ impl Downloader for AssetsDownloader {
     type Data = DownloadResult;
 
     fn total(&self) -> u32 {
-        self.queue.total()
+        self.downloads.len() as u32
     }
 
     #[tracing::instrument(skip_all)]
     async fn download(self: Box<Self>, sender: &dyn ProgressSender<Self::Data>) {
-        Box::new(self.queue).download(sender).await;
+        let mut set = DownloadSet::new().with_priority(Priority::Low);
+
+        for downloader in self.downloads {
+            set.add(Box::new(downloader.verify_first(self.verify_first).into_retry()));
+        }
+
+        Box::new(set).download(sender).await;
     }
 
-    fn io(&self) -> PinnedFutureWithBounds<anyhow::Result<()>> {
+    fn io(&self) -> PinnedFutureWithBounds<crate::Result<()>> {
         let id = self.id.clone();
         let indexes = self.indexes.clone();
         let assets = self.assets.clone();
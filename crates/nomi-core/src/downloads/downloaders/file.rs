@@ -1,13 +1,12 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use tracing::warn;
+use tracing::trace;
 
 use crate::{
-    calculate_sha1,
+    calculate_file_hashes,
     downloads::{
         download_file,
         traits::{DownloadResult, DownloadStatus, Downloadable},
-        DownloadError,
     },
 };
 
@@ -18,11 +17,19 @@ pub struct FileDownloader {
     url: String,
     path: PathBuf,
     hash_sha1: Option<String>,
+    hash_sha512: Option<String>,
+    verify_first: bool,
 }
 
 impl FileDownloader {
     pub fn new(url: String, path: PathBuf) -> Self {
-        Self { url, path, hash_sha1: None }
+        Self {
+            url,
+            path,
+            hash_sha1: None,
+            hash_sha512: None,
+            verify_first: false,
+        }
     }
 
     #[must_use]
@@ -31,6 +38,20 @@ impl FileDownloader {
         self
     }
 
+    #[must_use]
+    pub fn with_sha512(mut self, hash: String) -> Self {
+        self.hash_sha512 = Some(hash);
+        self
+    }
+
+    /// Skips the download if the file already exists and matches the hash.
+    /// Files without a hash are skipped if they exist.
+    #[must_use]
+    pub fn verify_first(mut self, verify_first: bool) -> Self {
+        self.verify_first = verify_first;
+        self
+    }
+
     #[must_use]
     pub fn into_retry(self) -> ReTryDownloader {
         ReTryDownloader::new(self)
@@ -42,36 +63,44 @@ impl Downloadable for FileDownloader {
     type Out = DownloadResult;
 
     async fn download(self: Box<Self>) -> Self::Out {
-        let result = download_file(&self.path, &self.url).await.map(|()| DownloadStatus::Success);
+        if self.verify_first && is_file_valid(&self.path, self.hash_sha1.as_deref(), self.hash_sha512.as_deref()).await {
+            trace!("Already downloaded {}", self.path.to_string_lossy());
+            return DownloadResult(Ok(DownloadStatus::Success));
+        }
 
-        let Ok(_) = result else {
-            dbg!("Returning");
-            return DownloadResult(result);
-        };
+        let mut downloader = download_file(&self.path, &self.url);
 
         if let Some(hash) = self.hash_sha1 {
-            let file = match tokio::fs::read(&self.path).await.map_err(|e| DownloadError::Error {
-                url: self.url.clone(),
-                path: self.path.clone(),
-                error: format!("Unable to open downloaded file. Original error: {e}"),
-            }) {
-                Ok(val) => val,
-                Err(e) => return DownloadResult(result.map_err(|_| e)),
-            };
-
-            let calculated_hash = calculate_sha1(file);
-
-            if hash != calculated_hash {
-                let s = format!("Hashes does not match. {hash} != {calculated_hash}");
-                warn!("{s}");
-                return DownloadResult(Err(DownloadError::Error {
-                    url: self.url.clone(),
-                    path: self.path.clone(),
-                    error: s,
-                }));
-            }
+            downloader = downloader.with_sha1(hash);
         }
 
-        DownloadResult(result)
+        if let Some(hash) = self.hash_sha512 {
+            downloader = downloader.with_sha512(hash);
+        }
+
+        DownloadResult(downloader.await.map(|()| DownloadStatus::Success))
     }
 }
+
+/// Checks that the file exists and matches every hash that is present.
+///
+/// The file is hashed on a blocking thread, so many files can be checked in parallel.
+pub async fn is_file_valid(path: &Path, sha1: Option<&str>, sha512: Option<&str>) -> bool {
+    let path = path.to_path_buf();
+    let sha1 = sha1.map(ToOwned::to_owned);
+    let sha512 = sha512.map(ToOwned::to_owned);
+
+    tokio::task::spawn_blocking(move || is_file_valid_blocking(&path, sha1.as_deref(), sha512.as_deref()))
+        .await
+        .unwrap_or(false)
+}
+
+/// Blocking version of [`is_file_valid`].
+pub fn is_file_valid_blocking(path: &Path, sha1: Option<&str>, sha512: Option<&str>) -> bool {
+    if sha1.is_none() && sha512.is_none() {
+        return path.is_file();
+    }
+
+    calculate_file_hashes(path, sha1.is_some(), sha512.is_some())
+        .is_ok_and(|hashes| hashes.sha1.as_deref() == sha1 && hashes.sha512.as_deref() == sha512)
+}
@@ -2,10 +2,9 @@ use std::{
     fs::File,
     path::{Path, PathBuf},
 };
-use thiserror::Error;
 use tracing::error;
 
-use crate::{downloads::progress::ProgressSender, PinnedFutureWithBounds, DOT_NOMI_TEMP_DIR};
+use crate::{downloads::progress::ProgressSender, Error, PinnedFutureWithBounds, DOT_NOMI_TEMP_DIR};
 
 use super::{
     super::traits::{DownloadResult, Downloader},
@@ -52,7 +51,7 @@ mod consts {
     pub(super) const ARCHIVE_FILENAME: &str = "openjdk-22.0.1_linux-aarch64_bin.tar.gz";
 }
 
-fn check_hash(path: PathBuf, hash: &str) -> anyhow::Result<bool> {
+fn check_hash(path: PathBuf, hash: &str) -> crate::Result<bool> {
     let sha = sha256::try_digest(path)?;
     Ok(dbg!(sha) == dbg!(hash))
 }
@@ -86,13 +85,13 @@ impl Downloader for JavaDownloader {
         Box::new(downloader).download(sender).await;
     }
 
-    fn io(&self) -> PinnedFutureWithBounds<anyhow::Result<()>> {
+    fn io(&self) -> PinnedFutureWithBounds<crate::Result<()>> {
         let target_directory = self.target_directory.clone();
 
         let fut = async move {
             let path = PathBuf::from(DOT_NOMI_TEMP_DIR).join(consts::ARCHIVE_FILENAME);
             if !check_hash(path.clone(), consts::SHA256)? {
-                return Err(JavaDownloaderError::HashDoesNotMatch.into());
+                return Err(Error::HashMismatch { path });
             }
 
             let file = File::open(&path)?;
@@ -109,13 +108,13 @@ impl Downloader for JavaDownloader {
 }
 
 #[cfg(target_os = "windows")]
-fn extract(archive: std::fs::File, target_path: &Path) -> anyhow::Result<()> {
+fn extract(archive: std::fs::File, target_path: &Path) -> crate::Result<()> {
     let mut zip = zip::ZipArchive::new(archive)?;
     zip.extract(target_path).map_err(Into::into)
 }
 
 #[cfg(not(target_os = "windows"))]
-fn extract(archive: std::fs::File, target_path: &Path) -> anyhow::Result<()> {
+fn extract(archive: std::fs::File, target_path: &Path) -> crate::Result<()> {
     use flate2::read::GzDecoder;
     use tar::Archive;
 
@@ -124,21 +123,6 @@ fn extract(archive: std::fs::File, target_path: &Path) -> anyhow::Result<()> {
     archive.unpack(target_path).map_err(Into::into)
 }
 
-#[derive(Error, Debug)]
-enum JavaDownloaderError {
-    #[error("Hash does not match")]
-    HashDoesNotMatch,
-
-    #[error("data store disconnected")]
-    IoError(#[from] std::io::Error),
-
-    #[error("Reqwest error")]
-    ReqwestError(#[from] reqwest::Error),
-
-    #[error("Join error")]
-    JoinError(#[from] tokio::task::JoinError),
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -178,7 +162,7 @@ mod tests {
         pub(super) const ARCHIVE_FILENAME: &str = "openjdk-22.0.1_linux-aarch64_bin.tar.gz";
     }
 
-    async fn java_downloader_test_helper(url: &str, file_name: &str, hash: &str) -> anyhow::Result<bool> {
+    async fn java_downloader_test_helper(url: &str, file_name: &str, hash: &str) -> crate::Result<bool> {
         let downloader = FileDownloader::new(url.to_owned(), PathBuf::from("./java_downloader_test").join(file_name)).into_retry();
 
         let (tx, mut rx) = tokio::sync::mpsc::channel(5);
@@ -217,7 +201,7 @@ mod tests {
 
     #[tokio::test]
     async fn tarball_structure_test() {
-        fn extract_tarball(archive: std::fs::File, target_path: &Path) -> anyhow::Result<()> {
+        fn extract_tarball(archive: std::fs::File, target_path: &Path) -> crate::Result<()> {
             use flate2::read::GzDecoder;
             use tar::Archive;
 
@@ -0,0 +1,332 @@
+use std::path::{Path, PathBuf};
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::{
+    cache::is_offline_mode,
+    downloads::{
+        progress::ProgressSender,
+        traits::{DownloadResult, Downloadable, Downloader},
+    },
+    fs::{read_json_config, write_json_config},
+    repository::{
+        java_runner::JavaRunner,
+        java_runtime::{
+            get_java_runtime_manifest, get_java_runtimes, runtime_platform, JavaRuntimeFile, JavaRuntimeManifest, RuntimeVersion, JAVA_RUNTIMES_URL,
+        },
+        manifest::JavaVersion,
+    },
+    PinnedFutureWithBounds,
+};
+
+use super::{is_file_valid_blocking, DownloadSet, FileDownloader};
+
+#[derive(Error, Debug)]
+pub enum JavaRuntimeError {
+    #[error("Java runtimes are not available for this platform")]
+    UnsupportedPlatform,
+
+    #[error("Java runtime is not installed and cannot be downloaded in the offline mode")]
+    Offline,
+
+    #[error("There is no Java runtime for `{component}` (Java {major_version})")]
+    NoSuchRuntime { component: String, major_version: i8 },
+
+    #[error("Java runtime `{component}` is corrupted. Files that do not match: {files:?}")]
+    Corrupted { component: String, files: Vec<String> },
+}
+
+/// Information about a runtime installed by [`JavaRuntimeManager`].
+///
+/// Stored next to the runtime directory as `<component>.json`
+/// and written only after every file of the runtime has been verified.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct InstalledJavaRuntime {
+    pub component: String,
+    pub version: RuntimeVersion,
+}
+
+/// Manages Java runtimes from Mojang's runtime index.
+///
+/// Every component is installed into its own directory: `<root>/<component>`.
+#[derive(Debug, Clone)]
+pub struct JavaRuntimeManager {
+    root: PathBuf,
+    runtimes_url: String,
+}
+
+impl JavaRuntimeManager {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            runtimes_url: JAVA_RUNTIMES_URL.to_owned(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_runtimes_url(mut self, url: impl Into<String>) -> Self {
+        self.runtimes_url = url.into();
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn component_dir(&self, component: &str) -> PathBuf {
+        self.root.join(component)
+    }
+
+    pub fn executable(&self, component: &str) -> PathBuf {
+        runtime_executable(&self.component_dir(component))
+    }
+
+    fn installed_info_path(&self, component: &str) -> PathBuf {
+        self.root.join(format!("{component}.json"))
+    }
+
+    pub fn is_installed(&self, component: &str) -> bool {
+        self.installed_info_path(component).exists() && self.executable(component).exists()
+    }
+
+    /// Returns all runtimes that were completely installed.
+    pub async fn installed(&self) -> crate::Result<Vec<InstalledJavaRuntime>> {
+        if !self.root.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut installed = Vec::new();
+        let mut entries = tokio::fs::read_dir(&self.root).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+
+            match read_json_config::<InstalledJavaRuntime>(&path).await {
+                Ok(runtime) if self.executable(&runtime.component).exists() => installed.push(runtime),
+                Ok(_) => {}
+                Err(e) => warn!(error = ?e, path = %path.display(), "Skipping invalid runtime info"),
+            }
+        }
+
+        Ok(installed)
+    }
+
+    /// Finds an installed runtime that satisfies `java_version`.
+    ///
+    /// The component requested by the version is preferred,
+    /// otherwise any runtime with the same major version is used.
+    pub async fn installed_runner(&self, java_version: &JavaVersion) -> crate::Result<Option<JavaRunner>> {
+        if self.is_installed(&java_version.component) {
+            return Ok(Some(JavaRunner::path(self.executable(&java_version.component))));
+        }
+
+        Ok(self
+            .installed()
+            .await?
+            .into_iter()
+            .find(|runtime| runtime.version.major() == Some(java_version.major_version))
+            .map(|runtime| JavaRunner::path(self.executable(&runtime.component))))
+    }
+
+    /// Creates a downloader for a runtime that satisfies `java_version`.
+    pub async fn downloader(&self, java_version: &JavaVersion) -> crate::Result<JavaRuntimeDownloader> {
+        if is_offline_mode() {
+            return Err(JavaRuntimeError::Offline.into());
+        }
+
+        let platform = runtime_platform().ok_or(JavaRuntimeError::UnsupportedPlatform)?;
+        let runtimes = get_java_runtimes(&self.runtimes_url).await?;
+
+        let (component, runtime) = runtimes.find(platform, java_version).ok_or_else(|| JavaRuntimeError::NoSuchRuntime {
+            component: java_version.component.clone(),
+            major_version: java_version.major_version,
+        })?;
+
+        let manifest = get_java_runtime_manifest(&runtime.manifest.url).await?;
+
+        Ok(JavaRuntimeDownloader::new(
+            InstalledJavaRuntime {
+                component: component.to_owned(),
+                version: runtime.version.clone(),
+            },
+            self.root.clone(),
+            manifest,
+        ))
+    }
+
+    /// Returns a runner for a runtime that satisfies `java_version`,
+    /// downloading the runtime if it is not installed yet.
+    pub async fn runner_for(&self, java_version: &JavaVersion) -> crate::Result<JavaRunner> {
+        if let Some(runner) = self.installed_runner(java_version).await? {
+            return Ok(runner);
+        }
+
+        let downloader = self.downloader(java_version).await?;
+        let component = downloader.runtime.component.clone();
+
+        info!("Downloading Java runtime {component}");
+
+        let io = downloader.io();
+
+        // Nobody listens to the progress here, every file is verified by `io` anyway.
+        let (sender, _) = tokio::sync::mpsc::channel(1);
+        Box::new(downloader).download(&sender).await;
+
+        io.await?;
+
+        Ok(JavaRunner::path(self.executable(&component)))
+    }
+}
+
+fn runtime_executable(component_dir: &Path) -> PathBuf {
+    if cfg!(windows) {
+        component_dir.join("bin").join("javaw.exe")
+    } else if cfg!(target_os = "macos") {
+        component_dir.join("jre.bundle/Contents/Home/bin/java")
+    } else {
+        component_dir.join("bin").join("java")
+    }
+}
+
+/// Downloads files of a runtime component that are missing or do not match their hashes.
+/// Existing files are [verified](FileDownloader::verify_first) in parallel.
+///
+/// You must call [`Downloader::io`] in order to finish the installation.
+/// It verifies every file and marks the runtime as installed.
+#[derive(Debug)]
+pub struct JavaRuntimeDownloader {
+    runtime: InstalledJavaRuntime,
+    root: PathBuf,
+    manifest: JavaRuntimeManifest,
+    set: DownloadSet,
+}
+
+impl JavaRuntimeDownloader {
+    pub fn new(runtime: InstalledJavaRuntime, root: PathBuf, manifest: JavaRuntimeManifest) -> Self {
+        let component_dir = root.join(&runtime.component);
+
+        let downloads = manifest
+            .files
+            .iter()
+            .filter_map(|(name, file)| match file {
+                JavaRuntimeFile::File { downloads, .. } => Some((component_dir.join(name), &downloads.raw)),
+                JavaRuntimeFile::Directory | JavaRuntimeFile::Link { .. } => None,
+            })
+            .map::<Box<dyn Downloadable<Out = DownloadResult>>, _>(|(path, raw)| {
+                let downloader = FileDownloader::new(raw.url.clone(), path).with_sha1(raw.sha1.clone());
+                Box::new(downloader.verify_first(true).into_retry())
+            })
+            .collect_vec();
+
+        Self {
+            runtime,
+            root,
+            manifest,
+            set: DownloadSet::from_vec_dyn(downloads),
+        }
+    }
+
+    pub fn runtime(&self) -> &InstalledJavaRuntime {
+        &self.runtime
+    }
+}
+
+#[async_trait::async_trait]
+impl Downloader for JavaRuntimeDownloader {
+    type Data = DownloadResult;
+
+    fn total(&self) -> u32 {
+        self.set.total()
+    }
+
+    #[tracing::instrument(skip_all, fields(component = self.runtime.component))]
+    async fn download(self: Box<Self>, sender: &dyn ProgressSender<Self::Data>) {
+        Box::new(self.set).download(sender).await;
+    }
+
+    fn io(&self) -> PinnedFutureWithBounds<crate::Result<()>> {
+        let runtime = self.runtime.clone();
+        let root = self.root.clone();
+        let manifest = self.manifest.clone();
+
+        let fut = async move {
+            let component_dir = root.join(&runtime.component);
+
+            let files = tokio::task::spawn_blocking(move || finish_installation(&component_dir, &manifest)).await??;
+
+            if !files.is_empty() {
+                return Err(JavaRuntimeError::Corrupted {
+                    component: runtime.component,
+                    files,
+                }
+                .into());
+            }
+
+            write_json_config(&runtime, root.join(format!("{}.json", runtime.component))).await?;
+
+            info!("Java runtime {} ({}) is installed", runtime.component, runtime.version.name);
+
+            Ok(())
+        };
+
+        Box::pin(fut)
+    }
+}
+
+/// Creates directories and links, sets permissions and verifies every file of the runtime.
+///
+/// Returns names of the files that are missing or do not match their hashes.
+fn finish_installation(component_dir: &Path, manifest: &JavaRuntimeManifest) -> crate::Result<Vec<String>> {
+    let mut invalid = Vec::new();
+
+    for (name, file) in &manifest.files {
+        let path = component_dir.join(name);
+
+        match file {
+            JavaRuntimeFile::Directory => std::fs::create_dir_all(&path)?,
+            JavaRuntimeFile::File { executable, downloads } => {
+                if !is_file_valid_blocking(&path, Some(&downloads.raw.sha1), None) {
+                    invalid.push(name.clone());
+                    continue;
+                }
+
+                #[cfg(unix)]
+                if *executable {
+                    use std::os::unix::fs::PermissionsExt;
+                    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+                }
+
+                #[cfg(not(unix))]
+                let _ = executable;
+            }
+            JavaRuntimeFile::Link { target } => create_link(&path, target)?,
+        }
+    }
+
+    Ok(invalid)
+}
+
+#[cfg(unix)]
+fn create_link(path: &Path, target: &str) -> crate::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    if path.symlink_metadata().is_ok() {
+        std::fs::remove_file(path)?;
+    }
+
+    std::os::unix::fs::symlink(target, path).map_err(Into::into)
+}
+
+/// Runtimes for Windows do not contain links.
+#[cfg(not(unix))]
+fn create_link(_path: &Path, _target: &str) -> crate::Result<()> {
+    Ok(())
+}
@@ -3,19 +3,24 @@ use tracing::debug;
 
 use crate::downloads::{
     progress::ProgressSender,
+    scheduler::Priority,
     traits::{DownloadResult, Downloader},
     DownloadSet,
 };
 
-use super::{file::FileDownloader, ReTryDownloader};
+use super::file::FileDownloader;
 
 pub trait LibrariesMapper<L> {
     fn proceed(&self, library: &L) -> Option<FileDownloader>;
 }
 
+/// Downloads libraries with the [high](Priority::High) priority.
+///
+/// Libraries that are already downloaded are [verified](FileDownloader::verify_first) and skipped by default.
 #[derive(Debug)]
 pub struct LibrariesDownloader {
-    downloads: Vec<ReTryDownloader>,
+    downloads: Vec<FileDownloader>,
+    verify_first: bool,
 }
 
 impl LibrariesDownloader {
@@ -23,13 +28,19 @@ impl LibrariesDownloader {
     where
         M: LibrariesMapper<L>,
     {
-        let downloads = libraries
-            .iter()
-            .filter_map(|lib| mapper.proceed(lib))
-            .map(FileDownloader::into_retry)
-            .collect_vec();
+        let downloads = libraries.iter().filter_map(|lib| mapper.proceed(lib)).collect_vec();
 
-        Self { downloads }
+        Self {
+            downloads,
+            verify_first: true,
+        }
+    }
+
+    /// Disabling it downloads every library again.
+    #[must_use]
+    pub fn verify_first(mut self, verify_first: bool) -> Self {
+        self.verify_first = verify_first;
+        self
     }
 }
 
@@ -42,10 +53,10 @@ impl Downloader for LibrariesDownloader {
     }
 
     async fn download(self: Box<Self>, sender: &dyn ProgressSender<Self::Data>) {
-        let mut download_set = DownloadSet::new();
+        let mut download_set = DownloadSet::new().with_priority(Priority::High);
 
         for downloader in self.downloads {
-            download_set.add(Box::new(downloader));
+            download_set.add(Box::new(downloader.verify_first(self.verify_first).into_retry()));
         }
 
         Box::new(download_set).download(sender).await;
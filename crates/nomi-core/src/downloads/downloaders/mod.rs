@@ -1,6 +1,7 @@
 pub mod assets;
 pub mod file;
 pub mod java;
+pub mod java_runtime;
 pub mod libraries;
 pub mod queue;
 pub mod retry;
@@ -1,14 +1,23 @@
 use std::fmt::Debug;
 
+use futures_util::future::join_all;
+
 use crate::downloads::{
     progress::ProgressSender,
+    scheduler::{DownloadContext, Priority},
     traits::{DownloadResult, Downloader},
 };
 
+/// Runs all downloaders as one install.
+///
+/// Downloaders run concurrently in the same [`DownloadContext`] group, the order in which
+/// their files are downloaded is decided by the [`DownloadScheduler`](crate::downloads::scheduler::DownloadScheduler)
+/// based on their priorities.
 #[derive(Default)]
 pub struct DownloadQueue {
     queue: Vec<Box<dyn Downloader<Data = DownloadResult>>>,
     inspector: Option<Box<dyn Fn() + Sync + Send>>,
+    priority: Option<Priority>,
 }
 
 impl Debug for DownloadQueue {
@@ -28,6 +37,13 @@ impl DownloadQueue {
         self
     }
 
+    /// Overrides the priority inherited from the current [`DownloadContext`].
+    #[must_use]
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
     pub fn add_downloader<D>(&mut self, downloader: D)
     where
         D: Downloader<Data = DownloadResult> + 'static,
@@ -60,9 +76,19 @@ impl Downloader for DownloadQueue {
     }
 
     async fn download(self: Box<Self>, sender: &dyn ProgressSender<Self::Data>) {
-        for downloader in self.queue {
-            downloader.download(sender).await;
-            self.inspector.as_ref().inspect(|f| f());
-        }
+        let Self { queue, inspector, priority } = *self;
+        let inspector = inspector.as_ref();
+
+        DownloadContext::run(priority, sender, |_| {
+            let downloads = queue.into_iter().map(|downloader| async move {
+                downloader.download(sender).await;
+                inspector.inspect(|f| f());
+            });
+
+            async move {
+                join_all(downloads).await;
+            }
+        })
+        .await;
     }
 }
@@ -8,6 +8,7 @@ use crate::downloads::{
     DownloadError,
 };
 
+/// Downloads the element again if it fails with a [retryable](DownloadError::is_retryable) error.
 pub struct ReTryDownloader {
     downloadable: Box<dyn DynCloneDownloadable>,
     iterations: usize,
@@ -60,18 +61,30 @@ impl Downloadable for ReTryDownloader {
 
     #[tracing::instrument(skip(self), fields(iterations = self.iterations, time_between = tracing::field::debug(&self.duration)))]
     async fn download(self: Box<Self>) -> Self::Out {
-        for i in 0..=self.iterations {
+        let mut i = 0;
+
+        loop {
             let downloadable = dyn_clone::clone_box(&*self.downloadable);
-            match downloadable.download().await.0 {
-                Ok(ok) => return DownloadResult(Ok(ok.clone())),
-                Err(err) => warn!("Downloading iteration {i} failed. Retrying. Error: {err}"),
+            let err = match downloadable.download().await.0 {
+                Ok(ok) => return DownloadResult(Ok(ok)),
+                Err(err) => err,
+            };
+
+            if !err.is_retryable() {
+                error!("Downloading iteration {i} failed and cannot be retried. Error: {err}");
+                return DownloadResult(Err(err));
+            }
+
+            if i == self.iterations {
+                error!("All iterations failed");
+                return DownloadResult(Err(DownloadError::AllIterationsFailed(Box::new(err))));
             }
+
+            warn!("Downloading iteration {i} failed. Retrying. Error: {err}");
+
             // Wait between iterations
             tokio::time::sleep(self.duration).await;
+            i += 1;
         }
-
-        error!("All iterations failed");
-
-        DownloadResult(Err(DownloadError::AllIterationsFailed))
     }
 }
@@ -1,19 +1,26 @@
 use std::fmt::Debug;
 
 use tokio::{sync::mpsc::Sender, task::JoinSet};
+use tracing::debug;
 
 use crate::downloads::{
     progress::ProgressSender,
+    scheduler::{DownloadContext, Priority},
     traits::{DownloadResult, Downloadable, Downloader},
     DownloadError,
 };
 
 /// Downloader that starts downloading all provided [`Downloadable`] elements
-/// when [`Downloader::download`] is called
+/// when [`Downloader::download`] is called.
+///
+/// The elements are started at once but every request waits for a slot from the
+/// [`DownloadScheduler`](crate::downloads::scheduler::DownloadScheduler).
+/// They are scheduled in the current [`DownloadContext`], or in a new group if there is none.
 #[derive(Default)]
 pub struct DownloadSet {
     set: Vec<Box<dyn Downloadable<Out = DownloadResult>>>,
     helper: Option<Sender<DownloadResult>>,
+    priority: Option<Priority>,
 }
 
 impl Debug for DownloadSet {
@@ -33,8 +40,19 @@ impl DownloadSet {
         self
     }
 
+    /// Overrides the priority inherited from the current [`DownloadContext`].
+    #[must_use]
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
     pub fn from_vec_dyn(vec: Vec<Box<dyn Downloadable<Out = DownloadResult>>>) -> Self {
-        Self { set: vec, helper: None }
+        Self {
+            set: vec,
+            helper: None,
+            priority: None,
+        }
     }
 
     pub fn add<D>(&mut self, downloader: Box<D>) -> &mut Self
@@ -54,26 +72,44 @@ impl Downloader for DownloadSet {
         self.set.len() as u32
     }
 
-    async fn download(mut self: Box<Self>, sender: &dyn ProgressSender<Self::Data>) {
-        let mut set = JoinSet::new();
+    async fn download(self: Box<Self>, sender: &dyn ProgressSender<Self::Data>) {
+        let Self {
+            set: downloads,
+            helper,
+            priority,
+        } = *self;
 
-        for downloader in self.set {
-            set.spawn(downloader.download());
-        }
+        DownloadContext::run(priority, sender, |context| async move {
+            let mut set = JoinSet::new();
 
-        while let Some(result) = set.join_next().await {
-            if let Ok(download_status) = result {
-                sender.update(download_status.clone()).await;
-                if let Some(sender) = self.helper.as_ref() {
-                    let _ = sender.send(download_status.clone()).await;
+            for downloader in downloads {
+                set.spawn(context.clone().scope(downloader.download()));
+            }
+
+            let (mut ok, mut err) = (0, 0);
+
+            while let Some(result) = set.join_next().await {
+                match &result {
+                    Ok(DownloadResult(Ok(_))) => ok += 1,
+                    _ => err += 1,
                 }
-            } else {
-                sender.update(DownloadResult(Err(DownloadError::JoinError))).await;
 
-                if let Some(sender) = self.helper.as_ref() {
-                    let _ = sender.send(DownloadResult(Err(DownloadError::JoinError))).await;
+                if let Ok(download_status) = result {
+                    sender.update(download_status.clone()).await;
+                    if let Some(sender) = helper.as_ref() {
+                        let _ = sender.send(download_status.clone()).await;
+                    }
+                } else {
+                    sender.update(DownloadResult(Err(DownloadError::JoinError))).await;
+
+                    if let Some(sender) = helper.as_ref() {
+                        let _ = sender.send(DownloadResult(Err(DownloadError::JoinError))).await;
+                    }
                 }
-            };
-        }
+            }
+
+            debug!(ok, err, "Finished downloading the set");
+        })
+        .await;
     }
 }
@@ -3,37 +3,102 @@ pub use downloaders::*;
 use std::{
     future::IntoFuture,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use futures_util::stream::StreamExt;
-use reqwest::{Client, RequestBuilder, Response};
+use reqwest::{
+    header::{CONTENT_RANGE, RANGE},
+    Client, RequestBuilder, Response, StatusCode,
+};
 use tokio::io::AsyncWriteExt;
-use tracing::{error, trace};
+use tracing::{debug, error, trace, warn};
+
+use crate::{calculate_file_hashes, mirrors::mirrored, network::http_client, Error, PinnedFutureWithBounds};
 
-use crate::PinnedFutureWithBounds;
+use progress::TransferProgress;
+use scheduler::{DownloadContext, DownloadScheduler};
 
 pub mod downloaders;
 pub mod progress;
+pub mod scheduler;
 pub mod traits;
 
 #[derive(Debug, thiserror::Error, Clone)]
 pub enum DownloadError {
-    #[error("DownloadError:\nurl: {url}\npath: {path}\nerror: {error:#?}")]
-    Error { url: String, path: PathBuf, error: String },
+    #[error("Cannot download `{url}` into `{}`: {source}", path.display())]
+    Error {
+        url: String,
+        path: PathBuf,
+        #[source]
+        source: Arc<Error>,
+    },
 
-    #[error("Hashes does not match.\nurl: {url}\npath: {path}\nerror: {error:#?}")]
+    #[error("`{url}` does not match its hash, expected {expected} but got {calculated}")]
     HashDoesNotMatch {
         url: String,
         path: PathBuf,
-        sha1: String,
-        error: String,
+        expected: String,
+        calculated: String,
     },
 
+    /// The partial file cannot be resumed. It is removed so the next attempt starts over.
+    #[error("Cannot resume `{url}` into `{}`: {reason}", path.display())]
+    Resume { url: String, path: PathBuf, reason: String },
+
     #[error("The task was cancelled or panicked")]
     JoinError,
 
-    #[error("All iterations failed")]
-    AllIterationsFailed,
+    #[error("All iterations failed, the last error: {0}")]
+    AllIterationsFailed(#[source] Box<DownloadError>),
+}
+
+impl DownloadError {
+    pub fn is_network(&self) -> bool {
+        match self {
+            Self::Error { source, .. } => source.is_network(),
+            Self::AllIterationsFailed(last) => last.is_network(),
+            _ => false,
+        }
+    }
+
+    pub fn is_io(&self) -> bool {
+        match self {
+            Self::Error { source, .. } => source.is_io(),
+            Self::AllIterationsFailed(last) => last.is_io(),
+            _ => false,
+        }
+    }
+
+    pub fn is_hash_mismatch(&self) -> bool {
+        match self {
+            Self::HashDoesNotMatch { .. } => true,
+            Self::AllIterationsFailed(last) => last.is_hash_mismatch(),
+            _ => false,
+        }
+    }
+
+    /// Path of the file that was not downloaded.
+    ///
+    /// `None` if the task was cancelled or panicked.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Error { path, .. } | Self::HashDoesNotMatch { path, .. } | Self::Resume { path, .. } => Some(path),
+            Self::JoinError => None,
+            Self::AllIterationsFailed(last) => last.path(),
+        }
+    }
+
+    /// Whether another attempt may succeed.
+    ///
+    /// The corrupted files are removed, so the attempts after a hash mismatch start over.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Error { source, .. } => source.is_retryable(),
+            Self::HashDoesNotMatch { .. } | Self::Resume { .. } => true,
+            Self::JoinError | Self::AllIterationsFailed(_) => false,
+        }
+    }
 }
 
 pub(crate) fn download_file(path: impl AsRef<Path>, url: impl Into<String>) -> Downloader {
@@ -43,14 +108,29 @@ pub(crate) fn download_file(path: impl AsRef<Path>, url: impl Into<String>) -> D
     Downloader {
         url,
         path,
+        sha1: None,
+        sha512: None,
         client: None,
         request_injection: Box::new(|r| r),
     }
 }
 
+/// Path of the file that holds the data downloaded so far, `<path>.part`.
+pub fn part_path(path: &Path) -> PathBuf {
+    let mut part = path.as_os_str().to_owned();
+    part.push(".part");
+    PathBuf::from(part)
+}
+
+/// Downloads a file into its [partial file](part_path) and renames it to the target path once it is complete.
+///
+/// If the partial file is left by a previous attempt, the download is resumed
+/// with a `Range` request. Servers that do not support it send the whole file again.
 pub struct Downloader {
     url: String,
     path: PathBuf,
+    sha1: Option<String>,
+    sha512: Option<String>,
     client: Option<Client>,
     request_injection: Box<dyn FnOnce(RequestBuilder) -> RequestBuilder + Send>,
 }
@@ -68,56 +148,186 @@ impl Downloader {
         self
     }
 
+    /// The complete file is checked before it is moved to the target path.
+    /// If it does not match, the partial file is removed so the next attempt starts over.
+    #[must_use]
+    pub fn with_sha1(mut self, sha1: String) -> Self {
+        self.sha1 = Some(sha1);
+        self
+    }
+
+    /// Same as [`Self::with_sha1`], both hashes are checked if both are set.
+    #[must_use]
+    pub fn with_sha512(mut self, sha512: String) -> Self {
+        self.sha512 = Some(sha512);
+        self
+    }
+
     async fn download(self) -> Result<(), DownloadError> {
-        let download_error = |error| -> DownloadError {
+        let download_error = |error: Error| -> DownloadError {
             DownloadError::Error {
                 url: self.url.clone(),
                 path: self.path.clone(),
-                error,
+                source: Arc::new(error),
+            }
+        };
+        let resume_error = |reason: String| -> DownloadError {
+            DownloadError::Resume {
+                url: self.url.clone(),
+                path: self.path.clone(),
+                reason,
             }
         };
 
         if let Some(path) = self.path.parent() {
-            tokio::fs::create_dir_all(path).await.map_err(|err| download_error(err.to_string()))?;
+            tokio::fs::create_dir_all(path).await.map_err(|err| download_error(err.into()))?;
         }
 
-        let client = self.client.unwrap_or_default();
-        let request = (self.request_injection)(client.get(&self.url));
-        let res = request
-            .send()
-            .await
-            .and_then(Response::error_for_status)
-            .map_err(|err| download_error(err.to_string()))?;
-
-        let mut file = tokio::fs::File::create(&self.path).await.map_err(|err| {
-            error!(
-                "Error occurred during file creating\nPath: {}\nError: {}",
-                self.path.to_string_lossy(),
-                err
-            );
-            download_error(err.to_string())
-        })?;
+        let part_path = part_path(&self.path);
+        let downloaded = tokio::fs::metadata(&part_path).await.map_or(0, |metadata| metadata.len());
 
-        let mut stream = res.bytes_stream();
+        let url = mirrored(&self.url);
+        // Held until the whole body is written.
+        let _permit = DownloadScheduler::global().acquire(&url).await;
 
-        while let Some(item) = stream.next().await {
-            let chunk = item.map_err(|err| {
-                error!("Error occurred during file downloading\nError: {}", err);
-                download_error(err.to_string())
-            })?;
+        let client = self.client.unwrap_or_else(http_client);
+        let mut request = (self.request_injection)(client.get(url));
+
+        if downloaded > 0 {
+            request = request.header(RANGE, format!("bytes={downloaded}-"));
+        }
+
+        let res = request.send().await.map_err(|err| download_error(err.into()))?;
+
+        // The partial file is either complete already or does not belong to this file.
+        let is_unsatisfiable = downloaded > 0 && res.status() == StatusCode::RANGE_NOT_SATISFIABLE;
+
+        if !is_unsatisfiable {
+            let res = res.error_for_status().map_err(|err| download_error(err.into()))?;
+
+            let resumed = downloaded > 0 && res.status() == StatusCode::PARTIAL_CONTENT;
+
+            if resumed && content_range_start(&res) != Some(downloaded) {
+                let _ = tokio::fs::remove_file(&part_path).await;
+                return Err(resume_error(format!("the server sent a range that does not start at {downloaded}")));
+            }
 
-            file.write_all(&chunk).await.map_err(|err| {
-                error!("Error occurred during writing to file\nError: {}", err);
-                download_error(err.to_string())
-            })?;
+            if resumed {
+                debug!("Resuming {} from {downloaded} bytes", part_path.to_string_lossy());
+            }
+
+            let offset = if resumed { downloaded } else { 0 };
+            let total = res.content_length().map(|length| offset + length);
+            let transfer = DownloadContext::current().map(|context| context.transfer);
+
+            let report = |written| {
+                if let Some(transfer) = &transfer {
+                    let progress = TransferProgress {
+                        downloaded: offset + written,
+                        total: total.unwrap_or(offset + written),
+                    };
+                    transfer.update(&self.path, progress);
+                }
+            };
+
+            write_response(res, &part_path, resumed, report).await.map_err(download_error)?;
+        }
+
+        if self.sha1.is_some() || self.sha512.is_some() {
+            let hashes = {
+                let part_path = part_path.clone();
+                let (sha1, sha512) = (self.sha1.is_some(), self.sha512.is_some());
+                tokio::task::spawn_blocking(move || calculate_file_hashes(part_path, sha1, sha512))
+                    .await
+                    .map_err(|err| download_error(err.into()))?
+                    .map_err(|err| download_error(err.into()))?
+            };
+
+            let mismatch = [self.sha1.as_ref().zip(hashes.sha1), self.sha512.as_ref().zip(hashes.sha512)]
+                .into_iter()
+                .flatten()
+                .find(|(expected, calculated)| *expected != calculated);
+
+            if let Some((expected, calculated)) = mismatch {
+                warn!("Hashes does not match. {expected} != {calculated}");
+                let _ = tokio::fs::remove_file(&part_path).await;
+                return Err(DownloadError::HashDoesNotMatch {
+                    url: self.url.clone(),
+                    path: self.path.clone(),
+                    expected: expected.clone(),
+                    calculated,
+                });
+            }
+        } else if is_unsatisfiable {
+            // Without a hash there is no way to tell whether the partial file is complete.
+            let _ = tokio::fs::remove_file(&part_path).await;
+            return Err(resume_error(String::from("the partial file does not match the remote one")));
         }
 
+        tokio::fs::rename(&part_path, &self.path)
+            .await
+            .map_err(|err| download_error(err.into()))?;
+
         trace!("Downloaded successfully {}", self.path.to_string_lossy());
 
         Ok(())
     }
 }
 
+/// Returns the first byte of the `Content-Range` header, `bytes <start>-<end>/<size>`.
+fn content_range_start(res: &Response) -> Option<u64> {
+    let range = res.headers().get(CONTENT_RANGE)?.to_str().ok()?;
+    range.strip_prefix("bytes ")?.split_once('-')?.0.trim().parse().ok()
+}
+
+/// Writes the body into the partial file, appending to it if `append` is set.
+/// `report` is called with the number of bytes written so far after every chunk.
+///
+/// Everything received before an error stays in the file so the next attempt can resume from it.
+async fn write_response(res: Response, part_path: &Path, append: bool, report: impl Fn(u64)) -> crate::Result<()> {
+    let file = if append {
+        tokio::fs::OpenOptions::new().append(true).open(part_path).await
+    } else {
+        tokio::fs::File::create(part_path).await
+    };
+
+    let mut file = file.map_err(|err| {
+        error!(
+            "Error occurred during file creating\nPath: {}\nError: {}",
+            part_path.to_string_lossy(),
+            err
+        );
+        err
+    })?;
+
+    let mut stream = res.bytes_stream();
+    let mut written = 0;
+
+    report(written);
+
+    while let Some(item) = stream.next().await {
+        let chunk = match item {
+            Ok(chunk) => chunk,
+            Err(err) => {
+                error!("Error occurred during file downloading\nError: {}", err);
+                let _ = file.flush().await;
+                return Err(err.into());
+            }
+        };
+
+        file.write_all(&chunk).await.map_err(|err| {
+            error!("Error occurred during writing to file\nError: {}", err);
+            err
+        })?;
+
+        written += chunk.len() as u64;
+        report(written);
+    }
+
+    // `tokio::fs::File` writes in the background, the data must be on disk before the hash is checked.
+    file.flush().await.map_err(Into::into)
+}
+
 impl IntoFuture for Downloader {
     type Output = Result<(), DownloadError>;
 
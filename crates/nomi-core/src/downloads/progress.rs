@@ -1,5 +1,21 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::{Duration, Instant},
+};
+
 use egui_task_manager::Progress;
 
+use super::traits::{DownloadResult, DownloadStatus};
+
+/// How often the [`TransferTracker`] reports the transferred bytes.
+pub const TRANSFER_REPORT_INTERVAL: Duration = Duration::from_millis(250);
+
 #[async_trait::async_trait]
 pub trait ProgressSender<P: Send>: Sync + Send {
     async fn update(&self, data: P);
@@ -77,6 +93,134 @@ impl<I: Progress + 'static> MappedSender<I, Box<dyn Progress>> {
     }
 }
 
+/// Bytes transferred by a downloader.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TransferProgress {
+    pub downloaded: u64,
+    /// Sum of the sizes of the files that have started downloading.
+    /// Files that are still waiting for a slot are not counted yet.
+    pub total: u64,
+}
+
+/// Collects the bytes transferred by every file of a download.
+///
+/// Files are tracked by their paths, so a retried file does not count twice.
+#[derive(Debug, Clone, Default)]
+pub struct TransferTracker {
+    files: Arc<Mutex<HashMap<PathBuf, TransferProgress>>>,
+    changed: Arc<AtomicBool>,
+}
+
+impl TransferTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the progress of the file downloaded into `path`.
+    pub fn update(&self, path: &Path, progress: TransferProgress) {
+        self.files
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(path.to_path_buf(), progress);
+        self.changed.store(true, Ordering::Relaxed);
+    }
+
+    /// Progress of all files together.
+    pub fn progress(&self) -> TransferProgress {
+        self.files
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .fold(TransferProgress::default(), |acc, file| TransferProgress {
+                downloaded: acc.downloaded + file.downloaded,
+                total: acc.total + file.total,
+            })
+    }
+
+    async fn report(&self, sender: &dyn ProgressSender<DownloadResult>) {
+        if self.changed.swap(false, Ordering::Relaxed) {
+            sender.update(DownloadResult(Ok(DownloadStatus::Transfer(self.progress())))).await;
+        }
+    }
+
+    /// Drives `future` to completion sending [`DownloadStatus::Transfer`] to `sender`
+    /// every [`TRANSFER_REPORT_INTERVAL`] if anything has changed.
+    pub async fn report_while<F: Future<Output = ()>>(&self, future: F, sender: &dyn ProgressSender<DownloadResult>) {
+        let mut future = std::pin::pin!(future);
+        let mut interval = tokio::time::interval(TRANSFER_REPORT_INTERVAL);
+
+        loop {
+            tokio::select! {
+                () = &mut future => break,
+                _ = interval.tick() => self.report(sender).await,
+            }
+        }
+
+        self.report(sender).await;
+    }
+}
+
+/// Estimates the speed and the remaining time of a transfer from its progress updates.
+#[derive(Debug, Default, Clone)]
+pub struct TransferRate {
+    progress: TransferProgress,
+    last_update: Option<(Instant, u64)>,
+    bytes_per_second: f64,
+}
+
+impl TransferRate {
+    /// Weight of the latest measurement, smooths out the bursts of small files.
+    const SMOOTHING: f64 = 0.3;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A progress that goes backwards starts a new measurement, the sender is reused for another download.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn update(&mut self, progress: TransferProgress, now: Instant) {
+        if progress.downloaded < self.progress.downloaded {
+            *self = Self::default();
+        }
+
+        if let Some((time, downloaded)) = self.last_update {
+            let elapsed = now.saturating_duration_since(time).as_secs_f64();
+
+            if elapsed > 0.0 {
+                let speed = progress.downloaded.saturating_sub(downloaded) as f64 / elapsed;
+
+                self.bytes_per_second = if self.bytes_per_second > 0.0 {
+                    Self::SMOOTHING * speed + (1.0 - Self::SMOOTHING) * self.bytes_per_second
+                } else {
+                    speed
+                };
+            }
+        }
+
+        self.last_update = Some((now, progress.downloaded));
+        self.progress = progress;
+    }
+
+    pub fn progress(&self) -> TransferProgress {
+        self.progress
+    }
+
+    pub fn bytes_per_second(&self) -> f64 {
+        self.bytes_per_second
+    }
+
+    /// Time left to download the bytes that are known so far.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn eta(&self) -> Option<Duration> {
+        if self.bytes_per_second <= 0.0 {
+            return None;
+        }
+
+        let remaining = self.progress.total.saturating_sub(self.progress.downloaded);
+        Some(Duration::from_secs_f64(remaining as f64 / self.bytes_per_second))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::any::Any;
@@ -91,4 +235,35 @@ mod tests {
         let (sender, _) = tokio::sync::mpsc::channel(1);
         let _ = MappedSender::new(Box::new(sender), |val: u32| Box::new(val) as Box<dyn Any + Send>);
     }
+
+    #[test]
+    fn tracker_test() {
+        let tracker = TransferTracker::new();
+
+        tracker.update(Path::new("a"), TransferProgress { downloaded: 10, total: 100 });
+        tracker.update(Path::new("b"), TransferProgress { downloaded: 5, total: 50 });
+        // A retried file replaces its previous progress.
+        tracker.update(Path::new("a"), TransferProgress { downloaded: 20, total: 100 });
+
+        assert_eq!(tracker.progress(), TransferProgress { downloaded: 25, total: 150 });
+    }
+
+    #[test]
+    fn rate_test() {
+        let start = Instant::now();
+        let mut rate = TransferRate::new();
+
+        rate.update(TransferProgress { downloaded: 0, total: 3_000 }, start);
+        assert!(rate.eta().is_none());
+
+        rate.update(
+            TransferProgress {
+                downloaded: 1_000,
+                total: 3_000,
+            },
+            start + Duration::from_secs(1),
+        );
+        assert!((rate.bytes_per_second() - 1_000.0).abs() < f64::EPSILON);
+        assert_eq!(rate.eta(), Some(Duration::from_secs(2)));
+    }
 }
@@ -0,0 +1,431 @@
+//! Central scheduler that limits the number of simultaneous downloads.
+//!
+//! Every request made through [`download_file`](super::download_file) waits for a slot first.
+//! Slots are handed out to the waiting downloads with the highest [`Priority`], and round-robin
+//! between the [groups](DownloadGroup) of the same priority, so concurrent profile installs share
+//! the slots instead of the last one waiting for the first to finish.
+//!
+//! The group and priority of a download are taken from the [`DownloadContext`] of the task it runs in.
+//! [`DownloadSet`](super::DownloadSet) and [`DownloadQueue`](super::DownloadQueue) propagate it to their elements.
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, LazyLock, Mutex, PoisonError,
+    },
+};
+
+use reqwest::Url;
+use tokio::sync::oneshot;
+
+use super::{
+    progress::{ProgressSender, TransferTracker},
+    traits::DownloadResult,
+};
+
+static SCHEDULER: LazyLock<DownloadScheduler> = LazyLock::new(|| DownloadScheduler::new(SchedulerLimits::default()));
+
+tokio::task_local! {
+    static CONTEXT: DownloadContext;
+}
+
+/// Downloads with a higher priority are started first.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Client jars and libraries. The game cannot be launched without them.
+    High,
+    #[default]
+    Normal,
+    /// Assets. The game can be launched while they are still downloading.
+    Low,
+}
+
+/// Downloads that belong to the same install.
+///
+/// The default group holds the downloads started outside of any [`DownloadContext`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DownloadGroup(u64);
+
+impl DownloadGroup {
+    /// Creates a group that is different from every other one.
+    pub fn new() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DownloadContext {
+    pub group: DownloadGroup,
+    pub priority: Priority,
+    /// Collects the bytes transferred by the downloads of the context.
+    pub transfer: TransferTracker,
+}
+
+impl DownloadContext {
+    /// Creates a context with a new group and the default priority.
+    pub fn new() -> Self {
+        Self {
+            group: DownloadGroup::new(),
+            ..Default::default()
+        }
+    }
+
+    /// Context of the current task, if any.
+    pub fn current() -> Option<Self> {
+        CONTEXT.try_with(Clone::clone).ok()
+    }
+
+    #[must_use]
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Runs `future` with this context.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CONTEXT.scope(self, future).await
+    }
+
+    /// Runs the future returned by `download` in the current context, overriding its priority if it is set.
+    ///
+    /// If there is no context yet, the download is started as a new group and the bytes
+    /// transferred by all of its downloads are reported to `sender` as [`DownloadStatus::Transfer`].
+    ///
+    /// [`DownloadStatus::Transfer`]: crate::downloads::traits::DownloadStatus::Transfer
+    pub async fn run<F, Fut>(priority: Option<Priority>, sender: &dyn ProgressSender<DownloadResult>, download: F)
+    where
+        F: FnOnce(DownloadContext) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let (mut context, is_root) = Self::current().map_or_else(|| (Self::new(), true), |context| (context, false));
+
+        if let Some(priority) = priority {
+            context.priority = priority;
+        }
+
+        let transfer = context.transfer.clone();
+        let future = context.clone().scope(download(context));
+
+        if is_root {
+            transfer.report_while(future, sender).await;
+        } else {
+            future.await;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchedulerLimits {
+    /// Maximum number of downloads running at the same time.
+    pub max_concurrent_downloads: usize,
+    /// Maximum number of downloads from the same host running at the same time.
+    pub max_connections_per_host: usize,
+}
+
+impl Default for SchedulerLimits {
+    fn default() -> Self {
+        Self {
+            max_concurrent_downloads: 32,
+            max_connections_per_host: 8,
+        }
+    }
+}
+
+struct Waiter {
+    host: String,
+    sender: oneshot::Sender<DownloadPermit>,
+}
+
+#[derive(Default)]
+struct State {
+    limits: SchedulerLimits,
+    running: usize,
+    per_host: HashMap<String, usize>,
+    /// Waiting downloads by priority. Groups are rotated after every started download.
+    waiting: BTreeMap<Priority, VecDeque<(DownloadGroup, VecDeque<Waiter>)>>,
+}
+
+impl State {
+    fn enqueue(&mut self, group: DownloadGroup, priority: Priority, waiter: Waiter) {
+        let groups = self.waiting.entry(priority).or_default();
+
+        match groups.iter_mut().find(|(waiting, _)| *waiting == group) {
+            Some((_, waiters)) => waiters.push_back(waiter),
+            None => groups.push_back((group, VecDeque::from([waiter]))),
+        }
+    }
+
+    /// Picks the next download to start and takes a slot for it.
+    fn next(&mut self) -> Option<Waiter> {
+        if self.running >= self.limits.max_concurrent_downloads.max(1) {
+            return None;
+        }
+
+        let per_host = &self.per_host;
+        let host_limit = self.limits.max_connections_per_host.max(1);
+        let is_host_free = |waiter: &Waiter| per_host.get(&waiter.host).copied().unwrap_or_default() < host_limit;
+
+        let waiter = self.waiting.values_mut().find_map(|groups| {
+            let (index, position) = groups
+                .iter()
+                .enumerate()
+                .find_map(|(index, (_, waiters))| waiters.iter().position(is_host_free).map(|position| (index, position)))?;
+
+            let (group, mut waiters) = groups.remove(index)?;
+            let waiter = waiters.remove(position);
+
+            if !waiters.is_empty() {
+                groups.push_back((group, waiters));
+            }
+
+            waiter
+        })?;
+
+        self.running += 1;
+        *self.per_host.entry(waiter.host.clone()).or_default() += 1;
+
+        Some(waiter)
+    }
+
+    fn release(&mut self, host: &str) {
+        self.running = self.running.saturating_sub(1);
+
+        if let Some(count) = self.per_host.get_mut(host) {
+            *count -= 1;
+            if *count == 0 {
+                self.per_host.remove(host);
+            }
+        }
+    }
+}
+
+/// Hands out download slots respecting the [`SchedulerLimits`].
+#[derive(Clone)]
+pub struct DownloadScheduler {
+    state: Arc<Mutex<State>>,
+}
+
+impl std::fmt::Debug for DownloadScheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.lock();
+        f.debug_struct("DownloadScheduler")
+            .field("limits", &state.limits)
+            .field("running", &state.running)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DownloadScheduler {
+    pub fn new(limits: SchedulerLimits) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                limits,
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Scheduler used by every download.
+    pub fn global() -> &'static DownloadScheduler {
+        &SCHEDULER
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn limits(&self) -> SchedulerLimits {
+        self.lock().limits
+    }
+
+    /// Running downloads keep their slots, new ones are started according to the new limits.
+    pub fn set_limits(&self, limits: SchedulerLimits) {
+        self.lock().limits = limits;
+        self.dispatch();
+    }
+
+    /// Number of downloads that hold a slot.
+    pub fn running(&self) -> usize {
+        self.lock().running
+    }
+
+    /// Waits for a slot to download `url` in the [current context](DownloadContext::current).
+    ///
+    /// The slot is held until the returned permit is dropped.
+    pub async fn acquire(&self, url: &str) -> DownloadPermit {
+        let context = DownloadContext::current().unwrap_or_default();
+        let (group, priority) = (context.group, context.priority);
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(ToOwned::to_owned))
+            .unwrap_or_default();
+
+        loop {
+            let (sender, receiver) = oneshot::channel();
+            self.lock().enqueue(group, priority, Waiter { host: host.clone(), sender });
+            self.dispatch();
+
+            // Senders are only dropped after sending a permit, so the loop does not repeat in practice.
+            if let Ok(permit) = receiver.await {
+                return permit;
+            }
+        }
+    }
+
+    /// Starts as many waiting downloads as the limits allow.
+    fn dispatch(&self) {
+        loop {
+            let Some(waiter) = self.lock().next() else {
+                break;
+            };
+
+            let permit = DownloadPermit {
+                scheduler: self.clone(),
+                host: waiter.host,
+            };
+
+            // If the waiting download was cancelled the permit is dropped here, which frees the slot again.
+            let _ = waiter.sender.send(permit);
+        }
+    }
+}
+
+/// A slot taken from the [`DownloadScheduler`]. Dropping it lets the next download start.
+#[must_use]
+pub struct DownloadPermit {
+    scheduler: DownloadScheduler,
+    host: String,
+}
+
+impl std::fmt::Debug for DownloadPermit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DownloadPermit").field("host", &self.host).finish_non_exhaustive()
+    }
+}
+
+impl Drop for DownloadPermit {
+    fn drop(&mut self) {
+        self.scheduler.lock().release(&self.host);
+        self.scheduler.dispatch();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Mutex as StdMutex, time::Duration};
+
+    use super::*;
+
+    fn scheduler(max_concurrent_downloads: usize, max_connections_per_host: usize) -> DownloadScheduler {
+        DownloadScheduler::new(SchedulerLimits {
+            max_concurrent_downloads,
+            max_connections_per_host,
+        })
+    }
+
+    /// Lets the spawned tasks enqueue themselves.
+    async fn settle() {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    #[tokio::test]
+    async fn limits_test() {
+        let scheduler = scheduler(3, 2);
+
+        let first = scheduler.acquire("https://libraries.minecraft.net/a.jar").await;
+        let _second = scheduler.acquire("https://libraries.minecraft.net/b.jar").await;
+
+        let waiting = tokio::spawn({
+            let scheduler = scheduler.clone();
+            async move { scheduler.acquire("https://libraries.minecraft.net/c.jar").await }
+        });
+        settle().await;
+        assert_eq!(scheduler.running(), 2, "the per host limit is reached");
+
+        let _other_host = scheduler.acquire("https://resources.download.minecraft.net/ab/abcd").await;
+        assert_eq!(scheduler.running(), 3);
+
+        drop(first);
+        let _third = waiting.await.unwrap();
+        assert_eq!(scheduler.running(), 3);
+    }
+
+    /// Returns the order in which the downloads waiting behind a single slot were started.
+    async fn start_order(waiting: Vec<(DownloadContext, &'static str)>) -> Vec<&'static str> {
+        let scheduler = scheduler(1, 1);
+        let order = Arc::new(StdMutex::new(Vec::new()));
+
+        let blocker = scheduler.acquire("https://example.com/blocker").await;
+
+        let mut handles = Vec::new();
+        for (context, name) in waiting {
+            let scheduler = scheduler.clone();
+            let order = order.clone();
+            handles.push(tokio::spawn(context.scope(async move {
+                let _permit = scheduler.acquire("https://example.com/file").await;
+                order.lock().unwrap().push(name);
+            })));
+            settle().await;
+        }
+
+        drop(blocker);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        Arc::try_unwrap(order).unwrap().into_inner().unwrap()
+    }
+
+    #[tokio::test]
+    async fn priority_test() {
+        let group = DownloadContext::new();
+        let context = |priority| group.clone().with_priority(priority);
+
+        let order = start_order(vec![
+            (context(Priority::Low), "asset"),
+            (context(Priority::Normal), "mod"),
+            (context(Priority::High), "client"),
+        ])
+        .await;
+
+        assert_eq!(order, ["client", "mod", "asset"]);
+    }
+
+    #[tokio::test]
+    async fn fair_sharing_test() {
+        let (first, second) = (DownloadContext::new(), DownloadContext::new());
+
+        let order = start_order(vec![
+            (first.clone(), "first 1"),
+            (first.clone(), "first 2"),
+            (first.clone(), "first 3"),
+            (second.clone(), "second 1"),
+            (second.clone(), "second 2"),
+        ])
+        .await;
+
+        assert_eq!(order, ["first 1", "second 1", "first 2", "second 2", "first 3"]);
+    }
+
+    #[tokio::test]
+    async fn cancelled_waiter_test() {
+        let scheduler = scheduler(1, 1);
+
+        let blocker = scheduler.acquire("https://example.com/blocker").await;
+        let cancelled = tokio::spawn({
+            let scheduler = scheduler.clone();
+            async move { scheduler.acquire("https://example.com/cancelled").await }
+        });
+        settle().await;
+        cancelled.abort();
+        let _ = cancelled.await;
+
+        drop(blocker);
+        let _permit = tokio::time::timeout(Duration::from_secs(1), scheduler.acquire("https://example.com/file"))
+            .await
+            .expect("the slot of the cancelled download must be released");
+    }
+}
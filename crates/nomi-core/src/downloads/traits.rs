@@ -2,14 +2,21 @@ use egui_task_manager::Progress;
 
 use crate::PinnedFutureWithBounds;
 
-use super::{progress::ProgressSender, DownloadError};
+use super::{
+    progress::{ProgressSender, TransferProgress},
+    scheduler::DownloadContext,
+    DownloadError,
+};
 
 #[derive(Debug, Clone)]
 pub struct DownloadResult(pub Result<DownloadStatus, DownloadError>);
 
 impl Progress for DownloadResult {
     fn apply(&self, current: &mut u32) {
-        *current += self.0.as_ref().map_or(0, |_| 1);
+        *current += match self.0 {
+            Ok(DownloadStatus::Success | DownloadStatus::SuccessWithProgress(_)) => 1,
+            Ok(DownloadStatus::Transfer(_)) | Err(_) => 0,
+        };
     }
 }
 
@@ -20,6 +27,8 @@ pub enum DownloadStatus {
     Success,
     /// Downloaded successfully certain amount of elements
     SuccessWithProgress(u32),
+    /// Bytes transferred so far by all elements of the downloader, does not complete any of them
+    Transfer(TransferProgress),
 }
 
 impl DownloadStatus {
@@ -47,7 +56,7 @@ pub trait Downloader: Send + Sync {
     async fn download(self: Box<Self>, sender: &dyn ProgressSender<Self::Data>);
 
     /// This method must return a pinned future that does not borrow any values from `Self`
-    fn io(&self) -> PinnedFutureWithBounds<anyhow::Result<()>> {
+    fn io(&self) -> PinnedFutureWithBounds<crate::Result<()>> {
         Box::pin(async { Ok(()) })
     }
 }
@@ -57,16 +66,19 @@ const _: Option<Box<dyn Downloader<Data = DownloadResult>>> = None;
 #[async_trait::async_trait]
 impl<T> Downloader for T
 where
-    T: Downloadable,
+    T: Downloadable<Out = DownloadResult>,
 {
-    type Data = T::Out;
+    type Data = DownloadResult;
 
     fn total(&self) -> u32 {
         1
     }
 
     async fn download(self: Box<Self>, sender: &dyn ProgressSender<Self::Data>) {
-        let result = self.download().await;
-        sender.update(result).await;
+        DownloadContext::run(None, sender, |_| async move {
+            let result = self.download().await;
+            sender.update(result).await;
+        })
+        .await;
     }
 }
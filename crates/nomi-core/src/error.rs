@@ -1,7 +1,167 @@
+use std::{io, path::PathBuf};
+
+use reqwest::StatusCode;
+
+use crate::{
+    cache::CacheError,
+    downloads::{java_runtime::JavaRuntimeError, DownloadError},
+    repository::{java_installation::JavaProbeError, username::ValidationError},
+};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Error returned by the `loaders`, `downloads`, `instance` and `repository` modules.
+///
+/// The underlying errors are kept as [sources](std::error::Error::source),
+/// use the classification methods like [`Error::is_retryable`] to react to them.
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum Error {
-    #[error("No such version")]
-    NoSuchVersion,
+    #[error("There is no version `{0}`")]
+    NoSuchVersion(String),
+
+    #[error("There are no {loader} versions for Minecraft {game_version}")]
+    NoLoaderVersions { loader: &'static str, game_version: String },
+
     #[error("Bad request")]
     BadRequest,
+
+    /// A manifest, an installer or a profile does not contain the expected data.
+    #[error("Invalid manifest: {0}")]
+    InvalidManifest(String),
+
+    /// One of the Forge installer processors exited with an error.
+    #[error("Processor `{processor}` failed: {stderr}")]
+    ProcessorFailed { processor: String, stderr: String },
+
+    #[error("Unable to get the exit status of the game")]
+    ExitStatusUnavailable,
+
+    #[error("`{}` does not match its hash", path.display())]
+    HashMismatch { path: PathBuf },
+
+    #[error("Cannot parse `{}`: {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    #[error("Network error: {0}")]
+    Network(#[from] reqwest::Error),
+
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Invalid TOML: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    #[error("Cannot serialize TOML: {0}")]
+    TomlSerialize(#[from] toml::ser::Error),
+
+    #[error("Invalid archive: {0}")]
+    Zip(#[from] zip::result::ZipError),
+
+    #[error(transparent)]
+    Download(#[from] DownloadError),
+
+    #[error(transparent)]
+    Cache(#[from] CacheError),
+
+    #[error(transparent)]
+    JavaRuntime(#[from] JavaRuntimeError),
+
+    #[error(transparent)]
+    JavaProbe(#[from] JavaProbeError),
+
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+
+    #[error("Background task failed: {0}")]
+    Join(#[from] tokio::task::JoinError),
+
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl Error {
+    pub(crate) fn parse(path: impl Into<PathBuf>, source: impl std::error::Error + Send + Sync + 'static) -> Self {
+        Self::Parse {
+            path: path.into(),
+            source: Box::new(source),
+        }
+    }
+
+    /// The request did not reach the server or the server failed to answer it.
+    pub fn is_network(&self) -> bool {
+        match self {
+            Self::Network(_) => true,
+            Self::Download(error) => error.is_network(),
+            _ => false,
+        }
+    }
+
+    pub fn is_io(&self) -> bool {
+        match self {
+            Self::Io(_) => true,
+            Self::Download(error) => error.is_io(),
+            _ => false,
+        }
+    }
+
+    /// The requested version, loader or Java runtime does not exist.
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            Self::NoSuchVersion(_) | Self::NoLoaderVersions { .. } | Self::JavaRuntime(JavaRuntimeError::NoSuchRuntime { .. }) => true,
+            Self::Network(error) => error.status() == Some(StatusCode::NOT_FOUND),
+            _ => false,
+        }
+    }
+
+    /// A downloaded file or a manifest is corrupted.
+    pub fn is_corrupted(&self) -> bool {
+        match self {
+            Self::HashMismatch { .. }
+            | Self::InvalidManifest(_)
+            | Self::Parse { .. }
+            | Self::Json(_)
+            | Self::Toml(_)
+            | Self::Zip(_)
+            | Self::JavaRuntime(JavaRuntimeError::Corrupted { .. }) => true,
+            Self::Download(error) => error.is_hash_mismatch(),
+            _ => false,
+        }
+    }
+
+    /// Trying again later may succeed, e.g. after a timeout or a dropped connection.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Network(error) => is_retryable_request(error),
+            Self::Io(error) => is_retryable_io(error),
+            Self::Download(error) => error.is_retryable(),
+            _ => false,
+        }
+    }
+}
+
+pub(crate) fn is_retryable_request(error: &reqwest::Error) -> bool {
+    match error.status() {
+        Some(status) => status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::REQUEST_TIMEOUT,
+        None => error.is_timeout() || error.is_connect() || error.is_request() || error.is_body() || error.is_decode(),
+    }
+}
+
+pub(crate) fn is_retryable_io(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::Interrupted
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::WouldBlock
+    )
 }
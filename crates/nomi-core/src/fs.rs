@@ -3,8 +3,10 @@ use std::path::Path;
 use serde::{de::DeserializeOwned, Serialize};
 use tokio::io::AsyncWriteExt;
 
+use crate::{Error, Result};
+
 #[tracing::instrument(skip_all, fields(path = path.as_ref().to_string_lossy().to_string()), err)]
-pub async fn write_toml_config<T>(data: &T, path: impl AsRef<Path>) -> anyhow::Result<()>
+pub async fn write_toml_config<T>(data: &T, path: impl AsRef<Path>) -> Result<()>
 where
     T: Serialize + ?Sized,
 {
@@ -18,21 +20,21 @@ where
 }
 
 #[tracing::instrument(skip_all, fields(path = path.as_ref().to_string_lossy().to_string()), err)]
-pub async fn read_toml_config<T>(path: impl AsRef<Path>) -> anyhow::Result<T>
+pub async fn read_toml_config<T>(path: impl AsRef<Path>) -> Result<T>
 where
     T: DeserializeOwned + ?Sized,
 {
     let path = path.as_ref();
 
     let string = tokio::fs::read_to_string(&path).await?;
-    let body: T = toml::from_str(&string)?;
+    let body: T = toml::from_str(&string).map_err(|error| Error::parse(path, error))?;
 
     tracing::info!("Config {} has been read successfully", path.to_string_lossy());
 
     Ok(body)
 }
 
-pub fn read_toml_config_sync<T>(path: impl AsRef<Path>) -> anyhow::Result<T>
+pub fn read_toml_config_sync<T>(path: impl AsRef<Path>) -> Result<T>
 where
     T: DeserializeOwned + ?Sized,
 {
@@ -40,7 +42,7 @@ where
     runtime.block_on(read_toml_config::<T>(path))
 }
 
-pub fn write_toml_config_sync<T>(data: &T, path: impl AsRef<Path>) -> anyhow::Result<()>
+pub fn write_toml_config_sync<T>(data: &T, path: impl AsRef<Path>) -> Result<()>
 where
     T: Serialize + ?Sized,
 {
@@ -49,17 +51,17 @@ where
 }
 
 #[tracing::instrument(skip_all, fields(path = path.as_ref().to_string_lossy().to_string()), err)]
-pub async fn read_json_config<T>(path: impl AsRef<Path>) -> anyhow::Result<T>
+pub async fn read_json_config<T>(path: impl AsRef<Path>) -> Result<T>
 where
     T: DeserializeOwned + ?Sized,
 {
     let path = path.as_ref();
     let s = tokio::fs::read_to_string(path).await?;
-    Ok(serde_json::from_str::<T>(&s)?)
+    serde_json::from_str::<T>(&s).map_err(|error| Error::parse(path, error))
 }
 
 #[tracing::instrument(skip_all, fields(path = path.as_ref().to_string_lossy().to_string()), err)]
-pub async fn write_json_config<T>(data: &T, path: impl AsRef<Path>) -> anyhow::Result<()>
+pub async fn write_json_config<T>(data: &T, path: impl AsRef<Path>) -> Result<()>
 where
     T: Serialize + ?Sized,
 {
@@ -74,7 +76,7 @@ where
 }
 
 #[tracing::instrument(skip_all, fields(path = path.as_ref().to_string_lossy().to_string()), err)]
-pub async fn write_to_file(data: &[u8], path: impl AsRef<Path>) -> anyhow::Result<()> {
+pub async fn write_to_file(data: &[u8], path: impl AsRef<Path>) -> Result<()> {
     let path = path.as_ref();
     if let Some(dir) = path.parent() {
         tokio::fs::create_dir_all(dir).await?;
@@ -5,6 +5,11 @@ use crate::{
     ASSETS_DIR, LIBRARIES_DIR,
 };
 
+/// Directory inside the assets directory where log4j configurations are stored.
+pub fn log_configs_dir(assets: &Path) -> PathBuf {
+    assets.join("log_configs")
+}
+
 #[derive(Debug, Clone)]
 pub struct GamePaths {
     /// Game root directory. This is usually corresponds to the instance directory.
@@ -36,7 +41,7 @@ impl GamePaths {
         }
     }
 
-    pub fn make_absolute(self) -> anyhow::Result<Self> {
+    pub fn make_absolute(self) -> crate::Result<Self> {
         let current_dir = std::env::current_dir()?;
 
         let make_path_absolute = |path: PathBuf| if path.is_absolute() { path } else { current_dir.join(path) };
@@ -65,6 +70,11 @@ impl GamePaths {
         self.profile.join(format!("{game_version}.jar"))
     }
 
+    /// log4j configuration file provided by the version manifest.
+    pub fn log_config_file(&self, id: &str) -> PathBuf {
+        log_configs_dir(&self.assets).join(id)
+    }
+
     pub fn minecraft(game_version: &str) -> Self {
         const MINECRAFT_DIR: &str = "./minecraft";
         Self {
@@ -0,0 +1,428 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    time::SystemTime,
+};
+
+use itertools::Itertools;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Directory inside [`GamePaths::game`](crate::game_paths::GamePaths::game) where Minecraft writes its crash reports.
+pub const CRASH_REPORTS_DIR: &str = "crash-reports";
+
+/// Where the information about the crash was taken from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum CrashReportSource {
+    /// Report written by the game into [`CRASH_REPORTS_DIR`].
+    Minecraft(PathBuf),
+    /// `hs_err_pid*.log` written by the JVM after a fatal error.
+    JvmFatalError(PathBuf),
+    /// The game did not write any report, only its output was analyzed.
+    Output,
+}
+
+impl CrashReportSource {
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Minecraft(path) | Self::JvmFatalError(path) => Some(path),
+            Self::Output => None,
+        }
+    }
+}
+
+/// Known causes of a crash.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CrashSignature {
+    /// The game or a mod was compiled for a newer Java than the one it was launched with.
+    WrongJavaVersion { required: Option<i8>, current: Option<i8> },
+    /// A mod requires another mod that is not installed.
+    MissingDependency { dependency: String, required_by: Option<String> },
+    /// A mixin could not be applied.
+    MixinFailure { mixin: Option<String>, mod_id: Option<String> },
+}
+
+impl Display for CrashSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WrongJavaVersion { required, current } => {
+                write!(f, "Wrong Java version")?;
+                if let Some(required) = required {
+                    write!(f, ", Java {required} is required")?;
+                }
+                if let Some(current) = current {
+                    write!(f, ", Java {current} was used")?;
+                }
+                Ok(())
+            }
+            Self::MissingDependency { dependency, required_by } => {
+                write!(f, "Missing dependency `{dependency}`")?;
+                if let Some(required_by) = required_by {
+                    write!(f, " required by `{required_by}`")?;
+                }
+                Ok(())
+            }
+            Self::MixinFailure { mixin, mod_id } => {
+                write!(f, "Mixin failure")?;
+                if let Some(mixin) = mixin {
+                    write!(f, " in `{mixin}`")?;
+                }
+                if let Some(mod_id) = mod_id {
+                    write!(f, " from `{mod_id}`")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Result of the analysis of a crashed game.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CrashReport {
+    /// Exit code of the game. `None` if the game was terminated by a signal.
+    pub exit_code: Option<i32>,
+    pub source: CrashReportSource,
+    /// The exception (or the JVM error) that caused the crash.
+    pub exception: Option<String>,
+    pub description: Option<String>,
+    /// Ids of the mods that may have caused the crash.
+    pub suspected_mods: Vec<String>,
+    pub java_version: Option<String>,
+    pub signatures: Vec<CrashSignature>,
+}
+
+impl CrashReport {
+    /// Analyzes the crash of a game located in `game_dir`.
+    ///
+    /// Only reports modified after `since` are considered, so reports of previous launches are not picked up.
+    /// `output` is the last lines of the game's output. It is used to detect failures
+    /// that happen before the game is able to write a report.
+    pub async fn analyze(game_dir: &Path, exit_code: Option<i32>, since: SystemTime, output: &[String]) -> crate::Result<Self> {
+        let source = find_crash_file(game_dir, since);
+
+        let text = match source.path() {
+            Some(path) => tokio::fs::read(path).await.map(|bytes| String::from_utf8_lossy(&bytes).into_owned())?,
+            None => String::new(),
+        };
+
+        let mut report = Self::parse(source, &text, output);
+        report.exit_code = exit_code;
+
+        Ok(report)
+    }
+
+    /// Parses the contents of a crash report and the game's output.
+    pub fn parse(source: CrashReportSource, text: &str, output: &[String]) -> Self {
+        let output = output.join("\n");
+
+        let (exception, description, java_version) = match source {
+            CrashReportSource::Minecraft(_) => (
+                minecraft_exception(text),
+                field(text, "Description"),
+                field(text, "Java Version").map(|version| version.split(',').next().unwrap_or_default().trim().to_owned()),
+            ),
+            CrashReportSource::JvmFatalError(_) => (jvm_error(text), None, jvm_version(text)),
+            CrashReportSource::Output => (output_exception(&output), None, None),
+        };
+
+        let mut signatures = Vec::new();
+        for text in [text, output.as_str()] {
+            signatures.extend(find_signatures(text));
+        }
+        let signatures = signatures.into_iter().unique().collect_vec();
+
+        let suspected_mods = suspected_mods(text)
+            .into_iter()
+            .chain(signatures.iter().filter_map(|signature| match signature {
+                CrashSignature::MixinFailure { mod_id, .. } => mod_id.clone(),
+                CrashSignature::MissingDependency { required_by, .. } => required_by.clone(),
+                CrashSignature::WrongJavaVersion { .. } => None,
+            }))
+            .unique()
+            .collect_vec();
+
+        Self {
+            exit_code: None,
+            source,
+            exception,
+            description,
+            suspected_mods,
+            java_version,
+            signatures,
+        }
+    }
+}
+
+impl Display for CrashReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.exit_code {
+            Some(code) => writeln!(f, "The game crashed with exit code {code}")?,
+            None => writeln!(f, "The game crashed")?,
+        }
+
+        if let Some(path) = self.source.path() {
+            writeln!(f, "Report: {}", path.display())?;
+        }
+        if let Some(description) = &self.description {
+            writeln!(f, "Description: {description}")?;
+        }
+        if let Some(exception) = &self.exception {
+            writeln!(f, "Exception: {exception}")?;
+        }
+        if let Some(java_version) = &self.java_version {
+            writeln!(f, "Java version: {java_version}")?;
+        }
+        if !self.suspected_mods.is_empty() {
+            writeln!(f, "Suspected mods: {}", self.suspected_mods.join(", "))?;
+        }
+        for signature in &self.signatures {
+            writeln!(f, "- {signature}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds the newest crash report or JVM error log modified after `since`.
+pub fn find_crash_file(game_dir: &Path, since: SystemTime) -> CrashReportSource {
+    let modified = |path: &Path| {
+        path.metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .filter(|modified| *modified >= since)
+    };
+
+    let files_in = |dir: &Path| {
+        std::fs::read_dir(dir)
+            .map(|entries| entries.filter_map(Result::ok).map(|entry| entry.path()).collect_vec())
+            .unwrap_or_default()
+    };
+
+    let reports = files_in(&game_dir.join(CRASH_REPORTS_DIR))
+        .into_iter()
+        .filter(|path| path.extension().is_some_and(|extension| extension == "txt"))
+        .map(CrashReportSource::Minecraft);
+
+    let jvm_errors = files_in(game_dir)
+        .into_iter()
+        .filter(|path| {
+            let is_jvm_error = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("hs_err_pid"));
+            is_jvm_error && path.extension().is_some_and(|extension| extension == "log")
+        })
+        .map(CrashReportSource::JvmFatalError);
+
+    reports
+        .chain(jvm_errors)
+        .filter_map(|source| source.path().and_then(modified).map(|modified| (modified, source)))
+        .max_by_key(|(modified, _)| *modified)
+        .map_or(CrashReportSource::Output, |(_, source)| source)
+}
+
+/// Value of a `Key: value` line.
+fn field(text: &str, name: &str) -> Option<String> {
+    text.lines().find_map(|line| {
+        let (key, value) = line.trim().split_once(':')?;
+        (key == name).then(|| value.trim().to_owned()).filter(|value| !value.is_empty())
+    })
+}
+
+/// The first line of the stacktrace that follows the description in Minecraft's crash report.
+fn minecraft_exception(text: &str) -> Option<String> {
+    text.lines()
+        .skip_while(|line| !line.starts_with("Description:"))
+        .skip(1)
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(ToOwned::to_owned)
+}
+
+/// The signal or the exception code from `# SIGSEGV (0xb) at pc=...`.
+fn jvm_error(text: &str) -> Option<String> {
+    static REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?m)^#\s+((?:SIG|EXCEPTION_)\w+(?: \(0x[0-9a-fA-F]+\))?)").unwrap());
+
+    REGEX.captures(text).map(|captures| captures[1].to_owned()).or_else(|| {
+        text.lines()
+            .find(|line| line.starts_with("# Out of Memory Error"))
+            .map(|line| line.trim_start_matches("# ").to_owned())
+    })
+}
+
+/// `17.0.8+7` from `# JRE version: OpenJDK Runtime Environment (17.0.8+7) (build 17.0.8+7)`.
+fn jvm_version(text: &str) -> Option<String> {
+    static REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"# JRE version: [^(]*\(([^)]+)\)").unwrap());
+
+    REGEX.captures(text).map(|captures| captures[1].trim().to_owned())
+}
+
+/// The last exception printed to the output.
+fn output_exception(output: &str) -> Option<String> {
+    static REGEX: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"(?m)^\s*(?:Exception in thread "[^"]*" |Caused by: )?((?:[a-z_$][\w$]*\.)+[A-Z][\w$]*(?:Exception|Error)(?::.*)?)$"#).unwrap()
+    });
+
+    REGEX.captures_iter(output).last().map(|captures| captures[1].trim().to_owned())
+}
+
+fn suspected_mods(text: &str) -> Vec<String> {
+    static MOD_ID: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\(([a-z0-9_.-]+)\)").unwrap());
+
+    let mut mods = Vec::new();
+    let mut lines = text.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        let Some((_, rest)) = line.split_once("Suspected Mod") else {
+            continue;
+        };
+
+        // `Suspected Mod: Name (id), Version: 1.0` or `Suspected Mods:` followed by indented lines.
+        let rest = rest.trim_start_matches('s').trim_start_matches(':');
+        mods.extend(MOD_ID.captures(rest).map(|captures| captures[1].to_owned()));
+
+        while let Some(next) = lines.next_if(|next| next.starts_with("\t\t") || next.starts_with("    ")) {
+            mods.extend(MOD_ID.captures(next).map(|captures| captures[1].to_owned()));
+        }
+    }
+
+    mods
+}
+
+fn find_signatures(text: &str) -> Vec<CrashSignature> {
+    static CLASS_VERSION: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"class file version (\d+)(?:\.\d+)?\), this version of the Java Runtime only recognizes class file versions up to (\d+)").unwrap()
+    });
+    static FABRIC_MISSING: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"Mod '[^']*' \(([^)]+)\) \S+ requires .*?\(([^)]+)\), which is missing").unwrap());
+    static FORGE_MISSING: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"Mod ID: '([^']+)', Requested by: '([^']+)'").unwrap());
+    static MIXIN_APPLY: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"Mixin apply(?: for mod ([\w.-]+))? failed (\S+)").unwrap());
+    static MIXIN_PHASE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"Mixin \[([^\]]+)\] from phase \[\w+\] in config \[[^\]]+\] FAILED").unwrap());
+
+    let mut signatures = Vec::new();
+
+    // Class file version 52 is Java 8, every next Java version increases it by one.
+    let java_major = |class_version: &str| class_version.parse::<i8>().ok().map(|version| version - 44);
+
+    signatures.extend(CLASS_VERSION.captures_iter(text).map(|captures| CrashSignature::WrongJavaVersion {
+        required: java_major(&captures[1]),
+        current: java_major(&captures[2]),
+    }));
+
+    signatures.extend(FABRIC_MISSING.captures_iter(text).map(|captures| CrashSignature::MissingDependency {
+        dependency: captures[2].to_owned(),
+        required_by: Some(captures[1].to_owned()),
+    }));
+
+    signatures.extend(FORGE_MISSING.captures_iter(text).map(|captures| CrashSignature::MissingDependency {
+        dependency: captures[1].to_owned(),
+        required_by: Some(captures[2].to_owned()),
+    }));
+
+    signatures.extend(MIXIN_APPLY.captures_iter(text).map(|captures| CrashSignature::MixinFailure {
+        mixin: Some(captures[2].to_owned()),
+        mod_id: captures.get(1).map(|id| id.as_str().to_owned()),
+    }));
+
+    signatures.extend(MIXIN_PHASE.captures_iter(text).map(|captures| CrashSignature::MixinFailure {
+        mixin: Some(captures[1].to_owned()),
+        mod_id: None,
+    }));
+
+    let has_mixin_failure = signatures
+        .iter()
+        .any(|signature| matches!(signature, CrashSignature::MixinFailure { .. }));
+    if !has_mixin_failure && text.contains("org.spongepowered.asm.mixin.transformer.throwables.MixinTransformerError") {
+        signatures.push(CrashSignature::MixinFailure { mixin: None, mod_id: None });
+    }
+
+    signatures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minecraft_report_test() {
+        let report = CrashReport::parse(
+            CrashReportSource::Minecraft(PathBuf::from("crash-2024-08-01_12.00.00-client.txt")),
+            include_str!("../../tests/fixtures/crash_report_mixin.txt"),
+            &[],
+        );
+
+        assert_eq!(report.description.as_deref(), Some("Initializing game"));
+        assert_eq!(
+            report.exception.as_deref(),
+            Some("java.lang.RuntimeException: Mixin transformation of net.minecraft.class_310 failed")
+        );
+        assert_eq!(report.java_version.as_deref(), Some("17.0.8"));
+        assert_eq!(report.suspected_mods, ["sodium"]);
+        assert_eq!(
+            report.signatures,
+            [CrashSignature::MixinFailure {
+                mixin: Some("sodium.mixins.json:core.MinecraftClientMixin".to_owned()),
+                mod_id: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn jvm_error_test() {
+        let report = CrashReport::parse(
+            CrashReportSource::JvmFatalError(PathBuf::from("hs_err_pid1234.log")),
+            include_str!("../../tests/fixtures/hs_err_pid.log"),
+            &[],
+        );
+
+        assert_eq!(report.exception.as_deref(), Some("SIGSEGV (0xb)"));
+        assert_eq!(report.java_version.as_deref(), Some("17.0.8+7"));
+        assert!(report.signatures.is_empty());
+    }
+
+    #[test]
+    fn output_signatures_test() {
+        let output = [
+            "Error: LinkageError occurred while loading main class net.minecraft.client.main.Main",
+            "\tjava.lang.UnsupportedClassVersionError: net/minecraft/client/main/Main has been compiled by a more recent version of the Java Runtime (class file version 65.0), this version of the Java Runtime only recognizes class file versions up to 52.0",
+            "[main/ERROR]: Incompatible mods found!",
+            "\t - Mod 'Sodium Extra' (sodium-extra) 0.5.7 requires any version of mod 'Sodium' (sodium), which is missing!",
+        ]
+        .map(String::from);
+
+        let report = CrashReport::parse(CrashReportSource::Output, "", &output);
+
+        assert_eq!(
+            report.exception.as_deref(),
+            Some("java.lang.UnsupportedClassVersionError: net/minecraft/client/main/Main has been compiled by a more recent version of the Java Runtime (class file version 65.0), this version of the Java Runtime only recognizes class file versions up to 52.0")
+        );
+        assert_eq!(
+            report.signatures,
+            [
+                CrashSignature::WrongJavaVersion {
+                    required: Some(21),
+                    current: Some(8),
+                },
+                CrashSignature::MissingDependency {
+                    dependency: "sodium".to_owned(),
+                    required_by: Some("sodium-extra".to_owned()),
+                },
+            ]
+        );
+        assert_eq!(report.suspected_mods, ["sodium-extra"]);
+    }
+
+    #[test]
+    fn forge_missing_dependency_test() {
+        let text = "Missing or unsupported mandatory dependencies:\n\tMod ID: 'create', Requested by: 'createaddition', Expected range: '[0.5.1,)', Actual version: '[MISSING]'";
+
+        assert_eq!(
+            find_signatures(text),
+            [CrashSignature::MissingDependency {
+                dependency: "create".to_owned(),
+                required_by: Some("createaddition".to_owned()),
+            }]
+        );
+    }
+}
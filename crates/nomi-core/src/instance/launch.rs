@@ -8,28 +8,27 @@ use std::{
 use arguments::UserData;
 use serde::{Deserialize, Serialize};
 use tokio::process::Command;
-use tokio_stream::StreamExt;
-use tokio_util::codec::{FramedRead, LinesCodec};
-use tracing::{error, info};
+use tracing::{debug, warn};
 
 use crate::{
+    downloads::java_runtime::JavaRuntimeManager,
     fs::read_json_config,
     game_paths::GamePaths,
     markers::Undefined,
     repository::{
+        java_installation::JavaInstallation,
         java_runner::JavaRunner,
-        manifest::{Manifest, VersionType},
+        manifest::{JavaVersion, Manifest, VersionType},
     },
+    DOT_NOMI_JAVA_DIR,
 };
 
-use self::arguments::ArgumentsBuilder;
+use self::{arguments::ArgumentsBuilder, features::LaunchFeatures};
 
-use super::{
-    loader::LoaderProfile,
-    logs::{GameLogsEvent, GameLogsWriter},
-};
+use super::{loader::LoaderProfile, process::GameProcess};
 
 pub mod arguments;
+pub mod features;
 pub mod rules;
 
 #[cfg(windows)]
@@ -38,11 +37,26 @@ pub const CLASSPATH_SEPARATOR: &str = ";";
 #[cfg(not(windows))]
 pub const CLASSPATH_SEPARATOR: &str = ":";
 
+async fn warn_if_unsupported(runner: &JavaRunner, java_version: &JavaVersion) {
+    match JavaInstallation::probe_runner(runner).await {
+        Ok(installation) if !installation.supports(java_version) => warn!(
+            "Java {} ({}) is too old for this version, it requires Java {}",
+            installation.version,
+            runner.get_string(),
+            java_version.major_version
+        ),
+        Ok(_) => {}
+        Err(e) => warn!(error = ?e, "Unable to check the version of {}", runner.get_string()),
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq, Hash)]
 pub struct LaunchSettings {
     pub java_runner: Option<JavaRunner>,
     pub version: String,
     pub version_type: VersionType,
+    #[serde(default)]
+    pub features: LaunchFeatures,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq, Hash)]
@@ -65,7 +79,7 @@ impl LaunchInstance {
         &mut self.jvm_args
     }
 
-    fn process_natives(natives_dir: &Path, natives: &[PathBuf]) -> anyhow::Result<()> {
+    fn process_natives(natives_dir: &Path, natives: &[PathBuf]) -> crate::Result<()> {
         for lib in natives {
             let reader = OpenOptions::new().read(true).open(lib)?;
             std::fs::create_dir_all(natives_dir)?;
@@ -89,21 +103,43 @@ impl LaunchInstance {
                     let mut out = File::create(natives_dir.join(lib))?;
                     io::copy(&mut file, &mut out)?;
 
-                    Ok::<_, anyhow::Error>(())
+                    Ok::<_, crate::Error>(())
                 })?;
         }
 
         Ok(())
     }
 
-    #[tracing::instrument(skip(self, logs_writer), err)]
-    pub async fn launch(
-        &self,
-        paths: GamePaths,
-        user_data: UserData,
-        java_runner: &JavaRunner,
-        logs_writer: &dyn GameLogsWriter,
-    ) -> anyhow::Result<()> {
+    /// Picks the runner for the game.
+    ///
+    /// The profile's own runner is used if it is set, then the `global` one.
+    /// If neither is configured, a runtime matching the version's [`JavaVersion`]
+    /// is used, it gets downloaded if necessary.
+    async fn java_runner(&self, manifest: &Manifest, global: Option<&JavaRunner>) -> crate::Result<JavaRunner> {
+        if let Some(runner) = self.settings.java_runner.as_ref().or(global) {
+            warn_if_unsupported(runner, &manifest.java_version).await;
+            return Ok(runner.clone());
+        }
+
+        let manager = JavaRuntimeManager::new(std::env::current_dir()?.join(DOT_NOMI_JAVA_DIR));
+
+        match manager.runner_for(&manifest.java_version).await {
+            Ok(runner) => Ok(runner),
+            Err(e) => {
+                let runner = JavaRunner::from_environment();
+                warn!(error = ?e, "Unable to get a Java runtime for the version, using {}", runner.get_string());
+                warn_if_unsupported(&runner, &manifest.java_version).await;
+                Ok(runner)
+            }
+        }
+    }
+
+    /// Starts the game.
+    ///
+    /// `java_runner` is the globally configured Java, see [`Self::java_runner`] for the priorities.
+    /// The returned [`GameProcess`] must be used to read the game's output, otherwise the game may block on writing it.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn launch(&self, paths: GamePaths, user_data: UserData, java_runner: Option<&JavaRunner>) -> crate::Result<GameProcess> {
         let paths = paths.make_absolute()?;
 
         let manifest = read_json_config::<Manifest>(paths.manifest_file(&self.settings.version)).await?;
@@ -118,7 +154,7 @@ impl LaunchInstance {
         let manifest_jvm_arguments = arguments_builder.manifest_jvm_arguments();
         let manifest_game_arguments = arguments_builder.manifest_game_arguments();
 
-        dbg!(arguments_builder.classpath_as_slice());
+        debug!(classpath = ?arguments_builder.classpath_as_slice());
 
         let main_class = arguments_builder.get_main_class();
 
@@ -127,45 +163,25 @@ impl LaunchInstance {
         let loader_jvm_arguments = loader_arguments.jvm_arguments();
         let loader_game_arguments = loader_arguments.game_arguments();
 
+        let java_runner = self.java_runner(&manifest, java_runner).await?;
+
         let mut command = Command::new(java_runner.get());
-        let command = dbg!(command
+        command
             .args(custom_jvm_arguments)
             .args(loader_jvm_arguments)
             .args(manifest_jvm_arguments)
             .arg(main_class)
             .args(manifest_game_arguments)
             .args(loader_game_arguments)
-            .current_dir(&paths.game))
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
-
-        let mut child = command.spawn()?;
-
-        let stdout = child.stdout.take().expect("child did not have a handle to stdout");
-        let stderr = child.stderr.take().expect("child did not have a handle to stderr");
-
-        // let mut stdout_reader = BufReader::new(stdout).lines();
-        // let mut stderr_reader = BufReader::new(stderr).lines();
+            .current_dir(&paths.game)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
 
-        let stdout = FramedRead::new(stdout, LinesCodec::new());
-        let stderr = FramedRead::new(stderr, LinesCodec::new());
+        debug!(?command, "Starting the game");
 
-        tokio::spawn(async move {
-            if let Ok(out) = child.wait().await.inspect_err(|e| error!(error = ?e, "Unable to get the exit code")) {
-                out.code().inspect(|code| info!("Minecraft exit code: {}", code));
-            };
-        });
+        let child = command.spawn()?;
 
-        let mut read = stdout.merge(stderr);
-
-        while let Some(line) = read.next().await {
-            match line {
-                Ok(line) => logs_writer.write(GameLogsEvent::new(line)),
-                Err(e) => error!(error = ?e, "Error occurred while decoding game's output"),
-            }
-        }
-
-        Ok(())
+        Ok(GameProcess::spawn(child, paths.game))
     }
 }
 
@@ -1,25 +1,28 @@
 use std::{marker::PhantomData, path::PathBuf};
 
 use itertools::Itertools;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::{
     game_paths::GamePaths,
     instance::{
-        launch::{macros::replace, rules::is_library_passes},
+        launch::{
+            macros::replace,
+            rules::{is_all_rules_passed, is_library_passes, platform_natives, Platform},
+        },
         loader::LoaderProfile,
     },
     markers::Undefined,
     maven_data::MavenArtifact,
     repository::{
-        manifest::{Argument, Arguments, Classifiers, DownloadFile, Manifest, Value},
+        manifest::{Argument, Arguments, Manifest, Value},
         username::Username,
     },
     utils::path_to_string,
     NOMI_NAME, NOMI_VERSION,
 };
 
-use super::{rules::is_rule_passes, LaunchInstance, CLASSPATH_SEPARATOR};
+use super::{features::LaunchFeatures, LaunchInstance, CLASSPATH_SEPARATOR};
 
 pub enum WithUserData {}
 pub enum WithClasspath {}
@@ -28,6 +31,8 @@ pub struct ArgumentsBuilder<'a, S = Undefined, U = Undefined> {
     instance: &'a LaunchInstance,
     manifest: &'a Manifest,
     paths: &'a GamePaths,
+    features: &'a LaunchFeatures,
+    platform: &'a Platform,
     classpath: Vec<PathBuf>,
     classpath_string: String,
     native_libs: Vec<PathBuf>,
@@ -72,6 +77,8 @@ impl<'a> ArgumentsBuilder<'a, Undefined, Undefined> {
             instance,
             manifest,
             paths,
+            features: &instance.settings.features,
+            platform: Platform::current(),
             classpath: Vec::new(),
             classpath_string: String::new(),
             native_libs: Vec::new(),
@@ -82,6 +89,22 @@ impl<'a> ArgumentsBuilder<'a, Undefined, Undefined> {
     }
 }
 
+impl<'a, S, U> ArgumentsBuilder<'a, S, U> {
+    /// Overrides the features stored in the profile's [`LaunchSettings`](super::LaunchSettings).
+    #[must_use]
+    pub fn with_features(mut self, features: &'a LaunchFeatures) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Evaluates the manifest rules against another platform instead of the current one.
+    #[must_use]
+    pub fn with_platform(mut self, platform: &'a Platform) -> Self {
+        self.platform = platform;
+        self
+    }
+}
+
 impl<'a, U> ArgumentsBuilder<'a, Undefined, U> {
     pub fn build_classpath(self) -> ArgumentsBuilder<'a, WithClasspath, U> {
         let (classpath, native_libs) = self.classpath();
@@ -89,6 +112,8 @@ impl<'a, U> ArgumentsBuilder<'a, Undefined, U> {
             instance: self.instance,
             manifest: self.manifest,
             paths: self.paths,
+            features: self.features,
+            platform: self.platform,
             user_data: self.user_data,
             classpath_string: itertools::intersperse(classpath.iter().map(|p| p.display().to_string()), CLASSPATH_SEPARATOR.to_string())
                 .collect::<String>(),
@@ -106,6 +131,8 @@ impl<'a, S> ArgumentsBuilder<'a, S, Undefined> {
             instance: self.instance,
             manifest: self.manifest,
             paths: self.paths,
+            features: self.features,
+            platform: self.platform,
             user_data,
             classpath_string: self.classpath_string,
             classpath: self.classpath,
@@ -149,7 +176,7 @@ impl<'a> ArgumentsBuilder<'a, WithClasspath, WithUserData> {
         }
     }
     pub fn manifest_jvm_arguments(&self) -> Vec<String> {
-        self.arguments_parser(
+        let mut arguments = self.arguments_parser(
             |JvmArguments(jvm), _| jvm.clone(),
             |_| {
                 vec![
@@ -164,14 +191,43 @@ impl<'a> ArgumentsBuilder<'a, WithClasspath, WithUserData> {
                     self.classpath_as_str().to_owned(),
                 ]
             },
-        )
+        );
+
+        arguments.extend(self.logging_argument());
+
+        arguments
+    }
+
+    /// `-Dlog4j.configurationFile` pointing to the configuration downloaded with the assets.
+    fn logging_argument(&self) -> Option<String> {
+        let config = self.manifest.logging.as_ref()?.client.as_ref()?;
+        let path = self.paths.log_config_file(&config.file.id);
+
+        if !path.exists() {
+            warn!(path = %path.display(), "The logging configuration is not downloaded. Download the assets again to use it");
+            return None;
+        }
+
+        Some(config.jvm_argument(&path))
     }
 
     pub fn manifest_game_arguments(&self) -> Vec<String> {
-        self.arguments_parser(
+        let mut arguments = self.arguments_parser(
             |_, GameArguments(game)| game.clone(),
-            |arguments| arguments.split_whitespace().map(|arg| self.parse_args_from_str(arg)).collect(),
-        )
+            |arguments| {
+                arguments
+                    .split_whitespace()
+                    .map(|arg| self.parse_args_from_str(arg))
+                    .chain(self.features.legacy_game_arguments())
+                    .collect()
+            },
+        );
+
+        if self.features.fullscreen {
+            arguments.push("--fullscreen".into());
+        }
+
+        arguments
     }
 
     fn parse_args_from_str(&self, source: &str) -> String {
@@ -200,6 +256,12 @@ impl<'a> ArgumentsBuilder<'a, WithClasspath, WithUserData> {
             "${assets_index_name}" => &self.manifest.asset_index.id,
             "${user_properties}" => "{}",
             "${classpath}" => &self.classpath_as_str(),
+            "${resolution_width}" => &self.features.resolution.map(|resolution| resolution.width.to_string()).unwrap_or_default(),
+            "${resolution_height}" => &self.features.resolution.map(|resolution| resolution.height.to_string()).unwrap_or_default(),
+            "${quickPlayPath}" => &path_to_string(self.paths.game.join("quickPlay").join("log.json")),
+            "${quickPlaySingleplayer}" => self.features.quick_play_singleplayer().unwrap_or_default(),
+            "${quickPlayMultiplayer}" => self.features.quick_play_multiplayer().unwrap_or_default(),
+            "${quickPlayRealms}" => self.features.quick_play_realms().unwrap_or_default(),
             "${classpath_separator}" => CLASSPATH_SEPARATOR
         )
     }
@@ -219,7 +281,7 @@ impl<'a> ArgumentsBuilder<'a, WithClasspath, WithUserData> {
         args.into_iter()
             .flat_map(|arg| match arg {
                 Argument::Struct { rules, value } => {
-                    if !rules.iter().all(is_rule_passes) {
+                    if !is_all_rules_passed(&rules, self.platform, self.features) {
                         return vec![String::new()];
                     }
 
@@ -238,22 +300,13 @@ impl<'a> ArgumentsBuilder<'a, WithClasspath, WithUserData> {
 impl<'a, S, U> ArgumentsBuilder<'a, S, U> {
     #[tracing::instrument(skip(self), fields(result))]
     fn classpath(&self) -> (Vec<PathBuf>, Vec<PathBuf>) {
-        fn match_natives(natives: &Classifiers) -> Option<&DownloadFile> {
-            match std::env::consts::OS {
-                "linux" => natives.natives_linux.as_ref(),
-                "windows" => natives.natives_windows.as_ref(),
-                "macos" => natives.natives_macos.as_ref(),
-                _ => unreachable!(),
-            }
-        }
-
         let mut classpath = vec![Some(self.paths.version_jar_file(&self.instance.settings.version))];
         let mut native_libs = vec![];
 
         self.manifest
             .libraries
             .iter()
-            .filter(|lib| is_library_passes(lib))
+            .filter(|lib| is_library_passes(lib, self.platform))
             .map(|lib| {
                 let name = lib.name.as_str();
                 (
@@ -280,7 +333,7 @@ impl<'a, S, U> ArgumentsBuilder<'a, S, U> {
                     lib.downloads
                         .classifiers
                         .as_ref()
-                        .and_then(|natives| match_natives(natives))
+                        .and_then(|natives| platform_natives(natives, self.platform))
                         .and_then(|native_lib| native_lib.path.as_ref())
                         .map(|path| self.paths.libraries.join(path)),
                 )
@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+
+use crate::repository::manifest::Features;
+
+/// Optional game features that change the launch arguments.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct LaunchFeatures {
+    /// Size of the game window.
+    pub resolution: Option<Resolution>,
+    pub fullscreen: bool,
+    pub demo: bool,
+    /// Where the game goes right after it starts.
+    pub quick_play: Option<QuickPlay>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum QuickPlay {
+    /// Name of the world's directory in `saves`.
+    Singleplayer(String),
+    /// Server address, optionally with the port (`host:port`).
+    Multiplayer(String),
+    /// Id of the realm.
+    Realms(String),
+}
+
+impl LaunchFeatures {
+    /// Whether the features required by a manifest rule are enabled.
+    ///
+    /// Features that are not mentioned in the rule are not checked.
+    pub fn matches(&self, features: &Features) -> bool {
+        let quick_play = self.quick_play.as_ref();
+
+        [
+            (features.is_demo_user, self.demo),
+            (features.has_custom_resolution, self.resolution.is_some()),
+            (features.has_quick_plays_support, quick_play.is_some()),
+            (
+                features.is_quick_play_singleplayer,
+                matches!(quick_play, Some(QuickPlay::Singleplayer(_))),
+            ),
+            (features.is_quick_play_multiplayer, matches!(quick_play, Some(QuickPlay::Multiplayer(_)))),
+            (features.is_quick_play_realms, matches!(quick_play, Some(QuickPlay::Realms(_)))),
+        ]
+        .into_iter()
+        .all(|(required, enabled)| required.is_none_or(|required| required == enabled))
+    }
+
+    pub fn quick_play_singleplayer(&self) -> Option<&str> {
+        match &self.quick_play {
+            Some(QuickPlay::Singleplayer(world)) => Some(world),
+            _ => None,
+        }
+    }
+
+    pub fn quick_play_multiplayer(&self) -> Option<&str> {
+        match &self.quick_play {
+            Some(QuickPlay::Multiplayer(server)) => Some(server),
+            _ => None,
+        }
+    }
+
+    pub fn quick_play_realms(&self) -> Option<&str> {
+        match &self.quick_play {
+            Some(QuickPlay::Realms(realm)) => Some(realm),
+            _ => None,
+        }
+    }
+
+    /// Game arguments for the versions that do not describe the features in their manifest.
+    ///
+    /// Such versions do not support quick play, but they are able to join a server on start.
+    pub fn legacy_game_arguments(&self) -> Vec<String> {
+        let mut arguments = Vec::new();
+
+        if let Some(resolution) = self.resolution {
+            arguments.extend([
+                "--width".into(),
+                resolution.width.to_string(),
+                "--height".into(),
+                resolution.height.to_string(),
+            ]);
+        }
+
+        if self.demo {
+            arguments.push("--demo".into());
+        }
+
+        if let Some(server) = self.quick_play_multiplayer() {
+            let (host, port) = server.split_once(':').unwrap_or((server, "25565"));
+            arguments.extend(["--server".into(), host.into(), "--port".into(), port.into()]);
+        }
+
+        arguments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn features() -> Features {
+        Features {
+            is_demo_user: None,
+            has_custom_resolution: None,
+            has_quick_plays_support: None,
+            is_quick_play_singleplayer: None,
+            is_quick_play_multiplayer: None,
+            is_quick_play_realms: None,
+        }
+    }
+
+    #[test]
+    fn matches_test() {
+        let launch_features = LaunchFeatures {
+            resolution: Some(Resolution { width: 1280, height: 720 }),
+            quick_play: Some(QuickPlay::Multiplayer("mc.example.com".into())),
+            ..Default::default()
+        };
+
+        let custom_resolution = Features {
+            has_custom_resolution: Some(true),
+            ..features()
+        };
+        let demo = Features {
+            is_demo_user: Some(true),
+            ..features()
+        };
+        let multiplayer = Features {
+            is_quick_play_multiplayer: Some(true),
+            ..features()
+        };
+        let singleplayer = Features {
+            is_quick_play_singleplayer: Some(true),
+            ..features()
+        };
+
+        assert!(launch_features.matches(&custom_resolution));
+        assert!(launch_features.matches(&multiplayer));
+        assert!(!launch_features.matches(&demo));
+        assert!(!launch_features.matches(&singleplayer));
+
+        assert!(!LaunchFeatures::default().matches(&custom_resolution));
+        assert!(LaunchFeatures::default().matches(&features()));
+    }
+
+    #[test]
+    fn legacy_arguments_test() {
+        let launch_features = LaunchFeatures {
+            resolution: Some(Resolution { width: 854, height: 480 }),
+            demo: true,
+            quick_play: Some(QuickPlay::Multiplayer("localhost:25570".into())),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            launch_features.legacy_game_arguments(),
+            ["--width", "854", "--height", "480", "--demo", "--server", "localhost", "--port", "25570"]
+        );
+    }
+}
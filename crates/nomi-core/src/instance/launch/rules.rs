@@ -1,47 +1,432 @@
-use std::env;
+use std::{env, fs, process::Command, sync::LazyLock};
 
-use crate::repository::manifest::{Action, Library, Rule, RuleKind};
+use regex::Regex;
+use tracing::warn;
 
-pub fn is_rule_passes(rule: &Rule) -> bool {
-    match rule.action {
-        Action::Allow => match rule.rule_kind.as_ref() {
-            Some(RuleKind::GameRule(features)) => {
-                // let is_demo = features.is_demo_user.map_or(true, |demo| )
+use crate::repository::manifest::{Action, Classifiers, DownloadFile, Library, Os, Rule, RuleKind};
 
-                // TODO: Make this check based on the settings
-                let custom_res = features.has_custom_resolution.unwrap_or(false);
-                let demo = features.is_demo_user.unwrap_or(false);
-                // let quick_realms = features.is_quick_play_realms.unwrap_or(false);
+use super::features::LaunchFeatures;
 
-                // It turns off the quick play
-                let quick_realms = true;
+static CURRENT_PLATFORM: LazyLock<Platform> = LazyLock::new(Platform::detect);
 
-                !(custom_res || demo || quick_realms)
-            }
-            Some(RuleKind::JvmRule(os)) => os.name.as_ref().map_or(true, |target_os| env::consts::OS == target_os),
+static WINDOWS_VERSION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\d+(\.\d+)+").unwrap());
+
+/// Operating system the manifest rules are evaluated against.
+///
+/// Uses the same naming as the manifests do.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Platform {
+    /// `windows`, `osx` or `linux`.
+    pub name: String,
+    /// `x86`, `x86_64`, `arm32` or `arm64`.
+    pub arch: String,
+    /// Version of the OS (e.g. `10.0.19045` on Windows 10).
+    pub version: String,
+}
+
+impl Platform {
+    pub fn new(name: impl Into<String>, arch: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            arch: arch.into(),
+            version: version.into(),
+        }
+    }
+
+    /// The platform the launcher is running on.
+    pub fn current() -> &'static Platform {
+        &CURRENT_PLATFORM
+    }
+
+    fn detect() -> Self {
+        let name = match env::consts::OS {
+            "macos" => "osx",
+            os => os,
+        };
+
+        let arch = match env::consts::ARCH {
+            "aarch64" => "arm64",
+            "arm" => "arm32",
+            arch => arch,
+        };
 
-            None => true,
-        },
-        Action::Disallow => false,
+        let version = detect_os_version().unwrap_or_else(|| {
+            warn!("Unable to detect the OS version");
+            String::new()
+        });
+
+        Self::new(name, arch, version)
+    }
+
+    /// Whether the platform matches every field that the rule specifies.
+    pub fn matches(&self, os: &Os) -> bool {
+        os.name.as_ref().is_none_or(|name| *name == self.name)
+            && os.arch.as_ref().is_none_or(|arch| *arch == self.arch)
+            && os.version.as_ref().is_none_or(|version| match Regex::new(version) {
+                Ok(regex) => regex.is_match(&self.version),
+                Err(error) => {
+                    warn!(%error, version, "Invalid OS version pattern in the rule");
+                    false
+                }
+            })
     }
 }
 
-pub fn is_all_rules_passed(rules: &[Rule]) -> bool {
-    for rule in rules {
-        let satisfied = is_rule_passes(rule);
-        let use_lib = matches!(rule.action, Action::Allow);
+fn detect_os_version() -> Option<String> {
+    let command_output = |program: &str, args: &[&str]| {
+        Command::new(program)
+            .args(args)
+            .output()
+            .ok()
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+    };
 
-        if satisfied && !use_lib || !satisfied && use_lib {
-            return false;
+    match env::consts::OS {
+        "linux" => fs::read_to_string("/proc/sys/kernel/osrelease").ok(),
+        "macos" => command_output("sw_vers", &["-productVersion"]),
+        // Prints `Microsoft Windows [Version 10.0.19045.4046]`.
+        "windows" => {
+            command_output("cmd", &["/C", "ver"]).and_then(|output| WINDOWS_VERSION.find(&output).map(|version| version.as_str().to_owned()))
         }
+        _ => None,
     }
-
-    true
+    .map(|version| version.trim().to_owned())
 }
 
-pub fn is_library_passes(lib: &Library) -> bool {
-    match lib.rules.as_ref() {
-        Some(rules) => is_all_rules_passed(rules),
+/// Whether the conditions of the rule hold.
+pub fn is_rule_matches(rule: &Rule, platform: &Platform, launch_features: &LaunchFeatures) -> bool {
+    match rule.rule_kind.as_ref() {
+        Some(RuleKind::GameRule(features)) => launch_features.matches(features),
+        Some(RuleKind::JvmRule(os)) => platform.matches(os),
         None => true,
     }
 }
+
+/// Evaluates the rules the same way as the official launcher does.
+///
+/// Empty rules allow everything. Otherwise the action of the last matching rule wins,
+/// and nothing is allowed if none of the rules match.
+pub fn is_all_rules_passed(rules: &[Rule], platform: &Platform, launch_features: &LaunchFeatures) -> bool {
+    if rules.is_empty() {
+        return true;
+    }
+
+    rules
+        .iter()
+        .rev()
+        .find(|rule| is_rule_matches(rule, platform, launch_features))
+        .is_some_and(|rule| matches!(rule.action, Action::Allow))
+}
+
+pub fn is_library_passes(lib: &Library, platform: &Platform) -> bool {
+    // Libraries do not depend on the game features.
+    lib.rules
+        .as_deref()
+        .is_none_or(|rules| is_all_rules_passed(rules, platform, &LaunchFeatures::default()))
+}
+
+/// Natives of a library for the platform.
+///
+/// Returns `None` if the library has no natives for it.
+pub fn platform_natives<'a>(natives: &'a Classifiers, platform: &Platform) -> Option<&'a DownloadFile> {
+    match platform.name.as_str() {
+        "linux" => natives.natives_linux.as_ref(),
+        "windows" => natives.natives_windows.as_ref(),
+        "osx" => natives.natives_macos.as_ref(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+
+    use crate::{
+        instance::launch::features::{QuickPlay, Resolution},
+        repository::manifest::{Argument, Value},
+    };
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct ManifestRules {
+        libraries: Vec<LibraryRules>,
+        arguments: Option<ArgumentsRules>,
+    }
+
+    #[derive(Deserialize)]
+    struct LibraryRules {
+        name: String,
+        rules: Option<Vec<Rule>>,
+    }
+
+    #[derive(Deserialize)]
+    struct ArgumentsRules {
+        game: Vec<Argument>,
+        jvm: Vec<Argument>,
+    }
+
+    fn manifests() -> HashMap<String, ManifestRules> {
+        serde_json::from_str(include_str!("../../../tests/fixtures/manifest_rules.json")).unwrap()
+    }
+
+    fn linux() -> Platform {
+        Platform::new("linux", "x86_64", "6.5.0-14-generic")
+    }
+
+    fn windows_10() -> Platform {
+        Platform::new("windows", "x86_64", "10.0.19045")
+    }
+
+    fn windows_7_x86() -> Platform {
+        Platform::new("windows", "x86", "6.1.7601")
+    }
+
+    fn macos_arm() -> Platform {
+        Platform::new("osx", "arm64", "14.2.1")
+    }
+
+    fn libraries(manifest: &ManifestRules, platform: &Platform) -> Vec<String> {
+        manifest
+            .libraries
+            .iter()
+            .filter(|library| {
+                library
+                    .rules
+                    .as_deref()
+                    .is_none_or(|rules| is_all_rules_passed(rules, platform, &LaunchFeatures::default()))
+            })
+            .map(|library| library.name.clone())
+            .collect()
+    }
+
+    fn arguments(arguments: &[Argument], platform: &Platform, features: &LaunchFeatures) -> Vec<String> {
+        arguments
+            .iter()
+            .flat_map(|argument| match argument {
+                Argument::String(value) => vec![value.clone()],
+                Argument::Struct { rules, .. } if !is_all_rules_passed(rules, platform, features) => Vec::new(),
+                Argument::Struct {
+                    value: Value::String(value), ..
+                } => vec![value.clone()],
+                Argument::Struct {
+                    value: Value::Array(values), ..
+                } => values.clone(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rule_semantics_test() {
+        let rules: Vec<Rule> = serde_json::from_str(
+            r#"[
+                { "action": "allow" },
+                { "action": "disallow", "os": { "name": "osx" } },
+                { "action": "allow", "os": { "name": "osx", "version": "^10\\.5\\.\\d$" } }
+            ]"#,
+        )
+        .unwrap();
+        let features = LaunchFeatures::default();
+
+        assert!(is_all_rules_passed(&[], &linux(), &features));
+        assert!(is_all_rules_passed(&rules, &linux(), &features));
+        assert!(!is_all_rules_passed(&rules, &macos_arm(), &features));
+        assert!(is_all_rules_passed(&rules, &Platform::new("osx", "x86_64", "10.5.8"), &features));
+
+        let only_osx = &rules[1..];
+        assert!(!is_all_rules_passed(only_osx, &linux(), &features));
+
+        let invalid_version: Vec<Rule> = serde_json::from_str(r#"[{ "action": "allow", "os": { "version": "^(10" } }]"#).unwrap();
+        assert!(!is_all_rules_passed(&invalid_version, &windows_10(), &features));
+    }
+
+    #[test]
+    fn libraries_table_test() {
+        let manifests = manifests();
+
+        let table: &[(&str, Platform, &[&str])] = &[
+            (
+                "1.8.9",
+                linux(),
+                &["com.mojang:authlib:1.5.21", "org.lwjgl.lwjgl:lwjgl:2.9.4-nightly-20150209"],
+            ),
+            (
+                "1.8.9",
+                windows_10(),
+                &[
+                    "com.mojang:authlib:1.5.21",
+                    "tv.twitch:twitch-platform:6.5",
+                    "tv.twitch:twitch-external-platform:4.5",
+                    "org.lwjgl.lwjgl:lwjgl:2.9.4-nightly-20150209",
+                ],
+            ),
+            (
+                "1.8.9",
+                macos_arm(),
+                &[
+                    "com.mojang:authlib:1.5.21",
+                    "tv.twitch:twitch-platform:6.5",
+                    "org.lwjgl.lwjgl:lwjgl:2.9.2-nightly-20140822",
+                ],
+            ),
+            (
+                "1.12.2",
+                linux(),
+                &["com.mojang:patchy:1.1", "org.lwjgl.lwjgl:lwjgl:2.9.4-nightly-20150209"],
+            ),
+            (
+                "1.12.2",
+                windows_7_x86(),
+                &["com.mojang:patchy:1.1", "org.lwjgl.lwjgl:lwjgl:2.9.4-nightly-20150209"],
+            ),
+            (
+                "1.12.2",
+                macos_arm(),
+                &[
+                    "com.mojang:patchy:1.1",
+                    "ca.weblite:java-objc-bridge:1.0.0",
+                    "org.lwjgl.lwjgl:lwjgl:2.9.2-nightly-20140822",
+                ],
+            ),
+            ("1.19", linux(), &["org.lwjgl:lwjgl:3.3.1", "org.lwjgl:lwjgl:3.3.1:natives-linux"]),
+            (
+                "1.19",
+                windows_10(),
+                &[
+                    "org.lwjgl:lwjgl:3.3.1",
+                    "org.lwjgl:lwjgl:3.3.1:natives-windows",
+                    "org.lwjgl:lwjgl:3.3.1:natives-windows-x86",
+                ],
+            ),
+            (
+                "1.19",
+                macos_arm(),
+                &[
+                    "org.lwjgl:lwjgl:3.3.1",
+                    "org.lwjgl:lwjgl:3.3.1:natives-macos",
+                    "org.lwjgl:lwjgl:3.3.1:natives-macos-arm64",
+                    "ca.weblite:java-objc-bridge:1.1",
+                ],
+            ),
+            ("1.20.4", linux(), &["org.lwjgl:lwjgl:3.3.2", "org.lwjgl:lwjgl:3.3.2:natives-linux"]),
+            (
+                "1.20.4",
+                windows_7_x86(),
+                &[
+                    "org.lwjgl:lwjgl:3.3.2",
+                    "org.lwjgl:lwjgl:3.3.2:natives-windows",
+                    "org.lwjgl:lwjgl:3.3.2:natives-windows-arm64",
+                    "org.lwjgl:lwjgl:3.3.2:natives-windows-x86",
+                ],
+            ),
+            (
+                "1.20.4",
+                macos_arm(),
+                &[
+                    "org.lwjgl:lwjgl:3.3.2",
+                    "org.lwjgl:lwjgl:3.3.2:natives-macos",
+                    "org.lwjgl:lwjgl:3.3.2:natives-macos-arm64",
+                    "ca.weblite:java-objc-bridge:1.1",
+                ],
+            ),
+        ];
+
+        for (version, platform, expected) in table {
+            assert_eq!(&libraries(&manifests[*version], platform), expected, "{version} on {platform:?}");
+        }
+    }
+
+    #[test]
+    fn jvm_arguments_table_test() {
+        let manifests = manifests();
+        let common = ["-Djava.library.path=${natives_directory}", "-cp", "${classpath}"];
+        let heap_dump = "-XX:HeapDumpPath=MojangTricksIntelDriversForPerformance_javaw.exe_minecraft.exe.heapdump";
+
+        let table: &[(Platform, &[&str])] = &[
+            (linux(), &[]),
+            (windows_10(), &[heap_dump, "-Dos.name=Windows 10", "-Dos.version=10.0"]),
+            (windows_7_x86(), &[heap_dump, "-Xss1M"]),
+            (macos_arm(), &["-XstartOnFirstThread"]),
+        ];
+
+        for version in ["1.19", "1.20.4"] {
+            let jvm = &manifests[version].arguments.as_ref().unwrap().jvm;
+
+            for (platform, expected) in table {
+                let expected = expected.iter().chain(&common).copied().collect::<Vec<_>>();
+                assert_eq!(
+                    arguments(jvm, platform, &LaunchFeatures::default()),
+                    expected,
+                    "{version} on {platform:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn game_arguments_table_test() {
+        let manifests = manifests();
+        let features = LaunchFeatures {
+            resolution: Some(Resolution { width: 1280, height: 720 }),
+            demo: true,
+            quick_play: Some(QuickPlay::Singleplayer("New World".into())),
+            ..Default::default()
+        };
+        let username = ["--username", "${auth_player_name}"];
+        let demo_and_resolution = ["--demo", "--width", "${resolution_width}", "--height", "${resolution_height}"];
+
+        let table: &[(&str, &LaunchFeatures, Vec<&str>)] = &[
+            ("1.19", &LaunchFeatures::default(), username.to_vec()),
+            ("1.19", &features, [&username[..], &demo_and_resolution].concat()),
+            ("1.20.4", &LaunchFeatures::default(), username.to_vec()),
+            (
+                "1.20.4",
+                &features,
+                [
+                    &username[..],
+                    &demo_and_resolution,
+                    &[
+                        "--quickPlayPath",
+                        "${quickPlayPath}",
+                        "--quickPlaySingleplayer",
+                        "${quickPlaySingleplayer}",
+                    ],
+                ]
+                .concat(),
+            ),
+        ];
+
+        for (version, features, expected) in table {
+            let game = &manifests[*version].arguments.as_ref().unwrap().game;
+
+            for platform in [linux(), windows_10(), macos_arm()] {
+                assert_eq!(&arguments(game, &platform, features), expected, "{version} on {platform:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn platform_natives_test() {
+        let file = |url: &str| DownloadFile {
+            path: None,
+            sha1: String::new(),
+            size: 0,
+            url: url.to_owned(),
+        };
+
+        let natives = Classifiers {
+            natives_macos: Some(file("macos")),
+            natives_windows: Some(file("windows")),
+            natives_linux: None,
+        };
+
+        let url = |platform: &Platform| platform_natives(&natives, platform).map(|file| file.url.as_str());
+
+        assert_eq!(url(&windows_10()), Some("windows"));
+        assert_eq!(url(&macos_arm()), Some("macos"));
+        assert_eq!(url(&linux()), None);
+        assert_eq!(url(&Platform::new("freebsd", "x86_64", "14.0")), None);
+    }
+}
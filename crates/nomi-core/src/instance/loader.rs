@@ -12,3 +12,37 @@ pub struct LoaderProfile {
     pub args: SimpleArgs,
     pub libraries: Vec<SimpleLib>,
 }
+
+impl LoaderProfile {
+    /// Version of the loader.
+    ///
+    /// Profiles created without an explicit version store the one that was the latest,
+    /// so it is taken from the game arguments or the loader's library.
+    pub fn loader_version(&self) -> Option<String> {
+        let explicit = match &self.loader {
+            Loader::Vanilla => return None,
+            Loader::Fabric { version } | Loader::Forge { version } | Loader::Quilt { version } | Loader::NeoForge { version } => version.clone(),
+        };
+
+        explicit
+            .or_else(|| self.argument("--fml.neoForgeVersion"))
+            .or_else(|| self.argument("--fml.forgeVersion"))
+            .or_else(|| {
+                let (group, artifact) = match self.loader {
+                    Loader::Fabric { .. } => ("net.fabricmc", "fabric-loader"),
+                    Loader::Quilt { .. } => ("org.quiltmc", "quilt-loader"),
+                    _ => return None,
+                };
+
+                self.libraries
+                    .iter()
+                    .find(|lib| lib.artifact.group == group && lib.artifact.artifact == artifact)
+                    .map(|lib| lib.artifact.version.clone())
+            })
+    }
+
+    fn argument(&self, name: &str) -> Option<String> {
+        let position = self.args.game.iter().position(|arg| arg == name)?;
+        self.args.game.get(position + 1).cloned()
+    }
+}
@@ -0,0 +1,312 @@
+use std::{
+    fs::File,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use tracing::{debug, warn};
+
+use crate::INSTANCE_LOGS_DIR;
+
+use super::{
+    logs::{GameLogsEvent, GameLogsParser, GameLogsWriter},
+    Instance,
+};
+
+/// How many sessions are kept by default, including the new one.
+pub const DEFAULT_MAX_SESSIONS: usize = 20;
+
+const LOG_EXTENSION: &str = "log";
+const COMPRESSED_LOG_EXTENSION: &str = "log.gz";
+
+/// Directory that stores the game's output of every launch in a separate file.
+///
+/// Sessions are named after the time they were started (`<unix millis>.log`).
+/// Finished sessions are compressed with gzip and only the newest [`Self::max_sessions`] are kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogSessions {
+    dir: PathBuf,
+    max_sessions: usize,
+}
+
+impl LogSessions {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_sessions: DEFAULT_MAX_SESSIONS,
+        }
+    }
+
+    /// Sessions stored in the [`INSTANCE_LOGS_DIR`] of the instance.
+    pub fn for_instance(id: usize) -> Self {
+        Self::new(Instance::path_from_id(id).join(INSTANCE_LOGS_DIR))
+    }
+
+    #[must_use]
+    pub fn with_max_sessions(mut self, max_sessions: usize) -> Self {
+        self.max_sessions = max_sessions.max(1);
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn max_sessions(&self) -> usize {
+        self.max_sessions
+    }
+
+    /// Creates a file for a new session.
+    ///
+    /// The oldest sessions are removed so that there are at most [`Self::max_sessions`] including the new one.
+    pub async fn start(&self) -> crate::Result<FileLogs> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+
+        self.rotate().await?;
+
+        let mut started_at = SystemTime::now();
+
+        // Two profiles of the same instance may be launched at the same millisecond.
+        loop {
+            let millis = unix_millis(started_at);
+            let path = self.dir.join(format!("{millis}.{LOG_EXTENSION}"));
+
+            if tokio::fs::try_exists(self.dir.join(format!("{millis}.{COMPRESSED_LOG_EXTENSION}"))).await? {
+                started_at += Duration::from_millis(1);
+                continue;
+            }
+
+            match tokio::fs::OpenOptions::new().write(true).create_new(true).open(&path).await {
+                Ok(file) => {
+                    debug!(path = %path.display(), "Started a new logs session");
+
+                    return Ok(FileLogs {
+                        path,
+                        file: Mutex::new(file.into_std().await),
+                    });
+                }
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists => started_at += Duration::from_millis(1),
+                Err(error) => return Err(error.into()),
+            }
+        }
+    }
+
+    /// Returns all the stored sessions, the newest first.
+    pub async fn list(&self) -> crate::Result<Vec<LogSession>> {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error.into()),
+        };
+
+        let mut sessions = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            sessions.extend(LogSession::from_path(entry.path()));
+        }
+
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.started_at));
+
+        Ok(sessions)
+    }
+
+    async fn rotate(&self) -> crate::Result<()> {
+        let sessions = self.list().await?;
+
+        for session in sessions.iter().skip(self.max_sessions - 1) {
+            debug!(path = %session.path.display(), "Removing an old logs session");
+            tokio::fs::remove_file(&session.path).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A stored launch session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogSession {
+    path: PathBuf,
+    started_at: SystemTime,
+    is_compressed: bool,
+}
+
+impl LogSession {
+    /// Returns `None` if the file is not a session.
+    pub fn from_path(path: PathBuf) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+
+        let (millis, is_compressed) = match name.strip_suffix(&format!(".{COMPRESSED_LOG_EXTENSION}")) {
+            Some(millis) => (millis, true),
+            None => (name.strip_suffix(&format!(".{LOG_EXTENSION}"))?, false),
+        };
+
+        let started_at = UNIX_EPOCH + Duration::from_millis(millis.parse().ok()?);
+
+        Some(Self {
+            path,
+            started_at,
+            is_compressed,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn started_at(&self) -> SystemTime {
+        self.started_at
+    }
+
+    /// Finished sessions are compressed.
+    /// The session may be still running if it is not compressed.
+    pub fn is_compressed(&self) -> bool {
+        self.is_compressed
+    }
+
+    pub async fn read_to_string(&self) -> crate::Result<String> {
+        let path = self.path.clone();
+        let is_compressed = self.is_compressed;
+
+        tokio::task::spawn_blocking(move || {
+            let file = File::open(path)?;
+
+            let mut content = String::new();
+            if is_compressed {
+                GzDecoder::new(file).read_to_string(&mut content)?;
+            } else {
+                io::BufReader::new(file).read_to_string(&mut content)?;
+            }
+
+            Ok(content)
+        })
+        .await?
+    }
+
+    /// Reads the session and parses it back into events.
+    ///
+    /// Loggers are not stored in the file, so the events do not have them.
+    pub async fn read(&self) -> crate::Result<Vec<GameLogsEvent>> {
+        let content = self.read_to_string().await?;
+
+        let mut parser = GameLogsParser::new();
+        let mut events = content.lines().flat_map(|line| parser.push(line.to_owned())).collect::<Vec<_>>();
+        events.extend(parser.flush());
+
+        Ok(events)
+    }
+}
+
+/// `GameLogsWriter` that writes logs into a session file created by [`LogSessions::start`].
+///
+/// Every event is written immediately so the logs are kept even if the launcher is closed.
+pub struct FileLogs {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl FileLogs {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Closes the session and compresses it.
+    ///
+    /// Returns the path to the compressed file.
+    pub async fn finish(self) -> crate::Result<PathBuf> {
+        let Self { path, file } = self;
+        drop(file);
+
+        tokio::task::spawn_blocking(move || compress(&path)).await?
+    }
+}
+
+impl GameLogsWriter for FileLogs {
+    fn write(&self, data: GameLogsEvent) {
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Err(error) = writeln!(file, "{data}") {
+            warn!(%error, path = %self.path.display(), "Cannot write the game logs");
+        }
+    }
+}
+
+fn compress(path: &Path) -> crate::Result<PathBuf> {
+    let target = path.with_extension(COMPRESSED_LOG_EXTENSION);
+
+    let mut input = File::open(path)?;
+    let mut encoder = GzEncoder::new(File::create(&target)?, Compression::default());
+
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?;
+
+    std::fs::remove_file(path)?;
+
+    Ok(target)
+}
+
+fn unix_millis(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instance::logs::LogLevel;
+
+    use super::*;
+
+    fn sessions(name: &str) -> LogSessions {
+        let dir = std::env::temp_dir().join(format!("nomi-log-sessions-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        LogSessions::new(dir)
+    }
+
+    #[tokio::test]
+    async fn write_and_read_test() {
+        let sessions = sessions("read");
+
+        let logs = sessions.start().await.unwrap();
+        logs.write(GameLogsEvent::parse_text("[12:34:56] [Render thread/INFO]: Setting user: Player").unwrap());
+        logs.write(GameLogsEvent::parse_text("[12:34:58] [Render thread/ERROR]: Failed to load texture").unwrap());
+        logs.write(GameLogsEvent::new("java.io.FileNotFoundException: missing.png".to_owned()));
+
+        let running = sessions.list().await.unwrap();
+        assert_eq!(running.len(), 1);
+        assert!(!running[0].is_compressed());
+
+        let path = logs.finish().await.unwrap();
+        assert!(path.to_string_lossy().ends_with(".log.gz"));
+
+        let finished = sessions.list().await.unwrap();
+        assert_eq!(finished.len(), 1);
+        assert!(finished[0].is_compressed());
+        assert_eq!(finished[0].started_at(), running[0].started_at());
+
+        let events = finished[0].read().await.unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].message(), "Setting user: Player");
+        assert_eq!(events[1].level(), Some(LogLevel::Error));
+        assert_eq!(events[1].throwable(), Some("java.io.FileNotFoundException: missing.png"));
+
+        std::fs::remove_dir_all(sessions.dir()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn rotation_test() {
+        let sessions = sessions("rotation").with_max_sessions(3);
+
+        for _ in 0..5 {
+            sessions.start().await.unwrap().finish().await.unwrap();
+        }
+
+        let listed = sessions.list().await.unwrap();
+        assert_eq!(listed.len(), 3);
+        assert!(listed.windows(2).all(|pair| pair[0].started_at() > pair[1].started_at()));
+
+        std::fs::write(sessions.dir().join("notes.txt"), "not a session").unwrap();
+        assert_eq!(sessions.list().await.unwrap().len(), 3);
+
+        std::fs::remove_dir_all(sessions.dir()).unwrap();
+    }
+}
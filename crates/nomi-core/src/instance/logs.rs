@@ -1,14 +1,176 @@
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    str::FromStr,
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+
+use futures_util::{stream, Stream, StreamExt};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// How long an event waits for the continuation lines (e.g. a stack trace) before it is emitted.
+const PENDING_EVENT_TIMEOUT: Duration = Duration::from_millis(100);
+
 pub trait GameLogsWriter: Send + Sync {
     fn write(&self, data: GameLogsEvent);
 }
 
+impl<T: GameLogsWriter + ?Sized> GameLogsWriter for &T {
+    fn write(&self, data: GameLogsEvent) {
+        (**self).write(data);
+    }
+}
+
+impl<T: GameLogsWriter + ?Sized> GameLogsWriter for Box<T> {
+    fn write(&self, data: GameLogsEvent) {
+        (**self).write(data);
+    }
+}
+
+impl<T: GameLogsWriter + ?Sized> GameLogsWriter for Arc<T> {
+    fn write(&self, data: GameLogsEvent) {
+        (**self).write(data);
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 6] = [Self::Trace, Self::Debug, Self::Info, Self::Warn, Self::Error, Self::Fatal];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Trace => "TRACE",
+            Self::Debug => "DEBUG",
+            Self::Info => "INFO",
+            Self::Warn => "WARN",
+            Self::Error => "ERROR",
+            Self::Fatal => "FATAL",
+        }
+    }
+}
+
+impl Display for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown log level `{0}`")]
+pub struct UnknownLogLevel(pub String);
+
+impl FromStr for LogLevel {
+    type Err = UnknownLogLevel;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|level| level.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| UnknownLogLevel(s.to_owned()))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum LogTimestamp {
+    /// Milliseconds since the Unix epoch. Used by log4j XML events.
+    UnixMillis(u64),
+    /// Time as printed by the text layout (e.g. `12:34:56`).
+    Text(String),
+}
+
+impl Display for LogTimestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnixMillis(millis) => {
+                let seconds = millis / 1000 % 86400;
+                write!(f, "{:02}:{:02}:{:02} UTC", seconds / 3600, seconds / 60 % 60, seconds % 60)
+            }
+            Self::Text(text) => f.write_str(text),
+        }
+    }
+}
+
+/// A single event of the game's output.
+///
+/// Lines that the game printed without log4j are kept as is and have no metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GameLogsEvent {
     message: String,
+    timestamp: Option<LogTimestamp>,
+    thread: Option<String>,
+    level: Option<LogLevel>,
+    logger: Option<String>,
+    throwable: Option<String>,
 }
 
 impl GameLogsEvent {
     pub fn new(message: String) -> Self {
-        Self { message }
+        Self {
+            message,
+            timestamp: None,
+            thread: None,
+            level: None,
+            logger: None,
+            throwable: None,
+        }
+    }
+
+    /// Parses a `[12:34:56] [Render thread/INFO]: message` line.
+    ///
+    /// Returns `None` if the line does not look like the beginning of a log4j event.
+    pub fn parse_text(line: &str) -> Option<Self> {
+        static REGEX: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"^\[(?P<time>[^\]]+)\] \[(?P<thread>[^\]]+)/(?P<level>[A-Z]+)\](?: \((?P<logger>[^)]+)\))?:? ?(?P<message>.*)$").unwrap()
+        });
+
+        let captures = REGEX.captures(line)?;
+        let level = captures["level"].parse().ok()?;
+
+        Some(Self {
+            message: captures["message"].to_owned(),
+            timestamp: Some(LogTimestamp::Text(captures["time"].to_owned())),
+            thread: Some(captures["thread"].to_owned()),
+            level: Some(level),
+            logger: captures.name("logger").map(|logger| logger.as_str().to_owned()),
+            throwable: None,
+        })
+    }
+
+    /// Parses a complete `<log4j:Event>` element produced by log4j's `XMLLayout`.
+    pub fn parse_xml(xml: &str) -> Option<Self> {
+        static ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"(\w+)="([^"]*)""#).unwrap());
+
+        let start = xml.find("<log4j:Event")?;
+        let tag = &xml[start..start + xml[start..].find('>')?];
+
+        let mut event = Self::new(String::new());
+
+        for captures in ATTRIBUTE.captures_iter(tag) {
+            let value = unescape_xml(&captures[2]);
+            match &captures[1] {
+                "logger" => event.logger = Some(value),
+                "timestamp" => event.timestamp = value.parse().ok().map(LogTimestamp::UnixMillis),
+                "level" => event.level = value.parse().ok(),
+                "thread" => event.thread = Some(value),
+                _ => {}
+            }
+        }
+
+        event.message = xml_element(xml, "log4j:Message").unwrap_or_default();
+        event.throwable = xml_element(xml, "log4j:Throwable").filter(|throwable| !throwable.is_empty());
+
+        Some(event)
     }
 
     pub fn into_message(self) -> String {
@@ -18,6 +180,202 @@ impl GameLogsEvent {
     pub fn message(&self) -> &str {
         &self.message
     }
+
+    pub fn timestamp(&self) -> Option<&LogTimestamp> {
+        self.timestamp.as_ref()
+    }
+
+    pub fn thread(&self) -> Option<&str> {
+        self.thread.as_deref()
+    }
+
+    pub fn level(&self) -> Option<LogLevel> {
+        self.level
+    }
+
+    pub fn logger(&self) -> Option<&str> {
+        self.logger.as_deref()
+    }
+
+    /// Stack trace attached to the event.
+    pub fn throwable(&self) -> Option<&str> {
+        self.throwable.as_deref()
+    }
+
+    /// Whether the message, the logger or the stack trace contain `query`, ignoring the case.
+    pub fn contains(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        [Some(self.message.as_str()), self.logger(), self.throwable()]
+            .into_iter()
+            .flatten()
+            .any(|text| text.to_lowercase().contains(&query))
+    }
+
+    fn push_continuation(&mut self, line: &str) {
+        let throwable = self.throwable.get_or_insert_with(String::new);
+        if !throwable.is_empty() {
+            throwable.push('\n');
+        }
+        throwable.push_str(line);
+    }
+}
+
+impl Display for GameLogsEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(timestamp) = &self.timestamp {
+            write!(f, "[{timestamp}] ")?;
+        }
+
+        match (&self.thread, self.level) {
+            (Some(thread), Some(level)) => write!(f, "[{thread}/{level}]: ")?,
+            (None, Some(level)) => write!(f, "[{level}]: ")?,
+            (Some(thread), None) => write!(f, "[{thread}]: ")?,
+            (None, None) => {}
+        }
+
+        f.write_str(&self.message)?;
+
+        if let Some(throwable) = &self.throwable {
+            write!(f, "\n{throwable}")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Text content of the first `name` element. CDATA sections are unwrapped.
+fn xml_element(xml: &str, name: &str) -> Option<String> {
+    let open = format!("<{name}>");
+    let close = format!("</{name}>");
+
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&close)?;
+    let content = xml[start..end].trim();
+
+    Some(match content.strip_prefix("<![CDATA[").and_then(|content| content.strip_suffix("]]>")) {
+        Some(content) => content.trim_end().to_owned(),
+        None => unescape_xml(content),
+    })
+}
+
+/// Lines that continue the previous event, like the lines of a stack trace.
+fn is_continuation(line: &str) -> bool {
+    line.starts_with([' ', '\t']) || line.starts_with("Caused by:") || line.starts_with("Suppressed:")
+}
+
+/// The first line of a stack trace (e.g. `java.lang.IllegalStateException: message`).
+fn is_exception(line: &str) -> bool {
+    static REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(?:[a-z_$][\w$]*\.)+[A-Z][\w$]*(?:Exception|Error|Throwable)\b").unwrap());
+
+    REGEX.is_match(line)
+}
+
+/// Groups the game's output lines into [`GameLogsEvent`]s.
+#[derive(Default)]
+pub struct GameLogsParser {
+    pending: Option<GameLogsEvent>,
+    xml: Option<String>,
+}
+
+impl GameLogsParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether there is an event that may still receive continuation lines.
+    pub fn has_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Feeds the next line and returns the events that are complete.
+    pub fn push(&mut self, line: String) -> Vec<GameLogsEvent> {
+        let mut events = Vec::new();
+
+        if let Some(mut xml) = self.xml.take() {
+            xml.push('\n');
+            xml.push_str(&line);
+
+            if line.contains("</log4j:Event>") {
+                events.extend(GameLogsEvent::parse_xml(&xml));
+            } else {
+                self.xml = Some(xml);
+            }
+
+            return events;
+        }
+
+        if line.trim_start().starts_with("<log4j:Event") {
+            events.extend(self.pending.take());
+
+            if line.contains("</log4j:Event>") {
+                events.extend(GameLogsEvent::parse_xml(&line));
+            } else {
+                self.xml = Some(line);
+            }
+
+            return events;
+        }
+
+        if let Some(event) = GameLogsEvent::parse_text(&line) {
+            events.extend(self.pending.replace(event));
+            return events;
+        }
+
+        match &mut self.pending {
+            // Stack traces printed by log4j follow the event's header line.
+            Some(pending) if is_continuation(&line) || (pending.level.is_some() && is_exception(&line)) => pending.push_continuation(&line),
+            _ => events.extend(self.pending.replace(GameLogsEvent::new(line))),
+        }
+
+        events
+    }
+
+    /// Returns the event that is still waiting for continuation lines.
+    pub fn flush(&mut self) -> Option<GameLogsEvent> {
+        self.pending.take().or_else(|| self.xml.take().map(GameLogsEvent::new))
+    }
+}
+
+/// Turns a stream of the game's output lines into a stream of [`GameLogsEvent`]s.
+///
+/// An event is emitted once the next event starts or when no more lines arrive for a short time.
+pub fn parse_logs(lines: impl Stream<Item = String> + Send + 'static) -> impl Stream<Item = GameLogsEvent> + Send {
+    let state = (Box::pin(lines), GameLogsParser::new(), VecDeque::new());
+
+    stream::unfold(state, |(mut lines, mut parser, mut ready)| async move {
+        loop {
+            if let Some(event) = ready.pop_front() {
+                return Some((event, (lines, parser, ready)));
+            }
+
+            let line = if parser.has_pending() {
+                if let Ok(line) = tokio::time::timeout(PENDING_EVENT_TIMEOUT, lines.next()).await {
+                    line
+                } else {
+                    ready.extend(parser.flush());
+                    continue;
+                }
+            } else {
+                lines.next().await
+            };
+
+            match line {
+                Some(line) => ready.extend(parser.push(line)),
+                None => match parser.flush() {
+                    Some(event) => ready.push_back(event),
+                    None => return None,
+                },
+            }
+        }
+    })
 }
 
 /// `GameLogsWriter` that does nothing with provided events.
@@ -32,6 +390,171 @@ pub struct PrintLogs;
 
 impl GameLogsWriter for PrintLogs {
     fn write(&self, data: GameLogsEvent) {
-        println!("{}", data.into_message());
+        println!("{data}");
+    }
+}
+
+/// `GameLogsWriter` that passes every event to all of its writers.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use nomi_core::instance::logs::{FanOutLogs, GameLogsWriter, PrintLogs, IgnoreLogs};
+/// let writer = FanOutLogs::new().with(PrintLogs).with(IgnoreLogs);
+/// ```
+#[derive(Default)]
+pub struct FanOutLogs<'a> {
+    writers: Vec<Box<dyn GameLogsWriter + 'a>>,
+}
+
+impl<'a> FanOutLogs<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with(mut self, writer: impl GameLogsWriter + 'a) -> Self {
+        self.push(writer);
+        self
+    }
+
+    pub fn push(&mut self, writer: impl GameLogsWriter + 'a) {
+        self.writers.push(Box::new(writer));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.writers.is_empty()
+    }
+}
+
+impl GameLogsWriter for FanOutLogs<'_> {
+    fn write(&self, data: GameLogsEvent) {
+        let Some((last, rest)) = self.writers.split_last() else {
+            return;
+        };
+
+        for writer in rest {
+            writer.write(data.clone());
+        }
+
+        last.write(data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_all(lines: &[&str]) -> Vec<GameLogsEvent> {
+        let mut parser = GameLogsParser::new();
+        let mut events = lines.iter().flat_map(|line| parser.push((*line).to_owned())).collect::<Vec<_>>();
+        events.extend(parser.flush());
+        events
+    }
+
+    #[test]
+    fn text_layout_test() {
+        let events = parse_all(&[
+            "[12:34:56] [Render thread/INFO]: Setting user: Player",
+            "[12:34:57] [main/WARN] (FabricLoader/Mixin): Reference map could not be read",
+            "[12:34:58] [Render thread/ERROR]: Failed to load texture",
+            "java.io.FileNotFoundException: minecraft:textures/missing.png",
+            "\tat net.minecraft.class_1049.method_4625(class_1049.java:67)",
+            "Caused by: java.lang.NullPointerException",
+            "\t... 3 more",
+            "Some line printed without log4j",
+        ]);
+
+        assert_eq!(events.len(), 4);
+
+        assert_eq!(events[0].timestamp(), Some(&LogTimestamp::Text("12:34:56".to_owned())));
+        assert_eq!(events[0].thread(), Some("Render thread"));
+        assert_eq!(events[0].level(), Some(LogLevel::Info));
+        assert_eq!(events[0].message(), "Setting user: Player");
+
+        assert_eq!(events[1].logger(), Some("FabricLoader/Mixin"));
+        assert_eq!(events[1].level(), Some(LogLevel::Warn));
+
+        assert_eq!(events[2].message(), "Failed to load texture");
+        assert_eq!(
+            events[2].throwable(),
+            Some("java.io.FileNotFoundException: minecraft:textures/missing.png\n\tat net.minecraft.class_1049.method_4625(class_1049.java:67)\nCaused by: java.lang.NullPointerException\n\t... 3 more")
+        );
+
+        assert_eq!(events[3], GameLogsEvent::new("Some line printed without log4j".to_owned()));
+    }
+
+    #[test]
+    fn xml_layout_test() {
+        let events = parse_all(&[
+            r#"<log4j:Event logger="net.minecraft.client.Minecraft" timestamp="1722513296000" level="INFO" thread="Render thread">"#,
+            "  <log4j:Message><![CDATA[Setting user: Player]]></log4j:Message>",
+            "</log4j:Event>",
+            r#"<log4j:Event logger="net.minecraft.class_1060" timestamp="1722513297000" level="ERROR" thread="Worker-Main-1">"#,
+            "  <log4j:Message><![CDATA[Failed to load <texture>]]></log4j:Message>",
+            "  <log4j:Throwable><![CDATA[java.io.FileNotFoundException: missing.png",
+            "\tat net.minecraft.class_1049.method_4625(class_1049.java:67)",
+            "]]></log4j:Throwable>",
+            "</log4j:Event>",
+        ]);
+
+        assert_eq!(events.len(), 2);
+
+        assert_eq!(events[0].logger(), Some("net.minecraft.client.Minecraft"));
+        assert_eq!(events[0].timestamp(), Some(&LogTimestamp::UnixMillis(1_722_513_296_000)));
+        assert_eq!(events[0].level(), Some(LogLevel::Info));
+        assert_eq!(events[0].thread(), Some("Render thread"));
+        assert_eq!(events[0].message(), "Setting user: Player");
+
+        assert_eq!(events[1].message(), "Failed to load <texture>");
+        assert_eq!(
+            events[1].throwable(),
+            Some("java.io.FileNotFoundException: missing.png\n\tat net.minecraft.class_1049.method_4625(class_1049.java:67)")
+        );
+        assert!(events[1].contains("FILENOTFOUND"));
+        assert!(!events[1].contains("Setting user"));
+    }
+
+    #[test]
+    fn fan_out_test() {
+        #[derive(Default)]
+        struct CollectLogs(std::sync::Mutex<Vec<String>>);
+
+        impl GameLogsWriter for CollectLogs {
+            fn write(&self, data: GameLogsEvent) {
+                self.0.lock().unwrap().push(data.into_message());
+            }
+        }
+
+        let first = CollectLogs::default();
+        let second = Arc::new(CollectLogs::default());
+
+        let writer = FanOutLogs::new().with(&first).with(second.clone());
+        writer.write(GameLogsEvent::new("one".to_owned()));
+        writer.write(GameLogsEvent::new("two".to_owned()));
+        drop(writer);
+
+        assert_eq!(*first.0.lock().unwrap(), ["one", "two"]);
+        assert_eq!(*second.0.lock().unwrap(), ["one", "two"]);
+    }
+
+    #[tokio::test]
+    async fn parse_stream_test() {
+        let lines = [
+            "Exception in thread \"main\" java.lang.IllegalStateException",
+            "\tat Main.main(Main.java:1)",
+            "[12:34:58] [main/ERROR]: Crashed",
+        ]
+        .map(String::from);
+
+        let events = parse_logs(tokio_stream::iter(lines)).collect::<Vec<_>>().await;
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].throwable(), Some("\tat Main.main(Main.java:1)"));
+        assert_eq!(
+            events[0].to_string(),
+            "Exception in thread \"main\" java.lang.IllegalStateException\n\tat Main.main(Main.java:1)"
+        );
+        assert_eq!(events[1].to_string(), "[12:34:58] [main/ERROR]: Crashed");
     }
 }
@@ -1,9 +1,13 @@
 pub mod builder_ext;
+pub mod crash;
 pub mod launch;
 pub mod loader;
+pub mod log_sessions;
 pub mod logs;
 pub mod marker;
+pub mod process;
 mod profile;
+pub mod verify;
 
 use std::path::{Path, PathBuf};
 
@@ -18,7 +22,7 @@ use crate::{
 };
 
 /// Loads all instances in the [`INSTANCES_DIR`](crate::consts::INSTANCES_DIR)
-pub fn load_instances() -> anyhow::Result<Vec<Instance>> {
+pub fn load_instances() -> crate::Result<Vec<Instance>> {
     let dir = std::fs::read_dir(INSTANCES_DIR)?;
 
     let mut instances = Vec::new();
@@ -118,11 +122,11 @@ impl Instance {
         &mut self.profiles
     }
 
-    pub async fn write(&self) -> anyhow::Result<()> {
+    pub async fn write(&self) -> crate::Result<()> {
         write_toml_config(&self, self.path().join(".nomi/Instance.toml")).await
     }
 
-    pub fn write_blocking(&self) -> anyhow::Result<()> {
+    pub fn write_blocking(&self) -> crate::Result<()> {
         write_toml_config_sync(&self, self.path().join(".nomi/Instance.toml"))
     }
 
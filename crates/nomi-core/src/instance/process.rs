@@ -0,0 +1,286 @@
+use std::{
+    collections::VecDeque,
+    path::PathBuf,
+    pin::Pin,
+    process::ExitStatus,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::SystemTime,
+};
+
+use tokio::{
+    process::Child,
+    sync::{watch, Notify},
+};
+use tokio_stream::{Stream, StreamExt};
+use tokio_util::codec::{FramedRead, LinesCodec};
+use tracing::{error, info};
+
+use crate::Error;
+
+use super::{
+    crash::CrashReport,
+    logs::{parse_logs, GameLogsEvent, GameLogsWriter},
+};
+
+/// Number of the last output lines kept for the crash analysis.
+const OUTPUT_TAIL_LEN: usize = 200;
+
+pub type GameLogsStream = Pin<Box<dyn Stream<Item = GameLogsEvent> + Send>>;
+
+/// Handle to the running game.
+///
+/// Cloned handles control the same process.
+/// Logs can be taken only once and only from the handle returned by the launch.
+pub struct GameProcess {
+    pid: Option<u32>,
+    game_dir: PathBuf,
+    started_at: SystemTime,
+    output_tail: Arc<Mutex<VecDeque<String>>>,
+    status: watch::Receiver<Option<ExitStatus>>,
+    kill: Arc<Notify>,
+    killed: Arc<AtomicBool>,
+    logs: Option<GameLogsStream>,
+}
+
+impl Clone for GameProcess {
+    fn clone(&self) -> Self {
+        Self {
+            pid: self.pid,
+            game_dir: self.game_dir.clone(),
+            started_at: self.started_at,
+            output_tail: self.output_tail.clone(),
+            status: self.status.clone(),
+            kill: self.kill.clone(),
+            killed: self.killed.clone(),
+            logs: None,
+        }
+    }
+}
+
+impl GameProcess {
+    /// Takes over the `child` running in `game_dir`. Its stdout and stderr must be piped.
+    pub(crate) fn spawn(mut child: Child, game_dir: PathBuf) -> Self {
+        let started_at = SystemTime::now();
+        let pid = child.id();
+
+        let stdout = child.stdout.take().expect("child did not have a handle to stdout");
+        let stderr = child.stderr.take().expect("child did not have a handle to stderr");
+
+        let stdout = FramedRead::new(stdout, LinesCodec::new());
+        let stderr = FramedRead::new(stderr, LinesCodec::new());
+
+        let output_tail = Arc::new(Mutex::new(VecDeque::with_capacity(OUTPUT_TAIL_LEN)));
+
+        let lines = stdout.merge(stderr).filter_map({
+            let output_tail = output_tail.clone();
+            move |line| {
+                let line = line
+                    .inspect_err(|e| error!(error = ?e, "Error occurred while decoding game's output"))
+                    .ok()?;
+
+                if let Ok(mut tail) = output_tail.lock() {
+                    if tail.len() == OUTPUT_TAIL_LEN {
+                        tail.pop_front();
+                    }
+                    tail.push_back(line.clone());
+                }
+
+                Some(line)
+            }
+        });
+
+        let logs = parse_logs(lines);
+
+        let (status_sender, status) = watch::channel(None);
+        let kill = Arc::new(Notify::new());
+
+        tokio::spawn({
+            let kill = kill.clone();
+            async move {
+                let result = tokio::select! {
+                    result = child.wait() => result,
+                    () = kill.notified() => {
+                        let _ = child.start_kill().inspect_err(|e| error!(error = ?e, "Unable to kill the game"));
+                        child.wait().await
+                    }
+                };
+
+                match result {
+                    Ok(exit_status) => {
+                        exit_status.code().inspect(|code| info!("Minecraft exit code: {}", code));
+                        let _ = status_sender.send(Some(exit_status));
+                    }
+                    Err(e) => error!(error = ?e, "Unable to get the exit code"),
+                }
+            }
+        });
+
+        Self {
+            pid,
+            game_dir,
+            started_at,
+            output_tail,
+            status,
+            kill,
+            killed: Arc::new(AtomicBool::new(false)),
+            logs: Some(Box::pin(logs)),
+        }
+    }
+
+    /// Id of the game process. `None` if the game exited before it was read.
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    /// Exit status of the game if it has already exited.
+    pub fn exit_status(&self) -> Option<ExitStatus> {
+        *self.status.borrow()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.exit_status().is_none()
+    }
+
+    /// Whether the game was stopped with [`GameProcess::kill`].
+    pub fn was_killed(&self) -> bool {
+        self.killed.load(Ordering::SeqCst)
+    }
+
+    /// Waits for the game to exit.
+    pub async fn wait(&self) -> crate::Result<ExitStatus> {
+        let mut status = self.status.clone();
+        let exit_status = *status.wait_for(Option::is_some).await.map_err(|_| Error::ExitStatusUnavailable)?;
+
+        exit_status.ok_or(Error::ExitStatusUnavailable)
+    }
+
+    /// Requests the game to be killed without waiting for it to exit.
+    pub fn start_kill(&self) {
+        if self.is_running() {
+            self.killed.store(true, Ordering::SeqCst);
+            self.kill.notify_one();
+        }
+    }
+
+    /// Kills the game and waits for it to exit.
+    pub async fn kill(&self) -> crate::Result<ExitStatus> {
+        self.start_kill();
+        self.wait().await
+    }
+
+    /// Waits for the game to exit and analyzes the crash if it exited with an error.
+    ///
+    /// Returns `None` if the game exited successfully or was killed.
+    pub async fn crash_report(&self) -> crate::Result<Option<CrashReport>> {
+        let status = self.wait().await?;
+
+        if status.success() || self.was_killed() {
+            return Ok(None);
+        }
+
+        let output = self
+            .output_tail
+            .lock()
+            .map(|tail| tail.iter().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        CrashReport::analyze(&self.game_dir, status.code(), self.started_at, &output)
+            .await
+            .map(Some)
+    }
+
+    /// Takes the stream of the game's stdout and stderr lines.
+    pub fn take_logs(&mut self) -> Option<GameLogsStream> {
+        self.logs.take()
+    }
+
+    /// Writes all the game's output into `logs_writer` until the game closes its output.
+    pub async fn write_logs(&mut self, logs_writer: &dyn GameLogsWriter) {
+        let Some(mut logs) = self.take_logs() else {
+            return;
+        };
+
+        while let Some(event) = logs.next().await {
+            logs_writer.write(event);
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{process::Stdio, sync::Mutex};
+
+    use tokio::process::Command;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct CollectLogs(Mutex<Vec<String>>);
+
+    impl GameLogsWriter for CollectLogs {
+        fn write(&self, data: GameLogsEvent) {
+            self.0.lock().unwrap().push(data.into_message());
+        }
+    }
+
+    fn spawn(script: &str) -> GameProcess {
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        GameProcess::spawn(child, std::env::temp_dir())
+    }
+
+    #[tokio::test]
+    async fn exit_code_test() {
+        let mut process = spawn("echo out; echo err >&2; exit 3");
+        assert!(process.pid().is_some());
+
+        let logs = CollectLogs::default();
+        process.write_logs(&logs).await;
+
+        let status = process.wait().await.unwrap();
+        assert_eq!(status.code(), Some(3));
+        assert!(!process.is_running());
+        assert!(!process.was_killed());
+
+        let mut logs = logs.0.into_inner().unwrap();
+        logs.sort();
+        assert_eq!(logs, ["err", "out"]);
+    }
+
+    #[tokio::test]
+    async fn crash_report_test() {
+        let mut process = spawn("echo 'Exception in thread \"main\" java.lang.NoClassDefFoundError: net/minecraft/Foo' >&2; exit 1");
+        process.write_logs(&CollectLogs::default()).await;
+
+        let report = process.crash_report().await.unwrap().unwrap();
+
+        assert_eq!(report.exit_code, Some(1));
+        assert_eq!(report.exception.as_deref(), Some("java.lang.NoClassDefFoundError: net/minecraft/Foo"));
+
+        assert!(spawn("exit 0").crash_report().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn kill_test() {
+        let process = spawn("sleep 30");
+        let handle = process.clone();
+
+        assert!(handle.is_running());
+        assert!(handle.clone().take_logs().is_none());
+
+        let status = handle.kill().await.unwrap();
+
+        assert!(!status.success());
+        assert!(process.was_killed());
+        assert_eq!(process.wait().await.unwrap(), status);
+    }
+}
@@ -1,7 +1,13 @@
 use tracing::{debug, warn};
 use typed_builder::TypedBuilder;
 
-use crate::{downloads::downloaders::assets::AssetsDownloader, game_paths::GamePaths, state::get_launcher_manifest};
+use crate::{
+    downloads::downloaders::assets::AssetsDownloader,
+    fs::read_json_config,
+    game_paths::{log_configs_dir, GamePaths},
+    repository::manifest::Manifest,
+    state::get_launcher_manifest,
+};
 
 use super::{
     launch::{LaunchInstance, LaunchInstanceBuilder, LaunchSettings},
@@ -21,9 +27,10 @@ impl Profile {
         self.downloader
     }
 
-    pub async fn assets(&self) -> anyhow::Result<AssetsDownloader> {
-        let manifest = get_launcher_manifest().await?;
-        let version_manifest = manifest.get_version_manifest(&self.version).await?;
+    pub async fn assets(&self) -> crate::Result<AssetsDownloader> {
+        let version_manifest = self.version_manifest().await?;
+
+        let logging = version_manifest.logging.and_then(|logging| logging.client);
 
         AssetsDownloader::new(
             version_manifest.asset_index.url,
@@ -32,6 +39,18 @@ impl Profile {
             self.game_paths.assets.join("indexes"),
         )
         .await
+        .map(|downloader| downloader.with_logging_config(logging.as_ref(), &log_configs_dir(&self.game_paths.assets)))
+    }
+
+    /// Reads the version manifest saved by the downloader, it is fetched only if the file is missing.
+    async fn version_manifest(&self) -> crate::Result<Manifest> {
+        let path = self.game_paths.manifest_file(&self.version);
+
+        if path.exists() {
+            return read_json_config(path).await;
+        }
+
+        get_launcher_manifest().await?.get_version_manifest(&self.version).await
     }
 
     #[must_use]
@@ -0,0 +1,255 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use futures_util::{stream, StreamExt};
+use itertools::Itertools;
+use tracing::{debug, warn};
+
+use crate::{
+    cache::{MetadataCache, IMMUTABLE_TTL},
+    downloads::{downloaders::assets::Assets, is_file_valid, DownloadSet, FileDownloader},
+    fs::read_json_config,
+    game_paths::GamePaths,
+    repository::manifest::{DownloadFile, Manifest},
+};
+
+use super::launch::{arguments::ArgumentsBuilder, LaunchInstance};
+
+/// How many files are hashed at the same time.
+const VERIFY_CONCURRENCY: usize = 64;
+
+/// File that is required to launch the game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedFile {
+    pub path: PathBuf,
+    /// `None` if the file cannot be downloaded again,
+    /// e.g. loader libraries that are generated by the installer.
+    pub url: Option<String>,
+    /// Files without a hash are only checked to exist.
+    pub sha1: Option<String>,
+}
+
+impl ExpectedFile {
+    fn from_download_file(path: PathBuf, file: &DownloadFile) -> Self {
+        Self {
+            path,
+            url: Some(file.url.clone()),
+            sha1: Some(file.sha1.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileProblem {
+    Missing,
+    /// The file exists but its hash is not the expected one.
+    Mismatched,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokenFile {
+    pub file: ExpectedFile,
+    pub problem: FileProblem,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct VerifyReport {
+    /// Number of checked files.
+    pub checked: usize,
+    pub broken: Vec<BrokenFile>,
+}
+
+impl VerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.broken.is_empty()
+    }
+
+    pub fn missing(&self) -> impl Iterator<Item = &BrokenFile> {
+        self.broken.iter().filter(|file| file.problem == FileProblem::Missing)
+    }
+
+    pub fn mismatched(&self) -> impl Iterator<Item = &BrokenFile> {
+        self.broken.iter().filter(|file| file.problem == FileProblem::Mismatched)
+    }
+
+    /// Broken files that cannot be downloaded again.
+    /// The profile must be reinstalled to fix them.
+    pub fn unrepairable(&self) -> impl Iterator<Item = &BrokenFile> {
+        self.broken.iter().filter(|file| file.file.url.is_none())
+    }
+
+    /// Downloads only the broken files again.
+    ///
+    /// [`Self::unrepairable`] files are skipped.
+    pub fn repair(&self) -> DownloadSet {
+        let mut set = DownloadSet::new();
+
+        for BrokenFile { file, .. } in &self.broken {
+            let Some(url) = &file.url else {
+                continue;
+            };
+
+            let mut downloader = FileDownloader::new(url.clone(), file.path.clone());
+            if let Some(sha1) = &file.sha1 {
+                downloader = downloader.with_sha1(sha1.clone());
+            }
+
+            set.add(Box::new(downloader.into_retry()));
+        }
+
+        set
+    }
+
+    /// Checks the broken files again, e.g. after the [`Self::repair`].
+    pub async fn recheck(&self) -> VerifyReport {
+        check_files(self.broken.iter().map(|broken| broken.file.clone()).collect()).await
+    }
+}
+
+/// Outcome of repairing the files of a [`VerifyReport`].
+#[derive(Debug, Default, Clone)]
+pub struct RepairReport {
+    /// Files found by the verification.
+    pub found: VerifyReport,
+    /// Files that are still broken after the repair.
+    pub remaining: VerifyReport,
+}
+
+impl RepairReport {
+    /// Number of files that were downloaded again successfully.
+    pub fn repaired(&self) -> usize {
+        self.found.broken.len() - self.remaining.broken.len()
+    }
+}
+
+/// Checks the classpath of the `instance`, its natives, the version jar and the assets.
+///
+/// The version manifest saved in the profile directory is used to get the hashes of the files.
+#[tracing::instrument(skip_all, fields(version = instance.settings.version), err)]
+pub async fn verify(paths: &GamePaths, instance: &LaunchInstance) -> crate::Result<VerifyReport> {
+    let manifest = read_json_config::<Manifest>(paths.manifest_file(&instance.settings.version)).await?;
+
+    let mut files = classpath_files(paths, instance, &manifest);
+
+    let index = ExpectedFile {
+        path: paths.assets.join("indexes").join(format!("{}.json", manifest.asset_index.id)),
+        url: Some(manifest.asset_index.url.clone()),
+        // The index is saved reformatted, so it does not match the hash from the manifest.
+        sha1: None,
+    };
+
+    let assets = match read_json_config::<Assets>(&index.path).await {
+        Ok(assets) => Some(assets),
+        Err(error) => {
+            if index.path.exists() {
+                warn!(%error, path = %index.path.display(), "Cannot read the asset index");
+            }
+            None
+        }
+    };
+
+    let is_index_corrupted = assets.is_none() && index.path.exists();
+
+    let assets = match assets {
+        Some(assets) => assets,
+        None => MetadataCache::global().get_json(&manifest.asset_index.url, IMMUTABLE_TTL).await?,
+    };
+
+    let objects = paths.assets.join("objects");
+    files.extend(assets.objects.values().map(|asset| ExpectedFile {
+        path: asset.path(&objects),
+        url: Some(asset.url()),
+        sha1: Some(asset.hash.clone()),
+    }));
+
+    if let Some(config) = manifest.logging.as_ref().and_then(|logging| logging.client.as_ref()) {
+        files.push(ExpectedFile {
+            path: paths.log_config_file(&config.file.id),
+            url: Some(config.file.url.clone()),
+            sha1: Some(config.file.sha1.clone()),
+        });
+    }
+
+    if is_index_corrupted {
+        let mut report = check_files(files).await;
+        report.checked += 1;
+        report.broken.push(BrokenFile {
+            file: index,
+            problem: FileProblem::Mismatched,
+        });
+        return Ok(report);
+    }
+
+    files.push(index);
+
+    Ok(check_files(files).await)
+}
+
+/// Checks that every file exists and matches its hash.
+pub async fn check_files(files: Vec<ExpectedFile>) -> VerifyReport {
+    let checked = files.len();
+
+    let broken = stream::iter(files)
+        .map(|file| async move {
+            if !tokio::fs::try_exists(&file.path).await.unwrap_or(false) {
+                return Some(BrokenFile {
+                    file,
+                    problem: FileProblem::Missing,
+                });
+            }
+
+            if is_file_valid(&file.path, file.sha1.as_deref(), None).await {
+                return None;
+            }
+
+            Some(BrokenFile {
+                file,
+                problem: FileProblem::Mismatched,
+            })
+        })
+        .buffer_unordered(VERIFY_CONCURRENCY)
+        .filter_map(|broken| async move { broken })
+        .collect::<Vec<_>>()
+        .await;
+
+    debug!(checked, broken = broken.len(), "Verified files");
+
+    VerifyReport { checked, broken }
+}
+
+/// Files from the classpath that [`LaunchInstance::launch`] builds, including the natives.
+fn classpath_files(paths: &GamePaths, instance: &LaunchInstance, manifest: &Manifest) -> Vec<ExpectedFile> {
+    let mut sources = HashMap::new();
+
+    sources.insert(paths.version_jar_file(&instance.settings.version), &manifest.downloads.client);
+
+    for lib in &manifest.libraries {
+        let natives = lib
+            .downloads
+            .classifiers
+            .iter()
+            .flat_map(|classifiers| [&classifiers.natives_linux, &classifiers.natives_windows, &classifiers.natives_macos]);
+
+        for file in natives.chain([&lib.downloads.artifact]).flatten() {
+            if let Some(path) = &file.path {
+                sources.insert(paths.libraries.join(path), file);
+            }
+        }
+    }
+
+    let builder = ArgumentsBuilder::new(paths, instance, manifest).build_classpath();
+
+    builder
+        .classpath_as_slice()
+        .iter()
+        .unique()
+        .map(|path| match sources.get(path) {
+            Some(file) => ExpectedFile::from_download_file(path.clone(), file),
+            // Loader libraries do not store where they were downloaded from.
+            None => ExpectedFile {
+                path: path.clone(),
+                url: None,
+                sha1: None,
+            },
+        })
+        .collect()
+}
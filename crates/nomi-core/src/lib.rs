@@ -3,10 +3,13 @@
 #![allow(clippy::module_name_repetitions)]
 #![allow(clippy::must_use_candidate)]
 #![allow(clippy::cast_possible_truncation)]
+pub mod auth;
+pub mod cache;
 pub mod configs;
 pub mod downloads;
 pub mod instance;
 pub mod loaders;
+pub mod modpacks;
 pub mod repository;
 
 pub mod error;
@@ -15,13 +18,22 @@ pub mod utils;
 pub mod fs;
 pub mod game_paths;
 pub mod maven_data;
+pub mod mirrors;
+pub mod network;
 pub mod state;
 
 pub mod consts;
 
-use std::{future::Future, pin::Pin};
+use std::{
+    fs::File,
+    future::Future,
+    io::{BufRead, BufReader},
+    path::Path,
+    pin::Pin,
+};
 
 pub use consts::*;
+pub use error::{Error, Result};
 
 pub use regex;
 use sha1::Digest;
@@ -34,6 +46,48 @@ pub fn calculate_sha1(data: impl AsRef<[u8]>) -> String {
     base16ct::lower::encode_string(&value)
 }
 
+pub fn calculate_sha512(data: impl AsRef<[u8]>) -> String {
+    let value = sha2::Sha512::digest(data);
+    base16ct::lower::encode_string(&value)
+}
+
+/// Hashes calculated by [`calculate_file_hashes`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FileHashes {
+    pub sha1: Option<String>,
+    pub sha512: Option<String>,
+}
+
+/// Streams the file through the requested hashers without reading it into memory.
+pub fn calculate_file_hashes(path: impl AsRef<Path>, sha1: bool, sha512: bool) -> std::io::Result<FileHashes> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let mut sha1_hasher = sha1.then(sha1::Sha1::new);
+    let mut sha512_hasher = sha512.then(sha2::Sha512::new);
+
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+
+        if let Some(hasher) = &mut sha1_hasher {
+            hasher.update(buf);
+        }
+        if let Some(hasher) = &mut sha512_hasher {
+            hasher.update(buf);
+        }
+
+        let len = buf.len();
+        reader.consume(len);
+    }
+
+    Ok(FileHashes {
+        sha1: sha1_hasher.map(|hasher| base16ct::lower::encode_string(&hasher.finalize())),
+        sha512: sha512_hasher.map(|hasher| base16ct::lower::encode_string(&hasher.finalize())),
+    })
+}
+
 mod markers {
     #[derive(Default, Debug)]
     pub struct Undefined;
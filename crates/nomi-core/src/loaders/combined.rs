@@ -8,6 +8,7 @@ use crate::{
     },
     game_paths::GamePaths,
     instance::marker::ProfileDownloader,
+    repository::manifest::Manifest,
     PinnedFutureWithBounds,
 };
 
@@ -32,7 +33,7 @@ impl<T> Debug for VanillaCombinedDownloader<T> {
 }
 
 impl VanillaCombinedDownloader<()> {
-    pub async fn new(game_version: impl Into<String>, game_paths: GamePaths) -> anyhow::Result<Self> {
+    pub async fn new(game_version: impl Into<String>, game_paths: GamePaths) -> crate::Result<Self> {
         let version = game_version.into();
         let vanilla = Vanilla::new(&version, game_paths.clone()).await?;
 
@@ -44,10 +45,14 @@ impl VanillaCombinedDownloader<()> {
         })
     }
 
-    pub async fn with_loader<T, F, Fut>(self, fun: F) -> anyhow::Result<VanillaCombinedDownloader<T>>
+    pub fn manifest(&self) -> &Manifest {
+        self.vanilla.manifest()
+    }
+
+    pub async fn with_loader<T, F, Fut>(self, fun: F) -> crate::Result<VanillaCombinedDownloader<T>>
     where
         F: FnOnce(String, GamePaths) -> Fut,
-        Fut: Future<Output = anyhow::Result<T>>,
+        Fut: Future<Output = crate::Result<T>>,
         T: ProfileDownloader,
     {
         let loader = (fun)(self.version.clone(), self.game_paths.clone()).await?;
@@ -81,7 +86,7 @@ impl<T: ProfileDownloader + 'static> Downloader for VanillaCombinedDownloader<T>
         downloader.download(sender).await;
     }
 
-    fn io(&self) -> PinnedFutureWithBounds<anyhow::Result<()>> {
+    fn io(&self) -> PinnedFutureWithBounds<crate::Result<()>> {
         let vanilla_io = self.vanilla.io();
         let loader_io = self.loader.io();
 
@@ -104,7 +109,7 @@ impl Downloader for VanillaCombinedDownloader<()> {
         Box::new(self.vanilla).download(sender).await;
     }
 
-    fn io(&self) -> PinnedFutureWithBounds<anyhow::Result<()>> {
+    fn io(&self) -> PinnedFutureWithBounds<crate::Result<()>> {
         self.vanilla.io()
     }
 }
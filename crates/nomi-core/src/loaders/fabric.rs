@@ -1,7 +1,6 @@
 use std::path::PathBuf;
 
 use itertools::Itertools;
-use reqwest::Client;
 
 use crate::{
     configs::profile::Loader,
@@ -17,6 +16,8 @@ use crate::{
     game_paths::GamePaths,
     instance::loader::LoaderProfile,
     maven_data::{MavenArtifact, MavenData},
+    mirrors::mirrored,
+    network::http_client,
     repository::{
         fabric_meta::FabricVersions,
         fabric_profile::{FabricLibrary, FabricProfile},
@@ -24,7 +25,7 @@ use crate::{
         simple_lib::SimpleLib,
     },
     state::get_launcher_manifest,
-    PinnedFutureWithBounds,
+    Error, PinnedFutureWithBounds,
 };
 
 use super::ToLoaderProfile;
@@ -39,25 +40,28 @@ pub struct Fabric {
 }
 
 impl Fabric {
-    pub async fn new(game_version: impl Into<String>, loader_version: Option<impl Into<String>>, game_paths: GamePaths) -> anyhow::Result<Self> {
+    pub async fn new(game_version: impl Into<String>, loader_version: Option<impl Into<String>>, game_paths: GamePaths) -> crate::Result<Self> {
         let game_version = game_version.into();
 
-        let client = Client::new();
+        let client = http_client();
         let launcher_manifest = get_launcher_manifest().await?;
 
         if !launcher_manifest.versions.iter().any(|v| v.id == game_version) {
-            return Err(crate::error::Error::NoSuchVersion.into());
+            return Err(Error::NoSuchVersion(game_version));
         };
 
         let versions: FabricVersions = client
-            .get(format!("https://meta.fabricmc.net/v2/versions/loader/{game_version}"))
+            .get(mirrored(&format!("https://meta.fabricmc.net/v2/versions/loader/{game_version}")))
             .send()
             .await?
             .json()
             .await?;
 
         if versions.is_empty() {
-            return Err(crate::error::Error::NoSuchVersion.into());
+            return Err(Error::NoLoaderVersions {
+                loader: "Fabric",
+                game_version,
+            });
         }
 
         let profile_version = loader_version
@@ -66,10 +70,10 @@ impl Fabric {
             .unwrap_or_else(|| &versions[0]);
 
         let profile: FabricProfile = client
-            .get(format!(
+            .get(mirrored(&format!(
                 "https://meta.fabricmc.net/v2/versions/loader/{}/{}/profile/json",
                 game_version, profile_version.loader.version
-            ))
+            )))
             .send()
             .await?
             .json()
@@ -119,7 +123,7 @@ impl LibrariesMapper<FabricLibrary> for FabricLibrariesMapper {
         let data = MavenData::new(&library.name);
         let path = self.libraries.join(&data.path);
 
-        (!path.exists()).then_some(FileDownloader::new(format!("{}{}", library.url, data.url), path))
+        Some(FileDownloader::new(format!("{}{}", library.url, data.url), path))
     }
 }
 
@@ -135,7 +139,7 @@ impl Downloader for Fabric {
         Box::new(self.libraries_downloader).download(sender).await;
     }
 
-    fn io(&self) -> PinnedFutureWithBounds<anyhow::Result<()>> {
+    fn io(&self) -> PinnedFutureWithBounds<crate::Result<()>> {
         let version_path = self.game_paths.profile.clone();
         let profile = self.profile.clone();
         let id = self.profile.id.clone();
@@ -6,7 +6,6 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use anyhow::{anyhow, bail};
 use itertools::Itertools;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use tokio::{io::AsyncWriteExt, process::Command};
@@ -22,24 +21,27 @@ use crate::{
         DownloadQueue, FileDownloader, LibrariesDownloader, LibrariesMapper,
     },
     game_paths::GamePaths,
-    instance::{launch::CLASSPATH_SEPARATOR, loader::LoaderProfile},
+    instance::{
+        launch::{rules::Platform, CLASSPATH_SEPARATOR},
+        loader::LoaderProfile,
+    },
     loaders::vanilla::VanillaLibrariesMapper,
     maven_data::{MavenArtifact, MavenData},
+    mirrors::mirrored,
+    network::http_client,
     repository::{
         java_runner::JavaRunner,
         manifest::{Argument, Arguments, Library},
         simple_args::SimpleArgs,
         simple_lib::SimpleLib,
     },
-    PinnedFutureWithBounds, DOT_NOMI_TEMP_DIR,
+    Error, PinnedFutureWithBounds, DOT_NOMI_TEMP_DIR,
 };
 
 use super::ToLoaderProfile;
 
 const FORGE_REPO_URL: &str = "https://maven.minecraftforge.net";
 
-const _NEO_FORGE_REPO_URL: &str = "https://maven.neoforged.net/releases/";
-
 /// Some versions require to have a suffix
 const FORGE_SUFFIXES: &[(&str, &[&str])] = &[
     ("1.11", &["-1.11.x"]),
@@ -69,10 +71,12 @@ pub struct Forge {
 
 impl Forge {
     #[tracing::instrument(skip_all, err)]
-    pub async fn get_versions(game_version: impl Into<String>) -> anyhow::Result<Vec<String>> {
+    pub async fn get_versions(game_version: impl Into<String>) -> crate::Result<Vec<String>> {
         let game_version = game_version.into();
 
-        let raw = reqwest::get(format!("{FORGE_REPO_URL}/net/minecraftforge/forge/maven-metadata.xml"))
+        let raw = http_client()
+            .get(mirrored(&format!("{FORGE_REPO_URL}/net/minecraftforge/forge/maven-metadata.xml")))
+            .send()
             .await?
             .text()
             .await?;
@@ -98,14 +102,18 @@ impl Forge {
 
         match versions {
             Some(v) => Ok(v),
-            None => Err(anyhow!("Error while matching forge versions")),
+            None => Err(Error::InvalidManifest(String::from(
+                "Forge maven metadata does not contain the versions list",
+            ))),
         }
     }
 
     /// Get forge versions that are recommended for specific game version
     #[tracing::instrument(err)]
-    pub async fn get_promo_versions() -> anyhow::Result<ForgeVersions> {
-        reqwest::get("https://files.minecraftforge.net/net/minecraftforge/forge/promotions_slim.json")
+    pub async fn get_promo_versions() -> crate::Result<ForgeVersions> {
+        http_client()
+            .get(mirrored("https://files.minecraftforge.net/net/minecraftforge/forge/promotions_slim.json"))
+            .send()
             .await?
             .json::<ForgeVersions>()
             .await
@@ -151,13 +159,15 @@ impl Forge {
         }).collect_vec()
     }
 
-    fn get_profile_from_installer(archive: &mut zip::ZipArchive<File>) -> anyhow::Result<ForgeProfile> {
+    fn get_profile_from_installer(archive: &mut zip::ZipArchive<File>) -> crate::Result<ForgeProfile> {
         let index = archive
             .index_for_name("version.json")
             .or_else(|| archive.index_for_name("install_profile.json"));
 
         let Some(idx) = index else {
-            bail!("Cannot find either `version.json` or `install_profile.json`")
+            return Err(Error::InvalidManifest(String::from(
+                "Forge installer contains neither `version.json` nor `install_profile.json`",
+            )));
         };
 
         let mut file = archive.by_index(idx)?;
@@ -165,7 +175,7 @@ impl Forge {
         read_json_from_zip(&mut file)
     }
 
-    fn get_install_profile(archive: &mut zip::ZipArchive<File>) -> anyhow::Result<ForgeInstallProfile> {
+    fn get_install_profile(archive: &mut zip::ZipArchive<File>) -> crate::Result<ForgeInstallProfile> {
         let mut file = archive.by_name("install_profile.json")?;
 
         read_json_from_zip(&mut file)
@@ -180,18 +190,16 @@ impl Forge {
     }
 
     #[tracing::instrument(skip(version), fields(game_version) err)]
-    pub async fn new(
-        version: impl Into<String>,
-        forge_version: ForgeVersion,
-        game_paths: GamePaths,
-        java_runner: JavaRunner,
-    ) -> anyhow::Result<Self> {
+    pub async fn new(version: impl Into<String>, forge_version: ForgeVersion, game_paths: GamePaths, java_runner: JavaRunner) -> crate::Result<Self> {
         let game_version: String = version.into();
 
         tracing::Span::current().record("game_version", &game_version);
 
         let Some(forge_version) = Self::proceed_version(&game_version, forge_version).await else {
-            bail!("Cannot match version");
+            return Err(Error::NoLoaderVersions {
+                loader: "Forge",
+                game_version,
+            });
         };
 
         let installer_path = forge_installer_path(&game_version, &forge_version);
@@ -211,12 +219,24 @@ impl Forge {
             break;
         }
 
+        Self::from_installer(game_version, forge_version, game_paths, java_runner)
+    }
+
+    /// Creates [`Forge`] from the installer that is already downloaded to [`Forge::installer_path`].
+    ///
+    /// Forks that use the same installer format (e.g. `NeoForge`) can reuse the whole installation pipeline through this.
+    pub(crate) fn from_installer(game_version: String, forge_version: String, game_paths: GamePaths, java_runner: JavaRunner) -> crate::Result<Self> {
+        let installer_path = forge_installer_path(&game_version, &forge_version);
+
         let file = std::fs::File::open(installer_path)?;
         let mut archive = zip::ZipArchive::new(file)?;
 
         let profile = Self::get_profile_from_installer(&mut archive)?;
 
-        let vanilla_mapper = VanillaLibrariesMapper { path: &game_paths.libraries };
+        let vanilla_mapper = VanillaLibrariesMapper {
+            path: &game_paths.libraries,
+            platform: Platform::current(),
+        };
 
         let downloader = match &profile {
             ForgeProfile::New(new) => LibrariesDownloader::new(&vanilla_mapper, &new.libraries),
@@ -290,7 +310,9 @@ impl Forge {
 impl ToLoaderProfile for Forge {
     fn to_profile(&self) -> LoaderProfile {
         LoaderProfile {
-            loader: Loader::Forge,
+            loader: Loader::Forge {
+                version: Some(self.forge_version.clone()),
+            },
             main_class: self.profile.main_class().to_string(),
             args: self.profile.simple_args(),
             libraries: self.profile.simple_libraries(),
@@ -298,7 +320,7 @@ impl ToLoaderProfile for Forge {
     }
 }
 
-fn forge_installer_path(game_version: &str, forge_version: &str) -> PathBuf {
+pub(crate) fn forge_installer_path(game_version: &str, forge_version: &str) -> PathBuf {
     Path::new(DOT_NOMI_TEMP_DIR).join(format!("{game_version}-{forge_version}.jar"))
 }
 
@@ -308,14 +330,13 @@ fn forge_binpatch_path(game_version: &str, forge_version: &str) -> PathBuf {
         .join("BINPATCH")
 }
 
-fn read_json_from_zip<T: DeserializeOwned>(file: &mut ZipFile<'_>) -> anyhow::Result<T> {
+fn read_json_from_zip<T: DeserializeOwned>(file: &mut ZipFile<'_>) -> crate::Result<T> {
     let mut string = String::new();
     file.read_to_string(&mut string)?;
 
     let mut deserializer = serde_json::Deserializer::from_str(&string);
 
-    serde_path_to_error::deserialize(&mut deserializer)
-        .map_err(|e| anyhow!("Path: {}. Error: {}", e.path().clone().to_string(), e.into_inner().to_string()))
+    serde_path_to_error::deserialize(&mut deserializer).map_err(|e| Error::parse(file.name(), e))
 }
 
 #[derive(Debug, Clone)]
@@ -336,7 +357,7 @@ impl Downloader for Forge {
         Box::new(self.downloader).download(sender).await;
     }
 
-    fn io(&self) -> PinnedFutureWithBounds<anyhow::Result<()>> {
+    fn io(&self) -> PinnedFutureWithBounds<crate::Result<()>> {
         #[tracing::instrument(name = "Forge IO", skip(lib_data, processors_data), err)]
         async fn inner(
             game_version: String,
@@ -346,7 +367,7 @@ impl Downloader for Forge {
             game_paths: GamePaths,
             processors_data: Option<ProcessorsData>,
             lib_data: Option<ForgeLibraryExtractionData>,
-        ) -> anyhow::Result<()> {
+        ) -> crate::Result<()> {
             if let Some(lib_data) = lib_data {
                 info!("Extracting {}", &lib_data.library_path);
                 let file = tokio::fs::File::open(installer_path).await?;
@@ -450,7 +471,7 @@ impl ProcessorsData {
     }
 
     #[tracing::instrument(err)]
-    async fn get_processor_main_class(processor_jar: PathBuf) -> anyhow::Result<String> {
+    async fn get_processor_main_class(processor_jar: PathBuf) -> crate::Result<String> {
         tokio::task::spawn_blocking(|| {
             let file = std::fs::File::open(processor_jar)?;
             let mut archive = zip::ZipArchive::new(file)?;
@@ -466,7 +487,7 @@ impl ProcessorsData {
 
             match opt {
                 Some(main_class) => Ok(main_class),
-                None => Err(anyhow!("Main class is not found")),
+                None => Err(Error::InvalidManifest(String::from("Processor jar does not specify the main class"))),
             }
         })
         .await?
@@ -507,13 +528,7 @@ impl ProcessorsData {
         args
     }
 
-    async fn run_processors(
-        mut self,
-        java_runner: &JavaRunner,
-        game_version: &str,
-        forge_version: &str,
-        game_paths: GamePaths,
-    ) -> anyhow::Result<()> {
+    async fn run_processors(mut self, java_runner: &JavaRunner, game_version: &str, forge_version: &str, game_paths: GamePaths) -> crate::Result<()> {
         // let game_paths = game_paths
         //     .make_absolute()
         //     .inspect_err(|error| error!(%error, "Failed to make `game_paths` absolute"))?;
@@ -544,8 +559,10 @@ impl ProcessorsData {
                 ok += 1;
                 info!("Processor finished successfully");
             } else {
-                let error = String::from_utf8_lossy(&output.stderr);
-                bail!("Processor failed, error: {error}")
+                return Err(Error::ProcessorFailed {
+                    processor: processor.jar,
+                    stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+                });
             }
         }
 
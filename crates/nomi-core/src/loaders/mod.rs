@@ -3,6 +3,8 @@ use crate::instance::loader::LoaderProfile;
 pub mod combined;
 pub mod fabric;
 pub mod forge;
+pub mod neoforge;
+pub mod quilt;
 pub mod vanilla;
 
 pub trait ToLoaderProfile {
@@ -0,0 +1,98 @@
+use crate::{
+    configs::profile::Loader,
+    downloads::{
+        download_file,
+        progress::ProgressSender,
+        traits::{DownloadResult, Downloader},
+    },
+    game_paths::GamePaths,
+    instance::loader::LoaderProfile,
+    repository::{
+        java_runner::JavaRunner,
+        neoforge_meta::{get_neoforge_versions, installer_url},
+    },
+    Error, PinnedFutureWithBounds,
+};
+
+use super::{
+    forge::{forge_installer_path, Forge},
+    ToLoaderProfile,
+};
+
+/// `NeoForge` installers share the format with the modern Forge ones,
+/// so the installation itself is done by the inner [`Forge`].
+#[derive(Debug)]
+pub struct NeoForge {
+    inner: Forge,
+    neoforge_version: String,
+}
+
+impl NeoForge {
+    /// Get `NeoForge` versions built for specific game version
+    #[tracing::instrument(skip_all, err)]
+    pub async fn get_versions(game_version: impl Into<String>) -> crate::Result<Vec<String>> {
+        let game_version = game_version.into();
+
+        let versions = get_neoforge_versions().await?;
+
+        Ok(versions.for_game_version(&game_version).map(String::from).collect())
+    }
+
+    #[tracing::instrument(skip(version, neoforge_version), fields(game_version) err)]
+    pub async fn new(
+        version: impl Into<String>,
+        neoforge_version: Option<impl Into<String>>,
+        game_paths: GamePaths,
+        java_runner: JavaRunner,
+    ) -> crate::Result<Self> {
+        let game_version: String = version.into();
+
+        tracing::Span::current().record("game_version", &game_version);
+
+        let neoforge_version = match neoforge_version {
+            Some(version) => version.into(),
+            None => get_neoforge_versions()
+                .await?
+                .latest_for_game_version(&game_version)
+                .map(String::from)
+                .ok_or_else(|| Error::NoLoaderVersions {
+                    loader: "NeoForge",
+                    game_version: game_version.clone(),
+                })?,
+        };
+
+        download_file(forge_installer_path(&game_version, &neoforge_version), installer_url(&neoforge_version)).await?;
+
+        let inner = Forge::from_installer(game_version, neoforge_version.clone(), game_paths, java_runner)?;
+
+        Ok(Self { inner, neoforge_version })
+    }
+}
+
+impl ToLoaderProfile for NeoForge {
+    fn to_profile(&self) -> LoaderProfile {
+        LoaderProfile {
+            loader: Loader::NeoForge {
+                version: Some(self.neoforge_version.clone()),
+            },
+            ..self.inner.to_profile()
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Downloader for NeoForge {
+    type Data = DownloadResult;
+
+    fn total(&self) -> u32 {
+        self.inner.total()
+    }
+
+    async fn download(self: Box<Self>, sender: &dyn ProgressSender<Self::Data>) {
+        Box::new(self.inner).download(sender).await;
+    }
+
+    fn io(&self) -> PinnedFutureWithBounds<crate::Result<()>> {
+        self.inner.io()
+    }
+}
@@ -0,0 +1,160 @@
+use std::path::PathBuf;
+
+use itertools::Itertools;
+
+use crate::{
+    configs::profile::Loader,
+    downloads::{
+        downloaders::{
+            file::FileDownloader,
+            libraries::{LibrariesDownloader, LibrariesMapper},
+        },
+        progress::ProgressSender,
+        traits::{DownloadResult, Downloader},
+    },
+    fs::write_to_file,
+    game_paths::GamePaths,
+    instance::loader::LoaderProfile,
+    maven_data::{MavenArtifact, MavenData},
+    mirrors::mirrored,
+    network::http_client,
+    repository::{
+        fabric_profile::{FabricLibrary, FabricProfile},
+        quilt_meta::{QuiltVersions, QUILT_META_URL},
+        simple_args::SimpleArgs,
+        simple_lib::SimpleLib,
+    },
+    state::get_launcher_manifest,
+    Error, PinnedFutureWithBounds,
+};
+
+use super::ToLoaderProfile;
+
+/// Quilt uses the same launcher profile format as Fabric
+/// so [`FabricProfile`] is reused here.
+#[derive(Debug)]
+pub struct Quilt {
+    pub game_version: String,
+    pub profile: FabricProfile,
+    game_paths: GamePaths,
+    loader_version: String,
+    libraries_downloader: LibrariesDownloader,
+}
+
+impl Quilt {
+    pub async fn new(game_version: impl Into<String>, loader_version: Option<impl Into<String>>, game_paths: GamePaths) -> crate::Result<Self> {
+        let game_version = game_version.into();
+
+        let client = http_client();
+        let launcher_manifest = get_launcher_manifest().await?;
+
+        if !launcher_manifest.versions.iter().any(|v| v.id == game_version) {
+            return Err(Error::NoSuchVersion(game_version));
+        }
+
+        let versions: QuiltVersions = client
+            .get(mirrored(&format!("{QUILT_META_URL}/versions/loader/{game_version}")))
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        if versions.is_empty() {
+            return Err(Error::NoLoaderVersions {
+                loader: "Quilt",
+                game_version,
+            });
+        }
+
+        let profile_version = loader_version
+            .map(Into::into)
+            .and_then(|loader| versions.iter().find(|i| i.loader.version == loader))
+            .or_else(|| versions.iter().find(|i| i.loader.is_stable()))
+            .unwrap_or_else(|| &versions[0]);
+
+        let profile: FabricProfile = client
+            .get(mirrored(&format!(
+                "{QUILT_META_URL}/versions/loader/{}/{}/profile/json",
+                game_version, profile_version.loader.version
+            )))
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        let mapper = QuiltLibrariesMapper {
+            libraries: game_paths.libraries.clone(),
+        };
+
+        let libraries_downloader = LibrariesDownloader::new(&mapper, &profile.libraries);
+
+        Ok(Self {
+            loader_version: profile_version.loader.version.clone(),
+            game_version,
+            profile,
+            game_paths,
+            libraries_downloader,
+        })
+    }
+}
+
+impl ToLoaderProfile for Quilt {
+    fn to_profile(&self) -> LoaderProfile {
+        LoaderProfile {
+            loader: Loader::Quilt {
+                version: Some(self.loader_version.clone()),
+            },
+            main_class: self.profile.main_class.clone(),
+            args: SimpleArgs::from(&self.profile.arguments),
+            libraries: self
+                .profile
+                .libraries
+                .iter()
+                .map(|l| MavenArtifact::new(&l.name))
+                .map(SimpleLib::from)
+                .collect_vec(),
+        }
+    }
+}
+
+struct QuiltLibrariesMapper {
+    libraries: PathBuf,
+}
+
+impl LibrariesMapper<FabricLibrary> for QuiltLibrariesMapper {
+    fn proceed(&self, library: &FabricLibrary) -> Option<FileDownloader> {
+        let data = MavenData::new(&library.name);
+        let path = self.libraries.join(&data.path);
+
+        Some(FileDownloader::new(format!("{}{}", library.url, data.url), path))
+    }
+}
+
+#[async_trait::async_trait]
+impl Downloader for Quilt {
+    type Data = DownloadResult;
+
+    fn total(&self) -> u32 {
+        self.libraries_downloader.total()
+    }
+
+    async fn download(self: Box<Self>, sender: &dyn ProgressSender<Self::Data>) {
+        Box::new(self.libraries_downloader).download(sender).await;
+    }
+
+    fn io(&self) -> PinnedFutureWithBounds<crate::Result<()>> {
+        let version_path = self.game_paths.profile.clone();
+        let profile = self.profile.clone();
+        let id = self.profile.id.clone();
+
+        let fut = async move {
+            let path = version_path.join(format!("{id}.json"));
+
+            let body = serde_json::to_string_pretty(&profile)?;
+
+            write_to_file(body.as_bytes(), &path).await
+        };
+
+        Box::pin(fut)
+    }
+}
@@ -1,7 +1,5 @@
 use std::path::Path;
 
-use reqwest::Client;
-
 use tracing::error;
 
 use crate::{
@@ -11,14 +9,16 @@ use crate::{
             libraries::{LibrariesDownloader, LibrariesMapper},
         },
         progress::ProgressSender,
+        scheduler::Priority,
         traits::{DownloadResult, Downloader},
         DownloadQueue,
     },
     fs::write_to_file,
     game_paths::GamePaths,
-    repository::manifest::{Classifiers, DownloadFile, Library, Manifest},
+    instance::launch::rules::{is_library_passes, platform_natives, Platform},
+    repository::manifest::{DownloadFile, Library, Manifest},
     state::get_launcher_manifest,
-    PinnedFutureWithBounds,
+    Error, PinnedFutureWithBounds,
 };
 
 #[derive(Debug)]
@@ -29,24 +29,30 @@ pub struct Vanilla {
 }
 
 impl Vanilla {
-    pub async fn new(version_id: impl Into<String>, game_paths: GamePaths) -> anyhow::Result<Self> {
+    pub async fn new(version_id: impl Into<String>, game_paths: GamePaths) -> crate::Result<Self> {
         let id = version_id.into();
-        let client = Client::new();
         let launcher_manifest = get_launcher_manifest().await?;
 
-        let Some(val) = launcher_manifest.versions.iter().find(|i| i.id == id) else {
+        if launcher_manifest.find_version(&id).is_none() {
             error!("Cannot find this version");
 
-            return Err(crate::error::Error::NoSuchVersion.into());
-        };
+            return Err(Error::NoSuchVersion(id));
+        }
 
-        let manifest = client.get(&val.url).send().await?.json::<Manifest>().await?;
+        let manifest = launcher_manifest.get_version_manifest(id).await?;
 
-        let libraries_mapper = VanillaLibrariesMapper { path: &game_paths.libraries };
+        let libraries_mapper = VanillaLibrariesMapper {
+            path: &game_paths.libraries,
+            platform: Platform::current(),
+        };
 
-        let native_libraries_mapper = VanillaNativeLibrariesMapper { path: &game_paths.libraries };
+        let native_libraries_mapper = VanillaNativeLibrariesMapper {
+            path: &game_paths.libraries,
+            platform: Platform::current(),
+        };
 
         let queue = DownloadQueue::new()
+            .with_priority(Priority::High)
             .with_downloader(LibrariesDownloader::new(&libraries_mapper, &manifest.libraries))
             .with_downloader(LibrariesDownloader::new(&native_libraries_mapper, &manifest.libraries))
             .with_downloader(
@@ -54,11 +60,17 @@ impl Vanilla {
                     manifest.downloads.client.url.clone(),
                     game_paths.profile.join(format!("{}.jar", manifest.id)),
                 )
+                .with_sha1(manifest.downloads.client.sha1.clone())
+                .verify_first(true)
                 .into_retry(),
             );
 
         Ok(Self { manifest, game_paths, queue })
     }
+
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
 }
 
 fn manifest_file_to_downloader(manifest_file: &DownloadFile, target_path: &Path) -> Option<FileDownloader> {
@@ -66,17 +78,20 @@ fn manifest_file_to_downloader(manifest_file: &DownloadFile, target_path: &Path)
         .path
         .as_ref()
         .map(|path| target_path.join(path))
-        .map(|path| (manifest_file.url.clone(), path))
-        .filter(|(_, path)| !path.exists())
-        .map(|(url, path)| FileDownloader::new(url, path))
+        .map(|path| FileDownloader::new(manifest_file.url.clone(), path).with_sha1(manifest_file.sha1.clone()))
 }
 
 pub(crate) struct VanillaLibrariesMapper<'a> {
     pub path: &'a Path,
+    pub platform: &'a Platform,
 }
 
 impl LibrariesMapper<Library> for VanillaLibrariesMapper<'_> {
     fn proceed(&self, library: &Library) -> Option<FileDownloader> {
+        if !is_library_passes(library, self.platform) {
+            return None;
+        }
+
         library
             .downloads
             .artifact
@@ -87,24 +102,20 @@ impl LibrariesMapper<Library> for VanillaLibrariesMapper<'_> {
 
 struct VanillaNativeLibrariesMapper<'a> {
     path: &'a Path,
+    platform: &'a Platform,
 }
 
 impl LibrariesMapper<Library> for VanillaNativeLibrariesMapper<'_> {
     fn proceed(&self, library: &Library) -> Option<FileDownloader> {
-        fn match_natives(natives: &Classifiers) -> Option<&DownloadFile> {
-            match std::env::consts::OS {
-                "linux" => natives.natives_linux.as_ref(),
-                "windows" => natives.natives_windows.as_ref(),
-                "macos" => natives.natives_macos.as_ref(),
-                _ => unreachable!(),
-            }
+        if !is_library_passes(library, self.platform) {
+            return None;
         }
 
         library
             .downloads
             .classifiers
             .as_ref()
-            .and_then(match_natives)
+            .and_then(|natives| platform_natives(natives, self.platform))
             .and_then(|file| manifest_file_to_downloader(file, self.path))
     }
 }
@@ -121,7 +132,7 @@ impl Downloader for Vanilla {
         Box::new(self.queue).download(sender).await;
     }
 
-    fn io(&self) -> PinnedFutureWithBounds<anyhow::Result<()>> {
+    fn io(&self) -> PinnedFutureWithBounds<crate::Result<()>> {
         let versions_path = self.game_paths.profile.clone();
         let manifest_id = self.manifest.id.clone();
         let manifest_res = serde_json::to_string_pretty(&self.manifest);
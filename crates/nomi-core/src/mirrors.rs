@@ -0,0 +1,125 @@
+//! Rewrites the hosts of the downloaded URLs to the configured mirrors.
+//!
+//! Every category is a base URL that replaces `scheme://host` of the URLs that belong to it,
+//! the path of the URL is kept as is. For example, with the `assets` mirror set to
+//! `https://mirror.example.com/assets`, `https://resources.download.minecraft.net/ab/abcd`
+//! is downloaded from `https://mirror.example.com/assets/ab/abcd`.
+
+use std::{
+    borrow::Cow,
+    sync::{LazyLock, RwLock},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Mojang's metadata: version manifests, asset indexes, client jars and Java runtimes.
+pub const META_HOSTS: &[&str] = &[
+    "launchermeta.mojang.com",
+    "launcher.mojang.com",
+    "piston-meta.mojang.com",
+    "piston-data.mojang.com",
+];
+pub const LIBRARIES_HOSTS: &[&str] = &["libraries.minecraft.net"];
+pub const ASSETS_HOSTS: &[&str] = &["resources.download.minecraft.net"];
+/// Metadata and mavens of the mod loaders.
+pub const LOADERS_HOSTS: &[&str] = &[
+    "meta.fabricmc.net",
+    "maven.fabricmc.net",
+    "meta.quiltmc.org",
+    "maven.quiltmc.org",
+    "files.minecraftforge.net",
+    "maven.minecraftforge.net",
+    "maven.neoforged.net",
+];
+pub const MODDING_HOSTS: &[&str] = &["api.modrinth.com", "cdn.modrinth.com"];
+
+static MIRRORS: LazyLock<RwLock<Mirrors>> = LazyLock::new(RwLock::default);
+
+/// Base URLs that replace the original hosts. `None` means the original host is used.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[serde(default)]
+pub struct Mirrors {
+    pub meta: Option<String>,
+    pub libraries: Option<String>,
+    pub assets: Option<String>,
+    pub loaders: Option<String>,
+    pub modding: Option<String>,
+}
+
+impl Mirrors {
+    /// Mirrors used by every download.
+    pub fn global() -> Mirrors {
+        MIRRORS.read().unwrap_or_else(std::sync::PoisonError::into_inner).clone()
+    }
+
+    /// Replaces the mirrors used by every download.
+    pub fn set_global(self) {
+        *MIRRORS.write().unwrap_or_else(std::sync::PoisonError::into_inner) = self;
+    }
+
+    fn mirror_for(&self, host: &str) -> Option<&str> {
+        [
+            (META_HOSTS, &self.meta),
+            (LIBRARIES_HOSTS, &self.libraries),
+            (ASSETS_HOSTS, &self.assets),
+            (LOADERS_HOSTS, &self.loaders),
+            (MODDING_HOSTS, &self.modding),
+        ]
+        .into_iter()
+        .find(|(hosts, _)| hosts.iter().any(|known| known.eq_ignore_ascii_case(host)))
+        .and_then(|(_, mirror)| mirror.as_deref())
+        .filter(|mirror| !mirror.trim().is_empty())
+    }
+
+    /// Returns the URL pointing to the mirror, or the original one if there is no mirror for its host.
+    pub fn rewrite<'a>(&self, url: &'a str) -> Cow<'a, str> {
+        let Some((_, rest)) = url.split_once("://") else {
+            return Cow::Borrowed(url);
+        };
+
+        let (host, path) = rest.find('/').map_or((rest, ""), |index| rest.split_at(index));
+
+        match self.mirror_for(host) {
+            Some(mirror) => Cow::Owned(format!("{}{path}", mirror.trim_end_matches('/'))),
+            None => Cow::Borrowed(url),
+        }
+    }
+}
+
+/// Rewrites `url` using the [global](Mirrors::global) mirrors.
+pub fn mirrored(url: &str) -> String {
+    Mirrors::global().rewrite(url).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrite_test() {
+        let mirrors = Mirrors {
+            meta: Some("https://bmclapi2.bangbang93.com".into()),
+            assets: Some("https://bmclapi2.bangbang93.com/assets/".into()),
+            libraries: Some(String::new()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            mirrors.rewrite("https://piston-meta.mojang.com/mc/game/version_manifest.json"),
+            "https://bmclapi2.bangbang93.com/mc/game/version_manifest.json"
+        );
+        assert_eq!(
+            mirrors.rewrite("https://resources.download.minecraft.net/ab/abcd"),
+            "https://bmclapi2.bangbang93.com/assets/ab/abcd"
+        );
+        assert_eq!(
+            mirrors.rewrite("https://libraries.minecraft.net/com/mojang/patchy/1.1/patchy-1.1.jar"),
+            "https://libraries.minecraft.net/com/mojang/patchy/1.1/patchy-1.1.jar"
+        );
+        assert_eq!(
+            mirrors.rewrite("https://meta.fabricmc.net/v2/versions"),
+            "https://meta.fabricmc.net/v2/versions"
+        );
+        assert_eq!(mirrors.rewrite("not a url"), "not a url");
+    }
+}
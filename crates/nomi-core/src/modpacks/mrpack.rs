@@ -0,0 +1,504 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, Write},
+    path::{Component, Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+    calculate_sha1, calculate_sha512,
+    configs::profile::{Loader, ProfileState, VersionProfile},
+    downloads::{DownloadSet, FileDownloader, ReTryDownloader},
+    instance::{loader::LoaderProfile, InstanceProfileId},
+    repository::manifest::VersionType,
+    Error,
+};
+
+/// Name of the index file in the root of the archive.
+pub const MRPACK_INDEX: &str = "modrinth.index.json";
+/// Files that are extracted into the game directory on both sides.
+pub const OVERRIDES_DIR: &str = "overrides";
+/// Files that are extracted into the game directory only on the client.
+pub const CLIENT_OVERRIDES_DIR: &str = "client-overrides";
+
+pub const FORMAT_VERSION: u32 = 1;
+pub const GAME: &str = "minecraft";
+
+/// Keys of [`MrpackIndex::dependencies`].
+pub mod dependencies {
+    pub const MINECRAFT: &str = "minecraft";
+    pub const FORGE: &str = "forge";
+    pub const NEOFORGE: &str = "neoforge";
+    pub const FABRIC: &str = "fabric-loader";
+    pub const QUILT: &str = "quilt-loader";
+}
+
+/// `modrinth.index.json` of a `.mrpack` archive.
+///
+/// See the [specification](https://support.modrinth.com/en/articles/8802351-modrinth-modpack-format-mrpack).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MrpackIndex {
+    pub format_version: u32,
+    pub game: String,
+    pub version_id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    pub files: Vec<MrpackFile>,
+    pub dependencies: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MrpackFile {
+    /// Path relative to the game directory.
+    pub path: String,
+    pub hashes: MrpackHashes,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<MrpackEnv>,
+    pub downloads: Vec<String>,
+    pub file_size: u64,
+}
+
+impl MrpackFile {
+    /// Whether the client needs this file.
+    pub fn is_client_side(&self) -> bool {
+        !matches!(
+            self.env,
+            Some(MrpackEnv {
+                client: EnvSupport::Unsupported,
+                ..
+            })
+        )
+    }
+
+    /// Downloads the file from the first of [`Self::downloads`] into `path` and checks both hashes.
+    ///
+    /// Files that are already downloaded are skipped.
+    pub fn downloader(&self, path: PathBuf) -> ReTryDownloader {
+        let url = self.downloads.first().cloned().unwrap_or_default();
+
+        FileDownloader::new(url, path)
+            .with_sha1(self.hashes.sha1.clone())
+            .with_sha512(self.hashes.sha512.clone())
+            .verify_first(true)
+            .into_retry()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MrpackHashes {
+    pub sha1: String,
+    pub sha512: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MrpackEnv {
+    pub client: EnvSupport,
+    pub server: EnvSupport,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvSupport {
+    Required,
+    Optional,
+    Unsupported,
+}
+
+/// Returns `minecraft` and the loader entries of [`MrpackIndex::dependencies`].
+pub fn index_dependencies(game_version: &str, loader_profile: Option<&LoaderProfile>) -> crate::Result<BTreeMap<String, String>> {
+    let mut dependencies = BTreeMap::from([(dependencies::MINECRAFT.to_owned(), game_version.to_owned())]);
+
+    let Some(loader_profile) = loader_profile else {
+        return Ok(dependencies);
+    };
+
+    let key = match loader_profile.loader {
+        Loader::Vanilla => return Ok(dependencies),
+        Loader::Fabric { .. } => dependencies::FABRIC,
+        Loader::Forge { .. } => dependencies::FORGE,
+        Loader::Quilt { .. } => dependencies::QUILT,
+        Loader::NeoForge { .. } => dependencies::NEOFORGE,
+    };
+
+    let version = loader_profile
+        .loader_version()
+        .ok_or_else(|| Error::InvalidManifest(format!("Cannot find the version of {}", loader_profile.loader)))?;
+
+    dependencies.insert(key.to_owned(), version);
+
+    Ok(dependencies)
+}
+
+/// Checks that the path stays inside the game directory as the specification requires.
+pub fn validate_path(path: &str) -> crate::Result<()> {
+    let is_valid =
+        !path.is_empty() && !path.contains('\\') && Path::new(path).components().all(|component| matches!(component, Component::Normal(_)));
+
+    if is_valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidManifest(format!("Invalid path `{path}` in the modpack")))
+    }
+}
+
+/// Writes a profile into a `.mrpack` archive.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use nomi_core::modpacks::mrpack::MrpackExporter;
+/// # async fn export() -> nomi_core::Result<()> {
+/// let mut exporter = MrpackExporter::new("My pack", "1.0.0", "1.20.1", None)?;
+///
+/// exporter
+///     .add_file("mods/sodium.jar", "./instances/0/.nomi/mods_stash/0/sodium.jar", vec!["https://cdn.modrinth.com/...".into()])
+///     .await?;
+/// exporter.add_override("config/sodium-options.json", "./instances/0/config/sodium-options.json")?;
+///
+/// exporter.export("./My pack.mrpack").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MrpackExporter {
+    index: MrpackIndex,
+    /// Paths inside the [`OVERRIDES_DIR`] and the files they are copied from.
+    overrides: Vec<(String, PathBuf)>,
+}
+
+impl MrpackExporter {
+    pub fn new(
+        name: impl Into<String>,
+        version_id: impl Into<String>,
+        game_version: &str,
+        loader_profile: Option<&LoaderProfile>,
+    ) -> crate::Result<Self> {
+        Ok(Self {
+            index: MrpackIndex {
+                format_version: FORMAT_VERSION,
+                game: GAME.to_owned(),
+                version_id: version_id.into(),
+                name: name.into(),
+                summary: None,
+                files: Vec::new(),
+                dependencies: index_dependencies(game_version, loader_profile)?,
+            },
+            overrides: Vec::new(),
+        })
+    }
+
+    #[must_use]
+    pub fn with_summary(mut self, summary: impl Into<String>) -> Self {
+        self.index.summary = Some(summary.into());
+        self
+    }
+
+    pub fn index(&self) -> &MrpackIndex {
+        &self.index
+    }
+
+    /// Adds a file that is downloaded from `downloads` when the pack is installed.
+    ///
+    /// The hashes and the size are calculated from the `local` file.
+    pub async fn add_file(&mut self, path: impl Into<String>, local: impl AsRef<Path>, downloads: Vec<String>) -> crate::Result<()> {
+        let path = path.into();
+        validate_path(&path)?;
+
+        let data = tokio::fs::read(local).await?;
+        let file_size = data.len() as u64;
+
+        let (sha1, sha512) = tokio::task::spawn_blocking(move || (calculate_sha1(&data), calculate_sha512(&data))).await?;
+
+        self.index.files.push(MrpackFile {
+            path,
+            hashes: MrpackHashes { sha1, sha512 },
+            env: None,
+            downloads,
+            file_size,
+        });
+
+        Ok(())
+    }
+
+    /// Adds a file that is stored in the archive and extracted into the game directory.
+    ///
+    /// `path` is relative to the game directory.
+    pub fn add_override(&mut self, path: impl Into<String>, local: impl Into<PathBuf>) -> crate::Result<()> {
+        let path = path.into();
+        validate_path(&path)?;
+
+        self.overrides.push((path, local.into()));
+
+        Ok(())
+    }
+
+    /// Writes the archive into `target`.
+    pub async fn export(self, target: impl Into<PathBuf>) -> crate::Result<()> {
+        let target = target.into();
+
+        if let Some(parent) = target.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        debug!(
+            path = %target.display(),
+            files = self.index.files.len(),
+            overrides = self.overrides.len(),
+            "Exporting a modpack"
+        );
+
+        tokio::task::spawn_blocking(move || self.write(&target)).await?
+    }
+
+    fn write(&self, target: &Path) -> crate::Result<()> {
+        let mut zip = ZipWriter::new(File::create(target)?);
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        zip.start_file(MRPACK_INDEX, options)?;
+        zip.write_all(&serde_json::to_vec_pretty(&self.index)?)?;
+
+        for (path, local) in &self.overrides {
+            zip.start_file(format!("{OVERRIDES_DIR}/{path}"), options)?;
+            io::copy(&mut File::open(local)?, &mut zip)?;
+        }
+
+        zip.finish()?;
+
+        Ok(())
+    }
+}
+
+/// Files in the game directory that are usually shared with a modpack:
+/// `options.txt` and everything in the `config` directory.
+///
+/// Returned paths are relative to `game_dir` and can be passed to [`MrpackExporter::add_override`].
+pub fn override_candidates(game_dir: &Path) -> crate::Result<Vec<String>> {
+    let mut files = Vec::new();
+
+    if game_dir.join("options.txt").is_file() {
+        files.push("options.txt".to_owned());
+    }
+
+    let mut dirs = vec![PathBuf::from("config")];
+    while let Some(dir) = dirs.pop() {
+        let entries = match std::fs::read_dir(game_dir.join(&dir)) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+            Err(error) => return Err(error.into()),
+        };
+
+        for entry in entries {
+            let entry = entry?;
+            let relative = dir.join(entry.file_name());
+
+            if entry.file_type()?.is_dir() {
+                dirs.push(relative);
+            } else if let Some(relative) = relative.to_str() {
+                files.push(relative.replace('\\', "/"));
+            }
+        }
+    }
+
+    files.sort();
+
+    Ok(files)
+}
+
+/// A `.mrpack` archive that is being imported.
+///
+/// The index is read and validated when the archive is opened,
+/// the overrides are read from the archive when they are extracted.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use nomi_core::{downloads::traits::Downloader, modpacks::mrpack::MrpackArchive};
+/// # async fn import() -> nomi_core::Result<()> {
+/// let archive = MrpackArchive::open("./My pack.mrpack").await?;
+///
+/// let game_dir = std::path::Path::new("./instances/0");
+/// let (sender, _receiver) = tokio::sync::mpsc::channel(100);
+///
+/// Box::new(archive.downloader(game_dir)).download(&sender).await;
+/// archive.extract_overrides(game_dir).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MrpackArchive {
+    path: PathBuf,
+    index: MrpackIndex,
+}
+
+impl MrpackArchive {
+    pub async fn open(path: impl Into<PathBuf>) -> crate::Result<Self> {
+        let path = path.into();
+
+        let index = {
+            let path = path.clone();
+            tokio::task::spawn_blocking(move || read_index(&path)).await??
+        };
+
+        validate_index(&index)?;
+
+        debug!(
+            path = %path.display(),
+            name = index.name,
+            files = index.files.len(),
+            "Opened a modpack"
+        );
+
+        Ok(Self { path, index })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn index(&self) -> &MrpackIndex {
+        &self.index
+    }
+
+    pub fn name(&self) -> &str {
+        &self.index.name
+    }
+
+    pub fn game_version(&self) -> crate::Result<&str> {
+        self.index
+            .dependencies
+            .get(dependencies::MINECRAFT)
+            .map(String::as_str)
+            .ok_or_else(|| Error::InvalidManifest(String::from("The modpack does not depend on a Minecraft version")))
+    }
+
+    /// The loader from [`MrpackIndex::dependencies`], [`Loader::Vanilla`] if there is none.
+    pub fn loader(&self) -> Loader {
+        let version = |key| self.index.dependencies.get(key).cloned();
+
+        if let Some(version) = version(dependencies::FABRIC) {
+            Loader::Fabric { version: Some(version) }
+        } else if let Some(version) = version(dependencies::QUILT) {
+            Loader::Quilt { version: Some(version) }
+        } else if let Some(version) = version(dependencies::FORGE) {
+            Loader::Forge { version: Some(version) }
+        } else if let Some(version) = version(dependencies::NEOFORGE) {
+            Loader::NeoForge { version: Some(version) }
+        } else {
+            Loader::Vanilla
+        }
+    }
+
+    /// A profile that installs the version and the loader of the modpack.
+    pub fn version_profile(&self, id: InstanceProfileId, name: impl Into<String>) -> crate::Result<VersionProfile> {
+        Ok(VersionProfile::builder()
+            .id(id)
+            .name(name.into())
+            .state(ProfileState::not_downloaded(
+                self.game_version()?.to_owned(),
+                VersionType::Release,
+                self.loader(),
+            ))
+            .build())
+    }
+
+    /// Files that are downloaded on the client.
+    pub fn client_files(&self) -> impl Iterator<Item = &MrpackFile> {
+        self.index.files.iter().filter(|file| file.is_client_side())
+    }
+
+    /// Downloads every [client file](Self::client_files) into `game_dir`.
+    pub fn downloader(&self, game_dir: &Path) -> DownloadSet {
+        let mut set = DownloadSet::new();
+
+        for file in self.client_files() {
+            set.add(Box::new(file.downloader(game_dir.join(&file.path))));
+        }
+
+        set
+    }
+
+    /// Extracts [`OVERRIDES_DIR`] and then [`CLIENT_OVERRIDES_DIR`] into `game_dir`,
+    /// so the client overrides replace the common ones.
+    ///
+    /// Returns the number of extracted files.
+    pub async fn extract_overrides(&self, game_dir: impl Into<PathBuf>) -> crate::Result<usize> {
+        let path = self.path.clone();
+        let game_dir = game_dir.into();
+
+        tokio::task::spawn_blocking(move || extract_overrides(&path, &game_dir)).await?
+    }
+}
+
+fn read_index(path: &Path) -> crate::Result<MrpackIndex> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    let index = archive.by_name(MRPACK_INDEX)?;
+
+    serde_json::from_reader(index).map_err(|error| Error::parse(path.join(MRPACK_INDEX), error))
+}
+
+fn validate_index(index: &MrpackIndex) -> crate::Result<()> {
+    if index.format_version != FORMAT_VERSION {
+        return Err(Error::InvalidManifest(format!(
+            "Unsupported modpack format version {}",
+            index.format_version
+        )));
+    }
+
+    if index.game != GAME {
+        return Err(Error::InvalidManifest(format!("The modpack is made for `{}`", index.game)));
+    }
+
+    for file in &index.files {
+        validate_path(&file.path)?;
+
+        if file.downloads.is_empty() {
+            return Err(Error::InvalidManifest(format!("`{}` cannot be downloaded", file.path)));
+        }
+    }
+
+    Ok(())
+}
+
+fn extract_overrides(path: &Path, game_dir: &Path) -> crate::Result<usize> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    let mut extracted = 0;
+
+    for dir in [OVERRIDES_DIR, CLIENT_OVERRIDES_DIR] {
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i)?;
+
+            if entry.is_dir() {
+                continue;
+            }
+
+            let Some(relative) = entry.enclosed_name().and_then(|name| name.strip_prefix(dir).ok().map(Path::to_path_buf)) else {
+                continue;
+            };
+
+            // `enclosed_name` allows `..` as long as it stays inside the archive.
+            if !relative.components().all(|component| matches!(component, Component::Normal(_))) {
+                warn!(name = entry.name(), "Skipping an override outside of the game directory");
+                continue;
+            }
+
+            let target = game_dir.join(relative);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+
+            io::copy(&mut entry, &mut File::create(&target)?)?;
+            extracted += 1;
+        }
+    }
+
+    debug!(extracted, game_dir = %game_dir.display(), "Extracted the modpack overrides");
+
+    Ok(extracted)
+}
@@ -0,0 +1,140 @@
+//! HTTP client shared by every request the launcher makes.
+
+use std::{
+    sync::{LazyLock, PoisonError, RwLock},
+    time::Duration,
+};
+
+use reqwest::{Client, Proxy};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    downloads::scheduler::{DownloadScheduler, SchedulerLimits},
+    NOMI_NAME, NOMI_VERSION,
+};
+
+static HTTP_CLIENT: LazyLock<RwLock<Client>> = LazyLock::new(|| RwLock::new(NetworkConfig::default().build_client().unwrap_or_default()));
+
+/// Returns the shared client configured with the last [applied](NetworkConfig::apply) [`NetworkConfig`].
+///
+/// Cloning the client is cheap and the clones share the connection pool.
+pub fn http_client() -> Client {
+    HTTP_CLIENT.read().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Sent with every request. Modrinth requires it to identify the application.
+    pub user_agent: String,
+    /// `http://`, `https://`, `socks5://` or `socks5h://` URL. Credentials can be specified in the URL.
+    pub proxy: Option<String>,
+    pub connect_timeout_secs: u64,
+    /// Maximum time to wait for the next chunk of a response.
+    pub read_timeout_secs: u64,
+    /// Maximum number of files downloaded at the same time.
+    pub max_concurrent_downloads: usize,
+    /// Maximum number of files downloaded from the same host at the same time.
+    ///
+    /// Only downloads going through the [`DownloadScheduler`] are limited,
+    /// other requests (queries, metadata) are not.
+    pub max_connections_per_host: usize,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        let limits = SchedulerLimits::default();
+
+        Self {
+            user_agent: format!("Umatriz/{}/{NOMI_VERSION} (github.com/Umatriz/nomi)", NOMI_NAME.to_lowercase()),
+            proxy: None,
+            connect_timeout_secs: 10,
+            read_timeout_secs: 30,
+            max_concurrent_downloads: limits.max_concurrent_downloads,
+            max_connections_per_host: limits.max_connections_per_host,
+        }
+    }
+}
+
+impl NetworkConfig {
+    pub fn build_client(&self) -> anyhow::Result<Client> {
+        let mut builder = Client::builder()
+            .user_agent(&self.user_agent)
+            .connect_timeout(Duration::from_secs(self.connect_timeout_secs))
+            .read_timeout(Duration::from_secs(self.read_timeout_secs));
+
+        if let Some(proxy) = self.proxy.as_deref().filter(|proxy| !proxy.trim().is_empty()) {
+            builder = builder.proxy(Proxy::all(proxy)?);
+        }
+
+        Ok(builder.build()?)
+    }
+
+    pub fn scheduler_limits(&self) -> SchedulerLimits {
+        SchedulerLimits {
+            max_concurrent_downloads: self.max_concurrent_downloads,
+            max_connections_per_host: self.max_connections_per_host,
+        }
+    }
+
+    /// Replaces the [shared client](http_client) with the one built from this config
+    /// and updates the limits of the [global scheduler](DownloadScheduler::global).
+    pub fn apply(&self) -> anyhow::Result<()> {
+        let client = self.build_client()?;
+        *HTTP_CLIENT.write().unwrap_or_else(PoisonError::into_inner) = client;
+
+        DownloadScheduler::global().set_limits(self.scheduler_limits());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mock_server::{MockResponse, MockServer};
+
+    use super::*;
+
+    async fn serve() -> MockServer {
+        MockServer::start(|_| MockResponse::new(200, Vec::new())).await
+    }
+
+    #[tokio::test]
+    async fn user_agent_test() {
+        let server = serve().await;
+
+        let config = NetworkConfig::default();
+        config.build_client().unwrap().get(server.url()).send().await.unwrap();
+
+        assert_eq!(server.requests()[0].header("User-Agent"), Some(config.user_agent.as_str()));
+    }
+
+    #[tokio::test]
+    async fn proxy_test() {
+        let server = serve().await;
+
+        let config = NetworkConfig {
+            proxy: Some(server.url()),
+            ..Default::default()
+        };
+        config
+            .build_client()
+            .unwrap()
+            .get("http://nomi.invalid/manifest.json")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(server.requests()[0].path, "http://nomi.invalid/manifest.json");
+    }
+
+    #[test]
+    fn invalid_proxy_test() {
+        let config = NetworkConfig {
+            proxy: Some("not a proxy".into()),
+            ..Default::default()
+        };
+
+        assert!(config.build_client().is_err());
+    }
+}
@@ -1,10 +1,13 @@
-use reqwest::get;
 use serde::{Deserialize, Serialize};
 
+use crate::{mirrors::mirrored, network::http_client};
+
 pub type FabricVersions = Vec<Version>;
 
-pub async fn get_fabric_versions(game_version: String) -> anyhow::Result<FabricVersions> {
-    get(format!("https://meta.fabricmc.net/v2/versions/loader/{game_version}"))
+pub async fn get_fabric_versions(game_version: String) -> crate::Result<FabricVersions> {
+    http_client()
+        .get(mirrored(&format!("https://meta.fabricmc.net/v2/versions/loader/{game_version}")))
+        .send()
         .await?
         .json()
         .await
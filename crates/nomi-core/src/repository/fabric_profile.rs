@@ -17,7 +17,9 @@ pub struct FabricProfile {
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Arguments {
+    #[serde(default)]
     pub game: Vec<String>,
+    #[serde(default)]
     pub jvm: Vec<String>,
 }
 
@@ -0,0 +1,301 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::process::Command;
+use tracing::debug;
+
+use crate::DOT_NOMI_JAVA_DIR;
+
+use super::{java_runner::JavaRunner, manifest::JavaVersion};
+
+#[cfg(windows)]
+pub(crate) const JAVA_EXECUTABLE: &str = "java.exe";
+
+#[cfg(not(windows))]
+pub(crate) const JAVA_EXECUTABLE: &str = "java";
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Error, Debug)]
+pub enum JavaProbeError {
+    #[error("`{path}` did not report the `{property}` property")]
+    MissingProperty { path: PathBuf, property: &'static str },
+
+    #[error("Unable to parse Java version `{0}`")]
+    InvalidVersion(String),
+
+    #[error("`{0}` did not respond in time")]
+    Timeout(PathBuf),
+}
+
+/// Java runtime found on the system.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct JavaInstallation {
+    /// Path to the `java` executable.
+    pub path: PathBuf,
+    pub version: String,
+    pub major_version: i8,
+    pub vendor: Option<String>,
+    pub arch: String,
+}
+
+impl JavaInstallation {
+    /// Runs `java -XshowSettings:properties -version` and reads the runtime properties from its output.
+    pub async fn probe(path: impl Into<PathBuf>) -> crate::Result<Self> {
+        let path = probe_executable(path.into());
+
+        let output = Command::new(&path)
+            .arg("-XshowSettings:properties")
+            .arg("-version")
+            .kill_on_drop(true)
+            .output();
+
+        let output = tokio::time::timeout(PROBE_TIMEOUT, output)
+            .await
+            .map_err(|_| JavaProbeError::Timeout(path.clone()))??;
+
+        // Properties are printed to stderr, but some builds use stdout instead.
+        let text = format!("{}\n{}", String::from_utf8_lossy(&output.stderr), String::from_utf8_lossy(&output.stdout));
+
+        Self::from_properties(path, &text).map_err(Into::into)
+    }
+
+    /// Probes the runtime that `runner` points to.
+    pub async fn probe_runner(runner: &JavaRunner) -> crate::Result<Self> {
+        match runner {
+            JavaRunner::Command(command) => Self::probe(command).await,
+            JavaRunner::Path(path) => Self::probe(path).await,
+        }
+    }
+
+    /// Parses the output of `java -XshowSettings:properties -version`.
+    pub fn from_properties(path: PathBuf, output: &str) -> Result<Self, JavaProbeError> {
+        let property = |name: &'static str| {
+            output.lines().find_map(|line| {
+                let (key, value) = line.split_once('=')?;
+                (key.trim() == name).then(|| value.trim().to_owned())
+            })
+        };
+
+        let missing = |property| JavaProbeError::MissingProperty {
+            path: path.clone(),
+            property,
+        };
+
+        let version = property("java.version").ok_or_else(|| missing("java.version"))?;
+        let arch = property("os.arch").ok_or_else(|| missing("os.arch"))?;
+        let major_version = java_major_version(&version).ok_or_else(|| JavaProbeError::InvalidVersion(version.clone()))?;
+
+        Ok(Self {
+            path,
+            version,
+            major_version,
+            vendor: property("java.vendor"),
+            arch,
+        })
+    }
+
+    pub fn runner(&self) -> JavaRunner {
+        JavaRunner::path(self.path.clone())
+    }
+
+    /// Whether this runtime can run a version that requires `java_version`.
+    pub fn supports(&self, java_version: &JavaVersion) -> bool {
+        self.major_version >= java_version.major_version
+    }
+}
+
+/// Major version of a Java version string.
+///
+/// Handles both the legacy (`1.8.0_51`) and the modern (`17.0.8`) version schemes.
+pub fn java_major_version(version: &str) -> Option<i8> {
+    let mut parts = version.split(['.', '_', '+', '-']);
+    match parts.next()? {
+        "1" => parts.next()?.parse().ok(),
+        major => major.parse().ok(),
+    }
+}
+
+/// `javaw` does not print anything, so the `java` executable next to it is used for probing.
+fn probe_executable(path: PathBuf) -> PathBuf {
+    if path.file_stem().is_some_and(|stem| stem.eq_ignore_ascii_case("javaw")) {
+        let java = path.with_file_name(JAVA_EXECUTABLE);
+        if java.exists() {
+            return java;
+        }
+    }
+
+    path
+}
+
+/// Directories that may contain the `java` executable of a runtime located in `home`.
+fn executables_in_home(home: &Path) -> impl Iterator<Item = PathBuf> {
+    [
+        home.join("bin"),
+        home.join("Contents/Home/bin"),
+        home.join("jre.bundle/Contents/Home/bin"),
+    ]
+    .into_iter()
+    .map(|bin| bin.join(JAVA_EXECUTABLE))
+}
+
+fn subdirectories(path: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn user_home() -> Option<PathBuf> {
+    std::env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" }).map(PathBuf::from)
+}
+
+/// Places where Java runtimes are searched for.
+#[derive(Debug, Clone, Default)]
+pub struct JavaSearchPaths {
+    /// Directories that contain the `java` executable directly (e.g. entries of `PATH`).
+    pub bin_dirs: Vec<PathBuf>,
+    /// Java home directories.
+    pub homes: Vec<PathBuf>,
+    /// Directories containing Java home directories (e.g. `/usr/lib/jvm`).
+    pub roots: Vec<PathBuf>,
+}
+
+impl JavaSearchPaths {
+    /// `JAVA_HOME`, `PATH`, `/usr/lib/jvm`, SDKMAN candidates and runtimes installed by Nomi.
+    pub fn from_environment() -> Self {
+        let homes = std::env::var_os("JAVA_HOME").map(PathBuf::from).into_iter().collect();
+
+        let bin_dirs = std::env::var_os("PATH")
+            .map(|path| std::env::split_paths(&path).collect())
+            .unwrap_or_default();
+
+        let sdkman = std::env::var_os("SDKMAN_DIR")
+            .map(PathBuf::from)
+            .or_else(|| user_home().map(|home| home.join(".sdkman")))
+            .map(|sdkman| sdkman.join("candidates").join("java"));
+
+        let roots = [
+            Some(PathBuf::from("/usr/lib/jvm")),
+            sdkman,
+            std::env::current_dir().ok().map(|dir| dir.join(DOT_NOMI_JAVA_DIR)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        Self { bin_dirs, homes, roots }
+    }
+
+    /// Existing `java` executables, without duplicates that point to the same file.
+    pub fn executables(&self) -> Vec<PathBuf> {
+        let from_bin_dirs = self.bin_dirs.iter().map(|dir| dir.join(JAVA_EXECUTABLE));
+        let from_homes = self
+            .homes
+            .iter()
+            .cloned()
+            .chain(self.roots.iter().flat_map(|root| subdirectories(root)))
+            .flat_map(|home| executables_in_home(&home).collect::<Vec<_>>());
+
+        let mut seen = HashSet::new();
+
+        from_bin_dirs
+            .chain(from_homes)
+            .filter(|path| path.is_file())
+            .filter(|path| seen.insert(std::fs::canonicalize(path).unwrap_or_else(|_| path.clone())))
+            .collect()
+    }
+
+    /// Probes every executable found in these paths.
+    ///
+    /// Executables that cannot be probed are skipped.
+    pub async fn discover(&self) -> Vec<JavaInstallation> {
+        let probes = self.executables().into_iter().map(|path| async move {
+            JavaInstallation::probe(&path)
+                .await
+                .inspect_err(|e| debug!(error = ?e, path = %path.display(), "Unable to probe Java"))
+                .ok()
+        });
+
+        join_all(probes).await.into_iter().flatten().collect()
+    }
+}
+
+/// Discovers Java runtimes using [`JavaSearchPaths::from_environment`].
+pub async fn discover_java_installations() -> Vec<JavaInstallation> {
+    JavaSearchPaths::from_environment().discover().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROPERTIES: &str = r#"Property settings:
+    file.encoding = UTF-8
+    java.home = /usr/lib/jvm/java-17-openjdk-amd64
+    java.library.path = /usr/java/packages/lib
+        /usr/lib/x86_64-linux-gnu/jni
+        /lib/x86_64-linux-gnu
+    java.runtime.version = 17.0.8+7-Debian-1deb12u1
+    java.vendor = Debian
+    java.version = 17.0.8
+    java.version.date = 2023-07-18
+    os.arch = amd64
+    os.name = Linux
+
+openjdk version "17.0.8" 2023-07-18
+OpenJDK Runtime Environment (build 17.0.8+7-Debian-1deb12u1)
+OpenJDK 64-Bit Server VM (build 17.0.8+7-Debian-1deb12u1, mixed mode, sharing)
+"#;
+
+    #[test]
+    fn major_version_test() {
+        assert_eq!(java_major_version("1.8.0_51"), Some(8));
+        assert_eq!(java_major_version("17.0.8"), Some(17));
+        assert_eq!(java_major_version("21"), Some(21));
+        assert_eq!(java_major_version("22-ea"), Some(22));
+        assert_eq!(java_major_version("16.0.1.9.1"), Some(16));
+        assert_eq!(java_major_version("unknown"), None);
+    }
+
+    #[test]
+    fn parse_properties_test() {
+        let installation = JavaInstallation::from_properties(PathBuf::from("/usr/bin/java"), PROPERTIES).unwrap();
+
+        assert_eq!(installation.version, "17.0.8");
+        assert_eq!(installation.major_version, 17);
+        assert_eq!(installation.vendor.as_deref(), Some("Debian"));
+        assert_eq!(installation.arch, "amd64");
+
+        let java_version = |major_version| JavaVersion {
+            component: String::new(),
+            major_version,
+        };
+        assert!(installation.supports(&java_version(8)));
+        assert!(installation.supports(&java_version(17)));
+        assert!(!installation.supports(&java_version(21)));
+    }
+
+    #[test]
+    fn missing_properties_test() {
+        let error = JavaInstallation::from_properties(PathBuf::from("java"), "Error: Could not create the Java Virtual Machine.").unwrap_err();
+        assert!(matches!(
+            error,
+            JavaProbeError::MissingProperty {
+                property: "java.version",
+                ..
+            }
+        ));
+    }
+}
@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::{utils::path_to_string, DOT_NOMI_JAVA_EXECUTABLE};
 
+use super::java_installation::JAVA_EXECUTABLE;
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Hash)]
 #[must_use]
 pub enum JavaRunner {
@@ -13,7 +15,9 @@ pub enum JavaRunner {
 
 impl JavaRunner {
     pub fn from_environment() -> Self {
-        if std::env::var("PATH").is_ok_and(|path| path.contains("java")) {
+        let in_path = std::env::var_os("PATH").is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(JAVA_EXECUTABLE).is_file()));
+
+        if in_path {
             let command = if cfg!(windows) { "javaw" } else { "java" };
             Self::command(command)
         } else {
@@ -0,0 +1,172 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cache::{MetadataCache, IMMUTABLE_TTL, JAVA_RUNTIMES_TTL};
+
+use super::{java_installation::java_major_version, manifest::JavaVersion};
+
+pub const JAVA_RUNTIMES_URL: &str = "https://launchermeta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json";
+
+pub async fn get_java_runtimes(url: &str) -> crate::Result<JavaRuntimes> {
+    MetadataCache::global().get_json(url, JAVA_RUNTIMES_TTL).await
+}
+
+pub async fn get_java_runtime_manifest(url: &str) -> crate::Result<JavaRuntimeManifest> {
+    MetadataCache::global().get_json(url, IMMUTABLE_TTL).await
+}
+
+/// Name of the current platform as used by Mojang's runtime index.
+pub fn runtime_platform() -> Option<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("linux", "x86_64") => Some("linux"),
+        ("linux", "x86") => Some("linux-i386"),
+        ("macos", "x86_64") => Some("mac-os"),
+        ("macos", "aarch64") => Some("mac-os-arm64"),
+        ("windows", "x86_64") => Some("windows-x64"),
+        ("windows", "x86") => Some("windows-x86"),
+        ("windows", "aarch64") => Some("windows-arm64"),
+        _ => None,
+    }
+}
+
+/// Index of all Java runtimes provided by Mojang.
+///
+/// Maps a platform to its components (`java-runtime-gamma`, `jre-legacy`, ...).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(transparent)]
+pub struct JavaRuntimes {
+    pub platforms: HashMap<String, BTreeMap<String, Vec<JavaRuntime>>>,
+}
+
+impl JavaRuntimes {
+    /// Finds a runtime that satisfies `java_version` on the given platform.
+    ///
+    /// The component requested by the version is preferred,
+    /// otherwise any component with the same major version is used.
+    pub fn find(&self, platform: &str, java_version: &JavaVersion) -> Option<(&str, &JavaRuntime)> {
+        let components = self.platforms.get(platform)?;
+
+        components
+            .get_key_value(&java_version.component)
+            .and_then(|(component, runtimes)| runtimes.first().map(|runtime| (component.as_str(), runtime)))
+            .or_else(|| {
+                components.iter().find_map(|(component, runtimes)| {
+                    runtimes
+                        .first()
+                        .filter(|runtime| runtime.version.major() == Some(java_version.major_version))
+                        .map(|runtime| (component.as_str(), runtime))
+                })
+            })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JavaRuntime {
+    pub availability: Availability,
+    pub manifest: RuntimeDownload,
+    pub version: RuntimeVersion,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Availability {
+    pub group: u32,
+    pub progress: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeDownload {
+    pub sha1: String,
+    pub size: u64,
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeVersion {
+    pub name: String,
+    pub released: String,
+}
+
+impl RuntimeVersion {
+    /// Major Java version of this runtime.
+    pub fn major(&self) -> Option<i8> {
+        java_major_version(&self.name)
+    }
+}
+
+/// List of files that make up a single runtime component.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JavaRuntimeManifest {
+    pub files: BTreeMap<String, JavaRuntimeFile>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum JavaRuntimeFile {
+    File {
+        #[serde(default)]
+        executable: bool,
+        downloads: JavaRuntimeFileDownloads,
+    },
+    Directory,
+    Link {
+        target: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JavaRuntimeFileDownloads {
+    pub raw: RuntimeDownload,
+    pub lzma: Option<RuntimeDownload>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn major_version_test() {
+        let version = |name: &str| RuntimeVersion {
+            name: name.to_owned(),
+            released: String::new(),
+        };
+
+        assert_eq!(version("1.8.0_51").major(), Some(8));
+        assert_eq!(version("17.0.8").major(), Some(17));
+        assert_eq!(version("21.0.3").major(), Some(21));
+        assert_eq!(version("16.0.1.9.1").major(), Some(16));
+        assert_eq!(version("unknown").major(), None);
+    }
+
+    #[test]
+    fn find_runtime_test() {
+        let runtimes: JavaRuntimes = serde_json::from_str(include_str!("../../tests/fixtures/java_runtimes.json")).unwrap();
+
+        let exact = JavaVersion {
+            component: "java-runtime-gamma".to_owned(),
+            major_version: 17,
+        };
+        let (component, runtime) = runtimes.find("linux", &exact).unwrap();
+        assert_eq!(component, "java-runtime-gamma");
+        assert_eq!(runtime.version.name, "17.0.8");
+
+        let by_major = JavaVersion {
+            component: "java-runtime-unknown".to_owned(),
+            major_version: 21,
+        };
+        let (component, _) = runtimes.find("linux", &by_major).unwrap();
+        assert_eq!(component, "java-runtime-delta");
+
+        assert!(runtimes.find("mac-os", &exact).is_none());
+        assert!(runtimes.find("linux-i386", &exact).is_none());
+    }
+
+    #[test]
+    fn runtime_manifest_test() {
+        let manifest: JavaRuntimeManifest = serde_json::from_str(include_str!("../../tests/fixtures/java_runtime_manifest.json")).unwrap();
+
+        assert!(matches!(manifest.files["bin"], JavaRuntimeFile::Directory));
+        assert!(matches!(manifest.files["bin/java"], JavaRuntimeFile::File { executable: true, .. }));
+        assert!(matches!(&manifest.files["lib/libjli.so"], JavaRuntimeFile::Link { target } if target == "jli/libjli.so"));
+    }
+}
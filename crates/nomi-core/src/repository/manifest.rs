@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -12,6 +14,7 @@ pub struct Manifest {
     pub id: String,
     pub java_version: JavaVersion,
     pub libraries: Vec<Library>,
+    pub logging: Option<Logging>,
     pub main_class: String,
     pub minimum_launcher_version: i8,
     pub release_time: String,
@@ -84,6 +87,9 @@ pub enum Action {
 pub struct Features {
     pub is_demo_user: Option<bool>,
     pub has_custom_resolution: Option<bool>,
+    pub has_quick_plays_support: Option<bool>,
+    pub is_quick_play_singleplayer: Option<bool>,
+    pub is_quick_play_multiplayer: Option<bool>,
     pub is_quick_play_realms: Option<bool>,
 }
 
@@ -121,7 +127,36 @@ pub struct DownloadFile {
     pub url: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Logging {
+    pub client: Option<LoggingConfig>,
+}
+
+/// log4j configuration provided by Mojang.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LoggingConfig {
+    /// JVM argument with the `${path}` placeholder for the configuration file.
+    pub argument: String,
+    pub file: LoggingFile,
+    #[serde(rename = "type")]
+    pub config_type: String,
+}
+
+impl LoggingConfig {
+    pub fn jvm_argument(&self, path: &Path) -> String {
+        self.argument.replace("${path}", &path.to_string_lossy())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LoggingFile {
+    pub id: String,
+    pub sha1: String,
+    pub size: i32,
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct JavaVersion {
     pub component: String,
@@ -159,6 +194,33 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn logging_test() {
+        let logging: Logging = serde_json::from_str(
+            r#"{
+                "client": {
+                    "argument": "-Dlog4j.configurationFile=${path}",
+                    "file": {
+                        "id": "client-1.12.xml",
+                        "sha1": "bd65e7d2e3c237be76cfbef4c2405033d7f91521",
+                        "size": 888,
+                        "url": "https://piston-data.mojang.com/v1/objects/bd65e7d2e3c237be76cfbef4c2405033d7f91521/client-1.12.xml"
+                    },
+                    "type": "log4j2-xml"
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let client = logging.client.unwrap();
+        assert_eq!(client.file.id, "client-1.12.xml");
+        assert_eq!(client.config_type, "log4j2-xml");
+        assert_eq!(
+            client.jvm_argument(Path::new("assets/log_configs/client-1.12.xml")),
+            "-Dlog4j.configurationFile=assets/log_configs/client-1.12.xml"
+        );
+    }
+
     #[tokio::test]
     async fn old_version_test() {
         let manifest: Manifest = get("https://piston-meta.mojang.com/v1/packages/d546f1707a3f2b7d034eece5ea2e311eda875787/1.8.9.json")
@@ -1,7 +1,9 @@
 pub mod launcher_manifest;
 pub mod manifest;
 
+pub mod java_installation;
 pub mod java_runner;
+pub mod java_runtime;
 pub mod simple_args;
 pub mod simple_lib;
 
@@ -9,3 +11,7 @@ pub mod username;
 
 pub mod fabric_meta;
 pub mod fabric_profile;
+
+pub mod quilt_meta;
+
+pub mod neoforge_meta;
@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{mirrors::mirrored, network::http_client};
+
+pub const NEOFORGE_MAVEN_URL: &str = "https://maven.neoforged.net/releases";
+
+/// `NeoForge` for 1.20.1 was published as `net.neoforged:forge`
+/// before the project moved to its own versioning.
+const LEGACY_ARTIFACT: &str = "forge";
+const ARTIFACT: &str = "neoforge";
+
+/// Versions of both the legacy and the current artifact.
+pub async fn get_neoforge_versions() -> crate::Result<NeoForgeVersions> {
+    let legacy = get_artifact_versions(LEGACY_ARTIFACT).await?;
+    let current = get_artifact_versions(ARTIFACT).await?;
+
+    Ok(legacy.chain(current))
+}
+
+async fn get_artifact_versions(artifact: &str) -> crate::Result<NeoForgeVersions> {
+    http_client()
+        .get(mirrored(&format!(
+            "https://maven.neoforged.net/api/maven/versions/releases/net/neoforged/{artifact}"
+        )))
+        .send()
+        .await?
+        .json()
+        .await
+        .map_err(Into::into)
+}
+
+pub fn installer_url(neoforge_version: &str) -> String {
+    let artifact = if is_legacy(neoforge_version) { LEGACY_ARTIFACT } else { ARTIFACT };
+
+    format!("{NEOFORGE_MAVEN_URL}/net/neoforged/{artifact}/{neoforge_version}/{artifact}-{neoforge_version}-installer.jar")
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NeoForgeVersions {
+    pub is_snapshot: bool,
+    pub versions: Vec<String>,
+}
+
+impl NeoForgeVersions {
+    /// Appends `newer` versions after these ones.
+    #[must_use]
+    pub fn chain(mut self, newer: NeoForgeVersions) -> Self {
+        self.versions.extend(newer.versions);
+        self
+    }
+
+    /// Returns all `NeoForge` versions built for the provided game version
+    /// ordered from the oldest to the newest.
+    pub fn for_game_version(&self, game_version: &str) -> impl Iterator<Item = &str> + '_ {
+        let game_version = game_version.to_owned();
+
+        self.versions
+            .iter()
+            .map(String::as_str)
+            .filter(move |version| game_version_of(version).is_some_and(|v| v == game_version))
+    }
+
+    /// The newest version for the provided game version.
+    /// Stable versions are preferred over `-beta` ones.
+    pub fn latest_for_game_version(&self, game_version: &str) -> Option<&str> {
+        let versions = self.for_game_version(game_version).collect::<Vec<_>>();
+
+        versions.iter().rev().find(|v| is_stable(v)).or_else(|| versions.last()).copied()
+    }
+}
+
+pub fn is_stable(neoforge_version: &str) -> bool {
+    !neoforge_version.ends_with("-beta")
+}
+
+/// Legacy versions are prefixed with the game version, e.g. `1.20.1-47.1.106`.
+fn is_legacy(neoforge_version: &str) -> bool {
+    neoforge_version.starts_with("1.")
+}
+
+/// `NeoForge` versions follow the `{minor}.{patch}.{build}` scheme
+/// where `minor` and `patch` are taken from the game version.
+///
+/// E.g. `20.4.237` is built for `1.20.4` and `21.0.1-beta` is built for `1.21`.
+/// Legacy `1.20.1-47.1.106` versions carry the game version in front of the build.
+pub fn game_version_of(neoforge_version: &str) -> Option<String> {
+    if is_legacy(neoforge_version) {
+        return neoforge_version.split_once('-').map(|(game_version, _)| game_version.to_owned());
+    }
+
+    let mut parts = neoforge_version.split('.');
+
+    let minor = parts.next()?.parse::<u32>().ok()?;
+    let patch = parts.next()?.parse::<u32>().ok()?;
+
+    Some(match patch {
+        0 => format!("1.{minor}"),
+        patch => format!("1.{minor}.{patch}"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_version_test() {
+        assert_eq!(game_version_of("20.4.237").as_deref(), Some("1.20.4"));
+        assert_eq!(game_version_of("21.0.1-beta").as_deref(), Some("1.21"));
+        assert_eq!(game_version_of("20.2.3-beta").as_deref(), Some("1.20.2"));
+        assert_eq!(game_version_of("1.20.1-47.1.106").as_deref(), Some("1.20.1"));
+        assert_eq!(game_version_of("broken"), None);
+    }
+
+    #[test]
+    fn deserialize_versions_test() {
+        let versions: NeoForgeVersions = serde_json::from_str(include_str!("../../tests/fixtures/neoforge_versions.json")).unwrap();
+
+        assert!(!versions.is_snapshot);
+        assert_eq!(
+            versions.for_game_version("1.20.4").collect::<Vec<_>>(),
+            ["20.4.80-beta", "20.4.236", "20.4.237"]
+        );
+        assert_eq!(versions.latest_for_game_version("1.20.4"), Some("20.4.237"));
+        assert_eq!(versions.latest_for_game_version("1.21"), Some("21.0.2-beta"));
+        assert_eq!(versions.latest_for_game_version("1.19.2"), None);
+    }
+
+    #[test]
+    fn legacy_versions_test() {
+        let legacy: NeoForgeVersions = serde_json::from_str(include_str!("../../tests/fixtures/neoforge_legacy_versions.json")).unwrap();
+        let current: NeoForgeVersions = serde_json::from_str(include_str!("../../tests/fixtures/neoforge_versions.json")).unwrap();
+        let versions = legacy.chain(current);
+
+        assert_eq!(versions.latest_for_game_version("1.20.1"), Some("1.20.1-47.1.106"));
+        assert_eq!(versions.latest_for_game_version("1.20.4"), Some("20.4.237"));
+
+        assert_eq!(
+            installer_url("1.20.1-47.1.106"),
+            "https://maven.neoforged.net/releases/net/neoforged/forge/1.20.1-47.1.106/forge-1.20.1-47.1.106-installer.jar"
+        );
+        assert_eq!(
+            installer_url("20.4.237"),
+            "https://maven.neoforged.net/releases/net/neoforged/neoforge/20.4.237/neoforge-20.4.237-installer.jar"
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{mirrors::mirrored, network::http_client};
+
+pub const QUILT_META_URL: &str = "https://meta.quiltmc.org/v3";
+
+pub type QuiltVersions = Vec<Version>;
+
+pub async fn get_quilt_versions(game_version: String) -> crate::Result<QuiltVersions> {
+    http_client()
+        .get(mirrored(&format!("{QUILT_META_URL}/versions/loader/{game_version}")))
+        .send()
+        .await?
+        .json()
+        .await
+        .map_err(Into::into)
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Version {
+    pub loader: VersionLoader,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VersionLoader {
+    pub separator: String,
+    pub build: i32,
+    pub maven: String,
+    pub version: String,
+}
+
+impl VersionLoader {
+    /// Quilt does not mark its versions as stable, so we treat
+    /// every version without a pre-release suffix (`-beta.N`, `-pre.N`) as stable.
+    pub fn is_stable(&self) -> bool {
+        !self.version.contains('-')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::repository::fabric_profile::FabricProfile;
+
+    use super::*;
+
+    #[test]
+    fn deserialize_versions_test() {
+        let versions: QuiltVersions = serde_json::from_str(include_str!("../../tests/fixtures/quilt_versions.json")).unwrap();
+
+        assert_eq!(versions.len(), 3);
+        assert_eq!(versions[0].loader.version, "0.26.4-beta.5");
+        assert_eq!(versions[0].loader.maven, "org.quiltmc:quilt-loader:0.26.4-beta.5");
+        assert!(!versions[0].loader.is_stable());
+        assert!(versions[1].loader.is_stable());
+    }
+
+    #[test]
+    fn deserialize_profile_test() {
+        let profile: FabricProfile = serde_json::from_str(include_str!("../../tests/fixtures/quilt_profile.json")).unwrap();
+
+        assert_eq!(profile.main_class, "org.quiltmc.loader.impl.launch.knot.KnotClient");
+        assert_eq!(profile.inherits_from, "1.20.1");
+        assert!(profile.arguments.jvm.is_empty());
+        assert!(profile
+            .libraries
+            .iter()
+            .any(|lib| lib.name == "org.quiltmc:quilt-loader:0.26.3" && lib.url == "https://maven.quiltmc.org/repository/release/"));
+    }
+}
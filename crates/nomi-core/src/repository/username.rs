@@ -4,7 +4,7 @@ use regex::Regex;
 use serde::{de::Visitor, Deserialize, Serialize};
 use thiserror::Error;
 
-#[derive(Serialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Debug, PartialEq, Eq, Hash, Clone)]
 pub struct Username(String);
 
 impl Default for Username {
@@ -63,7 +63,7 @@ pub enum ValidationError {
 }
 
 impl Username {
-    pub fn new(s: impl Into<String>) -> anyhow::Result<Self> {
+    pub fn new(s: impl Into<String>) -> crate::Result<Self> {
         static REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[a-zA-Z0-9_]{3,16}$").unwrap());
 
         let s = s.into();
@@ -1,10 +1,12 @@
-use anyhow::Context;
-use reqwest::{get, Client};
 use tokio::sync::OnceCell;
 
-use crate::repository::{
-    launcher_manifest::{LauncherManifest, Version},
-    manifest::Manifest,
+use crate::{
+    cache::{MetadataCache, IMMUTABLE_TTL, LAUNCHER_MANIFEST_TTL},
+    repository::{
+        launcher_manifest::{LauncherManifest, Version},
+        manifest::Manifest,
+    },
+    Error,
 };
 
 // TODO: Write helper functions for quick access
@@ -12,12 +14,12 @@ use crate::repository::{
 pub const LAUNCHER_MANIFEST: &str = "https://launchermeta.mojang.com/mc/game/version_manifest.json";
 pub static LAUNCHER_MANIFEST_STATE: OnceCell<LauncherManifest> = OnceCell::const_new();
 
-pub async fn get_launcher_manifest_owned() -> anyhow::Result<LauncherManifest> {
+pub async fn get_launcher_manifest_owned() -> crate::Result<LauncherManifest> {
     tracing::debug!("Calling Launcher Manifest");
-    Ok(Client::new().get(LAUNCHER_MANIFEST).send().await?.json::<LauncherManifest>().await?)
+    MetadataCache::global().get_json(LAUNCHER_MANIFEST, LAUNCHER_MANIFEST_TTL).await
 }
 
-pub async fn get_launcher_manifest() -> anyhow::Result<&'static LauncherManifest> {
+pub async fn get_launcher_manifest() -> crate::Result<&'static LauncherManifest> {
     LAUNCHER_MANIFEST_STATE.get_or_try_init(get_launcher_manifest_owned).await
 }
 
@@ -27,15 +29,18 @@ impl LauncherManifest {
         self.versions.iter().find(|v| v.id == version)
     }
 
-    pub async fn get_version_manifest(&self, version: impl Into<String>) -> anyhow::Result<Manifest> {
-        let url = &self.find_version(version).context("cannot find such version")?.url;
+    pub async fn get_version_manifest(&self, version: impl Into<String>) -> crate::Result<Manifest> {
+        let version = version.into();
+        let url = &self.find_version(&version).ok_or(Error::NoSuchVersion(version))?.url;
 
-        get(url).await?.json().await.map_err(Into::into)
+        MetadataCache::global().get_json(url, IMMUTABLE_TTL).await
     }
 
-    pub async fn get_version_manifest_content(&self, version: impl Into<String>) -> anyhow::Result<String> {
-        let url = &self.find_version(version).context("cannot find such version")?.url;
+    pub async fn get_version_manifest_content(&self, version: impl Into<String>) -> crate::Result<String> {
+        let version = version.into();
+        let url = &self.find_version(&version).ok_or(Error::NoSuchVersion(version))?.url;
 
-        Ok(Client::new().get(url).send().await?.text().await?)
+        let body = MetadataCache::global().get(url, IMMUTABLE_TTL).await?;
+        String::from_utf8(body).map_err(|error| Error::parse(url, error))
     }
 }
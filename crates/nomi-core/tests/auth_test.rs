@@ -0,0 +1,198 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use mock_server::{MockResponse, MockServer, RecordedRequest};
+use nomi_core::{
+    auth::{AuthEndpoints, AuthError, Authenticator, MicrosoftAccount},
+    configs::account::{Account, AccountStore},
+};
+
+const DEVICE_CODE: &str = r#"{
+    "user_code": "ABCD1234",
+    "device_code": "device-code",
+    "verification_uri": "https://www.microsoft.com/link",
+    "expires_in": 900,
+    "interval": 0,
+    "message": "To sign in, use a web browser to open the page https://www.microsoft.com/link and enter the code ABCD1234 to authenticate."
+}"#;
+
+const XBOX_TOKEN: &str = r#"{
+    "IssueInstant": "2024-07-22T13:53:56.1850000Z",
+    "NotAfter": "2024-08-05T13:53:56.1850000Z",
+    "Token": "xbl-token",
+    "DisplayClaims": { "xui": [{ "uhs": "user-hash" }] }
+}"#;
+
+const XSTS_TOKEN: &str = r#"{
+    "IssueInstant": "2024-07-22T13:53:56.1850000Z",
+    "NotAfter": "2024-07-23T05:53:56.1850000Z",
+    "Token": "xsts-token",
+    "DisplayClaims": { "xui": [{ "uhs": "user-hash" }] }
+}"#;
+
+const PROFILE: &str = r#"{
+    "id": "069a79f444e94726a5befca90e38aaf5",
+    "name": "Notch",
+    "skins": [],
+    "capes": []
+}"#;
+
+fn microsoft_token(access_token: &str, refresh_token: &str) -> String {
+    format!(
+        r#"{{"token_type": "Bearer", "scope": "XboxLive.signin offline_access", "expires_in": 3600, "access_token": "{access_token}", "refresh_token": "{refresh_token}"}}"#
+    )
+}
+
+/// Mimics all services involved in the authentication.
+///
+/// Token endpoint answers with `authorization_pending` `pending_polls` times before authorizing.
+fn services(pending_polls: usize) -> impl Fn(&RecordedRequest) -> MockResponse {
+    let polls = Arc::new(AtomicUsize::new(0));
+
+    move |request| match (request.method.as_str(), request.path.as_str()) {
+        ("POST", "/oauth/devicecode") => MockResponse::json(200, DEVICE_CODE),
+        ("POST", "/oauth/token") if request.body_str().contains("grant_type=refresh_token") => {
+            assert!(request.body_str().contains("refresh_token=ms-refresh"));
+            MockResponse::json(200, microsoft_token("ms-access-2", "ms-refresh-2"))
+        }
+        ("POST", "/oauth/token") => {
+            assert!(request.body_str().contains("device_code=device-code"));
+            if polls.fetch_add(1, Ordering::SeqCst) < pending_polls {
+                MockResponse::json(400, r#"{"error": "authorization_pending"}"#)
+            } else {
+                MockResponse::json(200, microsoft_token("ms-access", "ms-refresh"))
+            }
+        }
+        ("POST", "/user/authenticate") => {
+            assert!(request.body_str().contains("d=ms-access"));
+            MockResponse::json(200, XBOX_TOKEN)
+        }
+        ("POST", "/xsts/authorize") => {
+            assert!(request.body_str().contains("xbl-token"));
+            MockResponse::json(200, XSTS_TOKEN)
+        }
+        ("POST", "/authentication/login_with_xbox") => {
+            assert!(request.body_str().contains("XBL3.0 x=user-hash;xsts-token"));
+            MockResponse::json(
+                200,
+                r#"{"username": "uuid", "access_token": "mc-token", "token_type": "Bearer", "expires_in": 86400}"#,
+            )
+        }
+        ("GET", "/minecraft/profile") => {
+            assert_eq!(request.header("authorization"), Some("Bearer mc-token"));
+            MockResponse::json(200, PROFILE)
+        }
+        _ => MockResponse::not_found(),
+    }
+}
+
+fn endpoints(server: &MockServer) -> AuthEndpoints {
+    AuthEndpoints {
+        microsoft: format!("{}/oauth", server.url()),
+        xbox_live: server.url(),
+        xsts: server.url(),
+        minecraft_services: server.url(),
+    }
+}
+
+#[tokio::test]
+async fn device_code_flow_test() {
+    let server = MockServer::start(services(2)).await;
+    let authenticator = Authenticator::with_endpoints("client-id", &endpoints(&server));
+
+    let device_code = authenticator.request_device_code().await.unwrap();
+    assert_eq!(device_code.user_code, "ABCD1234");
+
+    let account = authenticator.wait_for_authorization(&device_code).await.unwrap();
+
+    assert_eq!(account.profile.name, "Notch");
+    assert_eq!(account.access_token, "mc-token");
+    assert_eq!(account.refresh_token, "ms-refresh");
+    assert!(!account.is_expired());
+    assert_eq!(server.requests_to("/oauth/token").len(), 3);
+
+    let user_data = account.user_data().unwrap();
+    assert_eq!(user_data.username.get(), "Notch");
+    assert_eq!(user_data.uuid.as_deref(), Some("069a79f444e94726a5befca90e38aaf5"));
+    assert_eq!(user_data.access_token.as_deref(), Some("mc-token"));
+}
+
+#[tokio::test]
+async fn refresh_stored_account_test() {
+    let server = MockServer::start(services(0)).await;
+    let authenticator = Authenticator::with_endpoints("client-id", &endpoints(&server));
+
+    let expired = MicrosoftAccount {
+        profile: serde_json::from_str(PROFILE).unwrap(),
+        access_token: String::from("old-token"),
+        refresh_token: String::from("ms-refresh"),
+        expires_at: 0,
+    };
+    let id = expired.profile.id.clone();
+
+    let mut store = AccountStore::default();
+    store.add(Account::Microsoft(expired));
+
+    let user_data = store.user_data(&id, Some(&authenticator)).await.unwrap();
+    assert_eq!(user_data.access_token.as_deref(), Some("mc-token"));
+
+    let Some(Account::Microsoft(account)) = store.get(&id) else {
+        panic!("The account must stay in the store");
+    };
+    assert_eq!(account.refresh_token, "ms-refresh-2");
+
+    // Valid token must not be refreshed again
+    let requests = server.requests().len();
+    store.user_data(&id, Some(&authenticator)).await.unwrap();
+    assert_eq!(server.requests().len(), requests);
+}
+
+#[tokio::test]
+async fn xsts_child_account_test() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/oauth/token" => MockResponse::json(200, microsoft_token("ms-access", "ms-refresh")),
+        "/user/authenticate" => MockResponse::json(200, XBOX_TOKEN),
+        "/xsts/authorize" => MockResponse::json(401, r#"{"Identity": "0", "XErr": 2148916238, "Message": "", "Redirect": ""}"#),
+        _ => MockResponse::not_found(),
+    })
+    .await;
+    let authenticator = Authenticator::with_endpoints("client-id", &endpoints(&server));
+
+    let device_code = serde_json::from_str(DEVICE_CODE).unwrap();
+    let error = authenticator.wait_for_authorization(&device_code).await.unwrap_err();
+
+    assert!(matches!(error.downcast_ref::<AuthError>(), Some(AuthError::ChildAccount)));
+}
+
+#[tokio::test]
+async fn no_minecraft_profile_test() {
+    let services = services(0);
+    let server = MockServer::start(move |request| match request.path.as_str() {
+        "/minecraft/profile" => MockResponse::json(404, r#"{"error": "NOT_FOUND"}"#),
+        _ => services(request),
+    })
+    .await;
+    let authenticator = Authenticator::with_endpoints("client-id", &endpoints(&server));
+
+    let device_code = serde_json::from_str(DEVICE_CODE).unwrap();
+    let error = authenticator.wait_for_authorization(&device_code).await.unwrap_err();
+
+    assert!(matches!(error.downcast_ref::<AuthError>(), Some(AuthError::NoMinecraftProfile)));
+}
+
+#[tokio::test]
+async fn declined_authorization_test() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/oauth/token" => MockResponse::json(400, r#"{"error": "authorization_declined"}"#),
+        _ => MockResponse::not_found(),
+    })
+    .await;
+    let authenticator = Authenticator::with_endpoints("client-id", &endpoints(&server));
+
+    let device_code = serde_json::from_str(DEVICE_CODE).unwrap();
+    let error = authenticator.wait_for_authorization(&device_code).await.unwrap_err();
+
+    assert!(matches!(error.downcast_ref::<AuthError>(), Some(AuthError::AuthorizationDeclined)));
+}
@@ -38,10 +38,12 @@ async fn vanilla_test() {
         java_runner: None,
         version: "1.20".to_string(),
         version_type: nomi_core::repository::manifest::VersionType::Release,
+        features: Default::default(),
     };
 
     let l = builder.launch_instance(settings, None);
-    l.launch(game_paths, UserData::default(), &JavaRunner::default(), &PrintLogs)
-        .await
-        .unwrap();
+    let mut game = l.launch(game_paths, UserData::default(), Some(&JavaRunner::default())).await.unwrap();
+
+    game.write_logs(&PrintLogs).await;
+    game.wait().await.unwrap();
 }
@@ -43,6 +43,7 @@ async fn forge_test() {
 
         version: "1.19.2".to_string(),
         version_type: nomi_core::repository::manifest::VersionType::Release,
+        features: Default::default(),
     };
 
     let launch = instance.launch_instance(settings, None);
@@ -65,8 +66,11 @@ async fn forge_test() {
         .state(ProfileState::downloaded(launch))
         .build();
 
-    dbg!(profile)
-        .launch(game_paths, UserData::default(), &JavaRunner::from_environment(), &PrintLogs)
+    let mut game = dbg!(profile)
+        .launch(game_paths, UserData::default(), Some(&JavaRunner::from_environment()))
         .await
         .unwrap();
+
+    game.write_logs(&PrintLogs).await;
+    game.wait().await.unwrap();
 }
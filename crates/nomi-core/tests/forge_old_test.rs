@@ -44,6 +44,7 @@ async fn forge_test() {
         java_runner: None,
         version: "1.7.10".to_string(),
         version_type: nomi_core::repository::manifest::VersionType::Release,
+        features: Default::default(),
     };
 
     let launch = instance.launch_instance(settings, None);
@@ -66,15 +67,17 @@ async fn forge_test() {
         .state(ProfileState::downloaded(launch))
         .build();
 
-    dbg!(profile)
+    let mut game = dbg!(profile)
         .launch(
             game_paths,
             UserData::default(),
-            &JavaRunner::path(PathBuf::from(
+            Some(&JavaRunner::path(PathBuf::from(
                 "E:/programming/code/nomi/crates/nomi-core/.nomi/java/jdk8u422-b05/bin/javaw.exe",
-            )),
-            &PrintLogs,
+            ))),
         )
         .await
         .unwrap();
+
+    game.write_logs(&PrintLogs).await;
+    game.wait().await.unwrap();
 }
@@ -35,6 +35,7 @@ async fn full_fabric_test() {
         java_runner: None,
         version: "1.19.4".to_string(),
         version_type: nomi_core::repository::manifest::VersionType::Release,
+        features: Default::default(),
     };
 
     let launch = instance.launch_instance(settings, None);
@@ -54,8 +55,11 @@ async fn full_fabric_test() {
         .state(ProfileState::downloaded(launch))
         .build();
 
-    dbg!(profile)
-        .launch(game_paths, UserData::default(), &JavaRunner::default(), &PrintLogs)
+    let mut game = dbg!(profile)
+        .launch(game_paths, UserData::default(), Some(&JavaRunner::default()))
         .await
         .unwrap();
+
+    game.write_logs(&PrintLogs).await;
+    game.wait().await.unwrap();
 }
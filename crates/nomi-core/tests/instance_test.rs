@@ -31,6 +31,7 @@ async fn instance_test() {
             java_runner: None,
             version: "1.19.2".to_owned(),
             version_type: VersionType::Release,
+            features: Default::default(),
         },
         None,
     );
@@ -51,6 +52,7 @@ async fn instance_test() {
         id: instance.next_id(),
         name: "Based".to_owned(),
         state: ProfileState::downloaded(launch_instance),
+        account: None,
     };
 
     instance.add_profile(ProfilePayload::from_version_profile(&version_profile, &paths.profile_config()));
@@ -59,8 +61,11 @@ async fn instance_test() {
 
     instance.write().await.unwrap();
 
-    version_profile
-        .launch(paths, UserData::default(), &JavaRunner::from_environment(), &PrintLogs)
+    let mut game = version_profile
+        .launch(paths, UserData::default(), Some(&JavaRunner::from_environment()))
         .await
         .unwrap();
+
+    game.write_logs(&PrintLogs).await;
+    game.wait().await.unwrap();
 }
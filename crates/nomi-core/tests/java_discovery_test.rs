@@ -0,0 +1,55 @@
+#![cfg(unix)]
+
+use std::{os::unix::fs::PermissionsExt, path::Path};
+
+use nomi_core::repository::java_installation::{JavaInstallation, JavaSearchPaths};
+
+fn fake_java(home: &Path, version: &str, vendor: &str) {
+    let bin = home.join("bin");
+    std::fs::create_dir_all(&bin).unwrap();
+
+    let script = format!(
+        "#!/bin/sh\ncat >&2 <<EOF\nProperty settings:\n    java.vendor = {vendor}\n    java.version = {version}\n    os.arch = amd64\n\nopenjdk version \"{version}\"\nEOF\n"
+    );
+
+    let path = bin.join("java");
+    std::fs::write(&path, script).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[tokio::test]
+async fn discover_test() {
+    let root = std::env::temp_dir().join(format!("nomi-java-discovery-{}", std::process::id()));
+    let jvm = root.join("jvm");
+
+    fake_java(&jvm.join("temurin-21"), "21.0.3", "Eclipse Adoptium");
+    fake_java(&jvm.join("legacy"), "1.8.0_412", "Oracle Corporation");
+    std::fs::create_dir_all(jvm.join("empty")).unwrap();
+
+    let search = JavaSearchPaths {
+        // The same runtime found through `PATH` and `JAVA_HOME` must be listed once.
+        bin_dirs: vec![jvm.join("temurin-21").join("bin"), root.join("missing")],
+        homes: vec![jvm.join("temurin-21")],
+        roots: vec![jvm.clone()],
+    };
+
+    assert_eq!(search.executables().len(), 2);
+
+    let mut installations = search.discover().await;
+    installations.sort_by_key(|installation| installation.major_version);
+
+    assert_eq!(installations.len(), 2);
+
+    assert_eq!(installations[0].major_version, 8);
+    assert_eq!(installations[0].version, "1.8.0_412");
+    assert_eq!(installations[0].vendor.as_deref(), Some("Oracle Corporation"));
+
+    assert_eq!(installations[1].major_version, 21);
+    assert_eq!(installations[1].arch, "amd64");
+    assert_eq!(installations[1].path, jvm.join("temurin-21/bin/java"));
+
+    let probed = JavaInstallation::probe_runner(&installations[1].runner()).await.unwrap();
+    assert_eq!(probed, installations[1]);
+
+    std::fs::remove_dir_all(root).unwrap();
+}
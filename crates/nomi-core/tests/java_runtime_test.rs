@@ -0,0 +1,175 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, OnceLock},
+};
+
+use mock_server::{MockResponse, MockServer, RecordedRequest};
+use nomi_core::{
+    calculate_sha1,
+    downloads::java_runtime::{JavaRuntimeError, JavaRuntimeManager},
+    repository::{java_runner::JavaRunner, java_runtime::runtime_platform, manifest::JavaVersion},
+    Error,
+};
+
+const JAVA: &[u8] = b"#!/bin/sh\necho java\n";
+const LIBJLI: &[u8] = b"libjli";
+
+fn executable_name() -> &'static str {
+    if cfg!(windows) {
+        "bin/javaw.exe"
+    } else if cfg!(target_os = "macos") {
+        "jre.bundle/Contents/Home/bin/java"
+    } else {
+        "bin/java"
+    }
+}
+
+fn runtimes(url: &str) -> String {
+    format!(
+        r#"{{
+            "{platform}": {{
+                "java-runtime-gamma": [{{
+                    "availability": {{ "group": 1, "progress": 100 }},
+                    "manifest": {{ "sha1": "unused", "size": 0, "url": "{url}/manifest.json" }},
+                    "version": {{ "name": "17.0.8", "released": "2023-07-18T00:00:00+00:00" }}
+                }}]
+            }}
+        }}"#,
+        platform = runtime_platform().unwrap(),
+    )
+}
+
+fn manifest(url: &str, java_sha1: &str) -> String {
+    format!(
+        r#"{{
+            "files": {{
+                "lib": {{ "type": "directory" }},
+                "{executable}": {{
+                    "type": "file",
+                    "executable": true,
+                    "downloads": {{ "raw": {{ "sha1": "{java_sha1}", "size": {java_size}, "url": "{url}/objects/java" }} }}
+                }},
+                "lib/jli/libjli.so": {{
+                    "type": "file",
+                    "downloads": {{ "raw": {{ "sha1": "{libjli_sha1}", "size": {libjli_size}, "url": "{url}/objects/libjli" }} }}
+                }},
+                "lib/libjli.so": {{ "type": "link", "target": "jli/libjli.so" }}
+            }}
+        }}"#,
+        executable = executable_name(),
+        java_size = JAVA.len(),
+        libjli_sha1 = calculate_sha1(LIBJLI),
+        libjli_size = LIBJLI.len(),
+    )
+}
+
+/// Serves the runtime index, a single component and its files.
+///
+/// `java_sha1` is the hash announced for the executable.
+async fn runtime_server(java_sha1: String) -> MockServer {
+    let url = Arc::new(OnceLock::<String>::new());
+
+    let server = MockServer::start({
+        let url = url.clone();
+        move |request: &RecordedRequest| {
+            let url = url.get().unwrap();
+            match request.path.as_str() {
+                "/all.json" => MockResponse::json(200, runtimes(url)),
+                "/manifest.json" => MockResponse::json(200, manifest(url, &java_sha1)),
+                "/objects/java" => MockResponse::new(200, JAVA),
+                "/objects/libjli" => MockResponse::new(200, LIBJLI),
+                _ => MockResponse::not_found(),
+            }
+        }
+    })
+    .await;
+
+    url.set(server.url()).unwrap();
+    server
+}
+
+fn gamma() -> JavaVersion {
+    JavaVersion {
+        component: "java-runtime-gamma".to_owned(),
+        major_version: 17,
+    }
+}
+
+fn test_root(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("nomi-{name}-{}", std::process::id()))
+}
+
+#[tokio::test]
+async fn install_runtime_test() {
+    let server = runtime_server(calculate_sha1(JAVA)).await;
+    let root = test_root("java-runtime");
+    let manager = JavaRuntimeManager::new(&root).with_runtimes_url(format!("{}/all.json", server.url()));
+
+    assert!(!manager.is_installed("java-runtime-gamma"));
+
+    let runner = manager.runner_for(&gamma()).await.unwrap();
+    let executable = root.join("java-runtime-gamma").join(executable_name());
+
+    assert_eq!(runner, JavaRunner::path(executable.clone()));
+    assert_eq!(std::fs::read(&executable).unwrap(), JAVA);
+    assert!(manager.is_installed("java-runtime-gamma"));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        assert_eq!(std::fs::metadata(&executable).unwrap().permissions().mode() & 0o111, 0o111);
+        assert_eq!(std::fs::read(root.join("java-runtime-gamma/lib/libjli.so")).unwrap(), LIBJLI);
+    }
+
+    let installed = manager.installed().await.unwrap();
+    assert_eq!(installed.len(), 1);
+    assert_eq!(installed[0].version.major(), Some(17));
+
+    // Installed runtimes are reused, even if the version requests another component with the same major version.
+    let other_component = JavaVersion {
+        component: "java-runtime-beta".to_owned(),
+        major_version: 17,
+    };
+    assert_eq!(manager.runner_for(&other_component).await.unwrap(), runner);
+    assert_eq!(server.requests_to("/objects/java").len(), 1);
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[tokio::test]
+async fn corrupted_runtime_test() {
+    let server = runtime_server(calculate_sha1(b"something else")).await;
+    let root = test_root("java-runtime-corrupted");
+    let manager = JavaRuntimeManager::new(&root).with_runtimes_url(format!("{}/all.json", server.url()));
+
+    let error = manager.runner_for(&gamma()).await.unwrap_err();
+
+    assert!(matches!(
+        &error,
+        Error::JavaRuntime(JavaRuntimeError::Corrupted { files, .. }) if files == &[executable_name()]
+    ));
+    assert!(error.is_corrupted());
+    assert!(!manager.is_installed("java-runtime-gamma"));
+
+    let _ = std::fs::remove_dir_all(root);
+}
+
+#[tokio::test]
+async fn missing_runtime_test() {
+    let server = runtime_server(calculate_sha1(JAVA)).await;
+    let root = test_root("java-runtime-missing");
+    let manager = JavaRuntimeManager::new(&root).with_runtimes_url(format!("{}/all.json", server.url()));
+
+    let java_version = JavaVersion {
+        component: "jre-legacy".to_owned(),
+        major_version: 8,
+    };
+
+    let error = manager.runner_for(&java_version).await.unwrap_err();
+
+    assert!(matches!(
+        error,
+        Error::JavaRuntime(JavaRuntimeError::NoSuchRuntime { major_version: 8, .. })
+    ));
+}
@@ -0,0 +1,35 @@
+use nomi_core::{
+    downloads::{
+        traits::{DownloadStatus, Downloadable},
+        FileDownloader,
+    },
+    mirrors::Mirrors,
+};
+
+use mock_server::{MockResponse, MockServer};
+
+#[tokio::test]
+async fn mirrors_test() {
+    let server = MockServer::start(|_| MockResponse::new(200, "patchy")).await;
+    Mirrors {
+        libraries: Some(format!("{}/maven/", server.url())),
+        ..Default::default()
+    }
+    .set_global();
+
+    let path = std::env::temp_dir().join(format!("nomi-mirrors-{}.jar", std::process::id()));
+    let downloader = FileDownloader::new(
+        "https://libraries.minecraft.net/com/mojang/patchy/1.1/patchy-1.1.jar".to_owned(),
+        path.clone(),
+    );
+    let status = Box::new(downloader).download().await.0.unwrap();
+    assert!(matches!(status, DownloadStatus::Success));
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, "GET");
+    assert_eq!(requests[0].path, "/maven/com/mojang/patchy/1.1/patchy-1.1.jar");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "patchy");
+
+    let _ = std::fs::remove_file(path);
+}
@@ -0,0 +1,283 @@
+use std::{
+    collections::BTreeMap,
+    io::{Read, Write},
+    path::Path,
+};
+
+use mock_server::{MockResponse, MockServer, RecordedRequest};
+use nomi_core::{
+    calculate_sha1, calculate_sha512,
+    configs::profile::{Loader, ProfileState},
+    downloads::traits::{DownloadResult, Downloader},
+    instance::{loader::LoaderProfile, InstanceProfileId},
+    modpacks::mrpack::{
+        dependencies, override_candidates, EnvSupport, MrpackArchive, MrpackEnv, MrpackExporter, MrpackFile, MrpackHashes, MrpackIndex, MRPACK_INDEX,
+    },
+    repository::simple_args::SimpleArgs,
+};
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+fn forge_profile() -> LoaderProfile {
+    LoaderProfile {
+        loader: Loader::Forge { version: None },
+        main_class: "cpw.mods.bootstraplauncher.BootstrapLauncher".to_owned(),
+        args: SimpleArgs {
+            game: [
+                "--launchTarget",
+                "forgeclient",
+                "--fml.forgeVersion",
+                "47.2.0",
+                "--fml.mcVersion",
+                "1.20.1",
+            ]
+            .map(String::from)
+            .to_vec(),
+            jvm: Vec::new(),
+        },
+        libraries: Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn export_test() {
+    let dir = std::env::temp_dir().join(format!("nomi-mrpack-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let game_dir = dir.join("game");
+    std::fs::create_dir_all(game_dir.join("config/sodium")).unwrap();
+    std::fs::write(game_dir.join("config/sodium/options.json"), "{}").unwrap();
+    std::fs::write(game_dir.join("options.txt"), "fov:0.5").unwrap();
+    std::fs::write(dir.join("sodium.jar"), "sodium").unwrap();
+
+    let candidates = override_candidates(&game_dir).unwrap();
+    assert_eq!(candidates, ["config/sodium/options.json", "options.txt"]);
+
+    let mut exporter = MrpackExporter::new("Pack", "1.0.0", "1.20.1", Some(&forge_profile())).unwrap();
+
+    exporter
+        .add_file(
+            "mods/sodium.jar",
+            dir.join("sodium.jar"),
+            vec!["https://cdn.modrinth.com/sodium.jar".to_owned()],
+        )
+        .await
+        .unwrap();
+
+    for path in &candidates {
+        exporter.add_override(path, game_dir.join(path)).unwrap();
+    }
+
+    assert!(exporter.add_override("../escape.txt", dir.join("sodium.jar")).is_err());
+    assert!(exporter.add_file("/mods/abs.jar", dir.join("sodium.jar"), Vec::new()).await.is_err());
+
+    let target = dir.join("Pack.mrpack");
+    exporter.export(&target).await.unwrap();
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&target).unwrap()).unwrap();
+
+    let mut index = String::new();
+    archive.by_name(MRPACK_INDEX).unwrap().read_to_string(&mut index).unwrap();
+    let index: MrpackIndex = serde_json::from_str(&index).unwrap();
+
+    assert_eq!(index.format_version, 1);
+    assert_eq!(index.game, "minecraft");
+    assert_eq!(index.dependencies[dependencies::MINECRAFT], "1.20.1");
+    assert_eq!(index.dependencies[dependencies::FORGE], "47.2.0");
+
+    assert_eq!(index.files.len(), 1);
+    assert_eq!(index.files[0].path, "mods/sodium.jar");
+    assert_eq!(index.files[0].hashes.sha1, calculate_sha1("sodium"));
+    assert_eq!(index.files[0].hashes.sha512.len(), 128);
+    assert_eq!(index.files[0].file_size, 6);
+
+    let mut options = String::new();
+    archive.by_name("overrides/options.txt").unwrap().read_to_string(&mut options).unwrap();
+    assert_eq!(options, "fov:0.5");
+    assert!(archive.by_name("overrides/config/sodium/options.json").is_ok());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn loader_version_test() {
+    let fabric = LoaderProfile {
+        loader: Loader::Fabric { version: None },
+        main_class: String::new(),
+        args: SimpleArgs {
+            game: Vec::new(),
+            jvm: Vec::new(),
+        },
+        libraries: vec![
+            nomi_core::maven_data::MavenArtifact::new("org.ow2.asm:asm:9.6").into(),
+            nomi_core::maven_data::MavenArtifact::new("net.fabricmc:fabric-loader:0.15.11").into(),
+        ],
+    };
+
+    assert_eq!(fabric.loader_version().as_deref(), Some("0.15.11"));
+
+    let explicit = LoaderProfile {
+        loader: Loader::Fabric {
+            version: Some("0.14.0".to_owned()),
+        },
+        ..fabric
+    };
+    assert_eq!(explicit.loader_version().as_deref(), Some("0.14.0"));
+
+    assert_eq!(forge_profile().loader_version().as_deref(), Some("47.2.0"));
+}
+
+const SODIUM: &str = "sodium";
+const SHADER: &str = "shader";
+
+fn mrpack_file(path: &str, body: &str, url: String, env: Option<MrpackEnv>) -> MrpackFile {
+    MrpackFile {
+        path: path.to_owned(),
+        hashes: MrpackHashes {
+            sha1: calculate_sha1(body),
+            sha512: calculate_sha512(body.as_bytes()),
+        },
+        env,
+        downloads: vec![url],
+        file_size: body.len() as u64,
+    }
+}
+
+fn write_mrpack(target: &Path, index: &MrpackIndex, entries: &[(&str, &str)]) {
+    let mut zip = ZipWriter::new(std::fs::File::create(target).unwrap());
+
+    zip.start_file(MRPACK_INDEX, SimpleFileOptions::default()).unwrap();
+    zip.write_all(&serde_json::to_vec(index).unwrap()).unwrap();
+
+    for (name, content) in entries {
+        zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(content.as_bytes()).unwrap();
+    }
+
+    zip.finish().unwrap();
+}
+
+#[tokio::test]
+async fn import_test() {
+    let server = MockServer::start(|request: &RecordedRequest| match request.path.as_str() {
+        "/sodium.jar" => MockResponse::new(200, SODIUM),
+        "/shader.zip" => MockResponse::new(200, SHADER),
+        _ => MockResponse::not_found(),
+    })
+    .await;
+
+    let dir = std::env::temp_dir().join(format!("nomi-mrpack-import-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let server_only = MrpackEnv {
+        client: EnvSupport::Unsupported,
+        server: EnvSupport::Required,
+    };
+
+    let index = MrpackIndex {
+        format_version: 1,
+        game: "minecraft".to_owned(),
+        version_id: "1.0.0".to_owned(),
+        name: "Pack".to_owned(),
+        summary: None,
+        files: vec![
+            mrpack_file("mods/sodium.jar", SODIUM, format!("{}/sodium.jar", server.url()), None),
+            mrpack_file("shaderpacks/shader.zip", SHADER, format!("{}/shader.zip", server.url()), None),
+            mrpack_file("mods/server.jar", "server", format!("{}/server.jar", server.url()), Some(server_only)),
+        ],
+        dependencies: BTreeMap::from([
+            (dependencies::MINECRAFT.to_owned(), "1.20.1".to_owned()),
+            (dependencies::FABRIC.to_owned(), "0.15.11".to_owned()),
+        ]),
+    };
+
+    let path = dir.join("Pack.mrpack");
+    write_mrpack(
+        &path,
+        &index,
+        &[
+            ("overrides/options.txt", "fov:0.5"),
+            ("overrides/config/sodium.json", "{}"),
+            ("client-overrides/options.txt", "fov:1.0"),
+            ("overrides/../escape.txt", "escape"),
+        ],
+    );
+
+    let archive = MrpackArchive::open(&path).await.unwrap();
+    assert_eq!(archive.name(), "Pack");
+    assert_eq!(archive.game_version().unwrap(), "1.20.1");
+    assert_eq!(
+        archive.loader(),
+        Loader::Fabric {
+            version: Some("0.15.11".to_owned())
+        }
+    );
+    assert_eq!(archive.client_files().count(), 2);
+
+    let profile = archive.version_profile(InstanceProfileId::new(3, 0), "Pack").unwrap();
+    assert!(matches!(profile.state, ProfileState::NotDownloaded { ref version, .. } if version == "1.20.1"));
+    assert!(profile.loader().is_fabric());
+
+    let game_dir = dir.join("game");
+    let downloader = archive.downloader(&game_dir);
+    assert_eq!(downloader.total(), 2);
+
+    let (sender, mut receiver) = tokio::sync::mpsc::channel::<DownloadResult>(100);
+    Box::new(downloader).download(&sender).await;
+    drop(sender);
+
+    while let Some(result) = receiver.recv().await {
+        assert!(result.0.is_ok(), "{:?}", result.0);
+    }
+
+    assert_eq!(std::fs::read_to_string(game_dir.join("mods/sodium.jar")).unwrap(), SODIUM);
+    assert_eq!(std::fs::read_to_string(game_dir.join("shaderpacks/shader.zip")).unwrap(), SHADER);
+    assert!(!game_dir.join("mods/server.jar").exists());
+    assert!(server.requests_to("/server.jar").is_empty());
+
+    assert_eq!(archive.extract_overrides(&game_dir).await.unwrap(), 3);
+    assert_eq!(std::fs::read_to_string(game_dir.join("options.txt")).unwrap(), "fov:1.0");
+    assert_eq!(std::fs::read_to_string(game_dir.join("config/sodium.json")).unwrap(), "{}");
+    assert!(!dir.join("escape.txt").exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn invalid_import_test() {
+    let dir = std::env::temp_dir().join(format!("nomi-mrpack-invalid-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let index = MrpackIndex {
+        format_version: 1,
+        game: "minecraft".to_owned(),
+        version_id: "1.0.0".to_owned(),
+        name: "Pack".to_owned(),
+        summary: None,
+        files: vec![mrpack_file("../mods/escape.jar", SODIUM, "http://localhost/escape.jar".to_owned(), None)],
+        dependencies: BTreeMap::from([(dependencies::MINECRAFT.to_owned(), "1.20.1".to_owned())]),
+    };
+
+    let escape = dir.join("escape.mrpack");
+    write_mrpack(&escape, &index, &[]);
+    assert!(MrpackArchive::open(&escape).await.is_err());
+
+    let unsupported = dir.join("unsupported.mrpack");
+    write_mrpack(
+        &unsupported,
+        &MrpackIndex {
+            format_version: 2,
+            files: Vec::new(),
+            ..index.clone()
+        },
+        &[],
+    );
+    assert!(MrpackArchive::open(&unsupported).await.is_err());
+
+    let vanilla = dir.join("vanilla.mrpack");
+    write_mrpack(&vanilla, &MrpackIndex { files: Vec::new(), ..index }, &[]);
+    assert_eq!(MrpackArchive::open(&vanilla).await.unwrap().loader(), Loader::Vanilla);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
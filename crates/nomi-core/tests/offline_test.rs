@@ -0,0 +1,53 @@
+use nomi_core::{
+    cache::{set_offline_mode, CacheError, MetadataCache, LAUNCHER_MANIFEST_TTL},
+    fs::write_to_file,
+    game_paths::GamePaths,
+    instance::launch::{arguments::UserData, LaunchInstanceBuilder, LaunchSettings},
+    repository::{java_runner::JavaRunner, manifest::VersionType},
+    state::LAUNCHER_MANIFEST,
+    Error,
+};
+
+/// A downloaded profile must be launchable without any network access.
+#[cfg(unix)]
+#[tokio::test]
+async fn offline_launch_test() {
+    set_offline_mode(true);
+
+    let root = std::env::temp_dir().join(format!("nomi-offline-{}", std::process::id()));
+    let paths = GamePaths {
+        game: root.clone(),
+        assets: root.join("assets"),
+        profile: root.join("profile"),
+        libraries: root.join("libraries"),
+    };
+
+    write_to_file(include_bytes!("fixtures/offline_manifest.json"), paths.manifest_file("1.20.4"))
+        .await
+        .unwrap();
+
+    let instance = LaunchInstanceBuilder::new()
+        .settings(LaunchSettings {
+            java_runner: None,
+            version: "1.20.4".to_owned(),
+            version_type: VersionType::Release,
+            features: Default::default(),
+        })
+        .build();
+
+    // There is no Java runtime for the version and it cannot be downloaded, so the fallback runner is used.
+    let game = instance
+        .launch(paths, UserData::default(), Some(&JavaRunner::command("true")))
+        .await
+        .unwrap();
+
+    assert!(game.wait().await.unwrap().success());
+
+    let error = MetadataCache::new(root.join("cache"))
+        .get(LAUNCHER_MANIFEST, LAUNCHER_MANIFEST_TTL)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::Cache(CacheError::Offline { .. })));
+
+    let _ = std::fs::remove_dir_all(root);
+}
@@ -0,0 +1,55 @@
+use nomi_core::downloads::{
+    progress::TransferProgress,
+    traits::{DownloadStatus, Downloader},
+    DownloadSet, FileDownloader,
+};
+
+use mock_server::{MockResponse, MockServer};
+
+/// Serves `/<size>` with a body of `size` bytes.
+async fn serve() -> MockServer {
+    MockServer::start(|request| match request.path.trim_start_matches('/').parse::<usize>() {
+        Ok(size) => MockResponse::new(200, vec![b'a'; size]),
+        Err(_) => MockResponse::not_found(),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn set_transfer_test() {
+    let server = serve().await;
+    let dir = std::env::temp_dir().join(format!("nomi-progress-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let mut set = DownloadSet::new();
+    for size in [100_000, 250_000] {
+        set.add(Box::new(FileDownloader::new(
+            format!("{}/{size}", server.url()),
+            dir.join(size.to_string()),
+        )));
+    }
+
+    let (sender, mut receiver) = tokio::sync::mpsc::channel(1024);
+    Box::new(set).download(&sender).await;
+    drop(sender);
+
+    let (mut completed, mut transfers) = (0, Vec::new());
+    while let Some(result) = receiver.recv().await {
+        match result.0.unwrap() {
+            DownloadStatus::Transfer(progress) => transfers.push(progress),
+            _ => completed += 1,
+        }
+    }
+
+    assert_eq!(completed, 2);
+    assert!(transfers.windows(2).all(|pair| pair[0].downloaded <= pair[1].downloaded));
+    assert_eq!(
+        transfers.last(),
+        Some(&TransferProgress {
+            downloaded: 350_000,
+            total: 350_000,
+        })
+    );
+
+    let _ = std::fs::remove_dir_all(dir);
+}
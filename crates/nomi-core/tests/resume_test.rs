@@ -0,0 +1,189 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use nomi_core::{
+    calculate_sha1,
+    downloads::{
+        part_path,
+        traits::{DownloadStatus, Downloadable},
+        DownloadError, FileDownloader,
+    },
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+use mock_server::{MockResponse, MockServer};
+
+const SIZE: usize = 64 * 1024;
+
+fn body() -> Vec<u8> {
+    (0..SIZE).map(|i| (i % 251) as u8).collect()
+}
+
+/// Serves [`body`] on localhost. The first connection is dropped after sending half of the body,
+/// which [`MockServer`] cannot do.
+///
+/// Returns the URL and the received requests.
+async fn serve(supports_ranges: bool) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/file.jar", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+
+    let received = requests.clone();
+    tokio::spawn(async move {
+        let body = body();
+
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 4096];
+            let read = stream.read(&mut request).await.unwrap();
+            let request = String::from_utf8_lossy(&request[..read]).to_lowercase();
+
+            let is_first = {
+                let mut received = received.lock().unwrap();
+                received.push(request.clone());
+                received.len() == 1
+            };
+
+            let start = request
+                .lines()
+                .find_map(|line| line.strip_prefix("range: bytes="))
+                .and_then(|range| range.trim_end_matches('-').parse::<usize>().ok())
+                .filter(|_| supports_ranges);
+
+            let (head, data) = match start {
+                Some(start) => (
+                    format!(
+                        "HTTP/1.1 206 Partial Content\r\ncontent-range: bytes {start}-{}/{SIZE}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                        SIZE - 1,
+                        SIZE - start
+                    ),
+                    &body[start..],
+                ),
+                None => (
+                    format!("HTTP/1.1 200 OK\r\ncontent-length: {SIZE}\r\nconnection: close\r\n\r\n"),
+                    &body[..],
+                ),
+            };
+
+            stream.write_all(head.as_bytes()).await.unwrap();
+
+            if is_first {
+                stream.write_all(&data[..SIZE / 2]).await.unwrap();
+                // Dropping the stream closes the connection in the middle of the body.
+                continue;
+            }
+
+            stream.write_all(data).await.unwrap();
+        }
+    });
+
+    (url, requests)
+}
+
+fn target(name: &str) -> PathBuf {
+    let path = std::env::temp_dir()
+        .join(format!("nomi-resume-{name}-{}", std::process::id()))
+        .join("file.jar");
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    path
+}
+
+#[tokio::test]
+async fn resume_test() {
+    let (url, requests) = serve(true).await;
+    let path = target("resume");
+
+    let downloader = FileDownloader::new(url, path.clone()).with_sha1(calculate_sha1(body()));
+
+    assert!(Box::new(downloader.clone()).download().await.0.is_err());
+    assert!(!path.exists());
+    assert_eq!(std::fs::metadata(part_path(&path)).unwrap().len(), (SIZE / 2) as u64);
+
+    let status = Box::new(downloader).download().await.0.unwrap();
+    assert!(matches!(status, DownloadStatus::Success));
+
+    assert!(requests.lock().unwrap()[1].contains(&format!("range: bytes={}-", SIZE / 2)));
+    assert_eq!(std::fs::read(&path).unwrap(), body());
+    assert!(!part_path(&path).exists());
+
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[tokio::test]
+async fn retry_resume_test() {
+    let (url, requests) = serve(true).await;
+    let path = target("retry");
+
+    let downloader = FileDownloader::new(url, path.clone())
+        .with_sha1(calculate_sha1(body()))
+        .into_retry()
+        .duration(Duration::from_millis(10));
+
+    let status = Box::new(downloader).download().await.0.unwrap();
+    assert!(matches!(status, DownloadStatus::Success));
+
+    assert_eq!(requests.lock().unwrap().len(), 2);
+    assert_eq!(std::fs::read(&path).unwrap(), body());
+
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[tokio::test]
+async fn no_ranges_test() {
+    let (url, requests) = serve(false).await;
+    let path = target("no-ranges");
+
+    let downloader = FileDownloader::new(url, path.clone()).with_sha1(calculate_sha1(body()));
+
+    assert!(Box::new(downloader.clone()).download().await.0.is_err());
+
+    // The server ignores the range and sends the whole file, so the partial file is overwritten.
+    let status = Box::new(downloader).download().await.0.unwrap();
+    assert!(matches!(status, DownloadStatus::Success));
+
+    assert!(requests.lock().unwrap()[1].contains("range: bytes="));
+    assert_eq!(std::fs::read(&path).unwrap(), body());
+
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[tokio::test]
+async fn hash_mismatch_test() {
+    let (url, _) = serve(true).await;
+    let path = target("hash");
+
+    let downloader = FileDownloader::new(url, path.clone()).with_sha1(calculate_sha1("something else"));
+
+    assert!(Box::new(downloader.clone()).download().await.0.is_err());
+
+    let result = Box::new(downloader).download().await.0;
+    assert!(matches!(result, Err(DownloadError::HashDoesNotMatch { .. })));
+
+    // The corrupted file is removed so the next attempt starts from scratch.
+    assert!(!path.exists());
+    assert!(!part_path(&path).exists());
+
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[tokio::test]
+async fn not_found_is_not_retried_test() {
+    let server = MockServer::start(|_| MockResponse::not_found()).await;
+    let url = format!("{}/missing.jar", server.url());
+
+    let path = target("not-found");
+    let downloader = FileDownloader::new(url, path.clone()).into_retry().duration(Duration::from_millis(10));
+
+    let error = Box::new(downloader).download().await.0.unwrap_err();
+
+    assert!(matches!(error, DownloadError::Error { .. }));
+    assert!(error.is_network());
+    assert!(!error.is_retryable());
+    assert_eq!(server.requests().len(), 1);
+}
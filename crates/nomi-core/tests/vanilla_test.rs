@@ -31,6 +31,7 @@ async fn vanilla_test() {
         java_runner: None,
         version: "1.20".to_string(),
         version_type: nomi_core::repository::manifest::VersionType::Release,
+        features: Default::default(),
     };
 
     // let l = builder.launch_instance(settings);
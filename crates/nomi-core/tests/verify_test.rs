@@ -0,0 +1,123 @@
+use nomi_core::{
+    calculate_sha1, calculate_sha512,
+    downloads::{
+        traits::{DownloadStatus, Downloadable},
+        FileDownloader,
+    },
+    instance::verify::{check_files, ExpectedFile, FileProblem, RepairReport},
+};
+
+use mock_server::{MockResponse, MockServer};
+
+const BODY: &str = "library";
+
+/// Serves [`BODY`] at `/library.jar`.
+async fn serve() -> (MockServer, String) {
+    let server = MockServer::start(|_| MockResponse::new(200, BODY)).await;
+    let url = format!("{}/library.jar", server.url());
+    (server, url)
+}
+
+#[tokio::test]
+async fn verify_first_test() {
+    let (server, url) = serve().await;
+    let dir = std::env::temp_dir().join(format!("nomi-verify-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let valid = dir.join("valid.jar");
+    std::fs::write(&valid, BODY).unwrap();
+
+    let corrupted = dir.join("corrupted.jar");
+    std::fs::write(&corrupted, "corrupted").unwrap();
+
+    let download = |path| {
+        let downloader = FileDownloader::new(url.clone(), path).with_sha1(calculate_sha1(BODY)).verify_first(true);
+        Box::new(downloader).download()
+    };
+
+    assert!(matches!(download(valid.clone()).await.0, Ok(DownloadStatus::Success)));
+    assert_eq!(server.requests().len(), 0, "a valid file must not be downloaded");
+
+    assert!(matches!(download(corrupted.clone()).await.0, Ok(DownloadStatus::Success)));
+    assert_eq!(server.requests().len(), 1);
+    assert_eq!(std::fs::read_to_string(&corrupted).unwrap(), BODY);
+
+    // Every present hash must match.
+    let sha512_only = dir.join("sha512.jar");
+    std::fs::write(&sha512_only, "corrupted").unwrap();
+    let downloader = FileDownloader::new(url.clone(), sha512_only.clone())
+        .with_sha512(calculate_sha512(BODY))
+        .verify_first(true);
+    assert!(matches!(Box::new(downloader).download().await.0, Ok(DownloadStatus::Success)));
+    assert_eq!(server.requests().len(), 2);
+
+    let downloader = FileDownloader::new(url.clone(), valid.clone())
+        .with_sha1(calculate_sha1(BODY))
+        .with_sha512(calculate_sha512("corrupted"))
+        .verify_first(true);
+    let error = Box::new(downloader)
+        .download()
+        .await
+        .0
+        .expect_err("the downloaded file does not match the sha512 either");
+    assert_eq!(error.path(), Some(valid.as_path()));
+    assert_eq!(server.requests().len(), 3);
+    assert_eq!(std::fs::read_to_string(&valid).unwrap(), BODY);
+
+    // Without the verification the file is downloaded anyway.
+    let downloader = FileDownloader::new(url.clone(), valid.clone()).with_sha1(calculate_sha1(BODY));
+    assert!(Box::new(downloader).download().await.0.is_ok());
+    assert_eq!(server.requests().len(), 4);
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[tokio::test]
+async fn repair_test() {
+    let (server, url) = serve().await;
+    let dir = std::env::temp_dir().join(format!("nomi-repair-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    std::fs::write(dir.join("valid.jar"), BODY).unwrap();
+    std::fs::write(dir.join("corrupted.jar"), "corrupted").unwrap();
+    std::fs::write(dir.join("unhashed.jar"), "anything").unwrap();
+
+    let file = |name: &str, url: Option<&String>| ExpectedFile {
+        path: dir.join(name),
+        url: url.cloned(),
+        sha1: url.map(|_| calculate_sha1(BODY)),
+    };
+
+    let files = vec![
+        file("valid.jar", Some(&url)),
+        file("corrupted.jar", Some(&url)),
+        file("missing.jar", Some(&url)),
+        file("unhashed.jar", None),
+        file("generated.jar", None),
+    ];
+
+    let report = check_files(files).await;
+    assert_eq!(report.checked, 5);
+    assert_eq!(report.broken.len(), 3);
+    assert!(report.mismatched().all(|broken| broken.file.path.ends_with("corrupted.jar")));
+    assert_eq!(report.missing().count(), 2);
+    assert!(report.unrepairable().all(|broken| broken.problem == FileProblem::Missing));
+
+    let (sender, _receiver) = tokio::sync::mpsc::channel(1024);
+    // `FileDownloader` implements both traits, so the call must be qualified.
+    nomi_core::downloads::traits::Downloader::download(Box::new(report.repair()), &sender).await;
+    assert_eq!(server.requests().len(), 2, "only the broken files must be downloaded");
+
+    let report = RepairReport {
+        remaining: report.recheck().await,
+        found: report,
+    };
+    assert_eq!(report.remaining.checked, 3);
+    assert_eq!(report.repaired(), 2);
+    assert_eq!(report.remaining.broken.len(), 1);
+    assert!(report.remaining.broken[0].file.path.ends_with("generated.jar"));
+
+    let _ = std::fs::remove_dir_all(dir);
+}
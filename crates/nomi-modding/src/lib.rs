@@ -1,11 +1,87 @@
-use std::marker::PhantomData;
+use std::{
+    marker::PhantomData,
+    sync::{LazyLock, PoisonError, RwLock},
+};
 
 use anyhow::anyhow;
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 mod queries;
 pub use queries::*;
 
+pub mod source;
+
+pub const MODRINTH_API_URL: &str = "https://api.modrinth.com";
+pub const CURSEFORGE_API_URL: &str = "https://api.curseforge.com";
+
+static MIRROR: RwLock<Option<String>> = RwLock::new(None);
+
+static CURSEFORGE: RwLock<CurseForgeConfig> = RwLock::new(CurseForgeConfig { api_key: None, mirror: None });
+
+static CLIENT: LazyLock<RwLock<reqwest::Client>> = LazyLock::new(|| {
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("Umatriz/nomi-modding/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .unwrap_or_default();
+
+    RwLock::new(client)
+});
+
+/// Sets the client used by every query.
+///
+/// Modrinth requires the client to send an identifying `User-Agent`.
+pub fn set_client(client: reqwest::Client) {
+    *CLIENT.write().unwrap_or_else(PoisonError::into_inner) = client;
+}
+
+fn client() -> reqwest::Client {
+    CLIENT.read().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+/// Sets the base URL that replaces [`MODRINTH_API_URL`] in every query.
+pub fn set_mirror(mirror: Option<String>) {
+    *MIRROR.write().unwrap_or_else(PoisonError::into_inner) = mirror.filter(|mirror| !mirror.trim().is_empty());
+}
+
+/// Configuration of the [`curseforge`] queries.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CurseForgeConfig {
+    /// Key from the CurseForge for Studios console. The API rejects requests without it.
+    pub api_key: Option<String>,
+    /// Base URL that replaces [`CURSEFORGE_API_URL`] in every query.
+    pub mirror: Option<String>,
+}
+
+/// Sets the configuration used by every [`curseforge`] query.
+pub fn set_curseforge_config(config: CurseForgeConfig) {
+    let config = CurseForgeConfig {
+        api_key: config.api_key.filter(|key| !key.trim().is_empty()),
+        mirror: config.mirror.filter(|mirror| !mirror.trim().is_empty()),
+    };
+
+    *CURSEFORGE.write().unwrap_or_else(PoisonError::into_inner) = config;
+}
+
+pub(crate) fn curseforge_config() -> CurseForgeConfig {
+    CURSEFORGE.read().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+fn mirrored(url: String) -> String {
+    let mirror = MIRROR.read().unwrap_or_else(PoisonError::into_inner);
+    let curseforge = CURSEFORGE.read().unwrap_or_else(PoisonError::into_inner);
+
+    let replace = |mirror: &str, path: &str| format!("{}{path}", mirror.trim_end_matches('/'));
+
+    if let (Some(mirror), Some(path)) = (mirror.as_deref(), url.strip_prefix(MODRINTH_API_URL)) {
+        return replace(mirror, path);
+    }
+
+    match (curseforge.mirror.as_deref(), url.strip_prefix(CURSEFORGE_API_URL)) {
+        (Some(mirror), Some(path)) => replace(mirror, path),
+        _ => url,
+    }
+}
+
 pub struct Query<Data, T>
 where
     Data: QueryData<T>,
@@ -24,7 +100,19 @@ where
     }
 
     pub async fn query(&self) -> anyhow::Result<T> {
-        let s = reqwest::get(dbg!(self.data.builder().build())).await?.text().await?;
+        let builder = self.data.builder();
+        let url = mirrored(builder.build());
+
+        let mut request = match &builder.body {
+            Some(body) => client().post(url).json(body),
+            None => client().get(url),
+        };
+
+        for (name, value) in &builder.headers {
+            request = request.header(name, value);
+        }
+
+        let s = request.send().await?.error_for_status()?.text().await?;
 
         let mut deserializer = serde_json::Deserializer::from_str(&s);
 
@@ -41,6 +129,8 @@ pub trait QueryData<T> {
 pub struct Builder {
     base_url: String,
     data: Vec<String>,
+    headers: Vec<(String, String)>,
+    body: Option<serde_json::Value>,
 }
 
 impl Builder {
@@ -48,9 +138,22 @@ impl Builder {
         Self {
             base_url: base_url.into(),
             data: Vec::new(),
+            headers: Vec::new(),
+            body: None,
         }
     }
 
+    pub fn add_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sends the query as a `POST` request with the JSON body.
+    pub fn json_body(mut self, body: &impl Serialize) -> Self {
+        self.body = serde_json::to_value(body).ok();
+        self
+    }
+
     fn check_and_add_symbol(&mut self) {
         if self.data.is_empty() {
             self.data.push("?".to_owned());
@@ -116,7 +219,7 @@ mod tests {
         dependencies::DependenciesData,
         project::{ProjectData, ProjectId, ProjectIdOrSlug},
         search::{Facets, InnerPart, Parts, ProjectType, Search, SearchData},
-        version::{MultipleVersionsData, ProjectVersionsData, SingleVersionData},
+        version::{MultipleVersionsData, ProjectVersionsData, SingleVersionData, VersionFileData},
     };
 
     use super::*;
@@ -252,6 +355,12 @@ mod tests {
             for version in data {
                 println!("Success (multiple): {}", version.name);
             }
+
+            for file in versions.iter().flat_map(|v| &v.files).take(3) {
+                let query = Query::new(VersionFileData::new(file.hashes.sha1.clone()));
+                let data = query.query().await.unwrap();
+                println!("Success (file): {} - {}", file.filename, data.name);
+            }
         }
 
         println!("EQ: {total_eq}\nNE: {total_ne}");
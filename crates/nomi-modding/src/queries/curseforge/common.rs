@@ -0,0 +1,112 @@
+//! Types shared by the CurseForge queries
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{curseforge_config, Builder, CURSEFORGE_API_URL};
+
+/// Id of Minecraft on CurseForge.
+pub const MINECRAFT_GAME_ID: u32 = 432;
+
+/// Ids of the Minecraft classes (project types).
+pub mod classes {
+    pub const BUKKIT_PLUGINS: u32 = 5;
+    pub const MODS: u32 = 6;
+    pub const RESOURCE_PACKS: u32 = 12;
+    pub const WORLDS: u32 = 17;
+    pub const MODPACKS: u32 = 4471;
+    pub const CUSTOMIZATION: u32 = 4546;
+    pub const SHADERS: u32 = 6552;
+    pub const DATA_PACKS: u32 = 6945;
+}
+
+/// Creates a builder for `path` that sends the configured API key.
+pub(crate) fn builder(path: impl Display) -> Builder {
+    let builder = Builder::new(format!("{CURSEFORGE_API_URL}{path}"));
+
+    match curseforge_config().api_key {
+        Some(api_key) => builder.add_header("x-api-key", api_key),
+        None => builder,
+    }
+}
+
+/// Every response of the API is wrapped into an object.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Response<T> {
+    pub data: T,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PaginatedResponse<T> {
+    pub data: Vec<T>,
+    pub pagination: Pagination,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Pagination {
+    pub index: u32,
+    pub page_size: u32,
+    pub result_count: u32,
+    pub total_count: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(transparent)]
+pub struct ModId(pub u32);
+
+impl Display for ModId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(transparent)]
+pub struct FileId(pub u32);
+
+impl Display for FileId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModLoaderType {
+    Any,
+    Forge,
+    Cauldron,
+    LiteLoader,
+    Fabric,
+    Quilt,
+    NeoForge,
+}
+
+impl ModLoaderType {
+    /// Value of the `modLoaderType` parameter.
+    pub fn id(self) -> u8 {
+        match self {
+            Self::Any => 0,
+            Self::Forge => 1,
+            Self::Cauldron => 2,
+            Self::LiteLoader => 3,
+            Self::Fabric => 4,
+            Self::Quilt => 5,
+            Self::NeoForge => 6,
+        }
+    }
+
+    /// Parses the loader name used in modpack manifests, e.g. `forge` in `forge-47.2.0`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "forge" => Some(Self::Forge),
+            "cauldron" => Some(Self::Cauldron),
+            "liteloader" => Some(Self::LiteLoader),
+            "fabric" => Some(Self::Fabric),
+            "quilt" => Some(Self::Quilt),
+            "neoforge" => Some(Self::NeoForge),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,42 @@
+//! Dependencies of a file
+
+use crate::QueryData;
+
+use super::{
+    files::{File, RelationType},
+    mods::{Mod, MultipleModsData},
+    ModId, Response,
+};
+
+/// Mods that the file depends on, required and optional ones.
+///
+/// Use [`File::dependencies`] to tell them apart.
+pub struct DependenciesData {
+    mods: MultipleModsData,
+}
+
+impl DependenciesData {
+    pub fn new(file: &File) -> Self {
+        let ids = file
+            .dependencies
+            .iter()
+            .filter(|dependency| {
+                matches!(
+                    dependency.relation_type,
+                    RelationType::RequiredDependency | RelationType::OptionalDependency
+                )
+            })
+            .map(|dependency| dependency.mod_id)
+            .collect::<Vec<ModId>>();
+
+        Self {
+            mods: MultipleModsData::new(ids),
+        }
+    }
+}
+
+impl QueryData<Response<Vec<Mod>>> for DependenciesData {
+    fn builder(&self) -> crate::Builder {
+        self.mods.builder()
+    }
+}
@@ -0,0 +1,256 @@
+//! Files of a mod
+
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+
+use crate::QueryData;
+
+use super::{builder, FileId, ModId, ModLoaderType, PaginatedResponse, Response};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct File {
+    pub id: FileId,
+    pub game_id: u32,
+    pub mod_id: ModId,
+    pub is_available: bool,
+    pub display_name: String,
+    pub file_name: String,
+    pub release_type: ReleaseType,
+    pub hashes: Vec<FileHash>,
+    pub file_date: String,
+    pub file_length: u64,
+    pub download_count: u64,
+    /// `None` if the author disallowed the distribution through third-party launchers.
+    pub download_url: Option<String>,
+    /// Contains the names of the loaders as well, e.g. `Forge`.
+    pub game_versions: Vec<String>,
+    pub dependencies: Vec<FileDependency>,
+    pub file_fingerprint: u32,
+}
+
+impl File {
+    pub fn sha1(&self) -> Option<&str> {
+        self.hashes
+            .iter()
+            .find(|hash| hash.algo == HashAlgo::Sha1)
+            .map(|hash| hash.value.as_str())
+    }
+
+    pub fn required_dependencies(&self) -> impl Iterator<Item = ModId> + '_ {
+        self.dependencies
+            .iter()
+            .filter(|dependency| dependency.relation_type == RelationType::RequiredDependency)
+            .map(|dependency| dependency.mod_id)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct FileHash {
+    pub value: String,
+    pub algo: HashAlgo,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum HashAlgo {
+    Sha1,
+    Md5,
+}
+
+impl TryFrom<u8> for HashAlgo {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::Sha1),
+            2 => Ok(Self::Md5),
+            value => Err(format!("Unknown hash algorithm {value}")),
+        }
+    }
+}
+
+impl From<HashAlgo> for u8 {
+    fn from(value: HashAlgo) -> Self {
+        match value {
+            HashAlgo::Sha1 => 1,
+            HashAlgo::Md5 => 2,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum ReleaseType {
+    Release,
+    Beta,
+    Alpha,
+}
+
+impl TryFrom<u8> for ReleaseType {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::Release),
+            2 => Ok(Self::Beta),
+            3 => Ok(Self::Alpha),
+            value => Err(format!("Unknown release type {value}")),
+        }
+    }
+}
+
+impl From<ReleaseType> for u8 {
+    fn from(value: ReleaseType) -> Self {
+        match value {
+            ReleaseType::Release => 1,
+            ReleaseType::Beta => 2,
+            ReleaseType::Alpha => 3,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FileDependency {
+    pub mod_id: ModId,
+    pub relation_type: RelationType,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum RelationType {
+    EmbeddedLibrary,
+    OptionalDependency,
+    RequiredDependency,
+    Tool,
+    Incompatible,
+    Include,
+}
+
+impl TryFrom<u8> for RelationType {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::EmbeddedLibrary),
+            2 => Ok(Self::OptionalDependency),
+            3 => Ok(Self::RequiredDependency),
+            4 => Ok(Self::Tool),
+            5 => Ok(Self::Incompatible),
+            6 => Ok(Self::Include),
+            value => Err(format!("Unknown relation type {value}")),
+        }
+    }
+}
+
+impl From<RelationType> for u8 {
+    fn from(value: RelationType) -> Self {
+        match value {
+            RelationType::EmbeddedLibrary => 1,
+            RelationType::OptionalDependency => 2,
+            RelationType::RequiredDependency => 3,
+            RelationType::Tool => 4,
+            RelationType::Incompatible => 5,
+            RelationType::Include => 6,
+        }
+    }
+}
+
+#[derive(Debug, TypedBuilder)]
+pub struct ModFilesData {
+    mod_id: ModId,
+    #[builder(default, setter(strip_option, into))]
+    game_version: Option<String>,
+    #[builder(default, setter(strip_option))]
+    mod_loader_type: Option<ModLoaderType>,
+    #[builder(default, setter(strip_option))]
+    index: Option<u32>,
+
+    /// Must be at most 50
+    #[builder(default, setter(strip_option))]
+    page_size: Option<u8>,
+}
+
+impl ModFilesData {
+    pub fn set_game_version(&mut self, game_version: Option<String>) {
+        self.game_version = game_version;
+    }
+
+    pub fn set_mod_loader_type(&mut self, mod_loader_type: Option<ModLoaderType>) {
+        self.mod_loader_type = mod_loader_type;
+    }
+}
+
+impl QueryData<PaginatedResponse<File>> for ModFilesData {
+    fn builder(&self) -> crate::Builder {
+        builder(format!("/v1/mods/{}/files", self.mod_id))
+            .add_optional_parameter("gameVersion", self.game_version.as_ref())
+            .add_optional_parameter("modLoaderType", self.mod_loader_type.map(|l| format!("{}", l.id())))
+            .add_optional_parameter("index", self.index.map(|i| format!("{i}")))
+            .add_optional_parameter("pageSize", self.page_size.map(|p| format!("{p}")))
+    }
+}
+
+pub struct SingleFileData {
+    mod_id: ModId,
+    file_id: FileId,
+}
+
+impl SingleFileData {
+    pub fn new(mod_id: ModId, file_id: FileId) -> Self {
+        Self { mod_id, file_id }
+    }
+}
+
+impl QueryData<Response<File>> for SingleFileData {
+    fn builder(&self) -> crate::Builder {
+        builder(format!("/v1/mods/{}/files/{}", self.mod_id, self.file_id))
+    }
+}
+
+/// Download URL of a file, the API responds with an error if the distribution is disallowed.
+pub struct DownloadUrlData {
+    mod_id: ModId,
+    file_id: FileId,
+}
+
+impl DownloadUrlData {
+    pub fn new(mod_id: ModId, file_id: FileId) -> Self {
+        Self { mod_id, file_id }
+    }
+}
+
+impl QueryData<Response<Option<String>>> for DownloadUrlData {
+    fn builder(&self) -> crate::Builder {
+        builder(format!("/v1/mods/{}/files/{}/download-url", self.mod_id, self.file_id))
+    }
+}
+
+#[derive(Default)]
+pub struct MultipleFilesData {
+    ids: Vec<FileId>,
+}
+
+impl MultipleFilesData {
+    pub fn new(ids: Vec<FileId>) -> Self {
+        Self { ids }
+    }
+
+    pub fn add_file(mut self, id: FileId) -> Self {
+        self.ids.push(id);
+        self
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MultipleFilesBody<'a> {
+    file_ids: &'a [FileId],
+}
+
+impl QueryData<Response<Vec<File>>> for MultipleFilesData {
+    fn builder(&self) -> crate::Builder {
+        builder("/v1/mods/files").json_body(&MultipleFilesBody { file_ids: &self.ids })
+    }
+}
@@ -0,0 +1,104 @@
+//! Fingerprint lookup
+//!
+//! CurseForge identifies files by a MurmurHash2 of their content without whitespace.
+
+use serde::{Deserialize, Serialize};
+
+use crate::QueryData;
+
+use super::{builder, files::File, ModId, Response, MINECRAFT_GAME_ID};
+
+/// Calculates the fingerprint of the file's content.
+pub fn fingerprint(data: &[u8]) -> u32 {
+    let data = data
+        .iter()
+        .copied()
+        .filter(|byte| !matches!(byte, b'\t' | b'\n' | b'\r' | b' '))
+        .collect::<Vec<_>>();
+
+    murmur2(&data, 1)
+}
+
+fn murmur2(data: &[u8], seed: u32) -> u32 {
+    const M: u32 = 0x5bd1_e995;
+    const R: u32 = 24;
+
+    let mut hash = seed ^ data.len() as u32;
+
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+
+        hash = hash.wrapping_mul(M);
+        hash ^= k;
+    }
+
+    let rest = chunks.remainder();
+    if rest.len() >= 3 {
+        hash ^= u32::from(rest[2]) << 16;
+    }
+    if rest.len() >= 2 {
+        hash ^= u32::from(rest[1]) << 8;
+    }
+    if let Some(first) = rest.first() {
+        hash ^= u32::from(*first);
+        hash = hash.wrapping_mul(M);
+    }
+
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(M);
+    hash ^= hash >> 15;
+
+    hash
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FingerprintMatches {
+    pub is_cache_built: bool,
+    pub exact_matches: Vec<FingerprintMatch>,
+    pub exact_fingerprints: Vec<u32>,
+    #[serde(default)]
+    pub unmatched_fingerprints: Option<Vec<u32>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FingerprintMatch {
+    pub id: ModId,
+    pub file: File,
+    pub latest_files: Vec<File>,
+}
+
+/// Finds the files with the given [fingerprints](fingerprint).
+#[derive(Default)]
+pub struct FingerprintsData {
+    fingerprints: Vec<u32>,
+}
+
+impl FingerprintsData {
+    pub fn new(fingerprints: Vec<u32>) -> Self {
+        Self { fingerprints }
+    }
+
+    pub fn add_fingerprint(mut self, fingerprint: u32) -> Self {
+        self.fingerprints.push(fingerprint);
+        self
+    }
+}
+
+#[derive(Serialize)]
+struct FingerprintsBody<'a> {
+    fingerprints: &'a [u32],
+}
+
+impl QueryData<Response<FingerprintMatches>> for FingerprintsData {
+    fn builder(&self) -> crate::Builder {
+        builder(format!("/v1/fingerprints/{MINECRAFT_GAME_ID}")).json_body(&FingerprintsBody {
+            fingerprints: &self.fingerprints,
+        })
+    }
+}
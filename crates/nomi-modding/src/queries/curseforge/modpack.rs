@@ -0,0 +1,242 @@
+//! Modpacks exported by the CurseForge app
+//!
+//! The archive contains a `manifest.json` that lists the files by their ids
+//! and a directory with the files that are extracted into the game directory.
+
+use std::{
+    fs,
+    io::{self, BufReader},
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail};
+use serde::{Deserialize, Serialize};
+use zip::ZipArchive;
+
+use crate::Query;
+
+use super::{
+    files::{File, MultipleFilesData},
+    FileId, ModId, ModLoaderType,
+};
+
+/// Name of the manifest in the root of the archive.
+pub const MANIFEST: &str = "manifest.json";
+pub const MANIFEST_TYPE: &str = "minecraftModpack";
+pub const MANIFEST_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    pub minecraft: ManifestMinecraft,
+    pub manifest_type: String,
+    pub manifest_version: u32,
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub author: String,
+    pub files: Vec<ManifestFile>,
+    /// Directory inside the archive that is extracted into the game directory.
+    #[serde(default = "default_overrides")]
+    pub overrides: String,
+}
+
+fn default_overrides() -> String {
+    String::from("overrides")
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestMinecraft {
+    pub version: String,
+    pub mod_loaders: Vec<ManifestModLoader>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ManifestModLoader {
+    /// The loader and its version, e.g. `forge-47.2.0`.
+    pub id: String,
+    #[serde(default)]
+    pub primary: bool,
+}
+
+impl ManifestModLoader {
+    /// Splits [`Self::id`] into the loader and its version.
+    pub fn loader(&self) -> Option<(ModLoaderType, &str)> {
+        let (name, version) = self.id.split_once('-')?;
+        ModLoaderType::from_name(name).map(|loader| (loader, version))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ManifestFile {
+    #[serde(rename = "projectID")]
+    pub project_id: ModId,
+    #[serde(rename = "fileID")]
+    pub file_id: FileId,
+    #[serde(default = "default_required")]
+    pub required: bool,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+/// A CurseForge modpack archive that is being imported.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use nomi_modding::curseforge::modpack::CurseForgePack;
+/// # async fn import() -> anyhow::Result<()> {
+/// let pack = CurseForgePack::open("./My pack.zip").await?;
+///
+/// for file in pack.resolve_files().await? {
+///     println!("{} - {:?}", file.file_name, file.download_url);
+/// }
+///
+/// pack.extract_overrides("./instances/0").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CurseForgePack {
+    path: PathBuf,
+    manifest: Manifest,
+}
+
+impl CurseForgePack {
+    pub async fn open(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+
+        let manifest = {
+            let path = path.clone();
+            tokio::task::spawn_blocking(move || read_manifest(&path)).await??
+        };
+
+        if manifest.manifest_type != MANIFEST_TYPE || manifest.manifest_version != MANIFEST_VERSION {
+            bail!(
+                "Unsupported modpack manifest `{}` version {}",
+                manifest.manifest_type,
+                manifest.manifest_version
+            );
+        }
+
+        if !is_relative(Path::new(&manifest.overrides)) {
+            bail!("Invalid overrides directory `{}`", manifest.overrides);
+        }
+
+        Ok(Self { path, manifest })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    pub fn name(&self) -> &str {
+        &self.manifest.name
+    }
+
+    pub fn game_version(&self) -> &str {
+        &self.manifest.minecraft.version
+    }
+
+    /// The primary loader and its version, `None` for vanilla packs.
+    pub fn loader(&self) -> Option<(ModLoaderType, &str)> {
+        let loaders = &self.manifest.minecraft.mod_loaders;
+
+        loaders
+            .iter()
+            .find(|loader| loader.primary)
+            .or_else(|| loaders.first())
+            .and_then(ManifestModLoader::loader)
+    }
+
+    /// Requests the required files of the pack.
+    ///
+    /// Fails if some of them cannot be found.
+    pub async fn resolve_files(&self) -> anyhow::Result<Vec<File>> {
+        let ids = self
+            .manifest
+            .files
+            .iter()
+            .filter(|file| file.required)
+            .map(|file| file.file_id)
+            .collect::<Vec<_>>();
+
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let files = Query::new(MultipleFilesData::new(ids.clone())).query().await?.data;
+
+        let missing = ids.iter().filter(|id| !files.iter().any(|file| file.id == **id)).collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(anyhow!("Cannot find the files {missing:?} of the modpack"));
+        }
+
+        Ok(files)
+    }
+
+    /// Extracts the [overrides](Manifest::overrides) into `game_dir`.
+    ///
+    /// Returns the number of extracted files.
+    pub async fn extract_overrides(&self, game_dir: impl Into<PathBuf>) -> anyhow::Result<usize> {
+        let path = self.path.clone();
+        let overrides = self.manifest.overrides.clone();
+        let game_dir = game_dir.into();
+
+        tokio::task::spawn_blocking(move || extract_overrides(&path, &overrides, &game_dir)).await?
+    }
+}
+
+fn read_manifest(path: &Path) -> anyhow::Result<Manifest> {
+    let mut archive = ZipArchive::new(BufReader::new(fs::File::open(path)?))?;
+    let manifest = archive.by_name(MANIFEST)?;
+
+    serde_json::from_reader(manifest).map_err(|e| anyhow!("Cannot parse `{MANIFEST}` of `{}`: {e}", path.display()))
+}
+
+fn is_relative(path: &Path) -> bool {
+    path.components().all(|component| matches!(component, Component::Normal(_)))
+}
+
+fn extract_overrides(path: &Path, overrides: &str, game_dir: &Path) -> anyhow::Result<usize> {
+    let mut archive = ZipArchive::new(BufReader::new(fs::File::open(path)?))?;
+    let mut extracted = 0;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+
+        if entry.is_dir() {
+            continue;
+        }
+
+        let Some(relative) = entry
+            .enclosed_name()
+            .and_then(|name| name.strip_prefix(overrides).ok().map(Path::to_path_buf))
+        else {
+            continue;
+        };
+
+        // `enclosed_name` allows `..` as long as it stays inside the archive.
+        if !is_relative(&relative) {
+            continue;
+        }
+
+        let target = game_dir.join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        io::copy(&mut entry, &mut fs::File::create(&target)?)?;
+        extracted += 1;
+    }
+
+    Ok(extracted)
+}
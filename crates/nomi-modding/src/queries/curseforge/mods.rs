@@ -0,0 +1,108 @@
+//! Mod details
+
+use serde::{Deserialize, Serialize};
+
+use crate::QueryData;
+
+use super::{builder, files::File, FileId, ModId, Response};
+
+/// A project on CurseForge, not necessarily a mod. See [`classes`](super::classes).
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mod {
+    pub id: ModId,
+    pub game_id: u32,
+    pub name: String,
+    pub slug: String,
+    pub links: Links,
+    pub summary: String,
+    pub download_count: u64,
+    pub class_id: Option<u32>,
+    pub categories: Vec<Category>,
+    pub authors: Vec<Author>,
+    pub logo: Option<Asset>,
+    pub main_file_id: FileId,
+    pub latest_files: Vec<File>,
+    pub date_created: String,
+    pub date_modified: String,
+    /// Files of mods that disallow the distribution do not have a download url.
+    pub allow_mod_distribution: Option<bool>,
+    pub is_available: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Links {
+    pub website_url: Option<String>,
+    pub wiki_url: Option<String>,
+    pub issues_url: Option<String>,
+    pub source_url: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Category {
+    pub id: u32,
+    pub name: String,
+    pub slug: String,
+    pub class_id: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Author {
+    pub id: u32,
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Asset {
+    pub title: String,
+    pub thumbnail_url: String,
+    pub url: String,
+}
+
+pub struct ModData {
+    id: ModId,
+}
+
+impl ModData {
+    pub fn new(id: ModId) -> Self {
+        Self { id }
+    }
+}
+
+impl QueryData<Response<Mod>> for ModData {
+    fn builder(&self) -> crate::Builder {
+        builder(format!("/v1/mods/{}", self.id))
+    }
+}
+
+#[derive(Default)]
+pub struct MultipleModsData {
+    ids: Vec<ModId>,
+}
+
+impl MultipleModsData {
+    pub fn new(ids: Vec<ModId>) -> Self {
+        Self { ids }
+    }
+
+    pub fn add_mod(mut self, id: ModId) -> Self {
+        self.ids.push(id);
+        self
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MultipleModsBody<'a> {
+    mod_ids: &'a [ModId],
+}
+
+impl QueryData<Response<Vec<Mod>>> for MultipleModsData {
+    fn builder(&self) -> crate::Builder {
+        builder("/v1/mods").json_body(&MultipleModsBody { mod_ids: &self.ids })
+    }
+}
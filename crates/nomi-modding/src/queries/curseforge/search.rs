@@ -0,0 +1,77 @@
+//! Search for mods
+
+use typed_builder::TypedBuilder;
+
+use crate::QueryData;
+
+use super::{builder, mods::Mod, ModLoaderType, PaginatedResponse, MINECRAFT_GAME_ID};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    Featured,
+    Popularity,
+    LastUpdated,
+    Name,
+    Author,
+    TotalDownloads,
+}
+
+impl SortField {
+    fn id(self) -> u8 {
+        match self {
+            Self::Featured => 1,
+            Self::Popularity => 2,
+            Self::LastUpdated => 3,
+            Self::Name => 4,
+            Self::Author => 5,
+            Self::TotalDownloads => 6,
+        }
+    }
+}
+
+#[derive(Debug, TypedBuilder)]
+pub struct SearchData {
+    #[builder(default, setter(strip_option, into))]
+    search_filter: Option<String>,
+    /// See [`classes`](super::classes).
+    #[builder(default, setter(strip_option))]
+    class_id: Option<u32>,
+    #[builder(default, setter(strip_option, into))]
+    game_version: Option<String>,
+    #[builder(default, setter(strip_option))]
+    mod_loader_type: Option<ModLoaderType>,
+    /// Sorted in the descending order.
+    #[builder(default, setter(strip_option))]
+    sort_field: Option<SortField>,
+    #[builder(default, setter(strip_option))]
+    index: Option<u32>,
+
+    /// Must be at most 50
+    #[builder(default, setter(strip_option))]
+    page_size: Option<u8>,
+}
+
+impl SearchData {
+    pub fn set_search_filter(&mut self, search_filter: Option<String>) {
+        self.search_filter = search_filter;
+    }
+
+    pub fn set_mod_loader_type(&mut self, mod_loader_type: Option<ModLoaderType>) {
+        self.mod_loader_type = mod_loader_type;
+    }
+}
+
+impl QueryData<PaginatedResponse<Mod>> for SearchData {
+    fn builder(&self) -> crate::Builder {
+        builder("/v1/mods/search")
+            .add_parameter("gameId", format!("{MINECRAFT_GAME_ID}"))
+            .add_optional_parameter("classId", self.class_id.map(|c| format!("{c}")))
+            .add_optional_parameter("searchFilter", self.search_filter.as_ref())
+            .add_optional_parameter("gameVersion", self.game_version.as_ref())
+            .add_optional_parameter("modLoaderType", self.mod_loader_type.map(|l| format!("{}", l.id())))
+            .add_optional_parameter("sortField", self.sort_field.map(|f| format!("{}", f.id())))
+            .add_optional_parameter("sortOrder", self.sort_field.map(|_| "desc"))
+            .add_optional_parameter("index", self.index.map(|i| format!("{i}")))
+            .add_optional_parameter("pageSize", self.page_size.map(|p| format!("{p}")))
+    }
+}
@@ -5,3 +5,18 @@ pub mod modrinth {
     pub mod search;
     pub mod version;
 }
+
+/// Queries to the [CurseForge API](https://docs.curseforge.com/rest-api/).
+///
+/// Every request requires an API key, see [`set_curseforge_config`](crate::set_curseforge_config).
+pub mod curseforge {
+    mod common;
+    pub use common::*;
+
+    pub mod dependencies;
+    pub mod files;
+    pub mod fingerprints;
+    pub mod modpack;
+    pub mod mods;
+    pub mod search;
+}
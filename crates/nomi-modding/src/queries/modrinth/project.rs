@@ -4,7 +4,7 @@ use std::ops::Deref;
 
 use serde::{Deserialize, Serialize};
 
-use crate::QueryData;
+use crate::{format_list, QueryData};
 
 use super::search::ProjectType;
 
@@ -145,3 +145,26 @@ impl QueryData<Project> for ProjectData {
         crate::Builder::new(format!("https://api.modrinth.com/v2/project/{}", self.project_id_or_slug.value()))
     }
 }
+
+#[derive(Default)]
+pub struct MultipleProjectsData {
+    ids: Vec<ProjectId>,
+}
+
+impl MultipleProjectsData {
+    pub fn new(ids: Vec<ProjectId>) -> Self {
+        Self { ids }
+    }
+
+    pub fn add_project(mut self, id: ProjectId) -> Self {
+        self.ids.push(id);
+        self
+    }
+}
+
+impl QueryData<Vec<Project>> for MultipleProjectsData {
+    fn builder(&self) -> crate::Builder {
+        crate::Builder::new("https://api.modrinth.com/v2/projects")
+            .add_parameter("ids", format_list(self.ids.iter().map(<ProjectId as Deref>::deref)))
+    }
+}
@@ -1,6 +1,6 @@
 //! Version
 
-use std::ops::Deref;
+use std::{collections::HashMap, ops::Deref};
 
 use serde::{Deserialize, Serialize};
 use typed_builder::TypedBuilder;
@@ -78,6 +78,16 @@ pub struct ProjectVersionsData {
     featured: Option<bool>,
 }
 
+impl ProjectVersionsData {
+    pub fn set_loaders(&mut self, loaders: Option<Vec<String>>) {
+        self.loaders = loaders;
+    }
+
+    pub fn set_game_versions(&mut self, game_versions: Option<Vec<String>>) {
+        self.game_versions = game_versions;
+    }
+}
+
 impl QueryData<ProjectVersions> for ProjectVersionsData {
     fn builder(&self) -> crate::Builder {
         crate::Builder::new(format!("https://api.modrinth.com/v2/project/{}/version", self.id_or_slug.value()))
@@ -103,6 +113,57 @@ impl QueryData<Version> for SingleVersionData {
     }
 }
 
+/// Version that contains a file with the given sha1 hash.
+pub struct VersionFileData {
+    sha1: String,
+}
+
+impl VersionFileData {
+    pub fn new(sha1: impl Into<String>) -> Self {
+        Self { sha1: sha1.into() }
+    }
+}
+
+impl QueryData<Version> for VersionFileData {
+    fn builder(&self) -> crate::Builder {
+        crate::Builder::new(format!("https://api.modrinth.com/v2/version_file/{}", self.sha1)).add_parameter("algorithm", "sha1")
+    }
+}
+
+/// Versions that contain the files with the given sha1 hashes, keyed by the hash.
+///
+/// Hashes of the unknown files are missing from the result.
+#[derive(Default)]
+pub struct VersionFilesData {
+    hashes: Vec<String>,
+}
+
+impl VersionFilesData {
+    pub fn new(hashes: Vec<String>) -> Self {
+        Self { hashes }
+    }
+
+    pub fn add_hash(mut self, sha1: impl Into<String>) -> Self {
+        self.hashes.push(sha1.into());
+        self
+    }
+}
+
+#[derive(Serialize)]
+struct VersionFilesBody<'a> {
+    hashes: &'a [String],
+    algorithm: &'static str,
+}
+
+impl QueryData<HashMap<String, Version>> for VersionFilesData {
+    fn builder(&self) -> crate::Builder {
+        crate::Builder::new("https://api.modrinth.com/v2/version_files").json_body(&VersionFilesBody {
+            hashes: &self.hashes,
+            algorithm: "sha1",
+        })
+    }
+}
+
 #[derive(Default)]
 pub struct MultipleVersionsData {
     ids: Vec<VersionId>,
@@ -0,0 +1,193 @@
+use anyhow::anyhow;
+use async_trait::async_trait;
+use itertools::Itertools;
+
+use crate::{
+    curseforge::{
+        classes,
+        files::{DownloadUrlData, File, ModFilesData, RelationType},
+        mods::{Mod, ModData, MultipleModsData},
+        search::{SearchData, SortField},
+        FileId, ModId, ModLoaderType,
+    },
+    Query,
+};
+
+use super::{ModDependency, ModFile, ModProject, ModSource, ModVersion, ProjectKind, SearchQuery, SourceKind, VersionDependency, VersionFilter};
+
+/// Requires an API key, see [`set_curseforge_config`](crate::set_curseforge_config).
+pub struct CurseForgeSource;
+
+fn mod_id(id: &str) -> anyhow::Result<ModId> {
+    Ok(ModId(id.parse().map_err(|_| anyhow!("{id} is not a CurseForge mod id"))?))
+}
+
+fn file_id(id: &str) -> anyhow::Result<FileId> {
+    Ok(FileId(id.parse().map_err(|_| anyhow!("{id} is not a CurseForge file id"))?))
+}
+
+#[async_trait]
+impl ModSource for CurseForgeSource {
+    fn kind(&self) -> SourceKind {
+        SourceKind::CurseForge
+    }
+
+    async fn search(&self, query: &SearchQuery) -> anyhow::Result<Vec<ModProject>> {
+        let mut data = SearchData::builder()
+            .class_id(class_id(query.kind))
+            .sort_field(SortField::Popularity)
+            .index(query.offset)
+            .page_size(query.limit)
+            .build();
+        data.set_search_filter(query.query.clone());
+
+        if query.kind == ProjectKind::Mod {
+            data.set_mod_loader_type(query.loader.as_deref().and_then(ModLoaderType::from_name));
+        }
+
+        let search = Query::new(data).query().await?;
+
+        Ok(search.data.into_iter().map(ModProject::from).collect())
+    }
+
+    async fn project(&self, id: &str) -> anyhow::Result<ModProject> {
+        let project = Query::new(ModData::new(mod_id(id)?)).query().await?;
+
+        Ok(project.data.into())
+    }
+
+    async fn versions(&self, project_id: &str, filter: &VersionFilter) -> anyhow::Result<Vec<ModVersion>> {
+        // The API filters by a single game version and loader.
+        let mut data = ModFilesData::builder().mod_id(mod_id(project_id)?).build();
+        data.set_game_version(filter.game_versions.first().cloned());
+        data.set_mod_loader_type(filter.loaders.first().and_then(|loader| ModLoaderType::from_name(loader)));
+
+        let files = Query::new(data).query().await?;
+
+        Ok(files.data.into_iter().map(ModVersion::from).collect())
+    }
+
+    async fn dependencies(&self, version: &ModVersion, filter: &VersionFilter) -> anyhow::Result<Vec<ModDependency>> {
+        let ids = version
+            .dependencies
+            .iter()
+            .map(|dependency| mod_id(&dependency.project_id))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let projects = Query::new(MultipleModsData::new(ids)).query().await?.data;
+
+        let mut dependencies = Vec::new();
+        for project in projects {
+            let project = ModProject::from(project);
+            let required = version
+                .dependencies
+                .iter()
+                .any(|dependency| dependency.project_id == project.id && dependency.required);
+
+            dependencies.push(ModDependency {
+                versions: self.versions(&project.id, filter).await?,
+                project,
+                required,
+            });
+        }
+
+        Ok(dependencies)
+    }
+
+    async fn download_url(&self, project_id: &str, version_id: &str, file: &ModFile) -> anyhow::Result<String> {
+        if let Some(url) = &file.url {
+            return Ok(url.clone());
+        }
+
+        let data = DownloadUrlData::new(mod_id(project_id)?, file_id(version_id)?);
+
+        Query::new(data).query().await.ok().and_then(|response| response.data).ok_or_else(|| {
+            anyhow!(
+                "The author of {} does not allow downloading it through launchers. Download it from CurseForge manually",
+                file.filename
+            )
+        })
+    }
+}
+
+fn class_id(kind: ProjectKind) -> u32 {
+    match kind {
+        ProjectKind::Mod => classes::MODS,
+        ProjectKind::Modpack => classes::MODPACKS,
+        ProjectKind::ResourcePack => classes::RESOURCE_PACKS,
+        ProjectKind::Shader => classes::SHADERS,
+        ProjectKind::DataPack => classes::DATA_PACKS,
+        ProjectKind::Plugin => classes::BUKKIT_PLUGINS,
+    }
+}
+
+fn project_kind(class_id: Option<u32>) -> ProjectKind {
+    match class_id {
+        Some(classes::MODPACKS) => ProjectKind::Modpack,
+        Some(classes::RESOURCE_PACKS) => ProjectKind::ResourcePack,
+        Some(classes::SHADERS) => ProjectKind::Shader,
+        Some(classes::DATA_PACKS) => ProjectKind::DataPack,
+        Some(classes::BUKKIT_PLUGINS) => ProjectKind::Plugin,
+        _ => ProjectKind::Mod,
+    }
+}
+
+impl From<Mod> for ModProject {
+    fn from(value: Mod) -> Self {
+        Self {
+            source: SourceKind::CurseForge,
+            id: value.id.to_string(),
+            slug: value.slug,
+            title: value.name,
+            body: value.summary.clone(),
+            description: value.summary,
+            icon_url: value.logo.map(|logo| logo.thumbnail_url),
+            kind: project_kind(value.class_id),
+        }
+    }
+}
+
+/// Every file is a separate version on CurseForge.
+impl From<File> for ModVersion {
+    fn from(value: File) -> Self {
+        let file = ModFile {
+            filename: value.file_name.clone(),
+            url: value.download_url.clone(),
+            sha1: value.sha1().map(ToOwned::to_owned),
+        };
+
+        let dependencies = value
+            .dependencies
+            .iter()
+            .filter_map(|dependency| {
+                let required = match dependency.relation_type {
+                    RelationType::RequiredDependency => true,
+                    RelationType::OptionalDependency => false,
+                    _ => return None,
+                };
+
+                Some(VersionDependency {
+                    project_id: dependency.mod_id.to_string(),
+                    version_id: None,
+                    required,
+                })
+            })
+            .unique()
+            .collect();
+
+        Self {
+            source: SourceKind::CurseForge,
+            id: value.id.to_string(),
+            project_id: value.mod_id.to_string(),
+            name: value.display_name,
+            version_number: value.file_name,
+            featured: false,
+            files: vec![file],
+            dependencies,
+        }
+    }
+}
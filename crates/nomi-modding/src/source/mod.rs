@@ -0,0 +1,170 @@
+//! Platform-independent access to the mods
+//!
+//! Every platform implements [`ModSource`] and converts its responses into [`ModProject`],
+//! [`ModVersion`] and [`ModFile`], so the launcher can treat all of them the same way.
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+
+mod curseforge;
+mod modrinth;
+
+pub use curseforge::CurseForgeSource;
+pub use modrinth::ModrinthSource;
+
+/// Where a mod comes from.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceKind {
+    #[default]
+    Modrinth,
+    CurseForge,
+    /// Files that are not published on any platform, e.g. the ones from the overrides of a modpack.
+    Local,
+}
+
+impl SourceKind {
+    pub fn iter() -> impl Iterator<Item = SourceKind> {
+        [Self::Modrinth, Self::CurseForge, Self::Local].into_iter()
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Modrinth => "Modrinth",
+            Self::CurseForge => "CurseForge",
+            Self::Local => "Local",
+        }
+    }
+
+    /// `None` for [`SourceKind::Local`], such files cannot be found anywhere.
+    pub fn source(self) -> Option<&'static dyn ModSource> {
+        match self {
+            Self::Modrinth => Some(&ModrinthSource),
+            Self::CurseForge => Some(&CurseForgeSource),
+            Self::Local => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectKind {
+    #[default]
+    Mod,
+    Modpack,
+    ResourcePack,
+    Shader,
+    DataPack,
+    Plugin,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ModProject {
+    pub source: SourceKind,
+    pub id: String,
+    pub slug: String,
+    pub title: String,
+    pub description: String,
+    /// Markdown. Search results and the platforms without a full description use [`Self::description`].
+    pub body: String,
+    pub icon_url: Option<String>,
+    pub kind: ProjectKind,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ModVersion {
+    pub source: SourceKind,
+    pub id: String,
+    pub project_id: String,
+    pub name: String,
+    pub version_number: String,
+    pub featured: bool,
+    /// Files that make up the version, additional files such as sources are left out.
+    pub files: Vec<ModFile>,
+    pub dependencies: Vec<VersionDependency>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ModFile {
+    pub filename: String,
+    /// `None` if the file must be resolved with [`ModSource::download_url`].
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub sha1: Option<String>,
+}
+
+/// Required or optional dependency of a version.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct VersionDependency {
+    pub project_id: String,
+    pub version_id: Option<String>,
+    pub required: bool,
+}
+
+/// Dependency with the versions that can be installed.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ModDependency {
+    pub project: ModProject,
+    pub versions: Vec<ModVersion>,
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, TypedBuilder)]
+pub struct SearchQuery {
+    #[builder(default, setter(strip_option, into))]
+    pub query: Option<String>,
+    #[builder(default)]
+    pub kind: ProjectKind,
+    /// Lowercase name of the loader, e.g. `fabric`. Only used to search mods.
+    #[builder(default, setter(strip_option, into))]
+    pub loader: Option<String>,
+    /// Modrinth category names, other sources ignore them.
+    #[builder(default)]
+    pub categories: Vec<String>,
+    #[builder(default)]
+    pub offset: u32,
+    #[builder(default = 10)]
+    pub limit: u8,
+}
+
+/// Empty lists do not filter anything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VersionFilter {
+    pub game_versions: Vec<String>,
+    /// Lowercase names of the loaders, e.g. `fabric`.
+    pub loaders: Vec<String>,
+}
+
+#[async_trait]
+pub trait ModSource: Send + Sync {
+    fn kind(&self) -> SourceKind;
+
+    async fn search(&self, query: &SearchQuery) -> anyhow::Result<Vec<ModProject>>;
+
+    async fn project(&self, id: &str) -> anyhow::Result<ModProject>;
+
+    /// Newest versions first.
+    async fn versions(&self, project_id: &str, filter: &VersionFilter) -> anyhow::Result<Vec<ModVersion>>;
+
+    async fn dependencies(&self, version: &ModVersion, filter: &VersionFilter) -> anyhow::Result<Vec<ModDependency>> {
+        let mut dependencies = Vec::new();
+
+        for dependency in &version.dependencies {
+            dependencies.push(ModDependency {
+                project: self.project(&dependency.project_id).await?,
+                versions: self.versions(&dependency.project_id, filter).await?,
+                required: dependency.required,
+            });
+        }
+
+        Ok(dependencies)
+    }
+
+    /// Resolves the URL of a file that belongs to the version.
+    async fn download_url(&self, _project_id: &str, _version_id: &str, file: &ModFile) -> anyhow::Result<String> {
+        file.url.clone().ok_or_else(|| anyhow!("{} has no download URL", file.filename))
+    }
+}
@@ -0,0 +1,164 @@
+use async_trait::async_trait;
+
+use crate::{
+    modrinth::{
+        project::{Project, ProjectData, ProjectId},
+        search::{Facets, Hit, InnerPart, Parts, ProjectType, SearchData},
+        version::{Dependency, File, ProjectVersionsData, Version},
+    },
+    Query,
+};
+
+use super::{ModFile, ModProject, ModSource, ModVersion, ProjectKind, SearchQuery, SourceKind, VersionDependency, VersionFilter};
+
+pub struct ModrinthSource;
+
+#[async_trait]
+impl ModSource for ModrinthSource {
+    fn kind(&self) -> SourceKind {
+        SourceKind::Modrinth
+    }
+
+    async fn search(&self, query: &SearchQuery) -> anyhow::Result<Vec<ModProject>> {
+        let project_type = ProjectType::from(query.kind);
+
+        let mut parts = match (query.kind, &query.loader) {
+            (ProjectKind::Mod, Some(loader)) => Parts::new().part(InnerPart::new().add_category(loader)).add_project_type(project_type),
+            _ => Parts::from_project_type(project_type),
+        };
+
+        if !query.categories.is_empty() {
+            parts.add_part(InnerPart::from_vec(query.categories.iter().map(InnerPart::format_category).collect()));
+        }
+
+        let mut data = SearchData::builder()
+            .facets(Facets::new(parts))
+            .offset(query.offset)
+            .limit(query.limit)
+            .build();
+        data.set_query(query.query.clone());
+
+        let search = Query::new(data).query().await?;
+
+        Ok(search.hits.into_iter().map(ModProject::from).collect())
+    }
+
+    async fn project(&self, id: &str) -> anyhow::Result<ModProject> {
+        let project = Query::new(ProjectData::new(ProjectId(id.to_owned()))).query().await?;
+
+        Ok(project.into())
+    }
+
+    async fn versions(&self, project_id: &str, filter: &VersionFilter) -> anyhow::Result<Vec<ModVersion>> {
+        let mut data = ProjectVersionsData::builder().id_or_slug(ProjectId(project_id.to_owned())).build();
+        data.set_loaders(Some(filter.loaders.clone()).filter(|loaders| !loaders.is_empty()));
+        data.set_game_versions(Some(filter.game_versions.clone()).filter(|versions| !versions.is_empty()));
+
+        let versions = Query::new(data).query().await?;
+
+        Ok(versions.into_iter().map(ModVersion::from).collect())
+    }
+}
+
+impl From<ProjectKind> for ProjectType {
+    fn from(value: ProjectKind) -> Self {
+        match value {
+            ProjectKind::Mod => Self::Mod,
+            ProjectKind::Modpack => Self::Modpack,
+            ProjectKind::ResourcePack => Self::ResourcePack,
+            ProjectKind::Shader => Self::Shader,
+            ProjectKind::DataPack => Self::DataPack,
+            ProjectKind::Plugin => Self::Plugin,
+        }
+    }
+}
+
+impl From<ProjectType> for ProjectKind {
+    fn from(value: ProjectType) -> Self {
+        match value {
+            ProjectType::Mod => Self::Mod,
+            ProjectType::Modpack => Self::Modpack,
+            ProjectType::ResourcePack => Self::ResourcePack,
+            ProjectType::Shader => Self::Shader,
+            ProjectType::DataPack => Self::DataPack,
+            ProjectType::Plugin => Self::Plugin,
+        }
+    }
+}
+
+impl From<Hit> for ModProject {
+    fn from(value: Hit) -> Self {
+        Self {
+            source: SourceKind::Modrinth,
+            id: value.project_id.0,
+            slug: value.slug.0,
+            title: value.title,
+            body: value.description.clone(),
+            description: value.description,
+            icon_url: Some(value.icon_url).filter(|url| !url.is_empty()),
+            kind: value.project_type.into(),
+        }
+    }
+}
+
+impl From<Project> for ModProject {
+    fn from(value: Project) -> Self {
+        Self {
+            source: SourceKind::Modrinth,
+            id: value.id.0,
+            slug: value.slug.0,
+            title: value.title,
+            description: value.description,
+            body: value.body,
+            icon_url: value.icon_url,
+            kind: value.project_type.into(),
+        }
+    }
+}
+
+impl From<Version> for ModVersion {
+    fn from(value: Version) -> Self {
+        // Some versions do not mark any file as primary.
+        let files = if value.files.iter().any(|file| file.primary) {
+            value.files.into_iter().filter(|file| file.primary).collect::<Vec<_>>()
+        } else {
+            value.files.into_iter().take(1).collect()
+        };
+
+        Self {
+            source: SourceKind::Modrinth,
+            id: value.id.0,
+            project_id: value.project_id.0,
+            name: value.name,
+            version_number: value.version_number,
+            featured: value.featured,
+            files: files.into_iter().map(ModFile::from).collect(),
+            dependencies: value.dependencies.into_iter().filter_map(dependency).collect(),
+        }
+    }
+}
+
+impl From<File> for ModFile {
+    fn from(value: File) -> Self {
+        Self {
+            filename: value.filename,
+            url: Some(value.url),
+            sha1: Some(value.hashes.sha1),
+        }
+    }
+}
+
+/// Incompatible and embedded dependencies are left out.
+fn dependency(value: Dependency) -> Option<VersionDependency> {
+    let required = match value.dependency_type.as_deref() {
+        None | Some("required") => true,
+        Some("optional") => false,
+        Some(_) => return None,
+    };
+
+    Some(VersionDependency {
+        project_id: value.project_id.0,
+        version_id: value.version_id.map(|id| id.0),
+        required,
+    })
+}
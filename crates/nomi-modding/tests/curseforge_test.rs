@@ -0,0 +1,309 @@
+use std::{io::Write, path::Path};
+
+use nomi_modding::{
+    curseforge::{
+        classes,
+        dependencies::DependenciesData,
+        files::{HashAlgo, ModFilesData, MultipleFilesData, RelationType, SingleFileData},
+        fingerprints::{fingerprint, FingerprintsData},
+        modpack::{CurseForgePack, MANIFEST},
+        mods::{ModData, MultipleModsData},
+        search::{SearchData, SortField},
+        FileId, ModId, ModLoaderType,
+    },
+    set_curseforge_config,
+    source::{ModFile, ProjectKind, SearchQuery, SourceKind, VersionFilter},
+    CurseForgeConfig, Query,
+};
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+use mock_server::{MockResponse, MockServer, RecordedRequest};
+
+const API_KEY: &str = "test-key";
+
+/// Serves the recorded responses from `tests/fixtures/curseforge`.
+async fn serve() -> MockServer {
+    MockServer::start(respond).await
+}
+
+fn respond(request: &RecordedRequest) -> MockResponse {
+    let api_key = request.header("x-api-key");
+
+    let route = request.path.split('?').next().unwrap();
+    let fixture = match (request.method.as_str(), route) {
+        _ if api_key != Some(API_KEY) => None,
+        ("GET", "/v1/mods/search") => Some("search.json"),
+        ("GET", "/v1/mods/328085") => Some("mod.json"),
+        ("GET", "/v1/mods/328085/files") => Some("mod_files.json"),
+        ("GET", "/v1/mods/328085/files/4835191") => Some("file.json"),
+        ("GET", "/v1/mods/238222/files" | "/v1/mods/405592/files") => Some("empty_files.json"),
+        ("POST", "/v1/mods/files") => Some("files.json"),
+        ("POST", "/v1/mods") => Some("mods.json"),
+        ("POST", "/v1/fingerprints/432") => Some("fingerprints.json"),
+        _ => None,
+    };
+
+    match fixture {
+        Some(name) => MockResponse::json(200, std::fs::read_to_string(Path::new("tests/fixtures/curseforge").join(name)).unwrap()),
+        None if api_key.is_none() => MockResponse::new(403, "Forbidden"),
+        None => MockResponse::not_found(),
+    }
+}
+
+fn write_pack(target: &Path, manifest: &str, entries: &[(&str, &str)]) {
+    let mut zip = ZipWriter::new(std::fs::File::create(target).unwrap());
+
+    zip.start_file(MANIFEST, SimpleFileOptions::default()).unwrap();
+    zip.write_all(manifest.as_bytes()).unwrap();
+
+    for (name, content) in entries {
+        zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(content.as_bytes()).unwrap();
+    }
+
+    zip.finish().unwrap();
+}
+
+fn manifest(files: &str) -> String {
+    format!(
+        r#"{{
+            "minecraft": {{
+                "version": "1.20.1",
+                "modLoaders": [{{ "id": "forge-47.2.0", "primary": true }}]
+            }},
+            "manifestType": "minecraftModpack",
+            "manifestVersion": 1,
+            "name": "Create Pack",
+            "version": "1.0.0",
+            "author": "nomi",
+            "files": [{files}],
+            "overrides": "overrides"
+        }}"#
+    )
+}
+
+#[tokio::test]
+async fn curseforge_test() {
+    let server = serve().await;
+
+    set_curseforge_config(CurseForgeConfig {
+        api_key: None,
+        mirror: Some(server.url()),
+    });
+
+    assert!(Query::new(ModData::new(ModId(328085))).query().await.is_err(), "the API requires a key");
+
+    set_curseforge_config(CurseForgeConfig {
+        api_key: Some(API_KEY.to_owned()),
+        mirror: Some(server.url()),
+    });
+
+    queries().await;
+    source().await;
+    modpack().await;
+
+    let requests = server.requests();
+    assert!(requests.iter().skip(1).all(|request| request.header("x-api-key") == Some(API_KEY)));
+
+    let find = |method: &str, path: &str| requests.iter().find(|r| r.method == method && r.path.starts_with(path)).unwrap();
+
+    assert_eq!(
+        find("GET", "/v1/mods/search").path,
+        "/v1/mods/search?gameId=432&classId=6&searchFilter=jei&gameVersion=1.20.1&modLoaderType=1&sortField=2&sortOrder=desc&pageSize=2"
+    );
+    assert_eq!(find("POST", "/v1/mods/files").body_str(), r#"{"fileIds":[4712866]}"#);
+    assert!(
+        requests
+            .iter()
+            .any(|r| r.path
+                == "/v1/mods/search?gameId=432&classId=6&searchFilter=create&modLoaderType=1&sortField=2&sortOrder=desc&index=10&pageSize=2")
+    );
+    assert!(requests
+        .iter()
+        .any(|r| r.path == "/v1/mods/328085/files?gameVersion=1.20.1&modLoaderType=1"));
+    assert_eq!(find("POST", "/v1/fingerprints/432").body_str(), r#"{"fingerprints":[1540447798,12345]}"#);
+}
+
+async fn queries() {
+    let data = SearchData::builder()
+        .search_filter("jei")
+        .class_id(classes::MODS)
+        .game_version("1.20.1")
+        .mod_loader_type(ModLoaderType::Forge)
+        .sort_field(SortField::Popularity)
+        .page_size(2)
+        .build();
+    let search = Query::new(data).query().await.unwrap();
+    assert_eq!(search.data.len(), 2);
+    assert_eq!(search.data[0].slug, "jei");
+    assert_eq!(search.pagination.total_count, 14327);
+
+    let create = Query::new(ModData::new(ModId(328085))).query().await.unwrap().data;
+    assert_eq!(create.name, "Create");
+    assert_eq!(create.class_id, Some(classes::MODS));
+    assert_eq!(create.main_file_id, FileId(4835191));
+
+    let data = ModFilesData::builder()
+        .mod_id(create.id)
+        .game_version("1.20.1")
+        .mod_loader_type(ModLoaderType::Forge)
+        .build();
+    let files = Query::new(data).query().await.unwrap();
+    assert_eq!(files.data.len(), 1);
+
+    let file = Query::new(SingleFileData::new(create.id, FileId(4835191))).query().await.unwrap().data;
+    assert_eq!(file, files.data[0]);
+    assert_eq!(file.sha1(), Some("8c0d1f2e3a4b5c6d7e8f9a0b1c2d3e4f5a6b7c8d"));
+    assert_eq!(file.hashes[1].algo, HashAlgo::Md5);
+    assert_eq!(file.required_dependencies().collect::<Vec<_>>(), [ModId(405592)]);
+    assert_eq!(file.dependencies[2].relation_type, RelationType::Incompatible);
+
+    let dependencies = Query::new(DependenciesData::new(&file)).query().await.unwrap().data;
+    assert_eq!(dependencies.len(), 2);
+
+    let mods = Query::new(MultipleModsData::new(vec![ModId(238222)]).add_mod(ModId(405592)))
+        .query()
+        .await
+        .unwrap();
+    assert_eq!(mods.data[1].name, "Flywheel");
+
+    let files = Query::new(MultipleFilesData::new(vec![FileId(4712866)])).query().await.unwrap().data;
+    assert!(files[2].download_url.is_none());
+
+    let matches = Query::new(FingerprintsData::new(vec![1540447798]).add_fingerprint(12345))
+        .query()
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(matches.exact_matches[0].id, ModId(238222));
+    assert_eq!(matches.exact_fingerprints, [1540447798]);
+    assert_eq!(matches.unmatched_fingerprints, Some(vec![12345]));
+}
+
+async fn source() {
+    let source = SourceKind::CurseForge.source().unwrap();
+    assert_eq!(source.kind(), SourceKind::CurseForge);
+
+    let query = SearchQuery::builder()
+        .query("create")
+        .kind(ProjectKind::Mod)
+        .loader("forge")
+        .offset(10)
+        .limit(2)
+        .build();
+    let projects = source.search(&query).await.unwrap();
+    assert_eq!(projects[1].id, "328085");
+    assert_eq!(projects[1].kind, ProjectKind::Mod);
+    assert!(projects[1].icon_url.is_some());
+
+    let project = source.project("328085").await.unwrap();
+    assert_eq!(project.title, "Create");
+    assert!(source.project("create").await.is_err());
+
+    let filter = VersionFilter {
+        game_versions: vec!["1.20.1".to_owned()],
+        loaders: vec!["forge".to_owned()],
+    };
+    let versions = source.versions("328085", &filter).await.unwrap();
+    let version = &versions[0];
+    assert_eq!(version.id, "4835191");
+    assert_eq!(version.files[0].sha1.as_deref(), Some("8c0d1f2e3a4b5c6d7e8f9a0b1c2d3e4f5a6b7c8d"));
+
+    // The incompatible mod is left out.
+    assert_eq!(version.dependencies.len(), 2);
+
+    let dependencies = source.dependencies(version, &filter).await.unwrap();
+    assert_eq!(dependencies.len(), 2);
+    assert!(!dependencies[0].required);
+    assert!(dependencies[1].required);
+    assert_eq!(dependencies[1].project.title, "Flywheel");
+
+    let url = source.download_url("328085", "4835191", &version.files[0]).await.unwrap();
+    assert_eq!(Some(url), version.files[0].url);
+
+    let restricted = ModFile {
+        filename: "restricted.jar".to_owned(),
+        url: None,
+        sha1: None,
+    };
+    assert!(source.download_url("60089", "4912733", &restricted).await.is_err());
+}
+
+async fn modpack() {
+    let dir = std::env::temp_dir().join(format!("nomi-curseforge-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let path = dir.join("Create Pack.zip");
+    write_pack(
+        &path,
+        &manifest(
+            r#"
+            { "projectID": 238222, "fileID": 4712866, "required": true },
+            { "projectID": 328085, "fileID": 4835191, "required": true },
+            { "projectID": 60089, "fileID": 4912733 },
+            { "projectID": 1, "fileID": 1, "required": false }
+            "#,
+        ),
+        &[
+            ("overrides/config/jei/jei-client.toml", "[advanced]"),
+            ("overrides/options.txt", "fov:0.5"),
+            ("overrides/../escape.txt", "escape"),
+            ("modlist.html", "<ul></ul>"),
+        ],
+    );
+
+    let pack = CurseForgePack::open(&path).await.unwrap();
+    assert_eq!(pack.name(), "Create Pack");
+    assert_eq!(pack.game_version(), "1.20.1");
+    assert_eq!(pack.loader(), Some((ModLoaderType::Forge, "47.2.0")));
+
+    let files = pack.resolve_files().await.unwrap();
+    assert_eq!(files.len(), 3);
+
+    let game_dir = dir.join("game");
+    assert_eq!(pack.extract_overrides(&game_dir).await.unwrap(), 2);
+    assert_eq!(std::fs::read_to_string(game_dir.join("options.txt")).unwrap(), "fov:0.5");
+    assert!(game_dir.join("config/jei/jei-client.toml").exists());
+    assert!(!game_dir.join("modlist.html").exists());
+    assert!(!dir.join("escape.txt").exists());
+
+    let missing = dir.join("Missing.zip");
+    write_pack(&missing, &manifest(r#"{ "projectID": 1, "fileID": 1, "required": true }"#), &[]);
+    let pack = CurseForgePack::open(&missing).await.unwrap();
+    assert!(pack.resolve_files().await.is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn invalid_modpack_test() {
+    let dir = std::env::temp_dir().join(format!("nomi-curseforge-invalid-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let escape = dir.join("escape.zip");
+    write_pack(
+        &escape,
+        &manifest("").replace(r#""overrides": "overrides""#, r#""overrides": "../overrides""#),
+        &[],
+    );
+    assert!(CurseForgePack::open(&escape).await.is_err());
+
+    let server_pack = dir.join("server.zip");
+    write_pack(&server_pack, &manifest("").replace("minecraftModpack", "minecraftServerPack"), &[]);
+    assert!(CurseForgePack::open(&server_pack).await.is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn fingerprint_test() {
+    assert_eq!(fingerprint(b""), 1540447798);
+    assert_eq!(fingerprint(b"a"), 626045324);
+    assert_eq!(fingerprint(b"hello world"), 2824650221);
+    assert_eq!(fingerprint(b"The quick brown fox jumps over the lazy dog"), 3751777527);
+
+    // Whitespace is ignored.
+    assert_eq!(fingerprint(b"hello\r\n\tworld "), fingerprint(b"helloworld"));
+}
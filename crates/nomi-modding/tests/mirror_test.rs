@@ -0,0 +1,18 @@
+use nomi_modding::{modrinth::categories::CategoriesData, set_mirror, Query};
+
+use mock_server::{MockResponse, MockServer};
+
+#[tokio::test]
+async fn mirror_test() {
+    let server =
+        MockServer::start(|_| MockResponse::json(200, r#"[{"icon":"","name":"atmosphere","project_type":"shader","header":"features"}]"#)).await;
+    set_mirror(Some(format!("{}/modrinth", server.url())));
+
+    let categories = Query::new(CategoriesData).query().await.unwrap();
+    assert_eq!(categories.get_all_categories()[0].name, "atmosphere");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, "GET");
+    assert_eq!(requests[0].path, "/modrinth/v2/tag/category");
+}
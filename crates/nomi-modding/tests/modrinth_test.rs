@@ -0,0 +1,54 @@
+use std::path::Path;
+
+use nomi_modding::{
+    modrinth::{project::MultipleProjectsData, version::VersionFilesData},
+    set_mirror, Query,
+};
+
+use mock_server::{MockResponse, MockServer, RecordedRequest};
+
+const SHA1: &str = "c84dd4b8bb8f5ac0c7ab0e1b5fbc7e1e0f6cc5f8";
+
+/// Serves the responses from `tests/fixtures/modrinth`.
+fn respond(request: &RecordedRequest) -> MockResponse {
+    let route = request.path.split('?').next().unwrap();
+    let fixture = match (request.method.as_str(), route) {
+        ("POST", "/v2/version_files") => "version_files.json",
+        ("GET", "/v2/projects") => "projects.json",
+        _ => return MockResponse::not_found(),
+    };
+
+    MockResponse::json(200, std::fs::read_to_string(Path::new("tests/fixtures/modrinth").join(fixture)).unwrap())
+}
+
+#[tokio::test]
+async fn bulk_lookup_test() {
+    let server = MockServer::start(respond).await;
+    set_mirror(Some(server.url()));
+
+    let versions = Query::new(VersionFilesData::new(vec![SHA1.to_owned(), "unknown".to_owned()]))
+        .query()
+        .await
+        .unwrap();
+    assert_eq!(versions.len(), 1);
+
+    let version = &versions[SHA1];
+    assert_eq!(version.name, "Sodium 0.5.3");
+
+    let projects = Query::new(MultipleProjectsData::new(vec![version.project_id.clone()]))
+        .query()
+        .await
+        .unwrap();
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0].title, "Sodium");
+    assert_eq!(projects[0].id, version.project_id);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+
+    let body: serde_json::Value = serde_json::from_str(requests[0].body_str()).unwrap();
+    assert_eq!(body, serde_json::json!({ "hashes": [SHA1, "unknown"], "algorithm": "sha1" }));
+
+    assert!(requests[1].path.starts_with("/v2/projects?ids="));
+    assert!(requests[1].path.contains("AANobbMI"));
+}